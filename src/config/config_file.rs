@@ -0,0 +1,313 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use clap::ArgMatches;
+
+use crate::library::httm_dirs::HttmDir;
+use crate::library::results::{HttmError, HttmResult};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// any of these args specifies its own exec mode, so a default mode from the file is ignored
+const EXEC_MODE_ARGS: [&str; 35] = [
+    "BROWSE",
+    "SELECT",
+    "RESTORE",
+    "DELETED",
+    "RECURSIVE",
+    "LAST_SNAP",
+    "SNAPSHOT",
+    "MOUNT_SNAP",
+    "UNMOUNT",
+    "LIST_SNAPS",
+    "ROLL_FORWARD",
+    "PURGE",
+    "PRUNE_DITTO",
+    "FILE_MOUNT",
+    "MAP",
+    "FIND",
+    "COMPLETE",
+    "DIGEST",
+    "UNDO_LAST_RESTORE",
+    "DEDUP_RATIO",
+    "WATCH",
+    "CAT",
+    "DIFF",
+    "VERIFY",
+    "TIMELINE",
+    "SIZE_REPORT",
+    "CHURN",
+    "ROBOT",
+    "SERVE",
+    "SELF_TEST",
+    "SELECT_SNAPS",
+    "HISTORY",
+    "BOOKMARK",
+    "BOOKMARKS",
+    "NUM_VERSIONS",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FileValue {
+    Str(String),
+    Bool(bool),
+    Array(Vec<String>),
+}
+
+// persistent defaults, read from a small subset of TOML: top level "key = value" pairs only,
+// where a value is a string, a bool, or a single line array of strings
+#[derive(Debug, Clone)]
+pub struct ConfigFile {
+    path: PathBuf,
+    values: Vec<(String, FileValue)>,
+}
+
+impl ConfigFile {
+    pub fn new() -> HttmResult<Option<Self>> {
+        // no home dir means no config file, which is no reason to fail
+        let path = match HttmDir::Config.file(CONFIG_FILE_NAME) {
+            Ok(path) if path.exists() => path,
+            _ => return Ok(None),
+        };
+
+        let contents = std::fs::read_to_string(&path).map_err(|err| {
+            HttmError::with_context(&format!("Could not read config file: {:?}", path), &err)
+        })?;
+
+        let values = Self::parse(&path, &contents)?;
+
+        Ok(Some(Self { path, values }))
+    }
+
+    fn parse(path: &Path, contents: &str) -> HttmResult<Vec<(String, FileValue)>> {
+        contents
+            .lines()
+            .enumerate()
+            .filter_map(|(idx, line)| {
+                let line = line.trim();
+
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+
+                let res = Self::parse_line(line).ok_or_else(|| {
+                    HttmError::new(&format!(
+                        "Could not parse line {} of config file {:?}.  httm only supports top level \"key = value\" pairs, \
+                        where a value is a quoted string, a bool, or an array of quoted strings: {}",
+                        idx + 1,
+                        path,
+                        line
+                    ))
+                    .into()
+                });
+
+                Some(res)
+            })
+            .collect::<HttmResult<Vec<(String, FileValue)>>>()
+            .and_then(|values| {
+                let mut keys: Vec<&String> = values.iter().map(|(key, _value)| key).collect();
+                keys.sort_unstable();
+
+                match keys.windows(2).find(|pair| pair[0] == pair[1]) {
+                    Some(pair) => Err(HttmError::new(&format!(
+                        "\"{}\" is specified more than once in config file {:?}",
+                        pair[0], path
+                    ))
+                    .into()),
+                    None => Ok(values),
+                }
+            })
+    }
+
+    fn parse_line(line: &str) -> Option<(String, FileValue)> {
+        let (key, rest) = line.split_once('=')?;
+        let key = key.trim();
+
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return None;
+        }
+
+        let rest = rest.trim_start();
+
+        let (value, rest) = match rest.chars().next()? {
+            '"' | '\'' => {
+                let (string, rest) = Self::parse_string(rest)?;
+                (FileValue::Str(string), rest)
+            }
+            '[' => {
+                let mut strings = Vec::new();
+                let mut rest = rest[1..].trim_start();
+
+                loop {
+                    if let Some(remainder) = rest.strip_prefix(']') {
+                        break (FileValue::Array(strings), remainder);
+                    }
+
+                    let (string, remainder) = Self::parse_string(rest)?;
+                    strings.push(string);
+                    rest = remainder.trim_start();
+
+                    match rest.strip_prefix(',') {
+                        Some(remainder) => rest = remainder.trim_start(),
+                        None if rest.starts_with(']') => {}
+                        None => return None,
+                    }
+                }
+            }
+            _ if rest.starts_with("true") => (FileValue::Bool(true), &rest[4..]),
+            _ if rest.starts_with("false") => (FileValue::Bool(false), &rest[5..]),
+            _ => return None,
+        };
+
+        // only a comment may follow a value
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with('#') {
+            return None;
+        }
+
+        Some((key.replace('-', "_"), value))
+    }
+
+    // returns the string, and whatever remains after its closing quote
+    fn parse_string(input: &str) -> Option<(String, &str)> {
+        let mut chars = input.char_indices();
+
+        let quote = match chars.next()? {
+            (_, quote @ ('"' | '\'')) => quote,
+            _ => return None,
+        };
+
+        let mut string = String::new();
+
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                c if c == quote => return Some((string, &input[idx + 1..])),
+                // literal, single quoted, strings have no escapes
+                '\\' if quote == '"' => match chars.next()?.1 {
+                    'n' => string.push('\n'),
+                    't' => string.push('\t'),
+                    escaped @ ('"' | '\\') => string.push(escaped),
+                    _ => return None,
+                },
+                _ => string.push(c),
+            }
+        }
+
+        None
+    }
+
+    // the file only provides defaults, so we only return args for those values not already given on the command line
+    pub fn default_args(&self, matches: &ArgMatches) -> HttmResult<Vec<OsString>> {
+        let mut args: Vec<OsString> = Vec::new();
+
+        let opt_mode_arg = match self.values.iter().find(|(key, _value)| key == "mode") {
+            Some((_key, FileValue::Str(mode))) => Self::default_mode(mode, matches)?,
+            _ => None,
+        };
+
+        // a preview is only available in the select and restore views
+        let is_select_or_restore = matches.is_present("SELECT")
+            || matches.is_present("RESTORE")
+            || matches!(opt_mode_arg.as_deref(), Some("--select" | "--restore"));
+
+        for (key, value) in &self.values {
+            let opt_arg = match (key.as_str(), value) {
+                ("preview", _) if matches.is_present("PREVIEW") || !is_select_or_restore => None,
+                ("preview", FileValue::Bool(true)) => Some("--preview".to_owned()),
+                ("preview", FileValue::Bool(false)) => None,
+                ("preview", FileValue::Str(command)) => Some(format!("--preview={command}")),
+                ("utc", _) if matches.is_present("UTC") => None,
+                ("utc", FileValue::Bool(utc)) => utc.then(|| "--utc".to_owned()),
+                ("map_aliases", _) if matches.is_present("MAP_ALIASES") => None,
+                ("map_aliases", FileValue::Str(alias)) => Some(format!("--map-aliases={alias}")),
+                ("map_aliases", FileValue::Array(aliases)) if !aliases.is_empty() => {
+                    Some(format!("--map-aliases={}", aliases.join(",")))
+                }
+                ("exclude", _) if matches.is_present("EXCLUDE") => None,
+                ("exclude", FileValue::Str(glob)) => Some(format!("--exclude={glob}")),
+                ("exclude", FileValue::Array(globs)) if !globs.is_empty() => {
+                    Some(format!("--exclude={}", globs.join(",")))
+                }
+                ("map_aliases" | "exclude", FileValue::Array(_)) => None,
+                ("mode", FileValue::Str(_)) => opt_mode_arg.clone(),
+                ("preview" | "utc" | "map_aliases" | "exclude" | "mode", _) => {
+                    return Err(HttmError::new(&format!(
+                        "The value given for \"{key}\" in config file {:?} is not of a supported type.",
+                        self.path
+                    ))
+                    .into())
+                }
+                _ => {
+                    return Err(HttmError::new(&format!(
+                        "\"{key}\" in config file {:?} is not a supported key.  \
+                        Supported keys are: \"preview\", \"utc\", \"map_aliases\", \"exclude\", and \"mode\".",
+                        self.path
+                    ))
+                    .into())
+                }
+            };
+
+            if let Some(arg) = opt_arg {
+                args.push(arg.into());
+            }
+        }
+
+        Ok(args)
+    }
+
+    fn default_mode(mode: &str, matches: &ArgMatches) -> HttmResult<Option<String>> {
+        let arg = match mode {
+            "browse" => "--browse",
+            "select" => "--select",
+            "restore" => "--restore",
+            "display" => return Ok(None),
+            _ => {
+                return Err(HttmError::new(&format!(
+                    "\"{mode}\" is not a supported default mode.  Supported modes are: \"browse\", \"select\", \"restore\", and \"display\"."
+                ))
+                .into())
+            }
+        };
+
+        if EXEC_MODE_ARGS.iter().any(|name| matches.is_present(name)) {
+            return Ok(None);
+        }
+
+        // the interactive modes take, at most, a single directory, so a file path given
+        // on the command line is requesting the default, display, mode
+        let is_dir_or_nothing = match matches.values_of_os("INPUT_FILES") {
+            Some(mut input_files) => match (input_files.next(), input_files.next()) {
+                (Some(input_file), None) => Path::new(input_file).is_dir(),
+                _ => false,
+            },
+            None => true,
+        };
+
+        if !is_dir_or_nothing {
+            return Ok(None);
+        }
+
+        Ok(Some(arg.to_owned()))
+    }
+}
@@ -15,8 +15,12 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::ffi::OsString;
+use std::net::SocketAddr;
 use std::ops::Index;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use clap::OsValues;
 use rayon::prelude::*;
@@ -25,13 +29,32 @@ use clap::{crate_name, crate_version, Arg, ArgMatches};
 use indicatif::ProgressBar;
 use time::UtcOffset;
 
+use crate::config::config_file::ConfigFile;
 use crate::config::install_hot_keys::install_hot_keys;
 use crate::data::filesystem_info::FilesystemInfo;
 use crate::data::paths::PathData;
+use crate::display_versions::custom_format::CustomFormat;
 use crate::library::results::{HttmError, HttmResult};
-use crate::library::utility::{read_stdin, HttmIsDir};
+use crate::library::utility::{
+    date_string, display_relative_date, parse_date_string, read_stdin, time_zone_offset,
+    DateFormat, HttmIsDir,
+};
+use crate::lookup::object_versions::ObjectVersions;
+use crate::parse::mounts::ZFS_LIST_CACHE_DIR;
+use crate::parse::provenance::SnapOrigin;
+use crate::parse::replication::AltStore;
 use crate::ROOT_DIRECTORY;
 
+// remote filesystems are only stat-ed so many at a time, see NetworkIo
+const DEFAULT_NETWORK_CONCURRENCY: usize = 4;
+const DEFAULT_NETWORK_BATCH_SIZE: usize = 32;
+const DEFAULT_WATCH_INTERVAL: usize = 60;
+const DEFAULT_NTH_VERSION: usize = 1;
+const DEFAULT_CHURN_NUM_FILES: usize = 10;
+const DEFAULT_RESTORE_SUFFIX: &str = "{name}.httm_restored.{date}";
+const DEFAULT_SENSITIVE_PATHS: [&str; 7] =
+    ["/boot", "/etc", "/bin", "/sbin", "/lib", "/lib64", "/usr"];
+
 #[derive(Debug, Clone)]
 pub enum ExecMode {
     Interactive(InteractiveMode),
@@ -39,10 +62,51 @@ pub enum ExecMode {
     Display,
     SnapFileMount(String),
     Purge(Option<ListSnapsFilters>),
+    PruneDitto,
     MountsForFiles(MountDisplay),
     SnapsForFiles(Option<ListSnapsFilters>),
     NumVersions(NumVersionsMode),
     RollForward(RollForwardConfig),
+    History,
+    DatasetMap,
+    MountSnap(MountSnapAction),
+    Find(String),
+    Complete(String),
+    BatchRestore(BatchRestoreConfig),
+    Digest(usize),
+    UndoRestore,
+    DedupRatio,
+    Bookmark(BookmarkAction),
+    Watch(usize),
+    Cat(CatVersion),
+    Diff,
+    Verify,
+    Timeline,
+    SizeReport,
+    Churn(ChurnConfig),
+    Robot,
+    Serve(SocketAddr),
+    SelfTest,
+    SnapshotSelect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiFrontend {
+    Skim,
+    Native,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MountSnapAction {
+    Mount,
+    Unmount,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookmarkAction {
+    Add,
+    Remove,
+    List,
 }
 
 #[derive(Debug, Clone)]
@@ -51,6 +115,13 @@ pub struct RollForwardConfig {
     pub progress_bar: indicatif::ProgressBar,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchRestoreConfig {
+    pub restore_mode: RestoreMode,
+    pub opt_from_file: Option<PathBuf>,
+    pub skip_consent: bool,
+}
+
 #[derive(Debug, Clone)]
 pub enum BulkExclusion {
     NoLive,
@@ -64,6 +135,13 @@ pub enum MountDisplay {
     RelativePath,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatasetPreference {
+    Alias,
+    Native,
+    Both,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InteractiveMode {
     Browse,
@@ -82,6 +160,8 @@ pub enum RestoreMode {
     CopyOnly,
     CopyAndPreserve,
     Overwrite(RestoreSnapGuard),
+    MetadataOnly,
+    Merge,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -97,6 +177,257 @@ pub enum DeletedMode {
     DepthOfOne,
     All,
     Only,
+    Depth,
+}
+
+// a restricted environment in which a preview command is executed, see PREVIEW_SANDBOX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewSandbox {
+    Bwrap(PathBuf),
+}
+
+// a tool with which a 'zfs' command is run as root, should the user lack the permission, see ESCALATE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrivilegeEscalation {
+    Sudo(PathBuf),
+    Doas(PathBuf),
+}
+
+// the versions of a path beneath the live root are looked up as though it were beneath the search root, see RELATIVE_TO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelativeTo {
+    pub live_root: PathBuf,
+    pub search_root: PathBuf,
+}
+
+impl RelativeTo {
+    // a path not beneath the live root is looked up where it is
+    pub fn search_pathdata(&self, pathdata: &PathData) -> Option<PathData> {
+        pathdata
+            .path_buf
+            .strip_prefix(&self.live_root)
+            .ok()
+            .map(|relative_path| PathData::from(self.search_root.join(relative_path).as_path()))
+    }
+}
+
+// the versions of a path beneath the live root are looked up by an httm on another host, beneath its remote root, see REMOTE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteHost {
+    pub destination: String,
+    pub live_root: PathBuf,
+    pub remote_root: PathBuf,
+    // the httm to run on the remote host, see REMOTE_COMMAND
+    pub remote_command: String,
+}
+
+impl RemoteHost {
+    // like RELATIVE_TO, a path not beneath the live root is looked up where it is
+    pub fn remote_path(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.live_root) {
+            Ok(relative_path) => self.remote_root.join(relative_path),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+// the versions of a path beneath the mount of an object store are listed by rclone, beneath its remote, see OBJECT_VERSIONS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectStore {
+    pub mount: PathBuf,
+    pub remote: String,
+}
+
+impl ObjectStore {
+    // like "s3:bucket/dir/docs", a path not beneath the mount has no remote path
+    pub fn remote_path(&self, path: &Path) -> Option<String> {
+        let relative_path = path.strip_prefix(&self.mount).ok()?;
+
+        Some(Self::join(&self.remote, &relative_path.to_string_lossy()))
+    }
+
+    // an rclone remote is not a path, so is joined as rclone would, "s3:" and "bucket" as "s3:bucket"
+    pub fn join(remote: &str, relative: &str) -> String {
+        if relative.is_empty() {
+            return remote.to_owned();
+        }
+
+        if remote.ends_with(':') || remote.ends_with('/') {
+            return format!("{remote}{relative}");
+        }
+
+        format!("{remote}/{relative}")
+    }
+}
+
+// the version table, as rows of delimited values, see OUTPUT and COLUMNS
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelimitedOutput {
+    pub format: DelimitedFormat,
+    pub columns: Vec<DelimitedColumn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelimitedFormat {
+    Csv,
+    Tsv,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DelimitedColumn {
+    Path,
+    Snap,
+    Mtime,
+    Size,
+    Dataset,
+}
+
+impl DelimitedColumn {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DelimitedColumn::Path => "path",
+            DelimitedColumn::Snap => "snap",
+            DelimitedColumn::Mtime => "mtime",
+            DelimitedColumn::Size => "size",
+            DelimitedColumn::Dataset => "dataset",
+        }
+    }
+}
+
+// the template from which a restored copy is named, see RESTORE_SUFFIX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreSuffix {
+    template: String,
+}
+
+impl RestoreSuffix {
+    const PLACEHOLDERS: [&'static str; 5] = ["{name}", "{stem}", "{ext}", "{date}", "{n}"];
+
+    pub fn new(template: &str) -> HttmResult<Self> {
+        let without_placeholders = Self::PLACEHOLDERS
+            .iter()
+            .fold(template.to_owned(), |acc, placeholder| {
+                acc.replace(placeholder, "")
+            });
+
+        if without_placeholders.contains(['{', '}']) {
+            let msg = format!(
+                "RESTORE_SUFFIX {template:?} contains an unknown placeholder.  Supported placeholders are: {}.",
+                Self::PLACEHOLDERS.join(", ")
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        if template.contains('/') {
+            return Err(HttmError::new(
+                "RESTORE_SUFFIX must name a file, and may not contain a '/'.",
+            )
+            .into());
+        }
+
+        // otherwise, every file restored would have the same name
+        if !template.contains("{name}") && !template.contains("{stem}") {
+            return Err(
+                HttmError::new("RESTORE_SUFFIX must contain either {name} or {stem}.").into(),
+            );
+        }
+
+        Ok(Self {
+            template: template.to_owned(),
+        })
+    }
+
+    // only with a counter may we try another name, when one is already taken
+    pub fn has_counter(&self) -> bool {
+        self.template.contains("{n}")
+    }
+
+    // placeholders are replaced in a single pass, so a file name which itself contains a placeholder is left as is
+    pub fn render(&self, file_name: &str, date: &str, counter: usize) -> String {
+        // a leading '.', as in ".zshrc", begins a hidden file's name, not its extension
+        let (stem, ext) = match file_name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+            _ => (file_name, String::new()),
+        };
+
+        let counter = counter.to_string();
+        let values: [&str; 5] = [file_name, stem, &ext, date, &counter];
+
+        let mut res = String::new();
+        let mut rest = self.template.as_str();
+
+        while let Some(start) = rest.find('{') {
+            res.push_str(&rest[..start]);
+
+            let remainder = &rest[start..];
+
+            match Self::PLACEHOLDERS
+                .iter()
+                .zip(values)
+                .find(|(placeholder, _value)| remainder.starts_with(*placeholder))
+            {
+                Some((placeholder, value)) => {
+                    res.push_str(value);
+                    rest = &remainder[placeholder.len()..];
+                }
+                None => {
+                    res.push('{');
+                    rest = &remainder[1..];
+                }
+            }
+        }
+
+        res.push_str(rest);
+        res
+    }
+}
+
+// which entries the interactive browse view shows, may be changed during a browse session, see BrowseRelay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseFilter {
+    All,
+    FilesOnly,
+    DirsOnly,
+    DeletedOnly,
+    VersionedOnly,
+}
+
+impl BrowseFilter {
+    // the order in which the browse view cycles through each filter
+    pub fn next(&self) -> Self {
+        match self {
+            BrowseFilter::All => BrowseFilter::FilesOnly,
+            BrowseFilter::FilesOnly => BrowseFilter::DirsOnly,
+            BrowseFilter::DirsOnly => BrowseFilter::DeletedOnly,
+            BrowseFilter::DeletedOnly => BrowseFilter::VersionedOnly,
+            BrowseFilter::VersionedOnly => BrowseFilter::All,
+        }
+    }
+
+    // toggling the filter already shown returns the browse view to all entries
+    pub fn toggle(&self, filter: BrowseFilter) -> Self {
+        if *self == filter {
+            return BrowseFilter::All;
+        }
+
+        filter
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BrowseFilter::All => "All Entries",
+            BrowseFilter::FilesOnly => "Files Only",
+            BrowseFilter::DirsOnly => "Dirs Only",
+            BrowseFilter::DeletedOnly => "Deleted Only",
+            BrowseFilter::VersionedOnly => "Versioned Only",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeletedScan {
+    Exhaustive,
+    Fast,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +437,13 @@ pub enum ListSnapsOfType {
     UniqueContents,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DittoOfType {
+    ModifyTime,
+    Metadata,
+    Contents,
+}
+
 #[derive(Debug, Clone)]
 pub struct ListSnapsFilters {
     pub select_mode: bool,
@@ -120,6 +458,29 @@ pub enum LastSnapMode {
     DittoOnly,
     NoDittoExclusive,
     NoDittoInclusive,
+    Before(SystemTime),
+}
+
+// the leaderboard of those files beneath a dir with the most churn, see CHURN
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChurnConfig {
+    pub num_files: usize,
+    pub rank: ChurnRank,
+    pub opt_since: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChurnRank {
+    Versions,
+    Bytes,
+}
+
+// which snapshot version of a file to stream to stdout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatVersion {
+    // 1 is the latest snapshot version, 2 the version before, and so on
+    Nth(usize),
+    AsOf(SystemTime),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -132,10 +493,23 @@ pub enum NumVersionsMode {
     Multiple,
 }
 
-fn parse_args() -> ArgMatches {
+fn parse_args<I, T>(args: I) -> Result<ArgMatches, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
     clap::Command::new(crate_name!())
         .about("httm prints the size, date and corresponding locations of available unique versions of files residing on snapshots.  \
         May also be used interactively to select and restore from such versions, and even to snapshot datasets which contain certain files.")
+        .after_help("EXIT STATUS:\n    \
+        0  success\n    \
+        1  any other error\n    \
+        2  no snapshot versions were found\n    \
+        3  a path does not reside upon a snapshot-capable filesystem\n    \
+        4  the user declined a restore\n    \
+        5  a verified file's contents differ from its latest snapshot version\n    \
+        6  a dry run would have made changes\n\n\
+        When the user aborts an interactive session, httm exits with the ABORT_EXIT_CODE given, 0 by default.")
         .version(crate_version!())
         .arg(
             Arg::new("INPUT_FILES")
@@ -154,7 +528,10 @@ fn parse_args() -> ArgMatches {
                 .short_alias('i')
                 .long("browse")
                 .visible_alias("interactive")
-                .help("interactive browse and search a specified directory to display unique file versions.")
+                .help("interactive browse and search a specified directory to display unique file versions.  \
+                While browsing, the user may press ctrl-s to snapshot the dataset which contains the highlighted item, \
+                before returning to the browse view, whose previews will include the new snapshot.  This requires ZFS, \
+                and root or 'zfs allow' privileges (see ESCALATE).")
                 .display_order(2)
         )
         .arg(
@@ -165,13 +542,23 @@ fn parse_args() -> ArgMatches {
                 .conflicts_with("RESTORE")
                 .display_order(3)
         )
+        .arg(
+            Arg::new("PRINT_SNAP")
+                .long("print-snap")
+                .help("in select mode, rather than the location of the snapshot version selected, print the name of the ZFS snapshot which contains it, \
+                like \"pool/dataset@snapname\", so that the selection may be given directly to a 'zfs send', 'zfs clone', or 'zfs rollback' command \
+                (eg. zfs clone \"$(httm --select --print-snap ~/.zshrc)\" pool/recovered).  The snapshot name is never quoted.  \
+                Note: This option is only available for snapshots of ZFS datasets.")
+                .requires("SELECT")
+                .display_order(3)
+        )
         .arg(
             Arg::new("RESTORE")
                 .short('r')
                 .long("restore")
                 .takes_value(true)
                 .default_missing_value("copy")
-                .possible_values(["copy", "copy-and-preserve", "overwrite", "yolo", "guard"])
+                .possible_values(["copy", "copy-and-preserve", "overwrite", "yolo", "guard", "metadata-only", "merge"])
                 .min_values(0)
                 .require_equals(true)
                 .help("interactive browse and search a specified directory to display unique file versions.  Continue to another dialog to select a snapshot version to restore.  \
@@ -180,24 +567,202 @@ fn parse_args() -> ArgMatches {
                 Overwrite mode will attempt to preserve attributes, like the permissions/mode, timestamps, xattrs and ownership of the selected snapshot file version (this is and will likely remain a UNIX only feature).  \
                 In order to preserve such attributes in \"copy\" mode, specify the \"copy-and-preserve\" value.  User may also specify \"guard\".  \
                 Guard mode has the same semantics as \"overwrite\" but will attempt to take a precautionary snapshot before any overwrite action occurs.  \
-                Note: Guard mode is a ZFS only option.")
+                Note: Guard mode is a ZFS only option.  \
+                When only the permissions of a file were mangled, the user may specify \"metadata-only\", which applies the ownership, mode, xattrs and ACLs of the selected snapshot version \
+                upon the live file, without touching its content or timestamps, after a preview of exactly which attributes would change.  \
+                When a text file, like a config file, has diverged since the snapshot, the user may specify \"merge\", which performs a three-way merge upon the live file, \
+                of the changes made since the snapshot version before the selected version (the common ancestor) in the live version, and in the selected version, \
+                as \"diff3 -m\" would, and which leaves any conflicting changes between conflict markers, for the user to resolve.  \
+                Each file is first copied to a hidden \".httm_partial\" file beside its destination, and only renamed into place once complete, \
+                so an interrupted restore never leaves a truncated file which looks restored.  Re-running an interrupted restore resumes it.")
                 .conflicts_with("SELECT")
                 .display_order(4)
         )
+        .arg(
+            Arg::new("PRESERVE")
+                .long("preserve")
+                .help("in \"copy\" restore mode, preserve the attributes of the selected snapshot file version, like the permissions/mode, \
+                timestamps, xattrs, ACLs (when built with the \"acls\" feature) and ownership, upon the restored file.  Restoring ownership usually requires root privileges.  \
+                Equivalent to specifying the \"copy-and-preserve\" restore value.  Attributes are always preserved in \"overwrite\" and \"guard\" modes.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("DIR_TIMES")
+                .long("dir-times")
+                .help("when restoring, after all files have been copied, restore the timestamps of each restored directory, and of its parent directories, from the snapshot.  \
+                Copying files into a directory updates its modify time, so, without this final pass, a restored tree of directories will bear the time of the restore.  \
+                Directory timestamps are always restored when attributes are preserved, such as when \"--preserve\" is specified.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("QUIET")
+                .long("quiet")
+                .help("do not report the progress of restores.  By default, when restoring, httm displays a progress bar of the bytes copied, \
+                of the total bytes to be copied, and an estimate of the time remaining, upon stderr, but only when stderr is a terminal.")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("FORCE")
+                .long("force")
+                .requires("RESTORE")
+                .help("restore over files which are currently open.  By default, before overwriting any file, httm looks for processes which hold that file open, \
+                or hold a lock upon it, and, should any exist, lists those processes and refuses to restore.  When overwriting a directory, every file beneath is checked.  \
+                Overwriting a file which a running process holds open, like a database or a log, may leave it corrupt, or may be immediately undone by that process.  \
+                Note: This option is only available in the RESTORE mode, and only applies when overwriting.")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("SENSITIVE_PATHS")
+                .long("sensitive-paths")
+                .takes_value(true)
+                .require_equals(true)
+                .use_value_delimiter(true)
+                .value_name("PREFIX")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("before overwriting any file beneath one of the directories specified, ask the user to confirm the overwrite of each such file by typing its file name, \
+                as a misselected version of a system file may leave the system unbootable.  Such files are listed together, and the restore only proceeds should each be confirmed.  \
+                Multiple directories may be specified delimited by a comma, ','.  Specify an empty value, \"--sensitive-paths=\"\", to never ask.  \
+                The default value is \"/boot,/etc,/bin,/sbin,/lib,/lib64,/usr\".  \
+                Note: This option only applies when overwriting, and, in batch restore mode, only when the user's consent is asked for.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("GUARD_SENSITIVE")
+                .long("guard-sensitive")
+                .help("in \"overwrite\" restore mode, should any file to be overwritten be beneath a sensitive path (see SENSITIVE_PATHS), \
+                take a precautionary snapshot before any overwrite action occurs, exactly as in \"guard\" mode.  \
+                Note: This is a ZFS only option.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("TRANSCRIPT")
+                .long("transcript")
+                .takes_value(true)
+                .require_equals(true)
+                .value_name("FILE")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("record the interactive session, each query typed, each file and snapshot version selected, each action taken, \
+                and the result of each restore, with the date and time, to the file specified, \
+                so that any recovery made during an incident is documented, as it happens, for any later postmortem.  \
+                A transcript is appended to, should the file already exist, and so may be shared by many sessions.")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("NO_CLONE")
+                .long("no-clone")
+                .help("when restoring or rolling forward, always copy the bytes of each file.  By default, on Linux, httm first attempts a reflink copy (FICLONE), \
+                and, for new files, an in-kernel copy (copy_file_range), which, on filesystems which support cloning, such as btrfs, XFS, and ZFS with block cloning, \
+                makes restoring even very large files from snapshots nearly instantaneous, and free of any additional space.  \
+                httm falls back to copying bytes whenever the filesystem does not support cloning.")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("BATCH")
+                .long("batch")
+                .help("in restore mode, rather than browse and select interactively, restore each file listed on stdin, or in the file specified by FROM_FILE, one per line.  \
+                Each line consists of the live path and the path of the snapshot version to restore, separated by a tab character, \
+                where the snapshot version must be a snapshot version of that live path, or, when LAST_SNAP is specified, the live path alone, in which case httm restores the last snapshot version, per LAST_SNAP's value.  \
+                In \"overwrite\" and \"guard\" modes, each snapshot version is restored to its live path.  \
+                In \"copy\" modes, each snapshot version is restored beside its live path, with a new name, as in the interactive restore mode.  \
+                httm resolves every line before restoring any file, and will restore nothing should any line fail to resolve.  \
+                Useful for scripted recovery of many files at once.")
+                .requires("RESTORE")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "DELETED", "RECURSIVE", "HISTORY", "PREVIEW"])
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("FROM_FILE")
+                .long("from-file")
+                .takes_value(true)
+                .value_name("PATH")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("in batch restore mode, read the list of files to restore from the file specified, rather than from stdin.")
+                .requires("BATCH")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("YES")
+                .long("yes")
+                .help("in batch restore mode, restore without first asking for the user's consent.  \
+                Without this flag, httm asks for consent on the terminal, and will not restore should no terminal be available.")
+                .requires("BATCH")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("RESTORE_SUFFIX")
+                .long("restore-suffix")
+                .takes_value(true)
+                .require_equals(true)
+                .value_name("TEMPLATE")
+                .help("in the \"copy\" restore modes, name each restored file per the template specified, rather than append \".httm_restored.\" and a timestamp.  \
+                Within the template, {name} is replaced by the file's name, {stem} by its name without its extension, {ext} by its extension, including the leading '.', if any, \
+                {date} by the timestamp of the snapshot version, and {n} by a counter (eg. --restore-suffix=\"{stem}_{date}{ext}\", or --restore-suffix=\"{name}.{n}.bak\").  \
+                When the template contains {n}, httm counts up from 1 until it finds a name which is not already taken, otherwise httm will not restore over any file which already exists.  \
+                The default value is \"{name}.httm_restored.{date}\".")
+                .requires("RESTORE")
+                .conflicts_with("RESTORE_DEST")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("RESTORE_DEST")
+                .long("restore-dest")
+                .takes_value(true)
+                .require_equals(true)
+                .value_name("DIR")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("in the \"copy\" restore modes, rather than restore to the working directory, with a new name, restore to the directory specified, \
+                such as a staging area, with the original name.  The directory will be created, if it does not already exist.  \
+                When a single file is restored, the file is placed directly within the directory.  \
+                When multiple files are restored, their relative path structure is recreated within the directory, \
+                relative to the directory browsed, or, in batch mode, relative to the root directory.  \
+                httm will not restore over any file which already exists.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
+        .arg(
+            Arg::new("PRINT_CMD")
+                .long("print-cmd")
+                .help("in restore mode, rather than copying any file, print the shell command, properly quoted, which would perform the restore, \
+                so that the user may review the command, run the command themselves, or record the command elsewhere.  \
+                Note: the command printed may differ from httm's own restore in certain respects, such as how it handles ACLs and xattrs.")
+                .requires("RESTORE")
+                .display_order(4)
+        )
         .arg(
             Arg::new("DELETED")
                 .short('d')
                 .long("deleted")
                 .takes_value(true)
                 .default_missing_value("all")
-                .possible_values(["all", "single", "only"])
+                .possible_values(["all", "single", "only", "depth", "fast", "exhaustive"])
+                .use_value_delimiter(true)
                 .require_equals(true)
                 .min_values(0)
                 .require_equals(true)
                 .help("show deleted files in interactive modes.  In non-interactive modes, do a search for all files deleted from a specified directory. \
                 This argument optionally takes a value.  The default behavior/value is \"all\".  \
                 If \"only\" is specified, then, in the interactive modes, non-deleted files will be excluded from the search. \
-                If \"single\" is specified, then, deleted files behind deleted directories, (that is -- files with a depth greater than one) will be ignored.")
+                If \"single\" is specified, then, deleted files behind deleted directories, (that is -- files with a depth greater than one) will be ignored.  \
+                If \"depth\" is specified, then, even when not in recursive mode, httm will descend into directories which only exist on snapshots, \
+                so that the entire contents of a deleted subtree may be browsed.  \
+                The user may also specify how snapshots are scanned for deleted files, delimited from any mode by a comma, ',' (eg. --deleted=all,fast).  \
+                By default, the scan is \"exhaustive\", and every snapshot is read.  If \"fast\" is specified, httm reads snapshots newest first, \
+                and stops once many consecutive snapshots contain no new file names, which is much faster for datasets with thousands of snapshots, \
+                but may miss files deleted long ago.  A fast scan requires httm know the order in which snapshots were created, \
+                as it does for local ZFS, snapper and NILFS2 snapshots, and, where httm does not, every snapshot is read.")
+                .display_order(5)
+        )
+        .arg(
+            Arg::new("GHOSTS")
+                .long("ghosts")
+                .help("in the interactive browse view, show deleted entries, \"ghosts\", inline, immediately after the live entries of the same directory, \
+                dimmed and struck through, so that files which have vanished from a directory may be spotted without a separate deleted search.  \
+                Implies \"--deleted\", should no deleted mode be specified, and, like \"--deleted\", a deleted mode may be specified to limit the search.")
+                .conflicts_with_all(&["SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP"])
                 .display_order(5)
         )
         .arg(
@@ -208,22 +773,70 @@ fn parse_args() -> ArgMatches {
                 .help("recurse into the selected directory to find more files. Only available in interactive and deleted file modes.")
                 .display_order(6)
         )
+        .arg(
+            Arg::new("SHOW")
+                .long("show")
+                .takes_value(true)
+                .value_name("KIND")
+                .possible_values(["all", "files", "dirs", "deleted", "versioned"])
+                .require_equals(true)
+                .help("in the interactive browse view, show only entries of the kind given: \"files\", \"dirs\", \"deleted\", or \"versioned\", \
+                that is, files with at least one snapshot version.  The default value is \"all\".  \
+                While browsing, the user may cycle through each kind by pressing ctrl-o, or toggle showing only \
+                files, dirs, deleted, or versioned entries, by pressing alt-1, alt-2, alt-3, or alt-4, without starting the search over.  \
+                Deleted entries are only found when combined with \"--deleted\".")
+                .conflicts_with_all(&["SELECT", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP"])
+                .display_order(6)
+        )
         .arg(
             Arg::new("ALT_REPLICATED")
                 .short('a')
                 .long("alt-replicated")
                 .help("automatically discover locally replicated datasets and list their snapshots as well.  \
+                httm first looks for replicas named by ALT_STORE, or in any zrepl (\"zrepl.yml\"), pyznap (\"pyznap.conf\"), or syncoid (cron or systemd entries) config which it may read, \
+                then, upon ZFS, for datasets which share a snapshot with the source dataset, \
+                and otherwise, for datasets whose names end with the name of the source dataset, like \"tank/rpool\" for \"rpool\".  \
                 NOTE: Be certain such replicated datasets are mounted before use.  \
                 httm will silently ignore unmounted datasets in the interactive modes.")
                 .conflicts_with_all(&["REMOTE_DIR", "LOCAL_DIR"])
                 .display_order(7)
         )
+        .arg(
+            Arg::new("ALT_STORE")
+                .long("alt-store")
+                .help("manually map a source dataset to a locally replicated dataset, whatever their names, and list the replica's snapshots as well, as with ALT_REPLICATED, \
+                for instance, should a backup pool have a renamed hierarchy.  \
+                This option requires a value.  Such a value is delimited by an equals sign, '=', and is specified in the form <SOURCE_DATASET>=<REPLICA_DATASET> \
+                (eg. --alt-store tank/rpool=backup/laptop).  The children of the source dataset are mapped to the same children of the replica dataset.  \
+                Multiple maps may be specified delimited by a comma, ','.  \
+                Absent a user defined replica, httm discovers a ZFS replica which shares a snapshot with the source dataset, whatever its name.  \
+                This option implies ALT_REPLICATED.")
+                .use_value_delimiter(true)
+                .takes_value(true)
+                .conflicts_with_all(&["REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(7)
+        )
+        .arg(
+            Arg::new("SNAP_ORIGIN")
+                .long("snap-origin")
+                .takes_value(true)
+                .require_equals(true)
+                .possible_values(["local", "received"])
+                .help("list only those btrfs snapshots which were taken locally, \"local\", or only those which were received from another machine, \"received\", \
+                as determined by whether 'btrfs subvolume show' reports a received UUID for the snapshot.  \
+                Snapshots of datasets on other filesystems are unaffected.  \
+                NOTE: Determining the origin of a btrfs snapshot usually requires root privileges, and one 'btrfs' command per snapshot.  \
+                To instead label each snapshot version with its origin, see ANNOTATE.")
+                .display_order(7)
+        )
         .arg(
             Arg::new("PREVIEW")
                 .short('p')
                 .long("preview")
                 .help("user may specify a command to preview snapshots while in select view.  This argument optionally takes a value specifying the command to be executed.  \
                 The default value/command, if no command value specified, is a 'bowie' formatted 'diff'.  \
+                Should 'bowie' or a live version not be available, the default instead previews the snapshot version's contents, \
+                syntax highlighted if 'bat' is available, or as a hexdump, if the file is binary.  \
                 User defined commands must specify the snapshot file name \"{snap_file}\" and the live file name \"{live_file}\" within their shell command.")
                 .takes_value(true)
                 .min_values(0)
@@ -231,6 +844,39 @@ fn parse_args() -> ArgMatches {
                 .default_missing_value("default")
                 .display_order(8)
         )
+        .arg(
+            Arg::new("PREVIEW_SANDBOX")
+                .long("preview-sandbox")
+                .requires("PREVIEW")
+                .help("execute each preview command within a restricted environment, without network access, and with a read-only view of the filesystem, \
+                as a preview command is an arbitrary shell command, given the path of whatever file is selected, for instance, by an admin on a shared recovery host.  \
+                httm uses 'bwrap' (bubblewrap) to run the command with the whole filesystem bound read-only, a private \"/tmp\", and no network, in a new session.  \
+                Note: This option is only available on Linux, and may require unprivileged user namespaces.  Should 'bwrap' not be available, httm refuses to start.")
+                .display_order(8)
+        )
+        .arg(
+            Arg::new("UI")
+                .long("ui")
+                .takes_value(true)
+                .require_equals(true)
+                .possible_values(["skim", "native"])
+                .help("choose the frontend used for the interactive modes.  Possible values are: \
+                \"skim\", the default, a fuzzy finder with previews, and, \
+                \"native\", a simple picker which filters by substring, and which does not yet support previews.  \
+                Note: The \"native\" frontend is only available when httm is built with the \"native-ui\" feature.")
+                .display_order(8)
+        )
+        .arg(
+            Arg::new("ABORT_EXIT_CODE")
+                .long("abort-exit-code")
+                .value_name("CODE")
+                .takes_value(true)
+                .require_equals(true)
+                .help("the exit code httm should return when the user aborts any interactive session, \
+                so that wrapper scripts may distinguish a cancellation from success (0) and from an error (1).  \
+                This argument requires a value between 0 and 255.  The default value is 0.")
+                .display_order(8)
+        )
         .arg(
             Arg::new("UNIQUENESS")
                 .long("uniqueness")
@@ -252,7 +898,22 @@ fn parse_args() -> ArgMatches {
             Arg::new("EXACT")
                 .short('e')
                 .long("exact")
-                .help("use exact pattern matching for searches in the interactive modes (in contrast to the default fuzzy searching).")
+                .help("use exact pattern matching for searches in the interactive modes (in contrast to the default fuzzy searching).  \
+                In either case, the interactive query accepts skim's extended search syntax: terms separated by spaces must all match, \
+                terms separated by \" | \" match as alternatives, a term beginning with \"!\" excludes matches (eg. \"!target\"), \
+                a term beginning with \"'\" is matched exactly, even when searching fuzzily (eg. \"'exact\"), \
+                a term beginning with \"^\" matches at the start of the path (eg. \"^prefix\"), and a term ending with \"$\" matches at the end of the path (eg. \".conf$\").  \
+                The native UI, see \"--ui\", always matches exactly, but accepts the same syntax.")
+                .display_order(10)
+        )
+        .arg(
+            Arg::new("QUERY")
+                .long("query")
+                .takes_value(true)
+                .value_name("STRING")
+                .allow_hyphen_values(true)
+                .help("begin an interactive browse with the query given already entered, as if typed by the user (eg. --query \"!target .rs$\").  \
+                The query accepts the search syntax described under \"--exact\".")
                 .display_order(10)
         )
         .arg(
@@ -266,8 +927,40 @@ fn parse_args() -> ArgMatches {
                 .visible_aliases(&["snap-file", "snapshot", "snap-file-mount"])
                 .help("snapshot a file/s most immediate mount.  \
                 This argument optionally takes a value for a snapshot suffix.  The default suffix is 'httmSnapFileMount'.  \
-                Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges.")
-                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                Note: This is a ZFS only option which requires either superuser or 'zfs allow' privileges, see also ESCALATE.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(11)
+        )
+        .arg(
+            Arg::new("ESCALATE")
+                .long("escalate")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("auto")
+                .possible_values(["auto", "sudo", "doas"])
+                .help("should the user be neither root, nor have been delegated the necessary 'zfs allow' permissions upon a dataset, \
+                run the 'zfs' command, which takes or destroys a snapshot, or rolls back a dataset, via 'sudo' or 'doas', instead of simply failing.  \
+                Privileges are only escalated when necessary, and each time, 'sudo' or 'doas' may ask for a password.  \
+                This argument optionally takes a value.  The default value, \"auto\", uses 'sudo', if available, and otherwise 'doas'.  \
+                Note: This is a ZFS only option.  Should neither 'sudo' nor 'doas' be available, httm refuses to start.")
+                .display_order(11)
+        )
+        .arg(
+            Arg::new("MOUNT_SNAP")
+                .long("mount-snap")
+                .help("given the path of a snapshot version, mount the entire snapshot which contains that version to a temporary directory, and print the path of that directory.  \
+                Useful for exploring the entire snapshot, not just a single file.  ZFS snapshots are mounted via \"mount -t zfs\", and other snapshots are bind mounted.  \
+                Note: This option requires superuser privileges.  Use \"--unmount\" with the directory printed to unmount the snapshot when finished.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "UNMOUNT", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(11)
+        )
+        .arg(
+            Arg::new("UNMOUNT")
+                .long("unmount")
+                .help("unmount and remove a temporary directory upon which a snapshot was mounted by \"--mount-snap\".  \
+                Note: This option requires superuser privileges.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP"])
                 .display_order(11)
         )
         .arg(
@@ -300,7 +993,27 @@ fn parse_args() -> ArgMatches {
                 httm will also take two precautionary snapshots, one before and one after the copy.  \
                 Should the roll forward fail for any reason, httm will roll back to the pre-execution state.  \
                 Caveats: This is a ZFS only option which requires super user privileges.")
-                .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(13)
+        )
+        .arg(
+            Arg::new("DRY_RUN")
+                .long("dry-run")
+                .help("print the actions httm would take, without altering any files or taking any snapshots.  \
+                In the restore modes, httm prints, for each file, the snapshot version, its destination, its size in bytes, and whether the restore would create, \
+                overwrite, or make no change to the destination, and then exits with a status of 0 should nothing change, or of 6 should anything change.  \
+                Note: This option is only available in the ROLL_FORWARD, PRUNE_DITTO, and RESTORE modes.")
+                .display_order(13)
+        )
+        .arg(
+            Arg::new("ITEMIZE")
+                .long("itemize")
+                .visible_aliases(&["itemize-changes"])
+                .help("report each change as an rsync style itemized change code, followed by the path, one per line (eg. \">f.st......\" for a file whose size and modify time differ, \
+                \">f+++++++++\" for a new file, and \"*deleting  \" for a file which is gone), so that change types may be read at a glance, \
+                and tooling built for \"rsync --itemize-changes\" output may parse httm's reports.  \
+                Contents, size, modify time, permissions, owner, and group are compared, but ACLs, xattrs, and access times are not.  \
+                Note: This option is only available in the DIGEST mode, and in the RESTORE modes with DRY_RUN, other than \"metadata-only\".")
                 .display_order(13)
         )
         .arg(
@@ -312,10 +1025,21 @@ fn parse_args() -> ArgMatches {
                 This argument requires and will be filtered according to any values specified at LIST_SNAPS.  \
                 User may also enable SELECT mode to make a granular selection of specific snapshots to purge.  \
                 Note: This is a ZFS only option.")
-                .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                .conflicts_with_all(&["BROWSE", "RESTORE", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
                 .requires("LIST_SNAPS")
                 .display_order(13)
         )
+        .arg(
+            Arg::new("PRUNE_DITTO")
+                .long("prune-ditto")
+                .help("destroy each snapshot in which every version of the input file/s is identical to the live version (see OMIT_DITTO), via \"zfs destroy\" upon ZFS, \
+                or \"btrfs subvolume delete\" upon btrfs.  A snapshot which does not contain a version of each input file is never destroyed.  \
+                Destroying a snapshot is a DESTRUCTIVE operation which *does not* only apply to the files in question, but the entire snapshot.  \
+                Careless use may cause you to lose snapshot data you care about.  httm will ask for your consent before destroying any snapshot.  \
+                User may also specify DRY_RUN to print the snapshots which would be destroyed, and then exit with a status of 0 should there be none, or of 6 should there be any.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(13)
+        )
         .arg(
             Arg::new("FILE_MOUNT")
                 .short('m')
@@ -335,13 +1059,289 @@ fn parse_args() -> ArgMatches {
                 .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE"])
                 .display_order(14)
         )
+        .arg(
+            Arg::new("MAP")
+                .long("map")
+                .help("display, as JSON, each dataset httm has detected, including its underlying filesystem, mount point, filesystem type, \
+                whether it is a local or network mount, its snapshot directory, and its number of snapshots.  \
+                Useful for automation, and for determining why httm may not have found a dataset.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("FIND")
+                .long("find")
+                .takes_value(true)
+                .value_name("PATTERN")
+                .help("search for files, whose names match the pattern given, within every snapshot of the dataset which contains the input directory, \
+                and display each match along with the snapshot/s upon which it was found.  Useful when one doesn't remember where a lost file once lived.  \
+                The pattern is a glob, where '*' matches any characters and '?' matches any single character (eg. --find '*.conf' /home).  \
+                If no input directory is specified, httm searches beneath the working directory.  Only the portion of each snapshot beneath the input directory is searched.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("DIGEST")
+                .long("digest")
+                .takes_value(true)
+                .value_name("N")
+                .help("summarize which files, beneath the input directory, appeared, disappeared, or changed across the last N snapshots of the directory's dataset, \
+                where each snapshot is compared to the snapshot before it (eg. --digest 24 /srv/share).  A daily \"what happened here\" report.  \
+                If no input directory is specified, httm summarizes the working directory.  \
+                Snapshots are ordered by when each was taken, where ZFS, snapper, or NILFS2 can say, \
+                and otherwise, as httm will note, by the modify time of each snapshot's root directory.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "COMPLETE"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("WATCH")
+                .long("watch")
+                .takes_value(true)
+                .value_name("SECONDS")
+                .min_values(0)
+                .require_equals(true)
+                .help("keep running, and stream, as each appears, every new snapshot version of a file beneath the input directory, \
+                and every file deleted beneath the input directory, for which a snapshot version exists (eg. --watch=300 /srv/share).  \
+                Each event is printed on its own line, or, when combined with \"--json\", as a JSON object on its own line, for use by a dashboard or script.  \
+                httm searches for new snapshots every SECONDS seconds, and, on Linux, learns of deleted files at once, otherwise deleted files are reported once a newer snapshot no longer contains them.  \
+                If no input directory is specified, httm watches the working directory.  This argument requires a value greater than 0.  The default value is 60.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("CAT")
+                .long("cat")
+                .help("stream the content of a snapshot version of the input file to stdout, without a temporary file, \
+                so that it may be used directly in a pipeline (eg. httm --cat --as-of=yesterday ~/.zshrc | diff - ~/.zshrc).  \
+                By default, the latest snapshot version is streamed.  See \"--nth\" and \"--as-of\" to choose another version.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("FETCH_TO")
+                .long("fetch-to")
+                .takes_value(true)
+                .require_equals(true)
+                .value_name("PATH")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("rather than stream the version chosen by CAT to stdout, copy that version to the path specified, which must not already exist \
+                (eg. httm --cat --object-versions --nth=2 --fetch-to=./report.txt /mnt/bucket/report.txt).  \
+                This is how a prior version of an object, listed by OBJECT_VERSIONS, may be restored, as it is fetched by \"rclone copyto\".")
+                .requires("CAT")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("DIFF")
+                .long("diff")
+                .help("print a unified diff, as \"diff -u\" would, between two versions of the input file, without any need of a diff command.  \
+                Choose the two versions, among the snapshot versions and the live version, interactively (see SELECT, MULTIPLE), \
+                or, with \"--last-snap\", compare the last snapshot version to the live version, non-interactively.  \
+                The older version is always compared to the newer.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "ROBOT", "SERVE", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("VERIFY")
+                .long("verify")
+                .help("compare each input file to its latest snapshot version, and report any file whose contents differ, though its size and modify time \
+                are the same as the snapshot version's.  No ordinary write leaves a file so, therefore such a mismatch may indicate silent corruption, \
+                or a modification which was meant to go unnoticed.  Files whose size or modify time have changed are counted as modified, and are not read.  \
+                Only regular files are verified.  To verify a directory, pipe its files from \"find\".  \
+                If any file is mismatched, httm exits with exit code 5.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "DIFF", "ROBOT", "SERVE", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("TIMELINE")
+                .long("timeline")
+                .help("walk every snapshot of the input file's dataset, oldest to newest, and print each transition of the file: \
+                when it was created, modified (and by how much its size changed), deleted, or reappeared, and, last of all, \
+                any change made to the live version since the latest snapshot.  Snapshots in which nothing changed are not printed.  \
+                A file which exists in the oldest snapshot is shown as created in that snapshot.  \
+                Useful for answering \"when did this file break?\"")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "DIFF", "VERIFY", "ROBOT", "SERVE", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("SIZE_REPORT")
+                .long("size-report")
+                .help("for each input file, sum the bytes of each unique version which only its snapshots keep, that is, each version \
+                with a distinct size and modify time which is not also the live version, and list the files, largest first.  \
+                As copy-on-write snapshots share unchanged blocks, each sum is the most which pruning those versions could free.  \
+                On ZFS, the space the snapshots of each dataset occupy in total is reported as well.  \
+                Useful for finding which files are responsible for snapshot bloat before pruning (see PRUNE_DITTO and PURGE).  \
+                To report on a directory, pipe its files from \"find\".")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "DIFF", "VERIFY", "TIMELINE", "ROBOT", "SERVE", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("CHURN")
+                .long("churn")
+                .takes_value(true)
+                .value_name("N")
+                .min_values(0)
+                .require_equals(true)
+                .help("rank every file beneath the input directory by its churn, that is, by its number of unique snapshot versions, or, see CHURN_BY, \
+                by the total bytes of those versions, and print the top N files, as a leaderboard (eg. --churn=20 /var).  \
+                Useful for finding runaway log or database files which bloat snapshots.  Only versions modified within the window given by CHURN_SINCE, if any, are counted.  \
+                If no input directory is specified, httm ranks the files beneath the working directory.  \
+                This argument requires a value greater than 0.  The default value is 10.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "DIFF", "VERIFY", "TIMELINE", "SIZE_REPORT", "ROBOT", "SERVE", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("CHURN_BY")
+                .long("churn-by")
+                .takes_value(true)
+                .value_name("RANK")
+                .possible_values(["versions", "bytes"])
+                .require_equals(true)
+                .requires("CHURN")
+                .help("used with \"--churn\", rank files by their number of unique snapshot versions, \"versions\", \
+                or by the total bytes of those versions, \"bytes\".  The default value is \"versions\".")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("CHURN_SINCE")
+                .long("churn-since")
+                .takes_value(true)
+                .value_name("TIME")
+                .require_equals(true)
+                .requires("CHURN")
+                .help("used with \"--churn\", count only those snapshot versions modified at or after the time given.  \
+                The time is of the form \"YYYY-MM-DD\" or \"YYYY-MM-DD-HH:MM:SS\", in local time unless \"--utc\" is specified, \
+                or is one of \"now\", \"today\", \"yesterday\", or a number of minutes, hours, days, or weeks ago, like \"90m\", \"12h\", \"2d\", or \"1w\".")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("NTH")
+                .long("nth")
+                .takes_value(true)
+                .value_name("N")
+                .require_equals(true)
+                .requires("CAT")
+                .help("used with \"--cat\", stream the Nth most recent snapshot version, where 1 is the latest snapshot version, \
+                2 is the version before it, and so on.  Only unique versions are counted, see \"--uniqueness\".  \
+                This argument requires a value greater than 0.  The default value is 1.")
+                .conflicts_with("AS_OF")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("AS_OF")
+                .long("as-of")
+                .takes_value(true)
+                .value_name("TIME")
+                .require_equals(true)
+                .requires("CAT")
+                .help("used with \"--cat\", stream the version of the file as it was at the time given, that is, the latest version, \
+                including the live file, modified at or before that time.  The time is of the form \"YYYY-MM-DD\" or \"YYYY-MM-DD-HH:MM:SS\", \
+                in local time unless \"--utc\" is specified, or is one of \"now\", \"today\", \"yesterday\", \
+                or a number of minutes, hours, days, or weeks ago, like \"90m\", \"12h\", \"2d\", or \"1w\".")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("ROBOT")
+                .long("robot")
+                .help("speak a simple line based protocol, upon stdin and stdout, for use by GUI wrappers and editor plugins, \
+                so that a front end need not scrape httm's interactive views.  Each request, and each response, is a JSON object on its own line.  \
+                Once ready, httm writes a line with the protocol version.  A request names a command, \"cmd\", and may include an \"id\", which each response echoes.  \
+                The commands are: \"list-versions\", with a \"path\", which returns the live version and each unique snapshot version, \
+                \"preview\", with a \"path\" and, optionally, \"max_bytes\", which returns the beginning of the content of a version, \
+                \"restore\", with a snapshot version's \"path\" and, optionally, a \"mode\" of \"copy\", \"copy-and-preserve\", or \"overwrite\", \
+                a \"destination\" directory for a copy, and \"force\", and \"quit\".  Each response reports \"ok\", and, should \"ok\" be false, an \"error\".  \
+                A front end is expected to obtain the user's consent before any restore.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("SERVE")
+                .long("serve")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("127.0.0.1:8080")
+                .value_name("ADDRESS:PORT")
+                .help("serve snapshot versions, as JSON, over HTTP, at the address and port given, so that web dashboards, and other hosts, \
+                may query snapshot state without shell access.  The endpoints are: \"/versions?path=PATH\", which may be repeated for many paths, \
+                and which returns the live version and each unique snapshot version of each path, and \"/deleted?dir=DIR\", \
+                which returns the snapshot versions of each file deleted from the directory.  Each returns the same JSON as \"--json\" would, \
+                and other display options, like \"--omit-ditto\" and \"--raw\", apply as they would otherwise.  Paths must be absolute.  \
+                Nothing may be restored.  Note: Any who may connect may see any file httm may see, so httm will only serve upon a loopback address, \
+                unless SERVE_REMOTE is also given.  The default value is \"127.0.0.1:8080\".")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "ROBOT", "SELF_TEST", "SELECT_SNAPS"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("SERVE_REMOTE")
+                .long("serve-remote")
+                .requires("SERVE")
+                .help("allow SERVE to serve upon an address other than a loopback address, such that other hosts may connect.  \
+                Note: httm has no means to authenticate a client, so any who may connect may see any file httm may see.")
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("SELECT_SNAPS")
+                .long("select-snaps")
+                .visible_aliases(&["snap-select"])
+                .help("interactively select among all the snapshots of the dataset which contains the input file, whether or not the file differs between them, \
+                and print the names of the snapshots selected, one per line, for use by a command which prunes, mounts, or sends snapshots.  \
+                Each snapshot is listed with its creation time, and, for ZFS, the space it uses.  For ZFS, the name printed is of the form \"pool/dataset@snapname\", \
+                and, for other filesystems, which have no such names, the location of the snapshot is printed instead.  \
+                If no input file is specified, httm lists the snapshots of the working directory's dataset.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "ROBOT", "SELF_TEST"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("SELF_TEST")
+                .long("self-test")
+                .help("check, step by step, whether httm can find the snapshot versions of a file in the input directory.  \
+                httm creates a scratch file in the directory, takes a snapshot of the directory's dataset (ZFS only, see \"--snap\"), modifies the scratch file, \
+                and then verifies that both the snapshot version and the modified live version can be found.  \
+                Each step is reported as it completes, and any step which cannot be performed, for instance, for want of privileges, is reported as skipped.  \
+                The scratch file is removed afterwards, but the snapshot is not, and httm prints the command to destroy it.  \
+                If no input directory is specified, httm tests the working directory.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE", "DEDUP_RATIO", "BOOKMARK", "BOOKMARKS", "WATCH", "CAT", "ROBOT"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("DEDUP_RATIO")
+                .long("dedup-ratio")
+                .help("for each input file, report how much of its content is shared, block-wise, with its latest snapshot version, \
+                and how much is unique to that snapshot version.  Useful for estimating the real cost, in disk space, of keeping a version of a large file.  \
+                The file and its snapshot version are compared block by block, at the same offsets, using the block size the filesystem reports for the file, \
+                as copy-on-write filesystems share only those blocks which remain unchanged.  This is an estimate: compression, deduplication, \
+                and blocks shared with other snapshots are not taken into account.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE", "UNDO_LAST_RESTORE"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("COMPLETE")
+                .long("complete")
+                .takes_value(true)
+                .value_name("PREFIX")
+                .allow_hyphen_values(true)
+                .help("print the names of files which begin with the prefix given, and which exist on any snapshot of the directory the prefix names, \
+                one per line, for use by shell completion functions (eg. to complete the arguments of a custom restore function, even for deleted files).  \
+                Like a shell, the last component of the prefix is completed, directories end with a '/', and hidden files are only completed if the prefix begins with a '.'.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST"])
+                .display_order(14)
+        )
+        .arg(
+            Arg::new("UNDO_LAST_RESTORE")
+                .long("undo-last-restore")
+                .help("revert the most recent overwrite restore, by putting back the content each restored file had before it was overwritten.  \
+                httm records every restore it performs (the source, the destination, a hash of any pre-existing file, and the time) in an append-only journal, \
+                \"restore_journal\", in its data directory (usually ~/.local/share/httm/), and, before an overwrite, stashes the pre-existing file beside it.  \
+                Files which have been modified since the restore are skipped.  Only the stashes of the most recent 10 overwrite restores are kept.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "MOUNT_SNAP", "UNMOUNT", "FIND", "DIGEST", "COMPLETE"])
+                .display_order(14)
+        )
         .arg(
             Arg::new("LAST_SNAP")
                 .short('l')
                 .long("last-snap")
                 .takes_value(true)
                 .default_missing_value("any")
-                .possible_values(["any", "ditto", "no-ditto", "no-ditto-exclusive", "no-ditto-inclusive", "none", "without"])
                 .min_values(0)
                 .require_equals(true)
                 .help("automatically select and print the path of last-in-time unique snapshot version for the input file.  \
@@ -350,8 +1350,45 @@ fn parse_args() -> ArgMatches {
                 \"ditto\", return only last snaps which are the same as the live file version, \
                 \"no-ditto-exclusive\", return only a last snap which is not the same as the live version (argument \"--no-ditto\" is an alias for this option), \
                 \"no-ditto-inclusive\", return a last snap which is not the same as the live version, or should none exist, return the live file, and, \
-                \"none\" or \"without\", return the live file only for those files without a last snapshot.")
-                .conflicts_with_all(&["NUM_VERSIONS", "SNAPSHOT", "FILE_MOUNT", "ALT_REPLICATED", "REMOTE_DIR", "LOCAL_DIR"])
+                \"none\" or \"without\", return the live file only for those files without a last snapshot, and, \
+                \"before:<timestamp>\", return the last snapshot version modified before the given timestamp, \
+                where the timestamp is of the form \"YYYY-MM-DD\" or \"YYYY-MM-DD-HH:MM:SS\", in local time unless \"--utc\" is specified, \
+                or is one of \"now\", \"today\", \"yesterday\", or a number of minutes, hours, days, or weeks ago, like \"90m\", \"12h\", \"2d\", or \"1w\".")
+                .conflicts_with_all(&["NUM_VERSIONS", "SNAPSHOT", "FILE_MOUNT", "ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR"])
+                .display_order(15)
+        )
+        .arg(
+            Arg::new("HISTORY")
+                .long("history")
+                .help("display a history of snapshot versions previously selected or restored, paths previously browsed, and previous browse queries.  \
+                When combined with \"--select\", the user may select previous selections to print again, \
+                and, when combined with \"--restore\", the user may select previous snapshot versions to restore again.  \
+                History is stored in the user's XDG data directory, usually \"~/.local/share/httm/history\", \
+                unless the environment variable HTTM_DATA_DIR is set.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "GROUP_IDENTICAL"])
+                .display_order(15)
+        )
+        .arg(
+            Arg::new("BOOKMARK")
+                .long("bookmark")
+                .takes_value(true)
+                .value_name("ACTION")
+                .possible_values(["add", "remove", "list"])
+                .help("add the input paths to, or remove the input paths from, a persistent list of bookmarks, \
+                paths one recovers again and again, like a config file (eg. --bookmark add /etc/nginx/nginx.conf), or list all bookmarks.  \
+                If no input path is given, httm adds or removes the working directory.  \
+                Bookmarks are stored in the user's XDG data directory, usually \"~/.local/share/httm/bookmarks\", \
+                unless the environment variable HTTM_DATA_DIR is set.")
+                .conflicts_with_all(&["BROWSE", "SELECT", "RESTORE", "DELETED", "RECURSIVE", "LAST_SNAP", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP"])
+                .display_order(15)
+        )
+        .arg(
+            Arg::new("BOOKMARKS")
+                .long("bookmarks")
+                .help("pick a bookmarked path, see \"--bookmark\", and jump straight to selecting a snapshot version of that path, \
+                or, should the bookmark be a directory, to browsing that directory.  \
+                When combined with \"--restore\", the user may restore the snapshot version selected.  Otherwise, as with \"--select\", the version selected is printed.")
+                .conflicts_with_all(&["INPUT_FILES", "BROWSE", "DELETED", "RECURSIVE", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "NUM_VERSIONS", "HISTORY", "MAP", "BOOKMARK"])
                 .display_order(15)
         )
         .arg(
@@ -379,6 +1416,20 @@ fn parse_args() -> ArgMatches {
                 .conflicts_with_all(&["RAW", "ZEROS"])
                 .display_order(18)
         )
+        .arg(
+            Arg::new("RELATIVE")
+                .long("relative")
+                .value_name("ROOT")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("display snapshot and live paths relative to a root directory, in the formatted, raw, and JSON outputs.  \
+                This argument optionally takes a value.  The default value is the requested directory, or, \
+                if there is no requested directory, the current working directory.  Paths not beneath the root are displayed in full.")
+                .display_order(18)
+        )
         .arg(
             Arg::new("JSON")
                 .long("json")
@@ -386,10 +1437,103 @@ fn parse_args() -> ArgMatches {
                 .conflicts_with_all(&["SELECT", "RESTORE"])
                 .display_order(19)
         )
+        .arg(
+            Arg::new("WITH_DIFFSTAT")
+                .long("with-diffstat")
+                .aliases(&["diffstat"])
+                .requires("JSON")
+                .help("in the JSON display mode, summarize how each version changed since the version before it.  \
+                For text files, the summary is the number of bytes added and removed, line by line, regardless of where a line moved.  \
+                For binary files, and for files larger than 8MiB, the summary is the change in size.  \
+                Because every version must be read, this may be slow for files with many versions.")
+                .conflicts_with_all(&["NUM_VERSIONS", "LAST_SNAP"])
+                .display_order(19)
+        )
+        .arg(
+            Arg::new("OUTPUT")
+                .long("output")
+                .takes_value(true)
+                .require_equals(true)
+                .possible_values(["csv", "tsv"])
+                .help("display the ordinary output, but as a table of delimited values, one row per version, with a header row, \
+                so that versions may be loaded into a spreadsheet, or processed with awk.  \
+                \"csv\" delimits values with a comma, and quotes any value which contains a comma, a quotation mark, or a newline.  \
+                \"tsv\" delimits values with a tab, and escapes any tab, newline, or backslash within a value.")
+                .conflicts_with_all(&["NUM_VERSIONS", "LAST_SNAP", "JSON", "GROUP_IDENTICAL", "RAW", "ZEROS", "NOT_SO_PRETTY", "BROWSE", "SELECT", "RESTORE"])
+                .display_order(19)
+        )
+        .arg(
+            Arg::new("COLUMNS")
+                .long("columns")
+                .takes_value(true)
+                .min_values(1)
+                .require_equals(true)
+                .use_value_delimiter(true)
+                .requires("OUTPUT")
+                .possible_values(["path", "snap", "mtime", "size", "dataset"])
+                .help("the columns of the OUTPUT table, in the order given, delimited by a comma, ',' (eg. --columns=path,mtime,size).  \
+                \"path\" is the live path, \"snap\" is the location of the snapshot version, which is empty for the live version, \
+                \"mtime\" is the modify time of the version in ISO 8601 format, \"size\" is the size of the version in bytes, \
+                and \"dataset\" is the dataset, or subvolume, upon which the version resides.  \
+                The default value is path,snap,mtime,size,dataset.")
+                .display_order(19)
+        )
+        .arg(
+            Arg::new("FORMAT")
+                .long("format")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .require_equals(true)
+                .help("display each version on a line of its own, composed as the format string given, instead of as the display table \
+                (eg. --format='%p\\t%s\\t%m\\t%n').  Within the format string, \"%p\" is the live path, \"%v\" is the location of the version, \
+                \"%s\" is the size of the version in bytes, \"%m\" is the modify time of the version, as in the display table (see \"--human\"), \
+                \"%n\" is the name of the snapshot, which is empty for the live version, \"%d\" is the dataset upon which the version resides, \
+                and \"%%\" is a literal '%'.  The escapes \"\\t\", \"\\n\", \"\\0\", and \"\\\\\" are also understood.  \
+                When combined with \"--last-snap\", only the last snapshot version of each file is displayed.")
+                .conflicts_with_all(&["NUM_VERSIONS", "OUTPUT", "JSON", "GROUP_IDENTICAL", "RAW", "ZEROS", "NOT_SO_PRETTY", "BROWSE", "SELECT", "RESTORE"])
+                .display_order(19)
+        )
+        .arg(
+            Arg::new("ANNOTATE")
+                .long("annotate")
+                .takes_value(true)
+                .min_values(1)
+                .require_equals(true)
+                .use_value_delimiter(true)
+                .help("annotate each snapshot version with the values of the ZFS user properties specified, such as those set by backup and \
+                replication tools to describe a snapshot's job or retention class (eg. --annotate=zrepl:job,com.sun:auto-snapshot-desc).  \
+                Multiple properties may be specified delimited by a comma, ','.  Properties which are unset on a snapshot are omitted.  \
+                For snapshots of btrfs datasets, the pseudo-properties \"btrfs:origin\" (\"local\" or \"received\"), \"btrfs:uuid\", \"btrfs:parent_uuid\", and \"btrfs:received_uuid\", \
+                as reported by 'btrfs subvolume show', may be specified instead.  \
+                Annotations are only available for snapshots of ZFS and btrfs datasets, and only in the ordinary and JSON display modes.")
+                .conflicts_with_all(&["RAW", "ZEROS", "NUM_VERSIONS", "LAST_SNAP"])
+                .display_order(19)
+        )
+        .arg(
+            Arg::new("SNAP_INFO")
+                .long("snap-info")
+                .aliases(&["snap-name", "show-snap-name"])
+                .help("display the name of the snapshot, and the dataset, or subvolume, from which each version came, \
+                as columns of the display table, and as the \"snap_name\" and \"dataset\" fields of JSON output, \
+                so that one may tell, for instance, an hourly from a monthly snapshot.  \
+                The snapshot name is parsed from the location of the version, that is, from the name of the directory beneath the hidden snapshot directory \
+                of a ZFS dataset, or, for btrfs, from the name of the snapshot subvolume.  The live version has no snapshot name, and is displayed with a \"-\".")
+                .conflicts_with_all(&["RAW", "ZEROS", "NUM_VERSIONS", "LAST_SNAP"])
+                .display_order(19)
+        )
         .arg(
             Arg::new("OMIT_DITTO")
                 .long("omit-ditto")
-                .help("omit display of the snapshot version which may be identical to the live version (`httm` ordinarily displays all snapshot versions and the live version).")
+                .takes_value(true)
+                .default_missing_value("metadata")
+                .possible_values(["mtime", "metadata", "contents"])
+                .min_values(0)
+                .require_equals(true)
+                .help("omit display of the snapshot version which may be identical to the live version (`httm` ordinarily displays all snapshot versions and the live version).  \
+                This argument optionally takes a value which determines how a snapshot version is judged identical to the live version, here and for \"--last-snap\".  \
+                Possible values are: \"metadata\", the same modify time and size, this is the default behavior/value, \
+                \"mtime\", the same modify time only, and, \
+                \"contents\", the same size and contents, whatever the modify time.  Note: \"contents\" requires reading both the snapshot and the live file.")
                 .conflicts_with_all(&["NUM_VERSIONS"])
                 .display_order(20)
         )
@@ -407,6 +1551,30 @@ fn parse_args() -> ArgMatches {
                 .help("never show information regarding hidden files and directories (those that start with a \'.\') in the recursive or interactive modes.")
                 .display_order(22)
         )
+        .arg(
+            Arg::new("EXCLUDE")
+                .long("exclude")
+                .value_name("GLOB")
+                .help("never show information regarding files and directories whose names match the given glob pattern in the recursive or interactive modes, \
+                including deleted files found on snapshots, and never descend into such directories (eg. --exclude=node_modules --exclude='*.o').  \
+                Within the pattern, '*' matches any run of characters, and '?' matches any single character.  \
+                Multiple patterns may be specified delimited by a comma, ',', or by specifying this argument more than once.  Any exclusions are disabled by \"--no-filter\".")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .use_value_delimiter(true)
+                .require_equals(true)
+                .display_order(22)
+        )
+        .arg(
+            Arg::new("RESPECT_GITIGNORE")
+                .long("respect-gitignore")
+                .help("never show information regarding files and directories ignored by a \".gitignore\" in the recursive or interactive modes, \
+                including deleted files found on snapshots, and never descend into such directories, nor into any \".git\" directory.  \
+                Each \".gitignore\" from the directory of a file up to the root of its repository is consulted, as git would.  \
+                Most, but not all, gitignore patterns are supported: global excludes, and character classes, like \"[abc]\", are not.  \
+                Any exclusions are disabled by \"--no-filter\".")
+                .display_order(22)
+        )
         .arg(
             Arg::new("ONE_FILESYSTEM")
                 .long("one-filesystem")
@@ -415,6 +1583,28 @@ fn parse_args() -> ArgMatches {
                 .help("limit recursive search to file and directories on the same filesystem/device as the target directory.")
                 .display_order(23)
         )
+        .arg(
+            Arg::new("MAX_DEPTH")
+                .long("max-depth")
+                .takes_value(true)
+                .value_name("N")
+                .require_equals(true)
+                .help("in the recursive or interactive modes, never descend more than N directories below the target directory, \
+                whether those directories are live, or were deleted and are found on snapshots.  The entries of the target directory itself are at depth 1.  \
+                This argument requires a value greater than 0.  By default, there is no limit.")
+                .display_order(23)
+        )
+        .arg(
+            Arg::new("MAX_RESULTS")
+                .long("max-results")
+                .takes_value(true)
+                .value_name("N")
+                .require_equals(true)
+                .help("in the recursive or interactive modes, stop searching once N entries, live or deleted, have been shown.  \
+                Useful when a directory hierarchy is enormous, or when a script only needs so many results.  \
+                This argument requires a value greater than 0.  By default, there is no limit.")
+                .display_order(23)
+        )
         .arg(
             Arg::new("NO_TRAVERSE")
                 .long("no-traverse")
@@ -422,6 +1612,27 @@ fn parse_args() -> ArgMatches {
                 here, you may disable symlink traversal completely.  NOTE: httm will never traverse symlinks when a requested recursive search is on the root/base directory (\"/\").")
                 .display_order(24)
         )
+        .arg(
+            Arg::new("NETWORK_CONCURRENCY")
+                .long("network-concurrency")
+                .takes_value(true)
+                .value_name("N")
+                .require_equals(true)
+                .help("the maximum number of batches of stats, or reads of snapshot directories, httm will have in flight at once upon remote filesystems, such as NFS, SMB and AFP mounts, \
+                when searching for versions, or scanning for deleted files.  Thousands of parallel stats can overwhelm a file server.  Local filesystems are never limited.  \
+                This argument requires a value greater than 0.  The default value is 4.")
+                .display_order(24)
+        )
+        .arg(
+            Arg::new("NETWORK_BATCH")
+                .long("network-batch")
+                .takes_value(true)
+                .value_name("N")
+                .require_equals(true)
+                .help("the number of stats, or reads of snapshot directories, httm performs one after another, within each batch upon a remote filesystem.  \
+                See \"--network-concurrency\".  This argument requires a value greater than 0.  The default value is 32.")
+                .display_order(24)
+        )
         .arg(
             Arg::new("NO_LIVE")
                 .long("no-live")
@@ -444,21 +1655,62 @@ fn parse_args() -> ArgMatches {
                 .long("map-aliases")
                 .visible_aliases(&["aliases"])
                 .help("manually map a local directory (eg. \"/Users/<User Name>\") as an alias of a mount point for ZFS or btrfs, \
+                or of a share which exposes its snapshots in a \".snapshot\" or \"~snapshot\" directory, such as those of many NAS appliances, \
                 such as the local mount point for a backup on a remote share (eg. \"/Volumes/Home\").  \
                 This option is useful if you wish to view snapshot versions from within the local directory you back up to your remote share.  \
                 This option requires a value.  Such a value is delimited by a colon, ':', and is specified in the form <LOCAL_DIR>:<REMOTE_DIR> \
                 (eg. --map-aliases /Users/<User Name>:/Volumes/Home).  Multiple maps may be specified delimited by a comma, ','.  \
-                You may also set via the environment variable HTTM_MAP_ALIASES.")
+                You may also set via the environment variable HTTM_MAP_ALIASES, which is ignored when aliases are given as an argument.")
                 .use_value_delimiter(true)
                 .takes_value(true)
                 .value_parser(clap::builder::ValueParser::os_string())
                 .display_order(27)
         )
+        .arg(
+            Arg::new("PREFER")
+                .long("prefer")
+                .takes_value(true)
+                .possible_values(["alias", "native", "both"])
+                .require_equals(true)
+                .help("when a path is covered both by a user defined alias (see MAP_ALIASES) and a detected native dataset (ZFS, btrfs, etc.), \
+                choose which provider httm should search for snapshot versions.  By default, httm prefers the \"alias\".  \
+                The \"native\" value ignores any alias and uses only the most proximate native dataset, and \"both\" searches, and merges the results of, both providers.")
+                .display_order(27)
+        )
+        .arg(
+            Arg::new("ZFS_LIST_CACHE")
+                .long("zfs-list-cache")
+                .value_name("PATH")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .default_missing_value("")
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("build the collection of ZFS datasets from the cache files written for systemd's zfs-mount-generator, \
+                instead of parsing the system's mounts, which may be much faster on systems with thousands of datasets.  \
+                This argument optionally takes a value, a cache file or a directory of cache files.  The default value is \"/etc/zfs/zfs-list.cache\".  \
+                Only datasets which are actually mounted are used, and, should none be found, httm falls back to parsing mounts.  \
+                Note: As the cache lists only ZFS datasets, no btrfs or nilfs2 datasets will be found, and other filesystems will not be filtered.  \
+                You may also set via the environment variable HTTM_ZFS_LIST_CACHE.")
+                .display_order(27)
+        )
+        .arg(
+            Arg::new("INDEX")
+                .long("index")
+                .help("remember, in a persistent index, which snapshots contain which files, and the metadata of each version found, \
+                so that repeated lookups, upon datasets with thousands of snapshots, need not stat the same path upon every snapshot again.  \
+                Only snapshots not yet indexed, like newly taken snapshots, are searched, and snapshots which no longer exist are forgotten.  \
+                The index is kept in httm's cache directory.  \
+                Each answer is recorded with the identity of its snapshot, for ZFS, the snapshot's guid, and, otherwise, the inode and birth time of the snapshot's root, \
+                so, should your snapshots rotate names, like \"daily.0\", a snapshot now of the same name is searched again.  \
+                The index is written once, as httm exits.")
+                .display_order(27)
+        )
         .arg(
             Arg::new("NUM_VERSIONS")
                 .long("num-versions")
                 .default_missing_value("all")
-                .possible_values(["all", "graph", "single", "single-no-snap", "single-with-snap", "multiple"])
+                .possible_values(["all", "graph", "single", "single-no-snap", "single-with-snap", "ditto-only", "multiple"])
                 .min_values(0)
                 .require_equals(true)
                 .help("detect and display the number of unique versions available (e.g. one, \"1\", \
@@ -466,11 +1718,22 @@ fn parse_args() -> ArgMatches {
                 This argument optionally takes a value.  The default value, \"all\", will print the filename and number of versions, \
                 \"graph\" will print the filename and a line of characters representing the number of versions, \
                 \"single\" will print only filenames which only have one version, \
-                (and \"single-no-snap\" will print those without a snap taken, and \"single-with-snap\" will print those with a snap taken), \
+                (and \"single-no-snap\" will print those without a snap taken, and \"single-with-snap\" or \"ditto-only\" will print those with a snap taken, \
+                that is, those whose only snapshot version is identical to the live version), \
                 and \"multiple\" will print only filenames which only have multiple versions.")
                 .conflicts_with_all(&["LAST_SNAP", "BROWSE", "SELECT", "RESTORE", "RECURSIVE", "SNAPSHOT", "NOT_SO_PRETTY", "NO_LIVE", "NO_SNAP", "OMIT_DITTO", "RAW", "ZEROS"])
                 .display_order(28)
         )
+        .arg(
+            Arg::new("GROUP_IDENTICAL")
+                .long("group-identical")
+                .help("group the versions of each file requested by their contents, and display one line per distinct content, \
+                with the ranges of snapshots (and the live version) which contain that content.  \
+                Contents are compared by checksum, and any versions whose checksums match are then compared byte for byte, so this may be slow for large files.  \
+                Useful to see how many truly different versions of a file exist.")
+                .conflicts_with_all(&["NUM_VERSIONS", "LAST_SNAP", "BROWSE", "SELECT", "RESTORE", "RECURSIVE", "DELETED", "SNAPSHOT", "LIST_SNAPS", "PURGE", "ROLL_FORWARD", "FILE_MOUNT", "JSON", "ANNOTATE", "NO_LIVE", "NO_SNAP", "OMIT_DITTO", "RAW", "ZEROS"])
+                .display_order(28)
+        )
         .arg(
             Arg::new("REMOTE_DIR")
                 .long("remote-dir")
@@ -479,20 +1742,80 @@ fn parse_args() -> ArgMatches {
                 .help("DEPRECATED.  Use MAP_ALIASES. Manually specify that mount point for ZFS (directory which contains a \".zfs\" directory) or btrfs-snapper \
                 (directory which contains a \".snapshots\" directory), such as the local mount point for a remote share.  You may also set via the HTTM_REMOTE_DIR environment variable.")
                 .takes_value(true)
-                .value_parser(clap::builder::ValueParser::os_string())
-                .display_order(29)
+                .value_parser(clap::builder::ValueParser::os_string())
+                .display_order(29)
+        )
+        .arg(
+            Arg::new("LOCAL_DIR")
+                .long("local-dir")
+                .hide(true)
+                .visible_alias("local")
+                .help("DEPRECATED.  Use MAP_ALIASES.  Used with \"remote-dir\" to determine where the corresponding live root filesystem of the dataset is.  \
+                Put more simply, the \"local-dir\" is likely the directory you backup to your \"remote-dir\".  If not set, httm defaults to your current working directory.  \
+                You may also set via the environment variable HTTM_LOCAL_DIR.")
+                .requires("REMOTE_DIR")
+                .takes_value(true)
+                .value_parser(clap::builder::ValueParser::os_string())
+                .display_order(30)
+        )
+        .arg(
+            Arg::new("RELATIVE_TO")
+                .long("relative-to")
+                .value_name("DIR")
+                .takes_value(true)
+                .require_equals(true)
+                .value_parser(clap::builder::ValueParser::os_string())
+                .help("look up the snapshot versions of each input file as though the working directory were the directory given, \
+                that is, a file's path relative to the working directory is found beneath the directory given, and within that directory's snapshots, instead \
+                (eg. cd /backup/copy && httm --relative-to=/srv/data docs/report.txt, to compare a copy of a tree against the snapshots of the original).  \
+                The live version displayed remains the input file.  Input files not beneath the working directory are looked up as usual.")
+                .conflicts_with_all(&["RESTORE", "DELETED", "SNAPSHOT", "ROLL_FORWARD"])
+                .display_order(30)
+        )
+        .arg(
+            Arg::new("REMOTE")
+                .long("remote")
+                .value_name("[USER@]HOST:DIR")
+                .takes_value(true)
+                .require_equals(true)
+                .help("look up the snapshot versions of each input file on another host, over SSH, \
+                such as a backup server which holds the only replica of a dataset.  \
+                As with RELATIVE_TO, a file's path relative to the working directory is found beneath the remote directory given \
+                (eg. cd /srv/data && httm --remote=backup@nas:/tank/replica/data docs/report.txt).  \
+                The remote host must have an httm in the remote user's PATH, which is run as \"httm --robot --raw\", see ROBOT, or see REMOTE_COMMAND.  \
+                Only available in Display and Num Versions modes.")
+                .conflicts_with_all(&["ALT_REPLICATED", "ALT_STORE", "REMOTE_DIR", "LOCAL_DIR", "MAP_ALIASES", "RELATIVE_TO"])
+                .display_order(30)
+        )
+        .arg(
+            Arg::new("REMOTE_COMMAND")
+                .long("remote-command")
+                .value_name("COMMAND")
+                .takes_value(true)
+                .require_equals(true)
+                .requires("REMOTE")
+                .help("the httm to run on the remote host given to REMOTE, for instance, should httm not be in the remote user's PATH \
+                (eg. --remote-command=/usr/local/bin/httm).  The command is given to the remote user's shell, followed by \"--robot --raw\".  \
+                The default value is \"httm\".")
+                .display_order(30)
         )
         .arg(
-            Arg::new("LOCAL_DIR")
-                .long("local-dir")
-                .hide(true)
-                .visible_alias("local")
-                .help("DEPRECATED.  Use MAP_ALIASES.  Used with \"remote-dir\" to determine where the corresponding live root filesystem of the dataset is.  \
-                Put more simply, the \"local-dir\" is likely the directory you backup to your \"remote-dir\".  If not set, httm defaults to your current working directory.  \
-                You may also set via the environment variable HTTM_LOCAL_DIR.")
-                .requires("REMOTE_DIR")
+            Arg::new("OBJECT_VERSIONS")
+                .long("object-versions")
+                .value_name("MOUNT=REMOTE")
                 .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .use_value_delimiter(true)
                 .value_parser(clap::builder::ValueParser::os_string())
+                .help("list the prior versions of each input file beneath the mount of an object store which keeps each version of an object, \
+                such as a versioned S3 or B2 bucket, mounted via rclone or s3fs.  Versions are listed, and, with CAT, streamed, or, with FETCH_TO, fetched, by \"rclone\", \
+                which must be in your PATH, and are displayed as rclone names them (eg. s3:bucket/report-v2023-06-01-123456-000.txt).  \
+                Each rclone mount is found automatically, as its mount source is its rclone remote.  Any other mount, such as an s3fs mount, \
+                must be given the rclone remote of its bucket, in the form <MOUNT>=<REMOTE> (eg. --object-versions=/mnt/bucket=s3:bucket).  \
+                Multiple such values may be specified delimited by a comma, ','.  Input files not beneath any such mount are looked up as usual.  \
+                Only available in Display, Num Versions, and Cat modes.")
+                .conflicts_with_all(&["REMOTE", "RELATIVE_TO"])
                 .display_order(30)
         )
         .arg(
@@ -501,6 +1824,27 @@ fn parse_args() -> ArgMatches {
                 .help("use UTC for date display and timestamps")
                 .display_order(31)
         )
+        .arg(
+            Arg::new("TZ")
+                .long("tz")
+                .takes_value(true)
+                .value_name("ZONE")
+                .help("use the time zone given, as named in the tz database (eg. --tz Europe/Berlin), for date display and timestamps, \
+                instead of the local time zone.  Like the local time zone, the zone's current offset from UTC is used for every date.  \
+                JSON output always includes the offset of each date, in ISO 8601 format, so results are unambiguous across machines.")
+                .conflicts_with("UTC")
+                .display_order(31)
+        )
+        .arg(
+            Arg::new("HUMAN")
+                .long("human")
+                .visible_aliases(&["ago"])
+                .help("in the display table, in the interactive select view, and in \"--format\" output, display the modify time of each version \
+                relative to now (eg. \"3 days ago\"), instead of as an absolute date.  \
+                Sizes are always displayed in binary units (eg. KiB, MiB, GiB).  \
+                JSON, delimited, and raw output are unaffected.")
+                .display_order(31)
+        )
         .arg(
             Arg::new("DEBUG")
                 .long("debug")
@@ -514,7 +1858,7 @@ fn parse_args() -> ArgMatches {
                 .exclusive(true)
                 .display_order(33)
         )
-        .get_matches()
+        .try_get_matches_from(args)
 }
 
 #[derive(Debug, Clone)]
@@ -522,30 +1866,112 @@ pub struct Config {
     pub paths: Vec<PathData>,
     pub opt_recursive: bool,
     pub opt_exact: bool,
+    pub opt_query: Option<String>,
     pub opt_no_filter: bool,
     pub opt_debug: bool,
     pub opt_no_traverse: bool,
     pub opt_omit_ditto: bool,
+    pub ditto_of_type: DittoOfType,
     pub opt_no_hidden: bool,
+    pub opt_exclude_globs: Option<Vec<String>>,
+    pub opt_respect_gitignore: bool,
+    pub opt_max_depth: Option<usize>,
+    pub opt_max_results: Option<usize>,
     pub opt_json: bool,
+    pub opt_annotate: Option<Vec<String>>,
+    pub opt_snap_info: bool,
+    pub opt_diffstat: bool,
+    pub opt_delimited: Option<DelimitedOutput>,
+    pub opt_custom_format: Option<CustomFormat>,
+    pub opt_group_identical: bool,
+    pub opt_history: bool,
+    pub opt_print_cmd: bool,
+    pub opt_print_snap: bool,
+    pub opt_restore_dest: Option<PathBuf>,
+    pub opt_fetch_to: Option<PathBuf>,
+    pub restore_suffix: RestoreSuffix,
+    pub opt_dir_times: bool,
+    pub opt_no_clone: bool,
+    pub opt_quiet: bool,
+    pub opt_force: bool,
+    pub opt_bookmarks: bool,
+    pub opt_ghosts: bool,
+    pub browse_filter: BrowseFilter,
+    pub ui_frontend: UiFrontend,
+    pub abort_exit_code: i32,
     pub opt_one_filesystem: bool,
+    pub opt_version_index: bool,
+    pub network_concurrency: usize,
+    pub network_batch_size: usize,
+    pub opt_dry_run: bool,
+    pub sensitive_paths: Vec<PathBuf>,
+    pub opt_guard_sensitive: bool,
+    pub opt_transcript: Option<PathBuf>,
+    pub opt_itemize: bool,
     pub uniqueness: ListSnapsOfType,
+    pub dataset_preference: DatasetPreference,
     pub opt_bulk_exclusion: Option<BulkExclusion>,
     pub opt_last_snap: Option<LastSnapMode>,
     pub opt_preview: Option<String>,
+    pub opt_preview_sandbox: Option<PreviewSandbox>,
+    pub opt_privilege_escalation: Option<PrivilegeEscalation>,
     pub opt_deleted_mode: Option<DeletedMode>,
+    pub deleted_scan: DeletedScan,
     pub opt_requested_dir: Option<PathData>,
+    pub opt_relative_root: Option<PathBuf>,
+    pub opt_relative_to: Option<RelativeTo>,
+    pub opt_remote_host: Option<RemoteHost>,
+    pub opt_object_stores: Option<Vec<ObjectStore>>,
     pub requested_utc_offset: UtcOffset,
+    pub opt_human: bool,
     pub exec_mode: ExecMode,
     pub print_mode: PrintMode,
-    pub dataset_collection: FilesystemInfo,
+    pub dataset_collection: Arc<FilesystemInfo>,
     pub pwd: PathData,
 }
 
 impl Config {
     pub fn new() -> HttmResult<Self> {
-        let arg_matches = parse_args();
-        let config = Config::from_matches(&arg_matches)?;
+        let args: Vec<OsString> = std::env::args_os().collect();
+
+        // only our own program args, unlike a library consumer's, should print help, or a usage error, and exit
+        let user_matches = parse_args(args.clone()).unwrap_or_else(|error| error.exit());
+
+        match ConfigFile::new()? {
+            Some(config_file) => {
+                let file_args = config_file.default_args(&user_matches)?;
+
+                // values from the config file are placed after the program name, but ahead of any user args,
+                // as any following "--" would otherwise make them input files
+                let merged_args = args
+                    .iter()
+                    .take(1)
+                    .cloned()
+                    .chain(file_args)
+                    .chain(args.iter().skip(1).cloned());
+
+                let merged_matches = parse_args(merged_args).unwrap_or_else(|error| error.exit());
+
+                Self::from_parsed(&merged_matches)
+            }
+            None => Self::from_parsed(&user_matches),
+        }
+    }
+
+    // allows library consumers to generate a config from their own args,
+    // in the same form as those given on the command line, "httm --no-filter"
+    pub fn from_args<I, T>(args: I) -> HttmResult<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let arg_matches = parse_args(args)?;
+
+        Self::from_parsed(&arg_matches)
+    }
+
+    fn from_parsed(arg_matches: &ArgMatches) -> HttmResult<Self> {
+        let config = Config::from_matches(arg_matches)?;
         if config.opt_debug {
             eprintln!("{config:#?}");
         }
@@ -559,6 +1985,8 @@ impl Config {
 
         let requested_utc_offset = if matches.is_present("UTC") {
             UtcOffset::UTC
+        } else if let Some(time_zone) = matches.value_of("TZ") {
+            time_zone_offset(time_zone)?
         } else {
             // this fn is surprisingly finicky. it needs to be done
             // when program is not multithreaded, etc., so we don't even print an
@@ -568,6 +1996,49 @@ impl Config {
 
         let opt_json = matches.is_present("JSON");
 
+        let opt_human = matches.is_present("HUMAN");
+
+        let opt_annotate: Option<Vec<String>> = matches
+            .values_of("ANNOTATE")
+            .map(|properties| properties.map(|property| property.to_owned()).collect());
+
+        let opt_snap_info = matches.is_present("SNAP_INFO");
+
+        let opt_diffstat = matches.is_present("WITH_DIFFSTAT");
+
+        let opt_delimited = matches.value_of("OUTPUT").map(|value| {
+            let format = match value {
+                "tsv" => DelimitedFormat::Tsv,
+                _ => DelimitedFormat::Csv,
+            };
+
+            let columns = match matches.values_of("COLUMNS") {
+                Some(values) => values
+                    .map(|value| match value {
+                        "snap" => DelimitedColumn::Snap,
+                        "mtime" => DelimitedColumn::Mtime,
+                        "size" => DelimitedColumn::Size,
+                        "dataset" => DelimitedColumn::Dataset,
+                        _ => DelimitedColumn::Path,
+                    })
+                    .collect(),
+                None => vec![
+                    DelimitedColumn::Path,
+                    DelimitedColumn::Snap,
+                    DelimitedColumn::Mtime,
+                    DelimitedColumn::Size,
+                    DelimitedColumn::Dataset,
+                ],
+            };
+
+            DelimitedOutput { format, columns }
+        });
+
+        let opt_custom_format = match matches.value_of("FORMAT") {
+            Some(format_string) => Some(CustomFormat::parse(format_string)?),
+            None => None,
+        };
+
         let mut print_mode = if matches.is_present("ZEROS") {
             PrintMode::RawZero
         } else if matches.is_present("RAW") {
@@ -597,12 +2068,41 @@ impl Config {
 
         // force a raw mode if one is not set for no_snap mode
         let opt_one_filesystem = matches.is_present("ONE_FILESYSTEM");
+
+        let opt_version_index = matches.is_present("INDEX");
+
+        let network_concurrency =
+            Self::positive_number(matches, "NETWORK_CONCURRENCY", DEFAULT_NETWORK_CONCURRENCY)?;
+        let network_batch_size =
+            Self::positive_number(matches, "NETWORK_BATCH", DEFAULT_NETWORK_BATCH_SIZE)?;
         let opt_recursive = matches.is_present("RECURSIVE");
 
         let opt_exact = matches.is_present("EXACT");
+        let opt_query = matches.value_of("QUERY").map(|query| query.to_owned());
         let opt_no_filter = matches.is_present("NO_FILTER");
         let opt_debug = matches.is_present("DEBUG");
         let opt_no_hidden = matches.is_present("FILTER_HIDDEN");
+        let opt_exclude_globs: Option<Vec<String>> = matches
+            .values_of("EXCLUDE")
+            .map(|globs| globs.map(|glob| glob.to_owned()).collect());
+        let opt_respect_gitignore = matches.is_present("RESPECT_GITIGNORE");
+        let opt_max_depth = Self::opt_positive_number(matches, "MAX_DEPTH")?;
+        let opt_max_results = Self::opt_positive_number(matches, "MAX_RESULTS")?;
+        let opt_dry_run = matches.is_present("DRY_RUN");
+
+        let sensitive_paths: Vec<PathBuf> = match matches.values_of_os("SENSITIVE_PATHS") {
+            Some(values) => values
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+                .collect(),
+            None => DEFAULT_SENSITIVE_PATHS.iter().map(PathBuf::from).collect(),
+        };
+
+        let opt_guard_sensitive = matches.is_present("GUARD_SENSITIVE");
+
+        let opt_transcript: Option<PathBuf> = matches.value_of_os("TRANSCRIPT").map(PathBuf::from);
+
+        let opt_itemize = matches.is_present("ITEMIZE");
 
         let opt_last_snap = match matches.value_of("LAST_SNAP") {
             Some("" | "any") => Some(LastSnapMode::Any),
@@ -610,7 +2110,19 @@ impl Config {
             Some("ditto") => Some(LastSnapMode::DittoOnly),
             Some("no-ditto-inclusive") => Some(LastSnapMode::NoDittoInclusive),
             Some("no-ditto-exclusive" | "no-ditto") => Some(LastSnapMode::NoDittoExclusive),
-            _ => None,
+            Some(value) => match value.strip_prefix("before:") {
+                Some(timestamp) => Some(LastSnapMode::Before(parse_date_string(
+                    timestamp,
+                    requested_utc_offset,
+                )?)),
+                None => {
+                    let msg = format!(
+                        "{value:?} is not a valid value for LAST_SNAP.  Possible values are: \"any\", \"ditto\", \"no-ditto\", \"no-ditto-exclusive\", \"no-ditto-inclusive\", \"none\", \"without\", and \"before:<timestamp>\"."
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+            },
+            None => None,
         };
 
         let opt_num_versions = match matches.value_of("NUM_VERSIONS") {
@@ -618,7 +2130,7 @@ impl Config {
             Some("graph") => Some(NumVersionsMode::AllGraph),
             Some("single") => Some(NumVersionsMode::SingleAll),
             Some("single-no-snap") => Some(NumVersionsMode::SingleNoSnap),
-            Some("single-with-snap") => Some(NumVersionsMode::SingleWithSnap),
+            Some("single-with-snap" | "ditto-only") => Some(NumVersionsMode::SingleWithSnap),
             Some("multiple") => Some(NumVersionsMode::Multiple),
             _ => None,
         };
@@ -636,11 +2148,70 @@ impl Config {
             None => None,
         };
 
-        let mut opt_deleted_mode = match matches.value_of("DELETED") {
-            Some("" | "all") => Some(DeletedMode::All),
-            Some("single") => Some(DeletedMode::DepthOfOne),
-            Some("only") => Some(DeletedMode::Only),
-            _ => None,
+        // better to refuse to start than to run a preview outside the sandbox requested
+        // a sandbox which can't say whether each mount was made read-only is no sandbox, so only bwrap will do
+        let opt_preview_sandbox = if matches.is_present("PREVIEW_SANDBOX") {
+            match which::which("bwrap") {
+                Ok(bwrap) if cfg!(target_os = "linux") => Some(PreviewSandbox::Bwrap(bwrap)),
+                _ => {
+                    return Err(HttmError::new(
+                        "PREVIEW_SANDBOX requires 'bwrap' in the user's PATH, and is only available on Linux.",
+                    )
+                    .into())
+                }
+            }
+        } else {
+            None
+        };
+
+        let opt_privilege_escalation = match matches.value_of("ESCALATE") {
+            Some(value) => {
+                let opt_sudo = || which::which("sudo").ok().map(PrivilegeEscalation::Sudo);
+                let opt_doas = || which::which("doas").ok().map(PrivilegeEscalation::Doas);
+
+                let opt_escalation = match value {
+                    "sudo" => opt_sudo(),
+                    "doas" => opt_doas(),
+                    _ => opt_sudo().or_else(opt_doas),
+                };
+
+                match opt_escalation {
+                    Some(escalation) => Some(escalation),
+                    None => {
+                        return Err(HttmError::new(
+                            "ESCALATE requires 'sudo' or 'doas' in the user's PATH.",
+                        )
+                        .into())
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // deleted values may include both a mode and a scan type, like "all,fast"
+        let deleted_values: Vec<&str> = matches
+            .values_of("DELETED")
+            .map(|values| values.collect())
+            .unwrap_or_default();
+
+        let mut opt_deleted_mode = if matches.is_present("DELETED") {
+            match deleted_values
+                .iter()
+                .find(|value| !matches!(**value, "fast" | "exhaustive"))
+            {
+                Some(&"single") => Some(DeletedMode::DepthOfOne),
+                Some(&"only") => Some(DeletedMode::Only),
+                Some(&"depth") => Some(DeletedMode::Depth),
+                Some(_) | None => Some(DeletedMode::All),
+            }
+        } else {
+            None
+        };
+
+        let deleted_scan = if deleted_values.contains(&"fast") {
+            DeletedScan::Fast
+        } else {
+            DeletedScan::Exhaustive
         };
 
         let opt_interactive_mode = if matches.is_present("RESTORE") {
@@ -651,15 +2222,22 @@ impl Config {
                 Some("overwrite" | "yolo") => Some(InteractiveMode::Restore(
                     RestoreMode::Overwrite(RestoreSnapGuard::NotGuarded),
                 )),
+                Some("metadata-only") => Some(InteractiveMode::Restore(RestoreMode::MetadataOnly)),
+                Some("merge") => Some(InteractiveMode::Restore(RestoreMode::Merge)),
                 Some("copy-and-preserve") => {
                     Some(InteractiveMode::Restore(RestoreMode::CopyAndPreserve))
                 }
+                Some(_) | None if matches.is_present("PRESERVE") => {
+                    Some(InteractiveMode::Restore(RestoreMode::CopyAndPreserve))
+                }
                 Some(_) | None => Some(InteractiveMode::Restore(RestoreMode::CopyOnly)),
             }
         } else if matches.is_present("SELECT") {
             Some(InteractiveMode::Select)
         } else if matches.is_present("BROWSE") {
             Some(InteractiveMode::Browse)
+        } else if matches.is_present("BOOKMARKS") {
+            Some(InteractiveMode::Select)
         } else {
             None
         };
@@ -670,6 +2248,19 @@ impl Config {
             Some("metadata" | _) | None => ListSnapsOfType::UniqueMetadata,
         };
 
+        let opt_group_identical = matches.is_present("GROUP_IDENTICAL");
+
+        // every version must be available to be grouped, if no other uniqueness was requested
+        if opt_group_identical && !matches.is_present("UNIQUENESS") {
+            uniqueness = ListSnapsOfType::All;
+        }
+
+        let dataset_preference = match matches.value_of("PREFER") {
+            Some("native") => DatasetPreference::Native,
+            Some("both") => DatasetPreference::Both,
+            Some("alias" | _) | None => DatasetPreference::Alias,
+        };
+
         if opt_no_hidden && !opt_recursive && opt_interactive_mode.is_none() {
             return Err(HttmError::new(
                 "FILTER_HIDDEN is only available if either an interactive mode or recursive mode is specified.",
@@ -677,6 +2268,39 @@ impl Config {
             .into());
         }
 
+        let ui_frontend = match matches.value_of("UI") {
+            Some("native") => UiFrontend::Native,
+            Some("skim" | _) | None => UiFrontend::Skim,
+        };
+
+        if ui_frontend == UiFrontend::Native {
+            if cfg!(not(feature = "native-ui")) {
+                return Err(HttmError::new(
+                    "The native UI is only available when httm is built with the \"native-ui\" feature.",
+                )
+                .into());
+            }
+
+            if opt_preview.is_some() {
+                return Err(
+                    HttmError::new("PREVIEW is not yet available in the native UI.").into(),
+                );
+            }
+        }
+
+        let abort_exit_code = match matches.value_of("ABORT_EXIT_CODE") {
+            Some(value) => match value.parse::<u8>() {
+                Ok(code) => i32::from(code),
+                Err(_) => {
+                    return Err(HttmError::new(
+                        "Invalid abort exit code given.  The exit code must be a number between 0 and 255.",
+                    )
+                    .into())
+                }
+            },
+            None => 0,
+        };
+
         if opt_preview.is_some()
             && matches!(opt_interactive_mode, Some(InteractiveMode::Browse) | None)
         {
@@ -685,6 +2309,27 @@ impl Config {
             );
         }
 
+        if opt_dry_run
+            && !matches.is_present("ROLL_FORWARD")
+            && !matches.is_present("PRUNE_DITTO")
+            && !matches!(opt_interactive_mode, Some(InteractiveMode::Restore(_)))
+        {
+            return Err(HttmError::new(
+                "DRY_RUN is only available in Roll Forward, Prune Ditto, or Restore modes",
+            )
+            .into());
+        }
+
+        if opt_itemize
+            && !matches.is_present("DIGEST")
+            && !(opt_dry_run && matches!(opt_interactive_mode, Some(InteractiveMode::Restore(_))))
+        {
+            return Err(HttmError::new(
+                "ITEMIZE is only available in Digest mode, or in Restore modes with DRY_RUN",
+            )
+            .into());
+        }
+
         // if in last snap and select mode we will want to return a raw value,
         // better to have this here.  It's more confusing if we work this logic later, I think.
         if opt_last_snap.is_some() && matches!(opt_interactive_mode, Some(InteractiveMode::Select))
@@ -734,7 +2379,122 @@ impl Config {
             None
         };
 
-        let mut exec_mode = if let Some(full_snap_name) = matches.value_of("ROLL_FORWARD") {
+        let opt_history = matches.is_present("HISTORY");
+
+        let opt_print_cmd = matches.is_present("PRINT_CMD");
+
+        let opt_print_snap = matches.is_present("PRINT_SNAP");
+
+        let opt_dir_times = matches.is_present("DIR_TIMES");
+
+        let opt_no_clone = matches.is_present("NO_CLONE");
+
+        let opt_quiet = matches.is_present("QUIET");
+
+        let opt_force = matches.is_present("FORCE");
+
+        let opt_bookmarks = matches.is_present("BOOKMARKS");
+
+        let browse_filter = match matches.value_of("SHOW") {
+            Some("files") => BrowseFilter::FilesOnly,
+            Some("dirs") => BrowseFilter::DirsOnly,
+            Some("deleted") => BrowseFilter::DeletedOnly,
+            Some("versioned") => BrowseFilter::VersionedOnly,
+            _ => BrowseFilter::All,
+        };
+
+        let mut exec_mode = if opt_history && opt_interactive_mode.is_none() {
+            ExecMode::History
+        } else if matches.is_present("MAP") {
+            ExecMode::DatasetMap
+        } else if matches.is_present("MOUNT_SNAP") {
+            ExecMode::MountSnap(MountSnapAction::Mount)
+        } else if matches.is_present("UNMOUNT") {
+            ExecMode::MountSnap(MountSnapAction::Unmount)
+        } else if let Some(pattern) = matches.value_of("FIND") {
+            ExecMode::Find(pattern.to_owned())
+        } else if let Some(prefix) = matches.value_of("COMPLETE") {
+            ExecMode::Complete(prefix.to_owned())
+        } else if matches.is_present("DEDUP_RATIO") {
+            ExecMode::DedupRatio
+        } else if let Some(value) = matches.value_of("BOOKMARK") {
+            match value {
+                "remove" => ExecMode::Bookmark(BookmarkAction::Remove),
+                "list" => ExecMode::Bookmark(BookmarkAction::List),
+                _ => ExecMode::Bookmark(BookmarkAction::Add),
+            }
+        } else if matches.is_present("UNDO_LAST_RESTORE") {
+            ExecMode::UndoRestore
+        } else if matches.is_present("ROBOT") {
+            ExecMode::Robot
+        } else if let Some(value) = matches.value_of("SERVE") {
+            let socket_addr: SocketAddr = value.parse().map_err(|_err| {
+                HttmError::new("SERVE requires an address and a port, like 127.0.0.1:8080.")
+            })?;
+
+            // any who may connect may see any file httm may see
+            if !socket_addr.ip().is_loopback() && !matches.is_present("SERVE_REMOTE") {
+                return Err(HttmError::new(
+                    "SERVE will only serve upon a loopback address, like 127.0.0.1:8080, unless SERVE_REMOTE is also given.",
+                )
+                .into());
+            }
+
+            ExecMode::Serve(socket_addr)
+        } else if matches.is_present("SELF_TEST") {
+            ExecMode::SelfTest
+        } else if matches.is_present("SELECT_SNAPS") {
+            ExecMode::SnapshotSelect
+        } else if matches.is_present("CAT") {
+            let cat_version = match matches.value_of("AS_OF") {
+                Some(timestamp) => {
+                    CatVersion::AsOf(parse_date_string(timestamp, requested_utc_offset)?)
+                }
+                None => {
+                    CatVersion::Nth(Self::positive_number(matches, "NTH", DEFAULT_NTH_VERSION)?)
+                }
+            };
+
+            ExecMode::Cat(cat_version)
+        } else if matches.is_present("DIFF") {
+            ExecMode::Diff
+        } else if matches.is_present("VERIFY") {
+            ExecMode::Verify
+        } else if matches.is_present("TIMELINE") {
+            ExecMode::Timeline
+        } else if matches.is_present("SIZE_REPORT") {
+            ExecMode::SizeReport
+        } else if matches.is_present("CHURN") {
+            let rank = match matches.value_of("CHURN_BY") {
+                Some("bytes") => ChurnRank::Bytes,
+                _ => ChurnRank::Versions,
+            };
+
+            let opt_since = match matches.value_of("CHURN_SINCE") {
+                Some(timestamp) => Some(parse_date_string(timestamp, requested_utc_offset)?),
+                None => None,
+            };
+
+            ExecMode::Churn(ChurnConfig {
+                num_files: Self::positive_number(matches, "CHURN", DEFAULT_CHURN_NUM_FILES)?,
+                rank,
+                opt_since,
+            })
+        } else if matches.is_present("WATCH") {
+            ExecMode::Watch(Self::positive_number(
+                matches,
+                "WATCH",
+                DEFAULT_WATCH_INTERVAL,
+            )?)
+        } else if let Some(value) = matches.value_of("DIGEST") {
+            match value.parse::<usize>() {
+                Ok(num_snaps) if num_snaps > 0 => ExecMode::Digest(num_snaps),
+                _ => return Err(HttmError::new(
+                    "Invalid number of snapshots given.  DIGEST requires a number greater than 0.",
+                )
+                .into()),
+            }
+        } else if let Some(full_snap_name) = matches.value_of("ROLL_FORWARD") {
             let progress_bar: ProgressBar = indicatif::ProgressBar::new_spinner();
             let roll_config: RollForwardConfig = RollForwardConfig {
                 full_snap_name: full_snap_name.to_string(),
@@ -748,12 +2508,23 @@ impl Config {
             ExecMode::MountsForFiles(mount_display)
         } else if matches.is_present("PURGE") {
             ExecMode::Purge(opt_snap_mode_filters)
+        } else if matches.is_present("PRUNE_DITTO") {
+            ExecMode::PruneDitto
         } else if opt_snap_mode_filters.is_some() {
             ExecMode::SnapsForFiles(opt_snap_mode_filters)
         } else if let Some(requested_snapshot_suffix) = opt_snap_file_mount {
             ExecMode::SnapFileMount(requested_snapshot_suffix)
         } else if let Some(interactive_mode) = opt_interactive_mode {
-            ExecMode::Interactive(interactive_mode)
+            match interactive_mode {
+                InteractiveMode::Restore(restore_mode) if matches.is_present("BATCH") => {
+                    ExecMode::BatchRestore(BatchRestoreConfig {
+                        restore_mode,
+                        opt_from_file: matches.value_of_os("FROM_FILE").map(PathBuf::from),
+                        skip_consent: matches.is_present("YES"),
+                    })
+                }
+                _ => ExecMode::Interactive(interactive_mode),
+            }
         } else if opt_deleted_mode.is_some() {
             let progress_bar: ProgressBar = indicatif::ProgressBar::new_spinner();
             ExecMode::NonInteractiveRecursive(progress_bar)
@@ -761,6 +2532,14 @@ impl Config {
             ExecMode::Display
         };
 
+        // ghosts are deleted entries shown inline in the browse view, so only an interactive mode searches for them
+        let opt_ghosts =
+            matches.is_present("GHOSTS") && matches!(exec_mode, ExecMode::Interactive(_));
+
+        if opt_ghosts && opt_deleted_mode.is_none() {
+            opt_deleted_mode = Some(DeletedMode::All);
+        }
+
         if opt_recursive {
             if matches!(exec_mode, ExecMode::Display) {
                 return Err(HttmError::new("RECURSIVE not available in Display Mode.").into());
@@ -783,6 +2562,205 @@ impl Config {
         let opt_requested_dir: Option<PathData> =
             Self::opt_requested_dir(&mut exec_mode, &mut opt_deleted_mode, &paths, &pwd)?;
 
+        let opt_relative_root: Option<PathBuf> = match matches.value_of_os("RELATIVE") {
+            Some(value) if value.is_empty() => {
+                Some(opt_requested_dir.as_ref().unwrap_or(&pwd).path_buf.clone())
+            }
+            Some(value) => {
+                let relative_root = PathData::from(Path::new(value));
+
+                if !relative_root.path_buf.is_dir() {
+                    return Err(HttmError::new(
+                        "RELATIVE requires the root to be an existing directory.",
+                    )
+                    .into());
+                }
+
+                Some(relative_root.path_buf)
+            }
+            None => None,
+        };
+
+        let opt_relative_to: Option<RelativeTo> = match matches.value_of_os("RELATIVE_TO") {
+            Some(value) => {
+                let search_root = PathData::from(Path::new(value));
+
+                if !search_root.path_buf.is_dir() {
+                    return Err(
+                        HttmError::new("RELATIVE_TO requires an existing directory.").into(),
+                    );
+                }
+
+                Some(RelativeTo {
+                    live_root: pwd.path_buf.clone(),
+                    search_root: search_root.path_buf,
+                })
+            }
+            None => None,
+        };
+
+        let opt_remote_host: Option<RemoteHost> = match matches.value_of("REMOTE") {
+            Some(_) if !matches!(exec_mode, ExecMode::Display | ExecMode::NumVersions(_)) => {
+                return Err(HttmError::new(
+                    "REMOTE is only available in Display and Num Versions modes.",
+                )
+                .into());
+            }
+            Some(value) => {
+                // a bracketed IPv6 address may itself contain colons
+                let opt_split = match value.strip_prefix('[') {
+                    Some(rest) => rest
+                        .split_once("]:")
+                        .map(|(address, dir)| (format!("[{address}]"), dir)),
+                    None => value
+                        .split_once(':')
+                        .map(|(destination, dir)| (destination.to_owned(), dir)),
+                };
+
+                let remote_command = matches
+                    .value_of("REMOTE_COMMAND")
+                    .unwrap_or("httm")
+                    .to_owned();
+
+                match opt_split {
+                    // a destination like "-oProxyCommand=..." would be taken by ssh as an option
+                    Some((destination, _dir)) if destination.starts_with('-') => {
+                        return Err(HttmError::new(
+                            "REMOTE requires a host which does not begin with a '-'.",
+                        )
+                        .into());
+                    }
+                    Some((destination, dir)) if !destination.is_empty() && dir.starts_with('/') => {
+                        Some(RemoteHost {
+                            destination,
+                            live_root: pwd.path_buf.clone(),
+                            remote_root: PathBuf::from(dir),
+                            remote_command,
+                        })
+                    }
+                    _ => {
+                        return Err(HttmError::new(
+                            "REMOTE requires a host and an absolute directory, like [USER@]HOST:/DIR.",
+                        )
+                        .into());
+                    }
+                }
+            }
+            None => None,
+        };
+
+        // nothing already there is ever replaced by a fetch
+        let opt_fetch_to: Option<PathBuf> = match matches.value_of_os("FETCH_TO") {
+            Some(value) => {
+                let fetch_to = pwd.path_buf.join(value);
+
+                if fetch_to.symlink_metadata().is_ok() {
+                    let msg = format!(
+                        "FETCH_TO requires a path which does not already exist, and {fetch_to:?} exists."
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                Some(fetch_to)
+            }
+            None => None,
+        };
+
+        let opt_restore_dest: Option<PathBuf> = match matches.value_of_os("RESTORE_DEST") {
+            Some(value) => {
+                let is_overwrite = match &exec_mode {
+                    ExecMode::Interactive(InteractiveMode::Restore(restore_mode)) => {
+                        matches!(
+                            restore_mode,
+                            RestoreMode::Overwrite(_)
+                                | RestoreMode::MetadataOnly
+                                | RestoreMode::Merge
+                        )
+                    }
+                    ExecMode::BatchRestore(batch_config) => matches!(
+                        batch_config.restore_mode,
+                        RestoreMode::Overwrite(_) | RestoreMode::MetadataOnly | RestoreMode::Merge
+                    ),
+                    _ => false,
+                };
+
+                if is_overwrite {
+                    return Err(HttmError::new(
+                        "RESTORE_DEST is only available in the \"copy\" and \"copy-and-preserve\" restore modes.",
+                    )
+                    .into());
+                }
+
+                let restore_dest = pwd.path_buf.join(value);
+
+                // the destination dir will be created, if it does not already exist
+                if restore_dest.exists() && !restore_dest.is_dir() {
+                    return Err(HttmError::new(
+                        "RESTORE_DEST requires the destination be a directory.",
+                    )
+                    .into());
+                }
+
+                Some(restore_dest)
+            }
+            None => None,
+        };
+
+        let opt_object_stores: Option<Vec<ObjectStore>> = if matches.is_present("OBJECT_VERSIONS") {
+            if !matches!(
+                exec_mode,
+                ExecMode::Display | ExecMode::NumVersions(_) | ExecMode::Cat(_)
+            ) {
+                return Err(HttmError::new(
+                    "OBJECT_VERSIONS is only available in Display, Num Versions, and Cat modes.",
+                )
+                .into());
+            }
+
+            let mut object_stores = ObjectVersions::rclone_mounts();
+
+            let named_stores: Vec<ObjectStore> = matches
+                .values_of_os("OBJECT_VERSIONS")
+                .into_iter()
+                .flatten()
+                .map(|value| {
+                    let opt_split = value
+                        .to_str()
+                        .and_then(|value| value.split_once('='))
+                        .filter(|(mount, remote)| mount.starts_with('/') && remote.contains(':'));
+
+                    match opt_split {
+                        Some((mount, remote)) => Ok(ObjectStore {
+                            mount: PathBuf::from(mount),
+                            remote: remote.to_owned(),
+                        }),
+                        None => Err(HttmError::new(
+                            "OBJECT_VERSIONS requires each value be an absolute mount and an rclone remote, like /MOUNT=REMOTE:BUCKET.",
+                        )),
+                    }
+                })
+                .collect::<Result<_, HttmError>>()?;
+
+            object_stores.extend(named_stores);
+
+            if object_stores.is_empty() {
+                return Err(HttmError::new(
+                    "httm could find no rclone mount, and no mount was given, see OBJECT_VERSIONS.",
+                )
+                .into());
+            }
+
+            Some(object_stores)
+        } else {
+            None
+        };
+
+        let restore_suffix = RestoreSuffix::new(
+            matches
+                .value_of("RESTORE_SUFFIX")
+                .unwrap_or(DEFAULT_RESTORE_SUFFIX),
+        )?;
+
         if opt_one_filesystem && opt_requested_dir.is_none() {
             return Err(HttmError::new(
                 "ONE_FILESYSTEM requires a requested path for RECURSIVE search",
@@ -800,15 +2778,42 @@ impl Config {
             }
         };
 
-        if !matches!(opt_deleted_mode, None | Some(DeletedMode::All)) && !opt_recursive {
+        if !matches!(
+            opt_deleted_mode,
+            None | Some(DeletedMode::All | DeletedMode::Depth)
+        ) && !opt_recursive
+        {
             return Err(HttmError::new(
-                "Deleted modes other than \"all\" require recursive mode is enabled.  Quitting.",
+                "Deleted modes other than \"all\" and \"depth\" require recursive mode is enabled.  Quitting.",
             )
             .into());
         }
 
         let opt_omit_ditto = matches.is_present("OMIT_DITTO");
 
+        let ditto_of_type = match matches.value_of("OMIT_DITTO") {
+            Some("mtime") => DittoOfType::ModifyTime,
+            Some("contents") => DittoOfType::Contents,
+            Some("" | "metadata" | _) | None => DittoOfType::Metadata,
+        };
+
+        // a merge is performed by httm itself, so there is no command to print
+        if opt_print_cmd
+            && matches!(
+                exec_mode,
+                ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Merge))
+                    | ExecMode::BatchRestore(BatchRestoreConfig {
+                        restore_mode: RestoreMode::Merge,
+                        ..
+                    })
+            )
+        {
+            return Err(HttmError::new(
+                "PRINT_CMD is not available in the \"merge\" restore mode.",
+            )
+            .into());
+        }
+
         // opt_omit_identical doesn't make sense in Display Recursive mode as no live files will exists?
         if opt_omit_ditto && matches!(exec_mode, ExecMode::NonInteractiveRecursive(_)) {
             return Err(HttmError::new(
@@ -825,36 +2830,101 @@ impl Config {
 
         // obtain a map of datasets, a map of snapshot directories, and possibly a map of
         // alternate filesystems and map of aliases if the user requests
-        let dataset_collection = FilesystemInfo::new(
-            matches.is_present("ALT_REPLICATED"),
+        let opt_zfs_list_cache: Option<PathBuf> = match matches.value_of_os("ZFS_LIST_CACHE") {
+            Some(value) if value.is_empty() => Some(PathBuf::from(ZFS_LIST_CACHE_DIR)),
+            Some(value) => Some(PathBuf::from(value)),
+            None => std::env::var_os("HTTM_ZFS_LIST_CACHE").map(PathBuf::from),
+        };
+
+        let opt_snap_origin = match matches.value_of("SNAP_ORIGIN") {
+            Some("local") => Some(SnapOrigin::Local),
+            Some("received") => Some(SnapOrigin::Received),
+            _ => None,
+        };
+
+        // a user defined replica implies the user wants alt replicated datasets
+        let opt_alt_replicated: Option<Vec<AltStore>> = match matches.values_of("ALT_STORE") {
+            Some(values) => Some(values.map(AltStore::new).collect::<HttmResult<_>>()?),
+            None if matches.is_present("ALT_REPLICATED") => Some(Vec::new()),
+            None => None,
+        };
+
+        let dataset_collection = Arc::new(FilesystemInfo::new(
+            opt_alt_replicated,
             matches.value_of_os("REMOTE_DIR"),
             matches.value_of_os("LOCAL_DIR"),
             matches.values_of_os("MAP_ALIASES"),
+            opt_zfs_list_cache.as_deref(),
+            opt_snap_origin,
             &pwd,
-        )?;
+        )?);
 
         let config = Config {
             paths,
             opt_bulk_exclusion,
             opt_recursive,
             opt_exact,
+            opt_query,
             opt_no_filter,
             opt_debug,
             opt_no_traverse,
             opt_omit_ditto,
+            ditto_of_type,
             opt_no_hidden,
+            opt_exclude_globs,
+            opt_respect_gitignore,
+            opt_max_depth,
+            opt_max_results,
             opt_last_snap,
             opt_preview,
+            opt_preview_sandbox,
+            opt_privilege_escalation,
             opt_json,
+            opt_annotate,
+            opt_snap_info,
+            opt_diffstat,
+            opt_delimited,
+            opt_custom_format,
+            opt_group_identical,
+            opt_history,
+            opt_print_cmd,
+            opt_print_snap,
+            opt_restore_dest,
+            opt_fetch_to,
+            restore_suffix,
+            opt_dir_times,
+            opt_no_clone,
+            opt_quiet,
+            opt_force,
+            opt_bookmarks,
+            opt_ghosts,
+            browse_filter,
+            ui_frontend,
+            abort_exit_code,
             opt_one_filesystem,
+            opt_version_index,
+            network_concurrency,
+            network_batch_size,
+            opt_dry_run,
+            sensitive_paths,
+            opt_guard_sensitive,
+            opt_transcript,
+            opt_itemize,
             uniqueness,
+            dataset_preference,
             requested_utc_offset,
+            opt_human,
             exec_mode,
             print_mode,
             opt_deleted_mode,
+            deleted_scan,
             dataset_collection,
             pwd,
             opt_requested_dir,
+            opt_relative_root,
+            opt_relative_to,
+            opt_remote_host,
+            opt_object_stores,
         };
 
         Ok(config)
@@ -871,6 +2941,30 @@ impl Config {
         }
     }
 
+    fn positive_number(matches: &ArgMatches, arg_name: &str, default: usize) -> HttmResult<usize> {
+        match matches
+            .value_of(arg_name)
+            .map(|value| value.parse::<usize>())
+        {
+            None => Ok(default),
+            Some(Ok(number)) if number > 0 => Ok(number),
+            Some(_) => {
+                let msg =
+                    format!("Invalid number given.  {arg_name} requires a number greater than 0.");
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    // like positive_number, but for a limit which, when not given, is no limit at all
+    fn opt_positive_number(matches: &ArgMatches, arg_name: &str) -> HttmResult<Option<usize>> {
+        if !matches.is_present(arg_name) {
+            return Ok(None);
+        }
+
+        Self::positive_number(matches, arg_name, usize::MAX).map(Some)
+    }
+
     pub fn paths(
         opt_os_values: Option<OsValues>,
         exec_mode: &ExecMode,
@@ -891,15 +2985,39 @@ impl Config {
                 // input, and waiting on one input from stdin is pretty silly
                 ExecMode::Interactive(_)
                 | ExecMode::NonInteractiveRecursive(_)
-                | ExecMode::RollForward(_) => {
+                | ExecMode::RollForward(_)
+                | ExecMode::Find(_)
+                | ExecMode::Digest(_)
+                | ExecMode::Churn(_)
+                | ExecMode::Bookmark(_)
+                | ExecMode::Watch(_)
+                | ExecMode::SelfTest
+                | ExecMode::SnapshotSelect => {
                     vec![pwd.clone()]
                 }
+                // history, dataset map, complete, undo restore, robot, and serve modes need no paths at all
+                // batch restore reads its own pairs of paths, from stdin or a file
+                ExecMode::History
+                | ExecMode::DatasetMap
+                | ExecMode::Complete(_)
+                | ExecMode::UndoRestore
+                | ExecMode::BatchRestore(_)
+                | ExecMode::Robot
+                | ExecMode::Serve(_) => Vec::new(),
                 ExecMode::Display
                 | ExecMode::SnapFileMount(_)
                 | ExecMode::Purge(_)
+                | ExecMode::PruneDitto
                 | ExecMode::MountsForFiles(_)
                 | ExecMode::SnapsForFiles(_)
-                | ExecMode::NumVersions(_) => read_stdin()?,
+                | ExecMode::MountSnap(_)
+                | ExecMode::NumVersions(_)
+                | ExecMode::DedupRatio
+                | ExecMode::Cat(_)
+                | ExecMode::Diff
+                | ExecMode::Verify
+                | ExecMode::Timeline
+                | ExecMode::SizeReport => read_stdin()?,
             }
         };
 
@@ -971,8 +3089,30 @@ impl Config {
 
             ExecMode::Display
             | ExecMode::RollForward(_)
+            | ExecMode::History
+            | ExecMode::DatasetMap
+            | ExecMode::MountSnap(_)
+            | ExecMode::Find(_)
+            | ExecMode::Complete(_)
+            | ExecMode::BatchRestore(_)
+            | ExecMode::Digest(_)
+            | ExecMode::UndoRestore
+            | ExecMode::DedupRatio
+            | ExecMode::Bookmark(_)
+            | ExecMode::Watch(_)
+            | ExecMode::Cat(_)
+            | ExecMode::Diff
+            | ExecMode::Verify
+            | ExecMode::Timeline
+            | ExecMode::SizeReport
+            | ExecMode::Churn(_)
+            | ExecMode::Robot
+            | ExecMode::Serve(_)
+            | ExecMode::SelfTest
+            | ExecMode::SnapshotSelect
             | ExecMode::SnapFileMount(_)
             | ExecMode::Purge(_)
+            | ExecMode::PruneDitto
             | ExecMode::MountsForFiles(_)
             | ExecMode::SnapsForFiles(_)
             | ExecMode::NumVersions(_) => {
@@ -1021,6 +3161,28 @@ impl Config {
         })
     }
 
+    // the modify time of a version, as displayed in the display table, see HUMAN
+    pub fn display_date(&self, modify_time: &SystemTime) -> String {
+        if self.opt_human {
+            return display_relative_date(modify_time);
+        }
+
+        date_string(self.requested_utc_offset, modify_time, DateFormat::Display)
+    }
+
+    // paths beneath the relative root are displayed relative to that root, all others in full
+    pub fn display_path<'a>(&self, path: &'a Path) -> &'a Path {
+        match self
+            .opt_relative_root
+            .as_ref()
+            .and_then(|relative_root| path.strip_prefix(relative_root).ok())
+        {
+            Some(relative) if relative.as_os_str().is_empty() => Path::new("."),
+            Some(relative) => relative,
+            None => path,
+        }
+    }
+
     // use an associated function here because we may need this display again elsewhere
     pub fn generate_display_config(&self, paths_selected: &[PathData]) -> Self {
         // generate a config for a preview display only
@@ -1028,24 +3190,70 @@ impl Config {
             paths: paths_selected.to_vec(),
             opt_recursive: false,
             opt_exact: false,
+            opt_query: None,
             opt_no_filter: false,
             opt_debug: false,
             opt_no_traverse: false,
             opt_no_hidden: false,
+            opt_exclude_globs: None,
+            opt_respect_gitignore: false,
+            opt_max_depth: None,
+            opt_max_results: None,
             opt_json: false,
+            opt_annotate: self.opt_annotate.clone(),
+            opt_snap_info: self.opt_snap_info,
+            opt_diffstat: false,
+            opt_delimited: None,
+            opt_custom_format: None,
+            opt_group_identical: false,
+            opt_history: false,
+            opt_print_cmd: false,
+            opt_print_snap: false,
+            opt_restore_dest: None,
+            opt_fetch_to: None,
+            restore_suffix: self.restore_suffix.clone(),
+            opt_dir_times: false,
+            opt_no_clone: false,
+            opt_quiet: false,
+            opt_force: false,
+            opt_bookmarks: false,
+            opt_ghosts: false,
+            browse_filter: BrowseFilter::All,
+            ui_frontend: self.ui_frontend.clone(),
+            abort_exit_code: self.abort_exit_code,
             opt_one_filesystem: false,
+            opt_version_index: self.opt_version_index,
+            network_concurrency: self.network_concurrency,
+            network_batch_size: self.network_batch_size,
+            opt_dry_run: false,
+            sensitive_paths: Vec::new(),
+            opt_guard_sensitive: false,
+            opt_transcript: None,
+            opt_itemize: false,
             opt_bulk_exclusion: None,
             opt_last_snap: None,
             opt_preview: None,
+            opt_preview_sandbox: None,
+            opt_privilege_escalation: self.opt_privilege_escalation.clone(),
             opt_deleted_mode: None,
+            deleted_scan: self.deleted_scan.clone(),
             uniqueness: ListSnapsOfType::UniqueMetadata,
+            dataset_preference: self.dataset_preference.clone(),
             opt_omit_ditto: self.opt_omit_ditto,
+            ditto_of_type: self.ditto_of_type.clone(),
             requested_utc_offset: self.requested_utc_offset,
+            opt_human: self.opt_human,
             exec_mode: ExecMode::Display,
             print_mode: PrintMode::FormattedDefault,
-            dataset_collection: self.dataset_collection.clone(),
+            // a display config is generated for each preview, so we share, rather than copy, the collection
+            dataset_collection: FilesystemInfo::latest(&self.dataset_collection),
             pwd: self.pwd.clone(),
             opt_requested_dir: self.opt_requested_dir.clone(),
+            // the select views parse the selected path back from the display, so it must remain whole
+            opt_relative_root: None,
+            opt_relative_to: self.opt_relative_to.clone(),
+            opt_remote_host: self.opt_remote_host.clone(),
+            opt_object_stores: self.opt_object_stores.clone(),
         }
     }
 }
@@ -21,7 +21,7 @@ use std::{
     path::PathBuf,
 };
 
-use crate::library::results::{HttmError, HttmResult};
+use crate::library::results::{HttmError, HttmResult, SUCCESS_EXIT_CODE};
 use crate::library::utility::make_tmp_path;
 
 const HTTM_SCRIPT_PATH: &str = ".httm-key-bindings.zsh";
@@ -107,7 +107,7 @@ pub fn install_hot_keys() -> HttmResult<()> {
             ) {
                 Ok(_) => {
                     eprintln!("httm: zsh hot keys were installed successfully.");
-                    std::process::exit(0)
+                    std::process::exit(SUCCESS_EXIT_CODE)
                 }
                 Err(err) => {
                     Err(HttmError::with_context("httm: could not move .httm-key-bindings.zsh.tmp to .httm-key-bindings.zsh for the following reason: ", &err).into())
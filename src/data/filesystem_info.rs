@@ -15,22 +15,29 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::{ffi::OsStr, path::PathBuf};
+use std::fs::File;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::{ffi::OsStr, path::Path, path::PathBuf};
 
 use clap::OsValues;
+use once_cell::sync::Lazy;
 
 use crate::data::paths::PathData;
 use crate::library::results::HttmResult;
 use crate::parse::aliases::MapOfAliases;
 use crate::parse::alts::MapOfAlts;
 use crate::parse::mounts::{BaseFilesystemInfo, FilterDirs, MapOfDatasets};
+use crate::parse::provenance::SnapOrigin;
+use crate::parse::replication::AltStore;
 use crate::parse::snaps::MapOfSnaps;
+use crate::ZFS_SNAPSHOT_DIRECTORY;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FilesystemInfo {
     // key: mount, val: (dataset/subvol, fs_type, mount_type)
     pub map_of_datasets: MapOfDatasets,
-    // key: mount, val: vec snap locations on disk (e.g. /.zfs/snapshot/snap_8a86e4fc_prepApt/home)
+    // key: mount, val: vec snap locations on disk (e.g. /.zfs/snapshot/snap_8a86e4fc_prepApt/home), listed lazily
     pub map_of_snaps: MapOfSnaps,
     // vec dirs to be filtered
     pub filter_dirs: FilterDirs,
@@ -40,46 +47,121 @@ pub struct FilesystemInfo {
     pub opt_map_of_aliases: Option<MapOfAliases>,
     // opt single dir to to be filtered re: btrfs common snap dir
     pub opt_common_snap_dir: Option<PathBuf>,
+    // what we need to rebuild the above, should the mount table change
+    // any user defined replicas, should alt replicated datasets be requested
+    opt_alt_replicated: Option<Vec<AltStore>>,
+    opt_zfs_list_cache: Option<PathBuf>,
+    opt_snap_origin: Option<SnapOrigin>,
+}
+
+// on Linux, the kernel signals a change to the mount table, as an exceptional condition (POLLPRI), upon any open
+// /proc/self/mountinfo, so, like an inotify watch, a long running interactive session need only poll that file, and
+// need not re-read the mount table, or re-ask ZFS and btrfs about every dataset and snapshot, for every preview
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+static MOUNT_WATCH: Lazy<Mutex<MountWatch>> = Lazy::new(|| {
+    let opt_mountinfo = File::open(MOUNTINFO_PATH).ok();
+    let opt_mount_lines = opt_mountinfo.as_ref().and_then(MountWatch::mount_lines);
+
+    Mutex::new(MountWatch {
+        opt_mountinfo,
+        opt_mount_lines,
+        opt_latest: None,
+        is_stale: false,
+    })
+});
+
+struct MountWatch {
+    opt_mountinfo: Option<File>,
+    // the mount table, as of the last rebuild, less any snapshot automounts, see mount_lines
+    opt_mount_lines: Option<Vec<String>>,
+    opt_latest: Option<Arc<FilesystemInfo>>,
+    // a new snapshot need not change the mount table, so whoever takes a snapshot must say so, see invalidate
+    is_stale: bool,
+}
+
+impl MountWatch {
+    // ZFS mounts each snapshot, as in ".zfs/snapshot/<name>", upon its first access, and unmounts it once it expires,
+    // which, while a session previews many snapshot versions, changes the mount table again and again, but changes
+    // nothing we would rebuild, as snap mounts are listed from ZFS, not from the mount table.  so, the mount point of
+    // each line, its fifth field, is checked, and only the lines of other mounts are compared from one change to the next
+    fn mount_lines(mut mountinfo: &File) -> Option<Vec<String>> {
+        let mut contents = String::new();
+        mountinfo.read_to_string(&mut contents).ok()?;
+
+        let snapshot_dir = format!("/{ZFS_SNAPSHOT_DIRECTORY}/");
+
+        let res = contents
+            .lines()
+            .filter(|line| {
+                !line
+                    .split(' ')
+                    .nth(4)
+                    .is_some_and(|mount_point| mount_point.contains(&snapshot_dir))
+            })
+            .map(str::to_owned)
+            .collect();
+
+        Some(res)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn has_changed(&self) -> bool {
+        use std::os::unix::io::AsRawFd;
+
+        match &self.opt_mountinfo {
+            Some(mountinfo) => {
+                let mut poll_fd = libc::pollfd {
+                    fd: mountinfo.as_raw_fd(),
+                    events: libc::POLLPRI,
+                    revents: 0,
+                };
+
+                // a zero timeout returns at once, whether or not the mount table has changed
+                let res = unsafe { libc::poll(&mut poll_fd, 1, 0) };
+
+                res > 0 && (poll_fd.revents & (libc::POLLPRI | libc::POLLERR)) != 0
+            }
+            None => false,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn has_changed(&self) -> bool {
+        false
+    }
 }
 
 impl FilesystemInfo {
     pub fn new(
-        opt_alt_replicated: bool,
+        opt_alt_replicated: Option<Vec<AltStore>>,
         opt_remote_dir: Option<&OsStr>,
         opt_local_dir: Option<&OsStr>,
         opt_map_aliases: Option<OsValues>,
+        opt_zfs_list_cache: Option<&Path>,
+        opt_snap_origin: Option<SnapOrigin>,
         pwd: &PathData,
     ) -> HttmResult<FilesystemInfo> {
-        let base_fs_info = BaseFilesystemInfo::new()?;
-
-        // for a collection of btrfs mounts, indicates a common snapshot directory to ignore
-        let opt_common_snap_dir = base_fs_info.common_snap_dir();
+        // begin watching before reading the mount table, so no later change is missed
+        Lazy::force(&MOUNT_WATCH);
 
-        // only create a map of alts if necessary
-        let opt_map_of_alts = if opt_alt_replicated {
-            Some(MapOfAlts::new(&base_fs_info.map_of_datasets))
-        } else {
-            None
+        // like the remote dir, aliases given on the command line take precedence over the env var
+        let alias_values: Option<Vec<String>> = match opt_map_aliases {
+            Some(cmd_map_aliases) => Some(
+                cmd_map_aliases
+                    .into_iter()
+                    .map(|os_str| os_str.to_string_lossy().to_string())
+                    .collect(),
+            ),
+            None => std::env::var_os("HTTM_MAP_ALIASES").map(|env_map_aliases| {
+                env_map_aliases
+                    .to_string_lossy()
+                    .split_terminator(',')
+                    .map(std::borrow::ToOwned::to_owned)
+                    .collect()
+            }),
         };
 
-        let alias_values: Option<Vec<String>> =
-            if let Some(env_map_aliases) = std::env::var_os("HTTM_MAP_ALIASES") {
-                Some(
-                    env_map_aliases
-                        .to_string_lossy()
-                        .split_terminator(',')
-                        .map(std::borrow::ToOwned::to_owned)
-                        .collect(),
-                )
-            } else {
-                opt_map_aliases.map(|cmd_map_aliases| {
-                    cmd_map_aliases
-                        .into_iter()
-                        .map(|os_str| os_str.to_string_lossy().to_string())
-                        .collect()
-                })
-            };
-
         let raw_snap_dir = if let Some(value) = opt_remote_dir {
             Some(value.to_os_string())
         } else if std::env::var_os("HTTM_REMOTE_DIR").is_some() {
@@ -108,13 +190,118 @@ impl FilesystemInfo {
             None
         };
 
+        Self::from_mount_table(
+            opt_alt_replicated,
+            opt_zfs_list_cache.map(Path::to_path_buf),
+            opt_snap_origin,
+            opt_map_of_aliases,
+        )
+    }
+
+    // everything which depends upon the mount table, user defined aliases do not
+    fn from_mount_table(
+        opt_alt_replicated: Option<Vec<AltStore>>,
+        opt_zfs_list_cache: Option<PathBuf>,
+        opt_snap_origin: Option<SnapOrigin>,
+        opt_map_of_aliases: Option<MapOfAliases>,
+    ) -> HttmResult<FilesystemInfo> {
+        let base_fs_info = BaseFilesystemInfo::new(opt_zfs_list_cache.as_deref())?;
+
+        // for a collection of btrfs mounts, indicates a common snapshot directory to ignore
+        let opt_common_snap_dir = base_fs_info.common_snap_dir();
+
+        // only create a map of alts if necessary
+        let opt_map_of_alts = opt_alt_replicated
+            .as_ref()
+            .map(|alt_stores| MapOfAlts::new(&base_fs_info.map_of_datasets, alt_stores));
+
+        // only ask btrfs about the origin of each snapshot if necessary
+        let map_of_snaps = match opt_snap_origin {
+            Some(snap_origin) => base_fs_info.map_of_snaps.retain_origin(snap_origin)?,
+            None => base_fs_info.map_of_snaps,
+        };
+
         Ok(FilesystemInfo {
             map_of_datasets: base_fs_info.map_of_datasets,
-            map_of_snaps: base_fs_info.map_of_snaps,
+            map_of_snaps,
             filter_dirs: base_fs_info.filter_dirs,
             opt_map_of_alts,
             opt_common_snap_dir,
             opt_map_of_aliases,
+            opt_alt_replicated,
+            opt_zfs_list_cache,
+            opt_snap_origin,
         })
     }
+
+    // the collection as of the current mount table: the same shared collection, unless the mount table has
+    // changed since the collection was built, and, should a rebuild fail, the last good collection
+    pub fn latest(original: &Arc<FilesystemInfo>) -> Arc<FilesystemInfo> {
+        let mut mount_watch = match MOUNT_WATCH.lock() {
+            Ok(mount_watch) => mount_watch,
+            Err(_) => return original.clone(),
+        };
+
+        let current = mount_watch
+            .opt_latest
+            .clone()
+            .unwrap_or_else(|| original.clone());
+
+        if !mount_watch.is_stale && !mount_watch.has_changed() {
+            return current;
+        }
+
+        // re-opening resets the watch, so we will only be told of the next change
+        mount_watch.opt_mountinfo = File::open(MOUNTINFO_PATH).ok();
+
+        let opt_mount_lines = mount_watch
+            .opt_mountinfo
+            .as_ref()
+            .and_then(MountWatch::mount_lines);
+
+        // a snapshot automount, or its expiry, is no reason to rebuild, see mount_lines
+        if !mount_watch.is_stale
+            && opt_mount_lines.is_some()
+            && opt_mount_lines == mount_watch.opt_mount_lines
+        {
+            return current;
+        }
+
+        mount_watch.opt_mount_lines = opt_mount_lines;
+        mount_watch.is_stale = false;
+
+        match Self::from_mount_table(
+            current.opt_alt_replicated.clone(),
+            current.opt_zfs_list_cache.clone(),
+            current.opt_snap_origin,
+            current.opt_map_of_aliases.clone(),
+        ) {
+            Ok(refreshed) => {
+                let refreshed = Arc::new(refreshed);
+                mount_watch.opt_latest = Some(refreshed.clone());
+                refreshed
+            }
+            Err(_) => current,
+        }
+    }
+
+    // the next call to latest will rebuild the collection, whether or not the mount table has changed
+    pub fn invalidate() {
+        if let Ok(mut mount_watch) = MOUNT_WATCH.lock() {
+            mount_watch.is_stale = true;
+        }
+    }
+
+    // the dataset mount, and the snap mount, which contain a snapshot version's path, if any
+    pub fn snap_mount_containing(&self, path: &Path) -> Option<(&PathBuf, &PathBuf)> {
+        self.map_of_snaps
+            .iter()
+            .flat_map(|(mount, snap_mounts)| {
+                snap_mounts
+                    .iter()
+                    .map(move |snap_mount| (mount, snap_mount))
+            })
+            .filter(|(_mount, snap_mount)| path.starts_with(snap_mount))
+            .max_by_key(|(_mount, snap_mount)| snap_mount.components().count())
+    }
 }
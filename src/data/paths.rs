@@ -24,19 +24,18 @@ use std::{
     time::SystemTime,
 };
 
-use once_cell::sync::OnceCell;
 use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 
 use simd_adler32::Adler32;
 
+use crate::parse::aliases::MapOfAliases;
 use crate::parse::mounts::MapOfDatasets;
 use crate::parse::mounts::MaxLen;
-use crate::{config::generate::ListSnapsOfType, parse::aliases::MapOfAliases};
 use crate::{
     config::generate::PrintMode,
     library::{
-        results::{HttmError, HttmResult},
+        results::{HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE},
         utility::DateFormat,
     },
 };
@@ -195,9 +194,10 @@ impl PathData {
             .skip_while(|ancestor| ancestor.components().count() > dataset_max_len)
             .find(|ancestor| map_of_datasets.contains_key(*ancestor))
             .ok_or_else(|| {
-                HttmError::new(
+                HttmError::with_exit_code(
                     "httm could not identify any qualifying dataset.  \
                     Maybe consider specifying manually at SNAP_POINT?",
+                    NO_SNAPSHOT_SOURCE_EXIT_CODE,
                 )
                 .into()
             })
@@ -242,10 +242,11 @@ impl Serialize for PathMetadata {
             state.serialize_field("modify_time", &self.modify_time)?;
         } else {
             let size = display_human_size(self.size);
+            // JSON may be read on another machine, so we include the offset
             let date = date_string(
                 GLOBAL_CONFIG.requested_utc_offset,
                 &self.modify_time,
-                DateFormat::Display,
+                DateFormat::Iso8601,
             );
 
             state.serialize_field("size", &size)?;
@@ -273,7 +274,6 @@ pub const PHANTOM_PATH_METADATA: PathMetadata = PathMetadata {
 #[derive(Eq, PartialEq)]
 pub struct CompareVersionsContainer {
     pathdata: PathData,
-    opt_hash: Option<OnceCell<u32>>,
 }
 
 impl From<CompareVersionsContainer> for PathData {
@@ -282,6 +282,12 @@ impl From<CompareVersionsContainer> for PathData {
     }
 }
 
+impl From<PathData> for CompareVersionsContainer {
+    fn from(pathdata: PathData) -> Self {
+        CompareVersionsContainer { pathdata }
+    }
+}
+
 impl PartialOrd for CompareVersionsContainer {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -299,78 +305,17 @@ impl Ord for CompareVersionsContainer {
             return self_md.size.cmp(&other_md.size);
         }
 
-        // if files, differ re mtime, but have same size, we test by bytes whether the same
-        if self_md.size == other_md.size
-            && self.opt_hash.is_some()
-            // if above is true/false then "&& other.opt_hash.is_some()" is the same
-            && self.is_same_file(other)
-        {
-            return Ordering::Equal;
-        }
-
         self_md.modify_time.cmp(&other_md.modify_time)
     }
 }
 
-impl CompareVersionsContainer {
-    pub fn new(pathdata: PathData, snaps_of_type: &ListSnapsOfType) -> Self {
-        let opt_hash = match snaps_of_type {
-            ListSnapsOfType::UniqueContents => Some(OnceCell::new()),
-            ListSnapsOfType::UniqueMetadata | ListSnapsOfType::All => None,
-        };
-
-        CompareVersionsContainer { pathdata, opt_hash }
-    }
-
-    #[inline]
-    #[allow(unused_assignments)]
-    fn is_same_file(&self, other: &Self) -> bool {
-        // SAFETY: Unwrap will fail on opt_hash is None, here we've guarded this above
-        let self_hash_cell = self
-            .opt_hash
-            .as_ref()
-            .expect("opt_hash should be check prior to this point and must be Some");
-        let other_hash_cell = other
-            .opt_hash
-            .as_ref()
-            .expect("opt_hash should be check prior to this point and must be Some");
-
-        let (self_hash, other_hash): (HttmResult<u32>, HttmResult<u32>) = rayon::join(
-            || {
-                if let Some(hash_value) = self_hash_cell.get() {
-                    return Ok(*hash_value);
-                }
-
-                HashFromFile::try_from(self.pathdata.path_buf.as_path())
-                    .map(|hash| *self_hash_cell.get_or_init(|| hash.into_inner()))
-            },
-            || {
-                if let Some(hash_value) = other_hash_cell.get() {
-                    return Ok(*hash_value);
-                }
-
-                HashFromFile::try_from(other.pathdata.path_buf.as_path())
-                    .map(|hash| *other_hash_cell.get_or_init(|| hash.into_inner()))
-            },
-        );
-
-        if let Ok(res_self) = self_hash {
-            if let Ok(res_other) = other_hash {
-                return res_self == res_other;
-            }
-        }
-
-        false
-    }
-}
-
-struct HashFromFile {
+pub struct HashFromFile {
     hash: u32,
 }
 
 impl HashFromFile {
     #[inline(always)]
-    fn into_inner(self) -> u32 {
+    pub fn into_inner(self) -> u32 {
         self.hash
     }
 }
@@ -15,16 +15,20 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::sync::Mutex;
 use std::{fs::FileType, path::PathBuf};
 
 use lscolors::Colorable;
+use once_cell::sync::OnceCell;
 use skim::prelude::*;
 
+use crate::config::generate::BrowseFilter;
 use crate::data::paths::{BasicDirEntryInfo, PathData};
 use crate::display_versions::wrapper::VersionsDisplayWrapper;
-use crate::exec::recursive::PathProvenance;
+use crate::exec::recursive::{PathProvenance, SharedRecursive, SkippedDirs};
 use crate::library::results::HttmResult;
 use crate::library::utility::paint_string;
+use crate::ui::picker::FilterChange;
 use crate::{VersionsMap, GLOBAL_CONFIG};
 
 // these represent the items ready for selection and preview
@@ -34,28 +38,60 @@ use crate::{VersionsMap, GLOBAL_CONFIG};
 pub struct SelectionCandidate {
     path: PathBuf,
     file_type: Option<FileType>,
+    is_dir: bool,
+    is_phantom: bool,
+    // whether a live file has any snapshot versions is only looked up, once, should the user filter for it
+    opt_has_versions: OnceCell<bool>,
 }
 
+pub type CandidateSender = Sender<Arc<SelectionCandidate>>;
+pub type CandidateReceiver = Receiver<Arc<SelectionCandidate>>;
+
 impl SelectionCandidate {
     pub fn new(basic_info: BasicDirEntryInfo, is_phantom: PathProvenance) -> Self {
-        // here save space of bool/padding instead of an "is_phantom: bool"
-        //
+        let is_dir = SharedRecursive::is_entry_dir(&basic_info);
+
         // issue: conflate not having a file_type as phantom
         // for purposes of coloring the file_name/path only?
         //
         // std lib docs don't give much indication as to
         // when file_type() fails?  Doesn't seem to be a problem?
-        let file_type = match is_phantom {
-            PathProvenance::FromLiveDataset => basic_info.file_type,
-            PathProvenance::IsPhantom => None,
+        let (file_type, is_phantom) = match is_phantom {
+            PathProvenance::FromLiveDataset => (basic_info.file_type, false),
+            PathProvenance::IsPhantom => (None, true),
         };
 
         SelectionCandidate {
             path: basic_info.path,
             file_type,
+            is_dir,
+            is_phantom,
+            opt_has_versions: OnceCell::new(),
+        }
+    }
+
+    pub fn is_match(&self, filter: &BrowseFilter) -> bool {
+        match filter {
+            BrowseFilter::All => true,
+            BrowseFilter::FilesOnly => !self.is_dir,
+            BrowseFilter::DirsOnly => self.is_dir,
+            BrowseFilter::DeletedOnly => self.is_phantom,
+            // a deleted file, by definition, has a snapshot version
+            BrowseFilter::VersionedOnly => !self.is_dir && (self.is_phantom || self.has_versions()),
         }
     }
 
+    fn has_versions(&self) -> bool {
+        *self.opt_has_versions.get_or_init(|| {
+            let paths_selected = [PathData::from(self.path.as_path())];
+
+            // an entry whose snapshots we may not read is shown as though it has no versions
+            VersionsMap::lookup(&GLOBAL_CONFIG, &paths_selected)
+                .map(|versions_map| versions_map.values().any(|versions| !versions.is_empty()))
+                .unwrap_or(false)
+        })
+    }
+
     fn preview_view(&self) -> HttmResult<String> {
         let config = &GLOBAL_CONFIG;
         let paths_selected = &[PathData::from(self.path.as_path())];
@@ -65,7 +101,8 @@ impl SelectionCandidate {
 
         // finally run search on those paths
         let versions_map = VersionsMap::new(&display_config, &display_config.paths)?;
-        let output_buf = VersionsDisplayWrapper::from(&display_config, versions_map).to_string();
+        let output_buf =
+            VersionsDisplayWrapper::from(&display_config, versions_map).to_formatted()?;
 
         Ok(output_buf)
     }
@@ -111,6 +148,123 @@ impl SkimItem for SelectionCandidate {
     }
     fn preview(&self, _: PreviewContext<'_>) -> skim::ItemPreview {
         let preview_output = self.preview_view().unwrap_or_default();
+
+        // the skim header is drawn once, so the preview, redrawn as the user moves, is our status line
+        let preview_output = match SkippedDirs::status_line() {
+            Some(status_line) => format!("{status_line}\n{preview_output}"),
+            None => preview_output,
+        };
+
         skim::ItemPreview::AnsiText(preview_output)
     }
 }
+
+// the browse view receives its candidates from the recursive search through this relay, which keeps every
+// candidate sent so far, so that, when the user changes the filter, the browse view may be refiltered,
+// without starting the search over
+#[derive(Clone)]
+pub struct BrowseRelay {
+    inner: Arc<Mutex<RelayState>>,
+}
+
+struct RelayState {
+    candidates: Vec<Arc<SelectionCandidate>>,
+    filter: BrowseFilter,
+    opt_skim_tx: Option<SkimItemSender>,
+    is_complete: bool,
+}
+
+impl RelayState {
+    fn forward(&self, candidate: &Arc<SelectionCandidate>) {
+        if let Some(skim_tx) = &self.opt_skim_tx {
+            if candidate.is_match(&self.filter) {
+                // the browse view may have been closed to change the filter, and that is fine,
+                // any candidate is sent again when the browse view is reopened
+                let _ = skim_tx.try_send(candidate.clone());
+            }
+        }
+    }
+}
+
+impl BrowseRelay {
+    pub fn new(candidate_rx: CandidateReceiver, filter: BrowseFilter) -> (Self, SkimItemReceiver) {
+        let (skim_tx, skim_rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+
+        let relay = Self {
+            inner: Arc::new(Mutex::new(RelayState {
+                candidates: Vec::new(),
+                filter,
+                opt_skim_tx: Some(skim_tx),
+                is_complete: false,
+            })),
+        };
+
+        let relay_clone = relay.clone();
+
+        std::thread::spawn(move || {
+            candidate_rx.iter().for_each(|candidate| {
+                let mut state = relay_clone.lock();
+                state.forward(&candidate);
+                state.candidates.push(candidate);
+            });
+
+            // the search is done, so dropping the sender lets the browse view know there is nothing more to come
+            let mut state = relay_clone.lock();
+            state.is_complete = true;
+            state.opt_skim_tx = None;
+        });
+
+        (relay, skim_rx)
+    }
+
+    pub fn filter(&self) -> BrowseFilter {
+        self.lock().filter
+    }
+
+    // changes the filter, as the user asked, and returns a new receiver, for a new browse view, upon which every
+    // candidate sent so far, which matches the new filter, will be sent again, followed by any candidate still to come
+    pub fn change_filter(&self, filter_change: &FilterChange) -> SkimItemReceiver {
+        {
+            let mut state = self.lock();
+            state.filter = filter_change.apply(state.filter);
+        }
+
+        self.refresh()
+    }
+
+    // with the same filter, returns a new receiver, for a new browse view, upon which every candidate is sent again,
+    // so that, once the user has taken a snapshot, each preview is generated anew
+    pub fn refresh(&self) -> SkimItemReceiver {
+        {
+            // any candidate which arrives before we resend is sent along with the rest, below
+            self.lock().opt_skim_tx = None;
+        }
+
+        let (skim_tx, skim_rx): (SkimItemSender, SkimItemReceiver) = unbounded();
+
+        let relay_clone = self.clone();
+
+        // looking up whether each candidate has versions may take a while, so we don't wait here
+        std::thread::spawn(move || {
+            let mut state = relay_clone.lock();
+            state.opt_skim_tx = Some(skim_tx);
+
+            state
+                .candidates
+                .iter()
+                .for_each(|candidate| state.forward(candidate));
+
+            if state.is_complete {
+                state.opt_skim_tx = None;
+            }
+        });
+
+        skim_rx
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RelayState> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
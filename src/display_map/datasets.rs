@@ -0,0 +1,128 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::config::generate::PrintMode;
+use crate::data::filesystem_info::FilesystemInfo;
+use crate::library::results::HttmResult;
+use crate::library::utility::{delimiter, find_common_path};
+use crate::parse::aliases::FilesystemType;
+use crate::parse::mounts::{DatasetMetadata, MountType};
+use crate::GLOBAL_CONFIG;
+
+// a dump of the datasets httm detected, for automation and for
+// triaging why a dataset may not be found
+pub struct PrintDatasetMap<'a> {
+    inner: Vec<DatasetEntry<'a>>,
+}
+
+struct DatasetEntry<'a> {
+    mount: &'a Path,
+    metadata: &'a DatasetMetadata,
+    snaps: &'a [PathBuf],
+}
+
+impl<'a> From<&'a FilesystemInfo> for PrintDatasetMap<'a> {
+    fn from(dataset_collection: &'a FilesystemInfo) -> Self {
+        let mut inner: Vec<DatasetEntry> = dataset_collection
+            .map_of_datasets
+            .iter()
+            .map(|(mount, metadata)| DatasetEntry {
+                mount: mount.as_path(),
+                metadata,
+                snaps: dataset_collection
+                    .map_of_snaps
+                    .get(mount)
+                    .map(|snaps| snaps.as_slice())
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        inner.sort_unstable_by_key(|entry| entry.mount);
+
+        Self { inner }
+    }
+}
+
+impl<'a> Serialize for PrintDatasetMap<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("PrintDatasetMap", 1)?;
+
+        state.serialize_field("datasets", &self.inner)?;
+        state.end()
+    }
+}
+
+impl<'a> Serialize for DatasetEntry<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let fs_type = match self.metadata.fs_type {
+            FilesystemType::Zfs => "zfs",
+            FilesystemType::Btrfs => "btrfs",
+            FilesystemType::Nilfs2 => "nilfs2",
+            FilesystemType::SnapshotDir(_) => "snapshot-dir",
+        };
+
+        let provider = match self.metadata.mount_type {
+            MountType::Local => "local",
+            MountType::Network => "network",
+        };
+
+        // snap mounts share a parent, like "/.zfs/snapshot", except when there is only one snap,
+        // where the common path is the snap itself
+        let opt_snap_dir = match self.snaps {
+            [] => None,
+            [single] => single.parent().map(Path::to_path_buf),
+            _ => find_common_path(self.snaps),
+        };
+
+        let mut state = serializer.serialize_struct("DatasetEntry", 6)?;
+
+        state.serialize_field("filesystem", &self.metadata.source)?;
+        state.serialize_field("mountpoint", &self.mount)?;
+        state.serialize_field("fstype", fs_type)?;
+        state.serialize_field("provider", provider)?;
+        state.serialize_field("snapshot_dir", &opt_snap_dir)?;
+        state.serialize_field("snapshot_count", &self.snaps.len())?;
+        state.end()
+    }
+}
+
+impl<'a> PrintDatasetMap<'a> {
+    // like a ToString, but fallible, see VersionsDisplayWrapper::to_formatted
+    pub fn to_formatted(&self) -> HttmResult<String> {
+        let s = match GLOBAL_CONFIG.print_mode {
+            PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
+                serde_json::to_string(&self)?
+            }
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&self)?,
+        };
+
+        let delimiter = delimiter();
+
+        Ok(format!("{s}{delimiter}"))
+    }
+}
@@ -25,7 +25,8 @@ use crate::config::generate::MountDisplay;
 use crate::config::generate::{ExecMode, PrintMode};
 use crate::display_versions::format::NOT_SO_PRETTY_FIXED_WIDTH_PADDING;
 use crate::display_versions::format::QUOTATION_MARKS_LEN;
-use crate::library::utility::delimiter;
+use crate::library::results::HttmResult;
+use crate::library::utility::{delimiter, display_width, pad_to_width};
 use crate::SnapNameMap;
 use crate::VersionsMap;
 use crate::{MountsForFiles, GLOBAL_CONFIG};
@@ -115,10 +116,11 @@ impl From<&SnapNameMap> for PrintAsMap {
     }
 }
 
-impl std::string::ToString for PrintAsMap {
-    fn to_string(&self) -> String {
+impl PrintAsMap {
+    // like a ToString, but fallible, see VersionsDisplayWrapper::to_formatted
+    pub fn to_formatted(&self) -> HttmResult<String> {
         if GLOBAL_CONFIG.opt_json {
-            let json_string = self.to_json();
+            let json_string = self.to_json()?;
 
             let res = match &GLOBAL_CONFIG.exec_mode {
                 ExecMode::Display | ExecMode::Interactive(_) => {
@@ -128,10 +130,32 @@ impl std::string::ToString for PrintAsMap {
                 ExecMode::SnapsForFiles(_) => {
                     json_string.replace("\"inner\": ", "\"snapshot_names\": ")
                 }
+                ExecMode::Find(_) => json_string.replace("\"inner\": ", "\"matches\": "),
                 ExecMode::NonInteractiveRecursive(_)
                 | ExecMode::RollForward(_)
+                | ExecMode::History
+                | ExecMode::DatasetMap
+                | ExecMode::MountSnap(_)
+                | ExecMode::Complete(_)
+                | ExecMode::BatchRestore(_)
+                | ExecMode::Digest(_)
+                | ExecMode::UndoRestore
+                | ExecMode::DedupRatio
+                | ExecMode::Bookmark(_)
+                | ExecMode::Watch(_)
+                | ExecMode::Cat(_)
+                | ExecMode::Diff
+                | ExecMode::Verify
+                | ExecMode::Timeline
+                | ExecMode::SizeReport
+                | ExecMode::Churn(_)
+                | ExecMode::Robot
+                | ExecMode::Serve(_)
+                | ExecMode::SelfTest
+                | ExecMode::SnapshotSelect
                 | ExecMode::NumVersions(_)
                 | ExecMode::Purge(_)
+                | ExecMode::PruneDitto
                 | ExecMode::SnapFileMount(_) => {
                     unreachable!(
                         "JSON print should not be available in the selected {:?} execution mode.",
@@ -140,10 +164,10 @@ impl std::string::ToString for PrintAsMap {
                 }
             };
 
-            return res;
+            return Ok(res);
         }
 
-        match &GLOBAL_CONFIG.print_mode {
+        let res = match &GLOBAL_CONFIG.print_mode {
             PrintMode::RawNewline | PrintMode::RawZero => self
                 .values()
                 .flatten()
@@ -153,36 +177,31 @@ impl std::string::ToString for PrintAsMap {
                 })
                 .collect::<String>(),
             PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => self.format(),
-        }
+        };
+
+        Ok(res)
     }
 }
 
 impl PrintAsMap {
     pub fn map_padding(&self) -> usize {
-        self.keys().max_by_key(|key| key.len()).map_or_else(
-            || QUOTATION_MARKS_LEN,
-            |key| key.len() + QUOTATION_MARKS_LEN,
-        )
+        self.keys()
+            .map(|key| display_width(key))
+            .max()
+            .map_or_else(|| QUOTATION_MARKS_LEN, |width| width + QUOTATION_MARKS_LEN)
     }
 
-    pub fn to_json(&self) -> String {
-        let res = match GLOBAL_CONFIG.print_mode {
+    pub fn to_json(&self) -> HttmResult<String> {
+        let s = match GLOBAL_CONFIG.print_mode {
             PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
-                serde_json::to_string(&self)
+                serde_json::to_string(&self)?
             }
-            PrintMode::FormattedDefault => serde_json::to_string_pretty(&self),
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(&self)?,
         };
 
-        match res {
-            Ok(s) => {
-                let delimiter = delimiter();
-                format!("{s}{delimiter}")
-            }
-            Err(error) => {
-                eprintln!("Error: {error}");
-                std::process::exit(1)
-            }
-        }
+        let delimiter = delimiter();
+
+        Ok(format!("{s}{delimiter}"))
     }
 
     pub fn format(&self) -> String {
@@ -212,12 +231,7 @@ impl PrintAsMap {
                         if matches!(&GLOBAL_CONFIG.print_mode, PrintMode::FormattedNotPretty) {
                             format!("{NOT_SO_PRETTY_FIXED_WIDTH_PADDING}{value}")
                         } else if idx == 0 {
-                            format!(
-                                "{:<width$} : \"{}\"\n",
-                                display_path,
-                                value,
-                                width = padding
-                            )
+                            format!("{} : \"{value}\"\n", pad_to_width(&display_path, padding))
                         } else {
                             format!("{:<padding$} : \"{value}\"\n", "")
                         }
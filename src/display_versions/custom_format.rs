@@ -0,0 +1,175 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{BulkExclusion, Config};
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::snap_names::SnapOrigin;
+use crate::VersionsDisplayWrapper;
+
+// a user's format string, see FORMAT, parsed once, so an invalid string is an error before any lookup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomFormat {
+    tokens: Vec<FormatToken>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormatToken {
+    Literal(String),
+    LivePath,
+    VersionPath,
+    Size,
+    Mtime,
+    SnapName,
+    Dataset,
+}
+
+impl CustomFormat {
+    pub fn parse(format_string: &str) -> HttmResult<Self> {
+        let mut tokens = Vec::new();
+        let mut literal = String::new();
+        let mut chars = format_string.chars();
+
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '%' => match chars.next() {
+                    Some('p') => FormatToken::LivePath,
+                    Some('v') => FormatToken::VersionPath,
+                    Some('s') => FormatToken::Size,
+                    Some('m') => FormatToken::Mtime,
+                    Some('n') => FormatToken::SnapName,
+                    Some('d') => FormatToken::Dataset,
+                    Some('%') => {
+                        literal.push('%');
+                        continue;
+                    }
+                    Some(other) => {
+                        let msg = format!("Invalid FORMAT specifier: \"%{other}\".  Valid specifiers are %p, %v, %s, %m, %n, %d, and %%.");
+                        return Err(HttmError::new(&msg).into());
+                    }
+                    None => {
+                        return Err(HttmError::new(
+                            "Invalid FORMAT string: a '%' must be followed by a specifier.",
+                        )
+                        .into())
+                    }
+                },
+                // a format string quoted for the shell, like '%p\t%s', arrives with its escapes intact
+                '\\' => {
+                    match chars.next() {
+                        Some('t') => literal.push('\t'),
+                        Some('n') => literal.push('\n'),
+                        Some('0') => literal.push('\0'),
+                        Some('\\') => literal.push('\\'),
+                        Some(other) => {
+                            literal.push('\\');
+                            literal.push(other);
+                        }
+                        None => literal.push('\\'),
+                    }
+                    continue;
+                }
+                _ => {
+                    literal.push(c);
+                    continue;
+                }
+            };
+
+            if !literal.is_empty() {
+                tokens.push(FormatToken::Literal(std::mem::take(&mut literal)));
+            }
+
+            tokens.push(token);
+        }
+
+        if !literal.is_empty() {
+            tokens.push(FormatToken::Literal(literal));
+        }
+
+        Ok(Self { tokens })
+    }
+
+    fn render(&self, config: &Config, live_version: &PathData, version: &PathData) -> String {
+        // should the live version not exist, it has no size or modify time
+        let mut line: String = self
+            .tokens
+            .iter()
+            .map(|token| match token {
+                FormatToken::Literal(literal) => literal.clone(),
+                FormatToken::LivePath => config
+                    .display_path(&live_version.path_buf)
+                    .to_string_lossy()
+                    .into_owned(),
+                FormatToken::VersionPath => config
+                    .display_path(&version.path_buf)
+                    .to_string_lossy()
+                    .into_owned(),
+                FormatToken::Size => version
+                    .metadata
+                    .map(|md| md.size.to_string())
+                    .unwrap_or_default(),
+                FormatToken::Mtime => version
+                    .metadata
+                    .map(|md| config.display_date(&md.modify_time))
+                    .unwrap_or_default(),
+                FormatToken::SnapName => SnapOrigin::new(config, version)
+                    .and_then(|origin| origin.opt_snap_name)
+                    .unwrap_or_default(),
+                FormatToken::Dataset => SnapOrigin::new(config, version)
+                    .map(|origin| origin.dataset)
+                    .unwrap_or_default(),
+            })
+            .collect();
+
+        line.push('\n');
+        line
+    }
+}
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_custom(&self, custom_format: &CustomFormat) -> String {
+        self.iter()
+            .flat_map(|(live_version, snaps)| {
+                // in last snap mode, the last snap, or, sometimes, the live version, is all we display
+                if self.config.opt_last_snap.is_some() {
+                    return snaps
+                        .iter()
+                        .map(|version| custom_format.render(self.config, live_version, version))
+                        .collect::<Vec<String>>();
+                }
+
+                // snaps are already sorted in time order, and the live version is always last
+                let opt_snaps = match self.config.opt_bulk_exclusion {
+                    Some(BulkExclusion::NoSnap) => None,
+                    _ => Some(snaps),
+                };
+
+                let opt_live_version = match self.config.opt_bulk_exclusion {
+                    Some(BulkExclusion::NoLive) => None,
+                    _ => Some(live_version),
+                };
+
+                opt_snaps
+                    .into_iter()
+                    .flatten()
+                    .chain(opt_live_version)
+                    .map(|version| custom_format.render(self.config, live_version, version))
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,142 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{
+    BulkExclusion, Config, DelimitedColumn, DelimitedFormat, DelimitedOutput,
+};
+use crate::data::paths::PathData;
+use crate::library::utility::{date_string, DateFormat};
+use crate::VersionsDisplayWrapper;
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_delimited(&self, delimited: &DelimitedOutput) -> String {
+        let header = delimited
+            .columns
+            .iter()
+            .map(|column| column.name().to_owned())
+            .collect::<Vec<String>>();
+
+        let rows = self.iter().flat_map(|(live_version, snaps)| {
+            // snaps are already sorted in time order, and the live version is always last
+            let opt_snaps = match self.config.opt_bulk_exclusion {
+                Some(BulkExclusion::NoSnap) => None,
+                _ => Some(snaps),
+            };
+
+            let opt_live_version = match self.config.opt_bulk_exclusion {
+                Some(BulkExclusion::NoLive) => None,
+                _ => Some(live_version),
+            };
+
+            opt_snaps
+                .into_iter()
+                .flatten()
+                .map(move |snap| {
+                    Self::delimited_row(self.config, &delimited.columns, live_version, Some(snap))
+                })
+                .chain(opt_live_version.map(|live_version| {
+                    Self::delimited_row(self.config, &delimited.columns, live_version, None)
+                }))
+        });
+
+        std::iter::once(header)
+            .chain(rows)
+            .map(|row| Self::delimited_line(&delimited.format, &row))
+            .collect()
+    }
+
+    fn delimited_row(
+        config: &Config,
+        columns: &[DelimitedColumn],
+        live_version: &PathData,
+        opt_snap: Option<&PathData>,
+    ) -> Vec<String> {
+        let version = opt_snap.unwrap_or(live_version);
+
+        columns
+            .iter()
+            .map(|column| match column {
+                DelimitedColumn::Path => config
+                    .display_path(&live_version.path_buf)
+                    .to_string_lossy()
+                    .into_owned(),
+                DelimitedColumn::Snap => opt_snap
+                    .map(|snap| {
+                        config
+                            .display_path(&snap.path_buf)
+                            .to_string_lossy()
+                            .into_owned()
+                    })
+                    .unwrap_or_default(),
+                // a live version which does not exist has no modify time or size to display
+                DelimitedColumn::Mtime => version
+                    .metadata
+                    .map(|md| {
+                        date_string(
+                            config.requested_utc_offset,
+                            &md.modify_time,
+                            DateFormat::Iso8601,
+                        )
+                    })
+                    .unwrap_or_default(),
+                DelimitedColumn::Size => version
+                    .metadata
+                    .map(|md| md.size.to_string())
+                    .unwrap_or_default(),
+                DelimitedColumn::Dataset => version
+                    .proximate_dataset(&config.dataset_collection.map_of_datasets)
+                    .ok()
+                    .and_then(|mount| config.dataset_collection.map_of_datasets.get(mount))
+                    .map(|dataset_info| dataset_info.source.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn delimited_line(format: &DelimitedFormat, row: &[String]) -> String {
+        let (separator, escaped): (&str, Vec<String>) = match format {
+            DelimitedFormat::Csv => (
+                ",",
+                row.iter().map(|value| Self::csv_escape(value)).collect(),
+            ),
+            DelimitedFormat::Tsv => (
+                "\t",
+                row.iter().map(|value| Self::tsv_escape(value)).collect(),
+            ),
+        };
+
+        format!("{}\n", escaped.join(separator))
+    }
+
+    // per RFC 4180, a value is quoted only when it must be, and a quotation mark is doubled
+    fn csv_escape(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            return format!("\"{}\"", value.replace('"', "\"\""));
+        }
+
+        value.to_owned()
+    }
+
+    // a tab separated value may not contain a tab or a newline, so these are escaped as awk would read them
+    fn tsv_escape(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    }
+}
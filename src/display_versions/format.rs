@@ -23,7 +23,9 @@ use terminal_size::{terminal_size, Height, Width};
 use crate::config::generate::{BulkExclusion, Config, PrintMode};
 use crate::data::paths::{PathData, PHANTOM_DATE, PHANTOM_SIZE};
 use crate::library::utility::delimiter;
-use crate::library::utility::{date_string, display_human_size, paint_string, DateFormat};
+use crate::library::utility::{display_human_size, display_width, pad_to_width, paint_string};
+use crate::lookup::annotations::SnapAnnotations;
+use crate::lookup::snap_names::SnapOrigin;
 use crate::VersionsDisplayWrapper;
 // 2 space wide padding - used between date and size, and size and path
 pub const PRETTY_FIXED_WIDTH_PADDING: &str = "  ";
@@ -48,7 +50,11 @@ impl<'a> VersionsDisplayWrapper<'a> {
             PrintMode::FormattedDefault | PrintMode::FormattedNotPretty
         ) && self.len() == 1
         {
-            return global_display_set.format(self.config, &padding_collection);
+            return global_display_set.format(
+                self.config,
+                &padding_collection,
+                self.opt_annotations.as_ref(),
+            );
         }
 
         // else re compute for each instance and print per instance, now with uniform padding
@@ -60,9 +66,12 @@ impl<'a> VersionsDisplayWrapper<'a> {
                 let display_set = DisplaySet::from((keys, values));
 
                 match &self.config.print_mode {
-                    PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => {
-                        display_set.format(self.config, &padding_collection)
-                    }
+                    PrintMode::FormattedDefault | PrintMode::FormattedNotPretty => display_set
+                        .format(
+                            self.config,
+                            &padding_collection,
+                            self.opt_annotations.as_ref(),
+                        ),
                     PrintMode::RawNewline | PrintMode::RawZero => {
                         let delimiter = delimiter();
 
@@ -79,7 +88,10 @@ impl<'a> VersionsDisplayWrapper<'a> {
                                 snap_or_live_set
                                     .iter()
                                     .map(|pathdata| {
-                                        format!("{}{delimiter}", pathdata.path_buf.display())
+                                        format!(
+                                            "{}{delimiter}",
+                                            self.config.display_path(&pathdata.path_buf).display()
+                                        )
                                     })
                                     .collect::<String>()
                             })
@@ -131,7 +143,7 @@ impl From<usize> for DisplaySetType {
 
 impl DisplaySetType {
     #[inline]
-    fn filter_bulk_exclusions(&self, config: &Config) -> bool {
+    pub fn filter_bulk_exclusions(&self, config: &Config) -> bool {
         match &self {
             DisplaySetType::IsLive
                 if matches!(config.opt_bulk_exclusion, Some(BulkExclusion::NoLive)) =>
@@ -149,7 +161,12 @@ impl DisplaySetType {
 }
 
 impl<'a> DisplaySet<'a> {
-    pub fn format(&self, config: &Config, padding_collection: &PaddingCollection) -> String {
+    pub fn format(
+        &self,
+        config: &Config,
+        padding_collection: &PaddingCollection,
+        opt_annotations: Option<&SnapAnnotations>,
+    ) -> String {
         // get the display buffer for each set snaps and live
         self.iter()
             .enumerate()
@@ -163,7 +180,18 @@ impl<'a> DisplaySet<'a> {
                     let component_buffer: String = snap_or_live_set
                         .iter()
                         .map(|pathdata| {
-                            pathdata.format(config, &display_set_type, padding_collection)
+                            let opt_annotation = match display_set_type {
+                                DisplaySetType::IsSnap => opt_annotations
+                                    .and_then(|annotations| annotations.display(pathdata)),
+                                DisplaySetType::IsLive => None,
+                            };
+
+                            pathdata.format(
+                                config,
+                                &display_set_type,
+                                padding_collection,
+                                opt_annotation,
+                            )
                         })
                         .collect();
 
@@ -192,6 +220,7 @@ impl PathData {
         config: &Config,
         display_set_type: &DisplaySetType,
         padding_collection: &PaddingCollection,
+        opt_annotation: Option<String>,
     ) -> String {
         // obtain metadata for timestamp and size
         let metadata = self.md_infallible();
@@ -208,7 +237,7 @@ impl PathData {
                 } else {
                     Cow::Borrowed(&padding_collection.phantom_size_pad_str)
                 };
-                let path = self.path_buf.to_string_lossy();
+                let path = config.display_path(&self.path_buf).to_string_lossy();
                 let padding = NOT_SO_PRETTY_FIXED_WIDTH_PADDING;
                 (size, path, padding)
             }
@@ -227,7 +256,7 @@ impl PathData {
                     ))
                 };
                 let path = {
-                    let path_buf = &self.path_buf;
+                    let path_buf = config.display_path(&self.path_buf);
 
                     // paint the live strings with ls colors - idx == 1 is 2nd or live set
                     let painted_path_str = match display_set_type {
@@ -238,9 +267,8 @@ impl PathData {
                     };
 
                     Cow::Owned(format!(
-                        "\"{:<width$}\"",
-                        painted_path_str,
-                        width = padding_collection.size_padding_len
+                        "\"{}\"",
+                        pad_to_width(&painted_path_str, padding_collection.size_padding_len)
                     ))
                 };
                 // displays blanks for phantom values, equaling their dummy lens and dates.
@@ -249,25 +277,76 @@ impl PathData {
             }
         };
 
+        // a relative date may be of any width, so each is padded to the widest
         let display_date = if self.metadata.is_some() {
-            Cow::Owned(date_string(
-                config.requested_utc_offset,
-                &metadata.modify_time,
-                DateFormat::Display,
+            Cow::Owned(format!(
+                "{:<width$}",
+                config.display_date(&metadata.modify_time),
+                width = padding_collection.date_padding_len
             ))
         } else {
             Cow::Borrowed(&padding_collection.phantom_date_pad_str)
         };
 
-        format!(
-            "{}{}{}{}{}\n",
-            display_date, display_padding, display_size, display_padding, display_path
-        )
+        // the snapshot name and dataset columns, each followed by padding, see SNAP_INFO
+        let display_origin = if config.opt_snap_info {
+            let (snap_name, dataset) = Self::display_origin(config, self);
+
+            match &config.print_mode {
+                PrintMode::FormattedNotPretty => {
+                    format!("{snap_name}{display_padding}{dataset}{display_padding}")
+                }
+                _ => format!(
+                    "{}{display_padding}{}{display_padding}",
+                    pad_to_width(&snap_name, padding_collection.snap_name_padding_len),
+                    pad_to_width(&dataset, padding_collection.dataset_padding_len)
+                ),
+            }
+        } else {
+            String::new()
+        };
+
+        match opt_annotation {
+            Some(annotation) => format!(
+                "{}{}{}{}{}{}{}{}\n",
+                display_date,
+                display_padding,
+                display_size,
+                display_padding,
+                display_origin,
+                display_path,
+                display_padding,
+                annotation
+            ),
+            None => format!(
+                "{}{}{}{}{}{}\n",
+                display_date,
+                display_padding,
+                display_size,
+                display_padding,
+                display_origin,
+                display_path
+            ),
+        }
+    }
+
+    // the live version has no snapshot name, so it is displayed as a "-"
+    pub fn display_origin(config: &Config, pathdata: &PathData) -> (String, String) {
+        match SnapOrigin::new(config, pathdata) {
+            Some(origin) => (
+                origin.opt_snap_name.unwrap_or_else(|| "-".to_owned()),
+                origin.dataset,
+            ),
+            None => ("-".to_owned(), "-".to_owned()),
+        }
     }
 }
 
 pub struct PaddingCollection {
     pub size_padding_len: usize,
+    pub date_padding_len: usize,
+    pub snap_name_padding_len: usize,
+    pub dataset_padding_len: usize,
     pub fancy_border_string: String,
     pub phantom_date_pad_str: String,
     pub phantom_size_pad_str: String,
@@ -276,51 +355,71 @@ pub struct PaddingCollection {
 impl PaddingCollection {
     pub fn new(config: &Config, display_set: &DisplaySet) -> PaddingCollection {
         // calculate padding and borders for display later
-        let (size_padding_len, fancy_border_len) = display_set.iter().flatten().fold(
-            (0usize, 0usize),
-            |(mut size_padding_len, mut fancy_border_len), pathdata| {
-                let metadata = pathdata.md_infallible();
-
-                let (display_date, display_size, display_path) = {
-                    let date = date_string(
-                        config.requested_utc_offset,
-                        &metadata.modify_time,
-                        DateFormat::Display,
-                    );
-                    let size = format!(
-                        "{:>width$}",
-                        display_human_size(metadata.size),
-                        width = size_padding_len
-                    );
-                    let path = pathdata.path_buf.to_string_lossy();
+        let (size_padding_len, date_padding_len, fancy_border_len) =
+            display_set.iter().flatten().fold(
+                (0usize, 0usize, 0usize),
+                |(mut size_padding_len, mut date_padding_len, mut fancy_border_len), pathdata| {
+                    let metadata = pathdata.md_infallible();
+
+                    let (display_date, display_size, display_path) = {
+                        let date = config.display_date(&metadata.modify_time);
+                        let size = format!(
+                            "{:>width$}",
+                            display_human_size(metadata.size),
+                            width = size_padding_len
+                        );
+                        let path = config.display_path(&pathdata.path_buf).to_string_lossy();
+
+                        (date, size, path)
+                    };
 
-                    (date, size, path)
-                };
+                    let display_size_len = display_human_size(metadata.size).len();
+                    // a line's len is the number of columns it occupies, not its number of bytes
+                    let formatted_line_len = display_width(&display_date)
+                        + display_width(&display_size)
+                        + display_width(&display_path)
+                        + PRETTY_FIXED_WIDTH_PADDING_LEN_X2
+                        + QUOTATION_MARKS_LEN;
+
+                    size_padding_len = display_size_len.max(size_padding_len);
+                    date_padding_len = display_width(&display_date).max(date_padding_len);
+                    fancy_border_len = formatted_line_len.max(fancy_border_len);
+                    (size_padding_len, date_padding_len, fancy_border_len)
+                },
+            );
+
+        // only when requested, as finding the origin of each version is not free
+        let (snap_name_padding_len, dataset_padding_len) = if config.opt_snap_info {
+            display_set.iter().flatten().fold(
+                (0usize, 0usize),
+                |(snap_name_padding_len, dataset_padding_len), pathdata| {
+                    let (snap_name, dataset) = PathData::display_origin(config, pathdata);
+
+                    (
+                        display_width(&snap_name).max(snap_name_padding_len),
+                        display_width(&dataset).max(dataset_padding_len),
+                    )
+                },
+            )
+        } else {
+            (0usize, 0usize)
+        };
 
-                let display_size_len = display_human_size(metadata.size).len();
-                let formatted_line_len = display_date.len()
-                    + display_size.len()
-                    + display_path.len()
-                    + PRETTY_FIXED_WIDTH_PADDING_LEN_X2
-                    + QUOTATION_MARKS_LEN;
-
-                size_padding_len = display_size_len.max(size_padding_len);
-                fancy_border_len = formatted_line_len.max(fancy_border_len);
-                (size_padding_len, fancy_border_len)
-            },
-        );
+        let fancy_border_len = if config.opt_snap_info {
+            fancy_border_len
+                + snap_name_padding_len
+                + dataset_padding_len
+                + PRETTY_FIXED_WIDTH_PADDING_LEN_X2
+        } else {
+            fancy_border_len
+        };
 
         let fancy_border_string: String = Self::fancy_border_string(fancy_border_len);
 
         let phantom_date_pad_str = format!(
             "{:<width$}",
             "",
-            width = date_string(
-                config.requested_utc_offset,
-                &PHANTOM_DATE,
-                DateFormat::Display
-            )
-            .len()
+            width = date_padding_len.max(config.display_date(&PHANTOM_DATE).len())
         );
         let phantom_size_pad_str = format!(
             "{:<width$}",
@@ -330,6 +429,9 @@ impl PaddingCollection {
 
         PaddingCollection {
             size_padding_len,
+            date_padding_len,
+            snap_name_padding_len,
+            dataset_padding_len,
             fancy_border_string,
             phantom_date_pad_str,
             phantom_size_pad_str,
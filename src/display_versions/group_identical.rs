@@ -0,0 +1,205 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::config::generate::Config;
+use crate::data::paths::{HashFromFile, PathData};
+use crate::exec::digest::DigestOfSnaps;
+use crate::library::results::HttmResult;
+use crate::library::utility::display_human_size;
+use crate::VersionsDisplayWrapper;
+
+// versions which share the same contents, with the position of each version,
+// in time order, so we may display contiguous ranges of snapshots.  the key, a size and an Adler32,
+// only says which versions may be identical, and each is compared, byte for byte, with the group's first version
+struct IdenticalGroup<'a> {
+    key: (u64, u32),
+    first_version: &'a PathData,
+    members: Vec<(usize, String)>,
+}
+
+impl<'a> VersionsDisplayWrapper<'a> {
+    pub fn format_as_groups(&self) -> String {
+        self.iter()
+            .map(|(live_version, snaps)| {
+                Self::format_identical_groups(self.config, live_version, snaps)
+            })
+            .collect()
+    }
+
+    fn format_identical_groups(
+        config: &Config,
+        live_version: &PathData,
+        snaps: &[PathData],
+    ) -> String {
+        let display_path = config.display_path(&live_version.path_buf).display();
+
+        // snaps are already sorted in time order, and the live version, if it exists, is always last
+        let all_versions: Vec<(&PathData, String)> = snaps
+            .iter()
+            .map(|snap| (snap, Self::snap_label(config, snap)))
+            .chain(
+                std::iter::once(live_version)
+                    .filter(|live_version| live_version.metadata.is_some())
+                    .map(|live_version| (live_version, "live".to_owned())),
+            )
+            .collect();
+
+        // versions which cannot be read, or are not files, cannot be compared by contents
+        let keys: Vec<Option<(u64, u32)>> = all_versions
+            .par_iter()
+            .map(|(pathdata, _label)| {
+                let size = pathdata.metadata?.size;
+
+                HashFromFile::try_from(pathdata.path_buf.as_path())
+                    .ok()
+                    .map(|hash| (size, hash.into_inner()))
+            })
+            .collect();
+
+        let groups: Vec<IdenticalGroup> = all_versions.iter().zip(keys).enumerate().fold(
+            Vec::new(),
+            |mut groups, (idx, ((pathdata, label), opt_key))| {
+                if let Some(key) = opt_key {
+                    match groups.iter_mut().find(|group| {
+                        group.key == key
+                            && Self::is_same_contents(
+                                &group.first_version.path_buf,
+                                &pathdata.path_buf,
+                            )
+                            .unwrap_or(false)
+                    }) {
+                        Some(group) => group.members.push((idx, label.clone())),
+                        None => groups.push(IdenticalGroup {
+                            key,
+                            first_version: pathdata,
+                            members: vec![(idx, label.clone())],
+                        }),
+                    }
+                }
+
+                groups
+            },
+        );
+
+        if groups.is_empty() {
+            return format!("\"{display_path}\" : No versions could be compared by contents.\n");
+        }
+
+        let size_padding = groups
+            .iter()
+            .map(|group| display_human_size(group.key.0).len())
+            .max()
+            .unwrap_or_default();
+
+        let header = format!(
+            "\"{display_path}\" : {} distinct {}\n",
+            groups.len(),
+            if groups.len() == 1 {
+                "version"
+            } else {
+                "versions"
+            }
+        );
+
+        groups
+            .iter()
+            .enumerate()
+            .fold(header, |mut buffer, (group_idx, group)| {
+                let num_members = group.members.len();
+
+                buffer += &format!(
+                    "  {:>3}.  {:>size_padding$}  {}  {} ({num_members} {})\n",
+                    group_idx + 1,
+                    display_human_size(group.key.0),
+                    config.display_date(&group.first_version.md_infallible().modify_time),
+                    Self::format_ranges(&group.members),
+                    if num_members == 1 {
+                        "version"
+                    } else {
+                        "versions"
+                    }
+                );
+
+                buffer
+            })
+    }
+
+    // collapse contiguous runs of versions, like "snap_1 .. snap_4, snap_7, live"
+    fn format_ranges(members: &[(usize, String)]) -> String {
+        let mut ranges: Vec<(usize, &str, &str)> = Vec::new();
+
+        members
+            .iter()
+            .for_each(|(idx, label)| match ranges.last_mut() {
+                Some((last_idx, _first, last)) if *last_idx + 1 == *idx => {
+                    *last_idx = *idx;
+                    *last = label;
+                }
+                _ => ranges.push((*idx, label, label)),
+            });
+
+        ranges
+            .into_iter()
+            .map(|(_idx, first, last)| {
+                if first == last {
+                    first.to_owned()
+                } else {
+                    format!("{first} .. {last}")
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    fn is_same_contents(a: &Path, b: &Path) -> HttmResult<bool> {
+        let mut a_reader = BufReader::new(File::open(a)?);
+        let mut b_reader = BufReader::new(File::open(b)?);
+
+        loop {
+            let a_buf = a_reader.fill_buf()?;
+            let b_buf = b_reader.fill_buf()?;
+
+            if a_buf.is_empty() && b_buf.is_empty() {
+                return Ok(true);
+            }
+
+            let len = a_buf.len().min(b_buf.len());
+
+            if len == 0 || a_buf[..len] != b_buf[..len] {
+                return Ok(false);
+            }
+
+            a_reader.consume(len);
+            b_reader.consume(len);
+        }
+    }
+
+    // the snapshot name, from the snap mount which contains the version, whatever the provider
+    fn snap_label(config: &Config, snap: &PathData) -> String {
+        config
+            .dataset_collection
+            .snap_mount_containing(&snap.path_buf)
+            .map(|(_mount, snap_mount)| DigestOfSnaps::snap_name(snap_mount))
+            .unwrap_or_else(|| snap.path_buf.to_string_lossy().into_owned())
+    }
+}
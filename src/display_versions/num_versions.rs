@@ -15,9 +15,10 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use crate::config::generate::NumVersionsMode;
+use crate::config::generate::{DittoOfType, NumVersionsMode};
 use crate::data::paths::PathData;
 use crate::display_map::format::PrintAsMap;
+use crate::library::utility::pad_to_width;
 use crate::lookup::versions::VersionsMap;
 use crate::VersionsDisplayWrapper;
 
@@ -35,6 +36,7 @@ impl<'a> VersionsDisplayWrapper<'a> {
             .filter_map(|(live_version, snaps)| {
                 Self::parse_num_versions(
                     num_versions_mode,
+                    &self.config.ditto_of_type,
                     delimiter,
                     live_version,
                     snaps,
@@ -64,19 +66,17 @@ impl<'a> VersionsDisplayWrapper<'a> {
 
     fn parse_num_versions(
         num_versions_mode: &NumVersionsMode,
+        ditto_of_type: &DittoOfType,
         delimiter: char,
         live_version: &PathData,
         snaps: &[PathData],
         padding: usize,
     ) -> Option<String> {
         let display_path = live_version.path_buf.display();
+        let padded_path = pad_to_width(&display_path.to_string(), padding);
 
         if live_version.metadata.is_none() {
-            eprintln!(
-                "{:<width$} : Path does not exist.",
-                display_path,
-                width = padding
-            );
+            eprintln!("{padded_path} : Path does not exist.");
             return None;
         }
 
@@ -84,44 +84,33 @@ impl<'a> VersionsDisplayWrapper<'a> {
 
         match num_versions_mode {
             NumVersionsMode::AllGraph => {
-                if !VersionsMap::is_live_version_redundant(live_version, snaps) {
+                if !VersionsMap::is_live_version_redundant(live_version, snaps, ditto_of_type) {
                     num_versions += 1
                 };
 
-                Some(format!(
-                    "{:<width$} : {:*<num_versions$}{}",
-                    display_path,
-                    "",
-                    delimiter,
-                    width = padding
-                ))
+                Some(format!("{padded_path} : {:*<num_versions$}{delimiter}", ""))
             }
             NumVersionsMode::AllNumerals => {
-                if !VersionsMap::is_live_version_redundant(live_version, snaps) {
+                if !VersionsMap::is_live_version_redundant(live_version, snaps, ditto_of_type) {
                     num_versions += 1
                 };
 
                 if num_versions == 1 {
-                    Some(format!(
-                        "{:<width$} : 1 Version available.{}",
-                        display_path,
-                        delimiter,
-                        width = padding
-                    ))
+                    Some(format!("{padded_path} : 1 Version available.{delimiter}"))
                 } else {
                     Some(format!(
-                        "{:<width$} : {} Versions available.{}",
-                        display_path,
-                        num_versions,
-                        delimiter,
-                        width = padding
+                        "{padded_path} : {num_versions} Versions available.{delimiter}"
                     ))
                 }
             }
             NumVersionsMode::Multiple => {
                 if num_versions == 0
                     || (num_versions == 1
-                        && VersionsMap::is_live_version_redundant(live_version, snaps))
+                        && VersionsMap::is_live_version_redundant(
+                            live_version,
+                            snaps,
+                            ditto_of_type,
+                        ))
                 {
                     None
                 } else {
@@ -131,7 +120,11 @@ impl<'a> VersionsDisplayWrapper<'a> {
             NumVersionsMode::SingleAll => {
                 if num_versions == 0
                     || (num_versions == 1
-                        && VersionsMap::is_live_version_redundant(live_version, snaps))
+                        && VersionsMap::is_live_version_redundant(
+                            live_version,
+                            snaps,
+                            ditto_of_type,
+                        ))
                 {
                     Some(format!("{display_path}{delimiter}"))
                 } else {
@@ -146,7 +139,8 @@ impl<'a> VersionsDisplayWrapper<'a> {
                 }
             }
             NumVersionsMode::SingleWithSnap => {
-                if num_versions == 1 && VersionsMap::is_live_version_redundant(live_version, snaps)
+                if num_versions == 1
+                    && VersionsMap::is_live_version_redundant(live_version, snaps, ditto_of_type)
                 {
                     Some(format!("{display_path}{delimiter}"))
                 } else {
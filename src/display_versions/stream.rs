@@ -0,0 +1,128 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::os::unix::ffi::OsStrExt;
+
+use crate::config::generate::{Config, ExecMode, PrintMode};
+use crate::data::paths::PathData;
+use crate::display_versions::format::{DisplaySet, DisplaySetType};
+use crate::library::results::{
+    HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE, NO_VERSIONS_EXIT_CODE,
+};
+use crate::library::utility::{delimiter, print_output_bytes};
+use crate::lookup::versions::{VersionsMap, NOTHING_FOUND_MSG, NO_SNAPSHOT_SOURCE_MSG};
+
+// raw output needs no padding computed across every path, so, rather than wait upon
+// every lookup to complete, we print the versions of each path as soon as they are found
+pub struct RawStream;
+
+impl RawStream {
+    pub fn is_eligible(config: &Config) -> bool {
+        matches!(config.exec_mode, ExecMode::Display)
+            && matches!(
+                config.print_mode,
+                PrintMode::RawNewline | PrintMode::RawZero
+            )
+            && !config.opt_json
+            && !config.opt_group_identical
+            && config.opt_last_snap.is_none()
+            && config.opt_remote_host.is_none()
+            && config.opt_object_stores.is_none()
+    }
+
+    pub fn exec(config: &Config, path_set: &[PathData]) -> HttmResult<()> {
+        let mut is_any_found = false;
+        let mut is_any_resolved = false;
+        let mut is_any_snap_version = false;
+
+        for pathdata in path_set {
+            let mut versions_map = VersionsMap::lookup(config, std::slice::from_ref(pathdata))?;
+
+            // only an error if no path is found, just as when we lookup every path at once
+            if !versions_map.is_nothing_found(config) {
+                is_any_found = true;
+            }
+
+            if !versions_map.is_empty() {
+                is_any_resolved = true;
+            }
+
+            if !versions_map.is_no_snap_versions(config) {
+                is_any_snap_version = true;
+            }
+
+            versions_map.post_process(config);
+
+            Self::print(config, &versions_map)?;
+        }
+
+        if !is_any_found {
+            // as with VersionsMap::nothing_found_error, distinguish paths upon no snapshot source
+            let error = if is_any_resolved {
+                HttmError::with_exit_code(NOTHING_FOUND_MSG, NO_VERSIONS_EXIT_CODE)
+            } else {
+                HttmError::with_exit_code(NO_SNAPSHOT_SOURCE_MSG, NO_SNAPSHOT_SOURCE_EXIT_CODE)
+            };
+
+            return Err(error.into());
+        }
+
+        // live versions were printed, but there were no snapshot versions to print
+        if !is_any_snap_version {
+            return Err(HttmError::with_exit_code(
+                "httm found no snapshot versions of the paths given.",
+                NO_VERSIONS_EXIT_CODE,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    fn print(config: &Config, versions_map: &VersionsMap) -> HttmResult<()> {
+        let delimiter = delimiter() as u8;
+
+        let output_buf: Vec<u8> = versions_map
+            .iter()
+            .flat_map(|(key, values)| {
+                let display_set = DisplaySet::from((vec![key], values.iter().collect()));
+
+                display_set
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, snap_or_live_set)| (DisplaySetType::from(idx), snap_or_live_set))
+                    .filter(|(display_set_type, _snap_or_live_set)| {
+                        display_set_type.filter_bulk_exclusions(config)
+                    })
+                    .flat_map(|(_display_set_type, snap_or_live_set)| snap_or_live_set.iter())
+                    .flat_map(|pathdata| {
+                        // write the path as is, so a path with a newline remains safe when zero delimited
+                        let mut bytes = config
+                            .display_path(&pathdata.path_buf)
+                            .as_os_str()
+                            .as_bytes()
+                            .to_vec();
+                        bytes.push(delimiter);
+                        bytes
+                    })
+                    .collect::<Vec<u8>>()
+            })
+            .collect();
+
+        print_output_bytes(&output_buf)
+    }
+}
@@ -23,33 +23,54 @@ use serde::{Serialize, Serializer};
 use crate::config::generate::{BulkExclusion, Config, ExecMode, PrintMode};
 use crate::data::paths::PathData;
 use crate::display_map::format::PrintAsMap;
+use crate::library::results::HttmResult;
 use crate::library::utility::delimiter;
+use crate::lookup::annotations::SnapAnnotations;
+use crate::lookup::diffstat::VersionDiffStats;
+use crate::lookup::snap_names::SnapOrigin;
 use crate::lookup::versions::VersionsMap;
 
 pub struct VersionsDisplayWrapper<'a> {
     pub config: &'a Config,
     pub map: VersionsMap,
+    pub opt_annotations: Option<SnapAnnotations>,
+    pub opt_diffstats: Option<VersionDiffStats>,
 }
 
-impl<'a> std::string::ToString for VersionsDisplayWrapper<'a> {
-    fn to_string(&self) -> String {
-        match &self.config.exec_mode {
+impl<'a> VersionsDisplayWrapper<'a> {
+    // like a ToString, but fallible, as a library consumer's process should never exit upon a serialization error
+    pub fn to_formatted(&self) -> HttmResult<String> {
+        let res = match &self.config.exec_mode {
             ExecMode::NumVersions(num_versions_mode) => {
                 self.format_as_num_versions(num_versions_mode)
             }
             _ => {
+                if let Some(delimited) = &self.config.opt_delimited {
+                    return Ok(self.format_as_delimited(delimited));
+                }
+
+                if let Some(custom_format) = &self.config.opt_custom_format {
+                    return Ok(self.format_as_custom(custom_format));
+                }
+
                 if self.config.opt_last_snap.is_some() {
                     let printable_map = PrintAsMap::from(&self.map);
-                    return printable_map.to_string();
+                    return printable_map.to_formatted();
                 }
 
                 if self.config.opt_json {
                     return self.to_json();
                 }
 
+                if self.config.opt_group_identical {
+                    return Ok(self.format_as_groups());
+                }
+
                 self.format()
             }
-        }
+        };
+
+        Ok(res)
     }
 }
 
@@ -63,27 +84,36 @@ impl<'a> Deref for VersionsDisplayWrapper<'a> {
 
 impl<'a> VersionsDisplayWrapper<'a> {
     pub fn from(config: &'a Config, map: VersionsMap) -> Self {
-        Self { config, map }
+        // annotations are merely informational, so we warn, rather than quit, on error
+        let opt_annotations = config.opt_annotate.as_ref().and_then(|properties| {
+            SnapAnnotations::new(&map, properties)
+                .map_err(|error| eprintln!("WARNING: {error}"))
+                .ok()
+        });
+
+        // every version must be read, so only when the diffstat will actually be displayed
+        let opt_diffstats = (config.opt_diffstat && config.opt_json)
+            .then(|| VersionDiffStats::new(&map, &config.opt_bulk_exclusion));
+
+        Self {
+            config,
+            map,
+            opt_annotations,
+            opt_diffstats,
+        }
     }
 
-    pub fn to_json(&self) -> String {
-        let res = match self.config.print_mode {
+    pub fn to_json(&self) -> HttmResult<String> {
+        let s = match self.config.print_mode {
             PrintMode::FormattedNotPretty | PrintMode::RawNewline | PrintMode::RawZero => {
-                serde_json::to_string(self)
+                serde_json::to_string(self)?
             }
-            PrintMode::FormattedDefault => serde_json::to_string_pretty(self),
+            PrintMode::FormattedDefault => serde_json::to_string_pretty(self)?,
         };
 
-        match res {
-            Ok(s) => {
-                let delimiter = delimiter();
-                format!("{s}{delimiter}")
-            }
-            Err(error) => {
-                eprintln!("Error: {error}");
-                std::process::exit(1)
-            }
-        }
+        let delimiter = delimiter();
+
+        Ok(format!("{s}{delimiter}"))
     }
 }
 
@@ -93,25 +123,100 @@ impl<'a> Serialize for VersionsDisplayWrapper<'a> {
         S: Serializer,
     {
         // 3 is the number of fields in the struct.
-        let mut state = serializer.serialize_struct("VersionMap", 1)?;
+        let mut state = serializer.serialize_struct("VersionMap", 2)?;
 
         // add live file key to values if needed before serializing
-        let new_map: BTreeMap<String, Vec<PathData>> = self
+        let new_map: BTreeMap<String, Vec<DisplayPathData>> = self
             .deref()
             .clone()
             .into_iter()
-            .map(|(key, values)| match &self.config.opt_bulk_exclusion {
-                Some(BulkExclusion::NoLive) => (key.path_buf.display().to_string(), values),
-                Some(BulkExclusion::NoSnap) => (key.path_buf.display().to_string(), vec![key]),
-                None => {
-                    let mut new_values = values;
-                    new_values.push(key.clone());
-                    (key.path_buf.display().to_string(), new_values)
+            .map(|(key, values)| {
+                let display_key = self
+                    .config
+                    .display_path(&key.path_buf)
+                    .display()
+                    .to_string();
+
+                match &self.config.opt_bulk_exclusion {
+                    Some(BulkExclusion::NoLive) => (display_key, values),
+                    Some(BulkExclusion::NoSnap) => (display_key, vec![key]),
+                    None => {
+                        let mut new_values = values;
+                        new_values.push(key.clone());
+                        (display_key, new_values)
+                    }
                 }
             })
+            .map(|(display_key, values)| {
+                let display_values = values
+                    .into_iter()
+                    .map(|pathdata| DisplayPathData {
+                        config: self.config,
+                        pathdata,
+                    })
+                    .collect();
+
+                (display_key, display_values)
+            })
             .collect();
 
         state.serialize_field("versions", &new_map)?;
+
+        if let Some(annotations) = &self.opt_annotations {
+            let annotations_map: BTreeMap<String, &BTreeMap<String, String>> = annotations
+                .iter()
+                .map(|(path, props)| (self.config.display_path(path).display().to_string(), props))
+                .collect();
+
+            state.serialize_field("annotations", &annotations_map)?;
+        }
+
+        if let Some(diffstats) = &self.opt_diffstats {
+            let diffstat_map: BTreeMap<String, _> = diffstats
+                .iter()
+                .map(|(path, stat)| (self.config.display_path(path).display().to_string(), stat))
+                .collect();
+
+            state.serialize_field("diffstat", &diffstat_map)?;
+        }
+
+        state.end()
+    }
+}
+
+// a version, as displayed, relative to any RELATIVE root, and with its snapshot name and dataset, per SNAP_INFO,
+// which only the display need know of, so serializing a PathData alone, as a library consumer might, needs no config
+struct DisplayPathData<'a> {
+    config: &'a Config,
+    pathdata: PathData,
+}
+
+impl<'a> Serialize for DisplayPathData<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("PathData", 2)?;
+
+        state.serialize_field("path", self.config.display_path(&self.pathdata.path_buf))?;
+        state.serialize_field("metadata", &self.pathdata.metadata)?;
+
+        // the live version has no snapshot name, so its snap_name is null
+        if self.config.opt_snap_info {
+            let opt_origin = SnapOrigin::new(self.config, &self.pathdata);
+
+            state.serialize_field(
+                "snap_name",
+                &opt_origin
+                    .as_ref()
+                    .and_then(|origin| origin.opt_snap_name.as_ref()),
+            )?;
+            state.serialize_field(
+                "dataset",
+                &opt_origin.as_ref().map(|origin| &origin.dataset),
+            )?;
+        }
+
         state.end()
     }
 }
@@ -0,0 +1,274 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::config::generate::{BatchRestoreConfig, RestoreMode};
+use crate::data::paths::PathData;
+use crate::exec::interactive::InteractiveRestore;
+use crate::exec::pipeline::Output;
+use crate::library::results::{
+    HttmError, HttmResult, NO_VERSIONS_EXIT_CODE, RESTORE_DECLINED_EXIT_CODE,
+};
+use crate::lookup::versions::VersionsMap;
+use crate::{GLOBAL_CONFIG, ROOT_DIRECTORY};
+
+pub struct BatchRestore;
+
+impl BatchRestore {
+    pub fn exec(batch_config: &BatchRestoreConfig) -> HttmResult<Output> {
+        let input = Self::read_input(batch_config.opt_from_file.as_deref())?;
+
+        let lines: Vec<(usize, &str)> = input
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .enumerate()
+            .filter(|(_idx, line)| !line.trim().is_empty())
+            .collect();
+
+        if lines.is_empty() {
+            return Err(HttmError::new("httm found no files to restore.").into());
+        }
+
+        // resolve every line before restoring any file, so that a bad line restores nothing
+        let (resolved, unresolved): (Vec<_>, Vec<_>) = lines
+            .iter()
+            .map(|(idx, line)| {
+                (
+                    idx,
+                    Self::restore_pair(line, &batch_config.restore_mode, lines.len() > 1),
+                )
+            })
+            .partition(|(_idx, res)| res.is_ok());
+
+        if !unresolved.is_empty() {
+            unresolved.iter().for_each(|(idx, res)| {
+                if let Err(err) = res {
+                    eprintln!("httm could not resolve line {}: {err}", *idx + 1)
+                }
+            });
+
+            let msg = format!(
+                "httm could not resolve {} of {} lines.  No files were restored.",
+                unresolved.len(),
+                lines.len()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let restore_pairs: Vec<(PathData, PathBuf)> =
+            resolved.into_iter().flat_map(|(_idx, res)| res).collect();
+
+        InteractiveRestore::check_unique_destinations(&restore_pairs)?;
+
+        let should_preserve = InteractiveRestore::should_preserve_attributes();
+
+        // nothing is executed, so there is no need for the user's consent
+        if GLOBAL_CONFIG.opt_print_cmd {
+            return Ok(InteractiveRestore::print_cmds(
+                &restore_pairs,
+                should_preserve,
+            ));
+        }
+
+        // likewise, nothing is copied in a dry run
+        if GLOBAL_CONFIG.opt_dry_run {
+            return InteractiveRestore::dry_run(&restore_pairs);
+        }
+
+        InteractiveRestore::check_open_files(&restore_pairs)?;
+
+        if !batch_config.skip_consent && !Self::user_consents(&restore_pairs)? {
+            println!("User declined restore.  No files were restored.");
+            return Ok(Output::new(String::new(), RESTORE_DECLINED_EXIT_CODE));
+        }
+
+        // in overwrite mode, the live path of each pair is its destination, so there is no single live path
+        InteractiveRestore::restore_all(&restore_pairs, should_preserve, None)?;
+
+        Ok(Output::default())
+    }
+
+    fn read_input(opt_from_file: Option<&Path>) -> HttmResult<String> {
+        match opt_from_file {
+            Some(from_file) => std::fs::read_to_string(from_file).map_err(|err| {
+                let msg = format!("httm could not read the file list {from_file:?}: {err}");
+                HttmError::new(&msg).into()
+            }),
+            None => {
+                let mut buffer = String::new();
+                std::io::stdin().lock().read_to_string(&mut buffer)?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    // each line is a live path and a snapshot version, delimited by a tab, or, with last snap, just a live path
+    fn restore_pair(
+        line: &str,
+        restore_mode: &RestoreMode,
+        is_multiple: bool,
+    ) -> HttmResult<(PathData, PathBuf)> {
+        let (live_str, opt_snap_str) = match line.split_once('\t') {
+            Some((live_str, snap_str)) => (live_str, Some(snap_str)),
+            None => (line, None),
+        };
+
+        let live_path = GLOBAL_CONFIG.pwd.path_buf.join(live_str);
+
+        let snap_pathdata = match opt_snap_str {
+            Some(snap_str) => Self::snap_of_live_path(snap_str, &live_path)?,
+            None if GLOBAL_CONFIG.opt_last_snap.is_some() => Self::last_snap(&live_path)?,
+            None => {
+                return Err(HttmError::new(
+                    "no snapshot version was specified, and LAST_SNAP was not specified",
+                )
+                .into())
+            }
+        };
+
+        // sanity check -- snap version has good metadata?
+        let snap_path_metadata = snap_pathdata.metadata.ok_or_else(|| {
+            let msg = format!(
+                "snapshot version {:?} does not exist on disk",
+                snap_pathdata.path_buf
+            );
+            HttmError::new(&msg)
+        })?;
+
+        let new_file_path_buf = match (restore_mode, &GLOBAL_CONFIG.opt_restore_dest) {
+            (RestoreMode::Overwrite(_) | RestoreMode::MetadataOnly | RestoreMode::Merge, _) => {
+                live_path
+            }
+            // without a browsed dir, multiple files retain their full paths within the restore dest
+            (RestoreMode::CopyOnly | RestoreMode::CopyAndPreserve, Some(restore_dest)) => {
+                let opt_relative_root = is_multiple.then(|| Path::new(ROOT_DIRECTORY));
+
+                InteractiveRestore::restore_dest_path(
+                    &snap_pathdata.path_buf,
+                    &live_path,
+                    restore_dest,
+                    opt_relative_root,
+                )?
+            }
+            (RestoreMode::CopyOnly | RestoreMode::CopyAndPreserve, None) => {
+                // as in the interactive restore mode, but beside the live path, rather than in the working dir,
+                // as many live paths may share a file name
+                let new_file_dir = live_path.parent().unwrap_or(&GLOBAL_CONFIG.pwd.path_buf);
+
+                InteractiveRestore::restored_file_path(
+                    &snap_pathdata.path_buf,
+                    &snap_path_metadata.modify_time,
+                    new_file_dir,
+                )?
+            }
+        };
+
+        Ok((snap_pathdata, new_file_path_buf))
+    }
+
+    // a typo'd snapshot column must not restore some other file over the live path, so the snapshot version
+    // given must be within a snap mount, and its live path, as InteractiveRestore would determine it, must be the live path given
+    fn snap_of_live_path(snap_str: &str, live_path: &Path) -> HttmResult<PathData> {
+        let snap_pathdata = PathData::from(Path::new(snap_str));
+
+        let snap_live_path =
+            InteractiveRestore::live_path(&snap_pathdata.path_buf).map_err(|_err| {
+                let msg = format!(
+                    "{:?} is not within any snapshot, and so is not a snapshot version",
+                    snap_pathdata.path_buf
+                );
+                HttmError::new(&msg)
+            })?;
+
+        if Self::canonical_live_path(&snap_live_path) != Self::canonical_live_path(live_path) {
+            let msg = format!(
+                "{:?} is a snapshot version of {snap_live_path:?}, not of {live_path:?}",
+                snap_pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(snap_pathdata)
+    }
+
+    // a live path may not exist, and may itself be a symlink, which is what we would restore, so we only
+    // resolve its parent, so that equivalent paths to the same live path compare as equal
+    fn canonical_live_path(live_path: &Path) -> PathBuf {
+        match (live_path.parent(), live_path.file_name()) {
+            (Some(parent), Some(file_name)) => parent
+                .canonicalize()
+                .map(|parent| parent.join(file_name))
+                .unwrap_or_else(|_err| live_path.to_path_buf()),
+            _ => live_path.to_path_buf(),
+        }
+    }
+
+    fn last_snap(live_path: &Path) -> HttmResult<PathData> {
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &[PathData::from(live_path)])?;
+
+        // in some last snap modes, a live file without a snapshot version is returned as its own version
+        versions_map
+            .iter()
+            .flat_map(|(live_version, snaps)| {
+                snaps
+                    .iter()
+                    .filter(move |snap| snap.path_buf != live_version.path_buf)
+            })
+            .last()
+            .cloned()
+            .ok_or_else(|| {
+                HttmError::with_exit_code(
+                    "no last snapshot version exists, per the LAST_SNAP value given",
+                    NO_VERSIONS_EXIT_CODE,
+                )
+                .into()
+            })
+    }
+
+    // stdin may be our list of files, so we ask on the terminal itself
+    fn user_consents(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<bool> {
+        let tty = OpenOptions::new().read(true).open("/dev/tty").map_err(|_err| {
+            HttmError::new(
+                "httm could not ask for the user's consent, as no terminal is available.  Specify YES to restore without consent.  No files were restored.",
+            )
+        })?;
+
+        eprint!(
+            "{}Before httm restores {}, it would like your consent. Continue? (YES/NO): ",
+            InteractiveRestore::describe_for_consent(restore_pairs)?,
+            if restore_pairs.len() == 1 {
+                "this file"
+            } else {
+                "these files"
+            }
+        );
+
+        let mut tty_reader = BufReader::new(tty);
+
+        let mut input = String::new();
+        tty_reader.read_line(&mut input)?;
+
+        if !matches!(input.trim().to_ascii_uppercase().as_str(), "YES" | "Y") {
+            return Ok(false);
+        }
+
+        InteractiveRestore::confirm_sensitive(restore_pairs, &mut tty_reader)
+    }
+}
@@ -0,0 +1,100 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+
+use crate::config::generate::BookmarkAction;
+use crate::data::paths::PathData;
+use crate::exec::interactive::{select_restore_view, ViewMode};
+use crate::library::bookmarks::Bookmarks;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::print_output_buf;
+use crate::GLOBAL_CONFIG;
+
+pub struct BookmarkView;
+
+impl BookmarkView {
+    pub fn exec(bookmark_action: &BookmarkAction) -> HttmResult<()> {
+        let paths: Vec<&Path> = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| pathdata.path_buf.as_path())
+            .collect();
+
+        match bookmark_action {
+            BookmarkAction::Add => {
+                let num_added = Bookmarks::add(&paths)?;
+                eprintln!("httm added {num_added} bookmark(s).");
+                Ok(())
+            }
+            BookmarkAction::Remove => {
+                let num_removed = Bookmarks::remove(&paths)?;
+                eprintln!("httm removed {num_removed} bookmark(s).");
+                Ok(())
+            }
+            BookmarkAction::List => {
+                let entries = Bookmarks::entries()?;
+
+                if entries.is_empty() {
+                    eprintln!("NOTICE: httm has no bookmarks to display.");
+                    return Ok(());
+                }
+
+                let output_buf: String = entries
+                    .iter()
+                    .map(|entry| entry.to_string_lossy() + "\n")
+                    .collect();
+
+                print_output_buf(output_buf)
+            }
+        }
+    }
+
+    // pick a single bookmarked path, which then takes the place of a path browsed
+    pub fn select() -> HttmResult<PathData> {
+        let entries = Bookmarks::entries()?;
+
+        if entries.is_empty() {
+            return Err(HttmError::new(
+                "httm has no bookmarks.  Add a bookmark with \"--bookmark add <PATH>\".  Quitting.",
+            )
+            .into());
+        }
+
+        let selection_buffer: String = entries
+            .iter()
+            .map(|entry| format!("\"{}\"\n", entry.to_string_lossy()))
+            .collect();
+
+        // loop until user selects a valid entry
+        loop {
+            let requested_lines =
+                select_restore_view(&selection_buffer, &ViewMode::Bookmarks, false)?;
+
+            // ... we want everything between the quotes
+            let opt_selected = requested_lines
+                .iter()
+                .filter_map(|line| line.split_terminator('"').nth(1))
+                .map(PathBuf::from)
+                .find(|path| entries.contains(path));
+
+            if let Some(selected) = opt_selected {
+                break Ok(PathData::from(selected.as_path()));
+            }
+        }
+    }
+}
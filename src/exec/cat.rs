@@ -0,0 +1,141 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+use crate::config::generate::CatVersion;
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult, NO_VERSIONS_EXIT_CODE};
+use crate::library::utility::{copy_direct, date_string, DateFormat};
+use crate::lookup::object_versions::ObjectVersions;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+pub struct CatFromSnaps;
+
+impl CatFromSnaps {
+    pub fn exec(cat_version: &CatVersion) -> HttmResult<()> {
+        let pathdata = match GLOBAL_CONFIG.paths.as_slice() {
+            [pathdata] => pathdata,
+            _ => {
+                return Err(HttmError::new(
+                    "CAT requires exactly one input file, so that the content of one version is streamed.",
+                )
+                .into())
+            }
+        };
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        // versions are ordered oldest to newest
+        let snaps: &[PathData] = versions_map
+            .get(pathdata)
+            .map(|snaps| snaps.as_slice())
+            .unwrap_or_default();
+
+        let version = Self::version(pathdata, snaps, cat_version)?;
+
+        if version.path_buf.is_dir() {
+            let msg = format!(
+                "{:?} is a directory, and CAT can only stream the content of a file.",
+                version.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        match &GLOBAL_CONFIG.opt_fetch_to {
+            Some(fetch_to) => Self::fetch(version, fetch_to),
+            None => Self::stream(version),
+        }
+    }
+
+    fn version<'a>(
+        live_version: &'a PathData,
+        snaps: &'a [PathData],
+        cat_version: &CatVersion,
+    ) -> HttmResult<&'a PathData> {
+        match cat_version {
+            CatVersion::Nth(nth) => snaps.iter().rev().nth(nth - 1).ok_or_else(|| {
+                let msg = format!(
+                    "httm could not find snapshot version number {nth} of {:?}, as only {} unique snapshot version(s) exist.",
+                    live_version.path_buf,
+                    snaps.len()
+                );
+                HttmError::with_exit_code(&msg, NO_VERSIONS_EXIT_CODE).into()
+            }),
+            CatVersion::AsOf(as_of) => {
+                // the live version only counts should it exist
+                let opt_live_version =
+                    Some(live_version).filter(|live_version| live_version.metadata.is_some());
+
+                snaps
+                    .iter()
+                    .chain(opt_live_version)
+                    .filter(|version| version.md_infallible().modify_time <= *as_of)
+                    .max_by_key(|version| version.md_infallible().modify_time)
+                    .ok_or_else(|| {
+                        let msg = format!(
+                            "httm could not find any version of {:?} modified at or before {}.",
+                            live_version.path_buf,
+                            date_string(
+                                GLOBAL_CONFIG.requested_utc_offset,
+                                as_of,
+                                DateFormat::Display
+                            )
+                        );
+                        HttmError::with_exit_code(&msg, NO_VERSIONS_EXIT_CODE).into()
+                    })
+            }
+        }
+    }
+
+    // a version copied to a new path, with its timestamps and permissions, see FETCH_TO
+    fn fetch(version: &PathData, fetch_to: &Path) -> HttmResult<()> {
+        if ObjectVersions::is_object_version(&version.path_buf) {
+            ObjectVersions::fetch(&version.path_buf, fetch_to)?;
+        } else {
+            copy_direct(&version.path_buf, fetch_to, true)?;
+        }
+
+        eprintln!("httm fetched {:?} to {fetch_to:?}.", version.path_buf);
+
+        Ok(())
+    }
+
+    // the content is copied directly from the version to stdout, so even a very large file needs no temporary file
+    fn stream(version: &PathData) -> HttmResult<()> {
+        if ObjectVersions::is_object_version(&version.path_buf) {
+            return ObjectVersions::cat(&version.path_buf);
+        }
+
+        let mut file = File::open(&version.path_buf)?;
+
+        let out = std::io::stdout();
+        let mut out_locked = out.lock();
+
+        let res = std::io::copy(&mut file, &mut out_locked).and_then(|_| out_locked.flush());
+
+        match res {
+            Ok(_) => Ok(()),
+            // a reader, like "head", may stop reading before we're done, which is no reason to fail
+            Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::Path;
+
+use crate::config::generate::{ChurnConfig, ChurnRank};
+use crate::data::paths::{BasicDirEntryInfo, PathData};
+use crate::exec::recursive::SharedRecursive;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{display_human_size, print_output_buf, walk_tree};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+struct FileChurn<'a> {
+    live_version: &'a PathData,
+    num_versions: usize,
+    total_bytes: u64,
+}
+
+impl<'a> FileChurn<'a> {
+    // only those unique versions modified within the window count, see "--uniqueness"
+    fn new(live_version: &'a PathData, snaps: &[PathData], churn_config: &ChurnConfig) -> Self {
+        let within_window: Vec<&PathData> = snaps
+            .iter()
+            .filter(|snap| match churn_config.opt_since {
+                Some(since) => snap.md_infallible().modify_time >= since,
+                None => true,
+            })
+            .collect();
+
+        Self {
+            live_version,
+            num_versions: within_window.len(),
+            total_bytes: within_window
+                .iter()
+                .map(|snap| snap.md_infallible().size)
+                .sum(),
+        }
+    }
+
+    fn rank_key(&self, rank: &ChurnRank) -> u64 {
+        match rank {
+            ChurnRank::Versions => self.num_versions as u64,
+            ChurnRank::Bytes => self.total_bytes,
+        }
+    }
+}
+
+pub struct ChurnLeaderboard;
+
+impl ChurnLeaderboard {
+    pub fn exec(churn_config: &ChurnConfig) -> HttmResult<()> {
+        let output_buf: String = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| Self::leaderboard(pathdata, churn_config))
+            .collect::<HttmResult<Vec<String>>>()?
+            .join("\n");
+
+        print_output_buf(output_buf)
+    }
+
+    fn leaderboard(pathdata: &PathData, churn_config: &ChurnConfig) -> HttmResult<String> {
+        if !pathdata.path_buf.is_dir() {
+            let msg = format!(
+                "CHURN requires a directory, and {:?} is not a directory.",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let live_files = Self::live_files(&pathdata.path_buf);
+
+        // each file's versions are looked up in parallel, and a file without any is simply not ranked
+        let versions_map = VersionsMap::lookup(&GLOBAL_CONFIG, &live_files)?;
+
+        let mut ranked: Vec<FileChurn> = versions_map
+            .iter()
+            .map(|(live_version, snaps)| FileChurn::new(live_version, snaps, churn_config))
+            .filter(|churn| churn.num_versions > 0)
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.rank_key(&churn_config.rank)
+                .cmp(&a.rank_key(&churn_config.rank))
+                .then_with(|| a.live_version.cmp(b.live_version))
+        });
+
+        ranked.truncate(churn_config.num_files);
+
+        let ranked_by = match churn_config.rank {
+            ChurnRank::Versions => "unique versions",
+            ChurnRank::Bytes => "total bytes across versions",
+        };
+
+        let window = match churn_config.opt_since {
+            Some(since) => format!(
+                ", of versions modified since {}",
+                GLOBAL_CONFIG.display_date(&since)
+            ),
+            None => String::new(),
+        };
+
+        let mut buffer = format!(
+            "The most churned files beneath {:?}, by {ranked_by}{window}:\n",
+            GLOBAL_CONFIG.display_path(&pathdata.path_buf)
+        );
+
+        if ranked.is_empty() {
+            buffer += "\t(no file has a snapshot version)\n";
+            return Ok(buffer);
+        }
+
+        let sizes: Vec<String> = ranked
+            .iter()
+            .map(|churn| display_human_size(churn.total_bytes))
+            .collect();
+
+        let size_width = sizes.iter().map(String::len).max().unwrap_or_default();
+
+        ranked
+            .iter()
+            .zip(sizes.iter())
+            .enumerate()
+            .for_each(|(idx, (churn, size))| {
+                buffer += &format!(
+                    "{:>4}. {:>5} versions  {size:>size_width$}  {:?}\n",
+                    idx + 1,
+                    churn.num_versions,
+                    GLOBAL_CONFIG.display_path(&churn.live_version.path_buf)
+                );
+            });
+
+        Ok(buffer)
+    }
+
+    // every live file beneath the dir, without following symlinks, or descending into any snapshot dir,
+    // so a file deleted from the live dataset, which has nothing left to churn, is never ranked
+    fn live_files(dir: &Path) -> Vec<PathData> {
+        let mut live_files: Vec<PathData> = Vec::new();
+
+        walk_tree(dir, usize::MAX, |dir_entry, _depth| {
+            let entry = BasicDirEntryInfo::from(dir_entry);
+
+            match entry.file_type {
+                Some(file_type) if file_type.is_dir() => !SharedRecursive::is_filter_dir(&entry),
+                Some(file_type) if file_type.is_file() => {
+                    live_files.push(PathData::from(entry));
+                    false
+                }
+                _ => false,
+            }
+        });
+
+        live_files
+    }
+}
@@ -0,0 +1,121 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::data::paths::PathData;
+use crate::library::results::HttmResult;
+use crate::library::utility::{delimiter, print_output_buf};
+use crate::GLOBAL_CONFIG;
+
+pub struct CompleteFromSnaps;
+
+impl CompleteFromSnaps {
+    // like a shell, we complete the last component of the prefix, within the dir the prefix names,
+    // but from the names found on every snapshot of that dir, so a custom restore function may
+    // complete the names of deleted files
+    pub fn exec(prefix: &str) -> HttmResult<()> {
+        let (dir_part, name_prefix) = match prefix.rfind('/') {
+            Some(idx) => prefix.split_at(idx + 1),
+            None => ("", prefix),
+        };
+
+        let search_dir: PathBuf = if Path::new(dir_part).is_absolute() {
+            PathBuf::from(dir_part)
+        } else {
+            GLOBAL_CONFIG.pwd.path_buf.join(dir_part)
+        };
+
+        // a completion function should never be interrupted by an error, so we print nothing instead
+        let candidates = match Self::candidates(&search_dir, name_prefix) {
+            Ok(candidates) => candidates,
+            Err(err) => {
+                if GLOBAL_CONFIG.opt_debug {
+                    eprintln!("DEBUG: No completions for {prefix:?}: {err}");
+                }
+                return Ok(());
+            }
+        };
+
+        let delimiter = delimiter();
+
+        // candidates retain the form of the prefix given, relative or absolute, and, like a shell, dirs end with a '/'
+        let output_buf: String = candidates
+            .into_iter()
+            .map(|(name, is_dir)| {
+                let dir_marker = if is_dir { "/" } else { "" };
+                format!("{dir_part}{name}{dir_marker}{delimiter}")
+            })
+            .collect();
+
+        print_output_buf(output_buf)
+    }
+
+    // key: file name, val: whether it is a dir on any snapshot
+    fn candidates(search_dir: &Path, name_prefix: &str) -> HttmResult<BTreeMap<String, bool>> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let pathdata = PathData::from(search_dir);
+
+        let proximate_dataset_mount =
+            pathdata.proximate_dataset(&dataset_collection.map_of_datasets)?;
+
+        let relative_path = pathdata.path_buf.strip_prefix(proximate_dataset_mount)?;
+
+        let snap_mounts = match dataset_collection.map_of_snaps.get(proximate_dataset_mount) {
+            Some(snap_mounts) => snap_mounts,
+            None => return Ok(BTreeMap::new()),
+        };
+
+        // like a shell, hidden files are only completed when asked for
+        let include_hidden = name_prefix.starts_with('.');
+
+        let found: Vec<(String, bool)> = snap_mounts
+            .par_iter()
+            .filter_map(|snap_mount| std::fs::read_dir(snap_mount.join(relative_path)).ok())
+            .flat_map_iter(|dir_entries| {
+                dir_entries.flatten().filter_map(|dir_entry| {
+                    let name = dir_entry.file_name().to_string_lossy().into_owned();
+
+                    if !name.starts_with(name_prefix) || (name.starts_with('.') && !include_hidden)
+                    {
+                        return None;
+                    }
+
+                    let is_dir = dir_entry
+                        .file_type()
+                        .map(|file_type| file_type.is_dir())
+                        .unwrap_or(false);
+
+                    Some((name, is_dir))
+                })
+            })
+            .collect();
+
+        let candidates = found
+            .into_iter()
+            .fold(BTreeMap::new(), |mut map, (name, is_dir)| {
+                *map.entry(name).or_insert(false) |= is_dir;
+                map
+            });
+
+        Ok(candidates)
+    }
+}
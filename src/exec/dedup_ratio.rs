@@ -0,0 +1,179 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use crate::data::paths::PathData;
+use crate::library::results::HttmResult;
+use crate::library::utility::{display_human_size, print_output_buf};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+// ZFS' default recordsize, should the filesystem not report a preferred block size
+const DEFAULT_BLOCK_SIZE: u64 = 131_072;
+
+struct SharedBlocks {
+    block_size: u64,
+    num_blocks: u64,
+    num_shared: u64,
+    shared_bytes: u64,
+    snap_size: u64,
+}
+
+impl SharedBlocks {
+    // copy-on-write snapshots share only those blocks which remain unchanged, at the same offset,
+    // so a block-wise comparison of the live file and its snapshot version is a fair estimate of what
+    // is shared on disk, without needing FIEMAP or zdb, which don't work everywhere, or need root
+    fn new(live_path: &Path, snap_path: &Path, block_size: u64) -> HttmResult<Self> {
+        let mut live_reader = BufReader::new(File::open(live_path)?);
+        let mut snap_reader = BufReader::new(File::open(snap_path)?);
+
+        let mut live_block = vec![0u8; block_size as usize];
+        let mut snap_block = vec![0u8; block_size as usize];
+
+        let mut num_blocks = 0u64;
+        let mut num_shared = 0u64;
+        let mut shared_bytes = 0u64;
+        let mut snap_size = 0u64;
+
+        loop {
+            let snap_len = Self::read_block(&mut snap_reader, &mut snap_block)?;
+
+            if snap_len == 0 {
+                break;
+            }
+
+            let live_len = Self::read_block(&mut live_reader, &mut live_block)?;
+
+            num_blocks += 1;
+            snap_size += snap_len as u64;
+
+            if live_len == snap_len && live_block[..live_len] == snap_block[..snap_len] {
+                num_shared += 1;
+                shared_bytes += snap_len as u64;
+            }
+        }
+
+        Ok(Self {
+            block_size,
+            num_blocks,
+            num_shared,
+            shared_bytes,
+            snap_size,
+        })
+    }
+
+    // read until the block is full, or we reach the end of the file
+    fn read_block(reader: &mut impl Read, block: &mut [u8]) -> HttmResult<usize> {
+        let mut filled = 0usize;
+
+        while filled < block.len() {
+            match reader.read(&mut block[filled..])? {
+                0 => break,
+                n => filled += n,
+            }
+        }
+
+        Ok(filled)
+    }
+
+    fn ratio(&self) -> f64 {
+        if self.num_blocks == 0 {
+            return 0f64;
+        }
+
+        self.num_shared as f64 / self.num_blocks as f64 * 100f64
+    }
+
+    fn display(&self, live_path: &Path, snap_path: &Path) -> String {
+        format!(
+            "{live_path:?} compared to its latest snapshot version {snap_path:?}:\n\
+            \tblock size:  {}\n\
+            \tshared:      {} of {} blocks ({:.1}%), {}\n\
+            \tunique:      {} blocks, {} (the estimated cost of keeping this version)\n",
+            display_human_size(self.block_size),
+            self.num_shared,
+            self.num_blocks,
+            self.ratio(),
+            display_human_size(self.shared_bytes),
+            self.num_blocks - self.num_shared,
+            display_human_size(self.snap_size - self.shared_bytes),
+        )
+    }
+}
+
+pub struct DedupRatio;
+
+impl DedupRatio {
+    pub fn exec() -> HttmResult<()> {
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        let output_buf: String = versions_map
+            .iter()
+            .filter_map(|(live_version, snaps)| Self::report(live_version, snaps))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        if output_buf.is_empty() {
+            return Ok(());
+        }
+
+        print_output_buf(output_buf)
+    }
+
+    fn report(live_version: &PathData, snaps: &[PathData]) -> Option<String> {
+        let live_path = &live_version.path_buf;
+
+        let live_md = match live_path.metadata() {
+            Ok(md) => md,
+            Err(_) => {
+                eprintln!("WARN: {live_path:?} does not exist on the live dataset, skipping.");
+                return None;
+            }
+        };
+
+        if !live_md.is_file() {
+            eprintln!("WARN: {live_path:?} is not a regular file, skipping.");
+            return None;
+        }
+
+        // versions are ordered oldest to newest
+        let latest_snap = match snaps.last() {
+            Some(snap) => snap,
+            None => {
+                eprintln!("WARN: {live_path:?} has no snapshot version, skipping.");
+                return None;
+            }
+        };
+
+        let block_size = match live_md.blksize() {
+            0 => DEFAULT_BLOCK_SIZE,
+            size => size,
+        };
+
+        match SharedBlocks::new(live_path, &latest_snap.path_buf, block_size) {
+            Ok(shared_blocks) => Some(shared_blocks.display(live_path, &latest_snap.path_buf)),
+            Err(err) => {
+                eprintln!("WARN: httm could not compare {live_path:?} to its latest snapshot version: {err}");
+                None
+            }
+        }
+    }
+}
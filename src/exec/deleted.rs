@@ -22,7 +22,8 @@ use skim::prelude::*;
 
 use crate::config::generate::DeletedMode;
 use crate::data::paths::{BasicDirEntryInfo, PathData};
-use crate::exec::recursive::{PathProvenance, SharedRecursive};
+use crate::data::selection::CandidateSender;
+use crate::exec::recursive::{PathProvenance, SearchLimits, SharedRecursive};
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{is_channel_closed, Never};
 use crate::lookup::deleted::{DeletedFiles, LastInTimeSet};
@@ -35,7 +36,7 @@ impl SpawnDeletedThread {
     pub fn exec(
         requested_dir: &Path,
         deleted_scope: &Scope,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
         hangup_rx: &Receiver<Never>,
     ) {
         // spawn_enumerate_deleted will send deleted files back to
@@ -50,15 +51,15 @@ impl SpawnDeletedThread {
     }
 
     // deleted file search for all modes
-    fn enter_directory(
+    pub fn enter_directory(
         requested_dir: &Path,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
         hangup_rx: &Receiver<Never>,
     ) -> HttmResult<()> {
         // check -- should deleted threads keep working?
         // exit/error on disconnected channel, which closes
         // at end of browse scope
-        if is_channel_closed(hangup_rx) {
+        if is_channel_closed(hangup_rx) || SearchLimits::is_max_results_reached() {
             return Ok(());
         }
 
@@ -87,14 +88,26 @@ impl SpawnDeletedThread {
         // disable behind deleted dirs with DepthOfOne,
         // otherwise recurse and find all those deleted files
         //
+        // Depth always descends into deleted dirs, even when we do not
+        // recurse into live dirs
+        //
         // don't propagate errors, errors we are most concerned about
         // are transmission errors, which are handled elsewhere
-        if GLOBAL_CONFIG.opt_deleted_mode != Some(DeletedMode::DepthOfOne)
-            && GLOBAL_CONFIG.opt_recursive
-            && !vec_dirs.is_empty()
-        {
-            // get latest in time per our policy
-            let path_set: Vec<PathData> = vec_dirs.into_iter().map(PathData::from).collect();
+        let should_recurse_deleted = match GLOBAL_CONFIG.opt_deleted_mode {
+            Some(DeletedMode::Depth) => true,
+            Some(DeletedMode::DepthOfOne) => false,
+            _ => GLOBAL_CONFIG.opt_recursive,
+        };
+
+        if should_recurse_deleted && !vec_dirs.is_empty() {
+            // get latest in time per our policy, of those deleted dirs within the user's max depth
+            let path_set: Vec<PathData> = vec_dirs
+                .into_iter()
+                .filter(|basic_info| {
+                    SearchLimits::is_within_depth(&requested_dir.join(basic_info.filename()))
+                })
+                .map(PathData::from)
+                .collect();
 
             return LastInTimeSet::try_from(path_set)?
                 .iter()
@@ -126,13 +139,13 @@ impl RecurseBehindDeletedDir {
     fn exec(
         deleted_dir: &Path,
         requested_dir: &Path,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
         hangup_rx: &Receiver<Never>,
     ) -> HttmResult<()> {
         // check -- should deleted threads keep working?
         // exit/error on disconnected channel, which closes
         // at end of browse scope
-        if is_channel_closed(hangup_rx) {
+        if is_channel_closed(hangup_rx) || SearchLimits::is_max_results_reached() {
             return Ok(());
         }
 
@@ -164,7 +177,10 @@ impl RecurseBehindDeletedDir {
                     // check -- should deleted threads keep working?
                     // exit/error on disconnected channel, which closes
                     // at end of browse scope
-                    !is_channel_closed(hangup_rx)
+                    !is_channel_closed(hangup_rx) && !SearchLimits::is_max_results_reached()
+                })
+                .filter(|basic_info| {
+                    SearchLimits::is_within_depth(&item.pseudo_live_dir.join(basic_info.filename()))
                 })
                 .map(|basic_info| {
                     let dir_name = Path::new(basic_info.filename());
@@ -191,7 +207,7 @@ impl RecurseBehindDeletedDir {
         dir_name: &Path,
         from_deleted_dir: &Path,
         from_requested_dir: &Path,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
     ) -> HttmResult<RecurseBehindDeletedDir> {
         // deleted_dir_on_snap is the path from the deleted dir on the snapshot
         // pseudo_live_dir is the path from the fake, deleted directory that once was
@@ -0,0 +1,174 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::read;
+use std::path::Path;
+
+use crate::data::paths::PathData;
+use crate::display_versions::wrapper::VersionsDisplayWrapper;
+use crate::exec::interactive::{select_restore_view, ViewMode};
+use crate::library::results::{HttmError, HttmResult, NO_VERSIONS_EXIT_CODE};
+use crate::library::unified_diff::UnifiedDiff;
+use crate::library::utility::{date_string, is_binary, print_output_buf, DateFormat};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+pub struct DiffVersions;
+
+impl DiffVersions {
+    pub fn exec() -> HttmResult<()> {
+        let pathdata = match GLOBAL_CONFIG.paths.as_slice() {
+            [pathdata] => pathdata,
+            _ => {
+                return Err(HttmError::new(
+                    "DIFF requires exactly one input file, so that two of its versions may be compared.",
+                )
+                .into())
+            }
+        };
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        // the live version only counts should it exist
+        let versions: Vec<&PathData> = versions_map
+            .iter()
+            .flat_map(|(live_version, snaps)| {
+                snaps
+                    .iter()
+                    .chain(Some(live_version).filter(|live| live.metadata.is_some()))
+            })
+            .collect();
+
+        let (older, newer) = if GLOBAL_CONFIG.opt_last_snap.is_some() {
+            Self::last_snap(pathdata, &versions)?
+        } else {
+            Self::select_two(pathdata, &versions_map, &versions)?
+        };
+
+        print_output_buf(Self::diff(older, newer)?)
+    }
+
+    // the last snapshot version, and the live version
+    fn last_snap<'a>(
+        pathdata: &PathData,
+        versions: &[&'a PathData],
+    ) -> HttmResult<(&'a PathData, &'a PathData)> {
+        match versions {
+            [.., older, newer] if newer.path_buf == pathdata.path_buf => Ok((older, newer)),
+            _ => {
+                let msg = format!(
+                    "httm could not find both a live version and a snapshot version of {:?} to compare.",
+                    pathdata.path_buf
+                );
+                Err(HttmError::with_exit_code(&msg, NO_VERSIONS_EXIT_CODE).into())
+            }
+        }
+    }
+
+    // any two versions, whether snapshot or live, compared older to newer, whatever order they were selected in
+    fn select_two<'a>(
+        pathdata: &PathData,
+        versions_map: &VersionsMap,
+        versions: &[&'a PathData],
+    ) -> HttmResult<(&'a PathData, &'a PathData)> {
+        if versions.len() < 2 {
+            let msg = format!(
+                "httm could not find two versions of {:?} to compare.",
+                pathdata.path_buf
+            );
+            return Err(HttmError::with_exit_code(&msg, NO_VERSIONS_EXIT_CODE).into());
+        }
+
+        let display_config = GLOBAL_CONFIG.generate_display_config(std::slice::from_ref(pathdata));
+        let selection_buffer =
+            VersionsDisplayWrapper::from(&display_config, versions_map.clone()).to_formatted()?;
+
+        let opt_live_version = Some(pathdata.path_buf.to_string_lossy().into_owned());
+
+        // loop until the user selects exactly two versions
+        loop {
+            let requested_file_names = select_restore_view(
+                &selection_buffer,
+                &ViewMode::Select(opt_live_version.clone()),
+                true,
+            )?;
+
+            // ... we want everything between the quotes
+            let mut selected: Vec<&PathData> = requested_file_names
+                .iter()
+                .filter_map(|requested_file_name| requested_file_name.split_terminator('"').nth(1))
+                .filter_map(|path_string| {
+                    versions
+                        .iter()
+                        .find(|version| version.path_buf == Path::new(path_string))
+                        .copied()
+                })
+                .collect();
+
+            if let [first, second] = selected.as_mut_slice() {
+                if first.md_infallible().modify_time > second.md_infallible().modify_time {
+                    std::mem::swap(first, second);
+                }
+
+                return Ok((first, second));
+            }
+
+            eprintln!("DIFF requires exactly two versions be selected (see SELECT, MULTIPLE).");
+        }
+    }
+
+    fn diff(older: &PathData, newer: &PathData) -> HttmResult<String> {
+        if older.path_buf.is_dir() || newer.path_buf.is_dir() {
+            return Err(HttmError::new(
+                "DIFF can only compare the versions of a file, not a directory.",
+            )
+            .into());
+        }
+
+        let (old_bytes, new_bytes) = (read(&older.path_buf)?, read(&newer.path_buf)?);
+
+        if is_binary(&old_bytes) || is_binary(&new_bytes) {
+            if old_bytes == new_bytes {
+                return Ok(String::new());
+            }
+
+            return Ok(format!(
+                "Binary files {:?} and {:?} differ\n",
+                older.path_buf, newer.path_buf
+            ));
+        }
+
+        let unified_diff = UnifiedDiff::new(&old_bytes, &new_bytes);
+
+        // like diff, nothing at all is printed when there is no difference
+        if unified_diff.is_empty() {
+            return Ok(String::new());
+        }
+
+        Ok(unified_diff.format(&Self::label(older), &Self::label(newer)))
+    }
+
+    fn label(version: &PathData) -> String {
+        let date = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &version.md_infallible().modify_time,
+            DateFormat::Iso8601,
+        );
+
+        format!("{}\t{date}", version.path_buf.display())
+    }
+}
@@ -0,0 +1,281 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use rayon::prelude::*;
+
+use crate::data::paths::PathData;
+use crate::library::itemize::ItemizedEntry;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, print_output_buf, walk_tree, DateFormat};
+use crate::parse::snaps::MapOfSnaps;
+use crate::{BTRFS_SNAPPER_SUFFIX, GLOBAL_CONFIG};
+
+// as with find, snapshot trees can be very deep, so we stop somewhere
+const DIGEST_MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrySummary {
+    pub is_dir: bool,
+    is_symlink: bool,
+    size: u64,
+    opt_modify_time: Option<SystemTime>,
+}
+
+impl EntrySummary {
+    fn itemized(&self) -> ItemizedEntry {
+        ItemizedEntry::new(
+            self.is_dir,
+            self.is_symlink,
+            self.size,
+            self.opt_modify_time,
+        )
+    }
+}
+
+// key: path relative to the snap mount, val: what we need to know whether the entry changed
+pub type SnapTree = BTreeMap<PathBuf, EntrySummary>;
+
+pub struct DigestOfSnaps;
+
+impl DigestOfSnaps {
+    pub fn exec(num_snaps: usize) -> HttmResult<()> {
+        let output_buf: String = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| Self::digest(pathdata, num_snaps))
+            .collect::<HttmResult<Vec<String>>>()?
+            .join("\n");
+
+        print_output_buf(output_buf)
+    }
+
+    fn digest(pathdata: &PathData, num_snaps: usize) -> HttmResult<String> {
+        if !pathdata.path_buf.is_dir() {
+            let msg = format!(
+                "DIGEST requires a directory, and {:?} is not a directory.",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let proximate_dataset_mount =
+            pathdata.proximate_dataset(&dataset_collection.map_of_datasets)?;
+
+        // only the subtree of each snapshot which corresponds to the requested dir is of interest
+        let relative_path = pathdata.path_buf.strip_prefix(proximate_dataset_mount)?;
+
+        let snap_mounts = dataset_collection
+            .map_of_snaps
+            .get(proximate_dataset_mount)
+            .ok_or_else(|| {
+                let msg = format!(
+                    "httm could not find any snapshots for the dataset mounted at {proximate_dataset_mount:?}"
+                );
+                HttmError::new(&msg)
+            })?;
+
+        // like the fast deleted scan, we compare snapshots in the order in which they were created, see SnapOrderSource,
+        // as the modify time of a snap mount is not when it was taken, and only fall back to that modify time, when we must
+        let snaps_in_order = MapOfSnaps::oldest_first(
+            &dataset_collection.map_of_datasets,
+            proximate_dataset_mount,
+            snap_mounts,
+        );
+
+        let oldest_first = &snaps_in_order.oldest_first;
+
+        // each of the last n snapshots is compared to its predecessor, so we need one more snapshot
+        let window_start = oldest_first.len().saturating_sub(num_snaps + 1);
+        let window = &oldest_first[window_start..];
+
+        if window.len() < 2 {
+            let msg = format!(
+                "httm requires at least two snapshots of the dataset mounted at {proximate_dataset_mount:?} to compare."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let trees: Vec<SnapTree> = window
+            .par_iter()
+            .map(|(_opt_time, snap_mount)| Self::snap_tree(&snap_mount.join(relative_path)))
+            .collect();
+
+        let mut buffer = format!(
+            "Changes to {:?} across the last {} snapshots:\n",
+            GLOBAL_CONFIG.display_path(&pathdata.path_buf),
+            window.len() - 1
+        );
+
+        if !snaps_in_order.is_creation_order {
+            buffer += "Note: httm could not determine the order in which these snapshots were taken, \
+            so they are ordered, and dated, by the modify time of each snapshot's root directory, which may differ.\n";
+        }
+
+        let mut totals = (0usize, 0usize, 0usize);
+
+        window
+            .iter()
+            .zip(trees.iter())
+            .skip(1)
+            .zip(trees.iter())
+            .for_each(|(((opt_time, snap_mount), newer), older)| {
+                let changes = Self::compare(older, newer);
+
+                let snap_date = opt_time
+                    .map(|time| {
+                        date_string(
+                            GLOBAL_CONFIG.requested_utc_offset,
+                            &time,
+                            DateFormat::Display,
+                        )
+                    })
+                    .unwrap_or_else(|| "unknown date".to_owned());
+
+                buffer += &format!("\n{} ({snap_date}):\n", Self::snap_name(snap_mount));
+
+                if changes.is_empty() {
+                    buffer += "\t(no changes)\n";
+                    return;
+                }
+
+                changes.iter().for_each(|(marker, relpath)| {
+                    match marker {
+                        '+' => totals.0 += 1,
+                        '-' => totals.1 += 1,
+                        _ => totals.2 += 1,
+                    }
+
+                    let live_path = pathdata.path_buf.join(relpath);
+
+                    // an itemized line is read by tools built for rsync output, so it is neither indented nor quoted
+                    if GLOBAL_CONFIG.opt_itemize {
+                        buffer += &Self::itemized_line(older, newer, relpath, &live_path);
+                        return;
+                    }
+
+                    buffer += &format!("\t{marker} {:?}\n", GLOBAL_CONFIG.display_path(&live_path));
+                });
+            });
+
+        buffer += &format!(
+            "\nIn total: {} appeared, {} disappeared, and {} changed.\n",
+            totals.0, totals.1, totals.2
+        );
+
+        Ok(buffer)
+    }
+
+    fn itemized_line(
+        older: &SnapTree,
+        newer: &SnapTree,
+        relpath: &Path,
+        live_path: &Path,
+    ) -> String {
+        let opt_older = older.get(relpath).map(EntrySummary::itemized);
+        let opt_newer = newer.get(relpath).map(EntrySummary::itemized);
+
+        let code = ItemizedEntry::itemize(opt_older.as_ref(), opt_newer.as_ref(), false);
+
+        // like rsync, a dir is marked with a trailing slash
+        let slash = match opt_newer.or(opt_older) {
+            Some(entry) if entry.is_dir() => "/",
+            _ => "",
+        };
+
+        format!(
+            "{code} {}{slash}\n",
+            GLOBAL_CONFIG.display_path(live_path).display()
+        )
+    }
+
+    // '+' appeared, '-' disappeared, '~' changed, dirs change whenever their contents do, so only files may change
+    pub fn compare(older: &SnapTree, newer: &SnapTree) -> Vec<(char, PathBuf)> {
+        let mut changes: Vec<(char, PathBuf)> = newer
+            .iter()
+            .filter_map(|(relpath, newer_entry)| match older.get(relpath) {
+                None => Some(('+', relpath.clone())),
+                Some(older_entry) if older_entry.is_dir != newer_entry.is_dir => {
+                    Some(('~', relpath.clone()))
+                }
+                Some(older_entry) if !newer_entry.is_dir && older_entry != newer_entry => {
+                    Some(('~', relpath.clone()))
+                }
+                Some(_) => None,
+            })
+            .chain(
+                older
+                    .keys()
+                    .filter(|relpath| !newer.contains_key(*relpath))
+                    .map(|relpath| ('-', relpath.clone())),
+            )
+            .collect();
+
+        changes.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.cmp(&b.0)));
+
+        changes
+    }
+
+    // a summary of each entry of the snapshot tree, keyed by its path relative to the search dir
+    pub fn snap_tree(search_dir: &Path) -> SnapTree {
+        let mut tree = SnapTree::new();
+
+        walk_tree(search_dir, DIGEST_MAX_DEPTH, |dir_entry, _depth| {
+            let path = dir_entry.path();
+
+            let (is_dir, is_symlink, size, opt_modify_time) = match dir_entry.metadata() {
+                Ok(md) => (md.is_dir(), md.is_symlink(), md.len(), md.modified().ok()),
+                Err(_) => return false,
+            };
+
+            if let Ok(relpath) = path.strip_prefix(search_dir) {
+                tree.insert(
+                    relpath.to_path_buf(),
+                    EntrySummary {
+                        is_dir,
+                        is_symlink,
+                        size,
+                        opt_modify_time,
+                    },
+                );
+            }
+
+            true
+        });
+
+        tree
+    }
+
+    // btrfs snapper snapshots are named by the dir containing the "snapshot" dir
+    pub fn snap_name(snap_mount: &Path) -> String {
+        let opt_snap_name = match snap_mount.file_name() {
+            Some(file_name) if file_name == BTRFS_SNAPPER_SUFFIX => {
+                snap_mount.parent().and_then(Path::file_name)
+            }
+            opt_file_name => opt_file_name,
+        };
+
+        opt_snap_name
+            .map(|snap_name| snap_name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| snap_mount.to_string_lossy().into_owned())
+    }
+}
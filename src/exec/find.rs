@@ -0,0 +1,119 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::data::paths::PathData;
+use crate::display_map::format::PrintAsMap;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{glob_match, print_output_buf, walk_tree};
+use crate::GLOBAL_CONFIG;
+
+// snapshot trees can be very deep, and we are searching every snapshot, so we stop somewhere
+const FIND_MAX_DEPTH: usize = 32;
+
+pub struct FindInSnaps;
+
+impl FindInSnaps {
+    pub fn exec(pattern: &str) -> HttmResult<()> {
+        // key: the path as it would be on the live dataset, val: each snapshot version which matches
+        let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+        for pathdata in GLOBAL_CONFIG.paths.iter() {
+            Self::find(pathdata, pattern)?
+                .into_iter()
+                .for_each(|(live_path, snap_path)| {
+                    map.entry(live_path.to_string_lossy().to_string())
+                        .or_default()
+                        .push(snap_path.to_string_lossy().to_string())
+                });
+        }
+
+        if map.is_empty() {
+            let msg =
+                format!("httm could not find any files matching {pattern:?} on any snapshot.");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        map.values_mut().for_each(|snap_paths| snap_paths.sort());
+
+        let printable_map = PrintAsMap::from(map);
+        let output_buf = printable_map.to_formatted()?;
+
+        print_output_buf(output_buf)
+    }
+
+    fn find(pathdata: &PathData, pattern: &str) -> HttmResult<Vec<(PathBuf, PathBuf)>> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let proximate_dataset_mount =
+            pathdata.proximate_dataset(&dataset_collection.map_of_datasets)?;
+
+        // only search the subtree of each snapshot which corresponds to the requested dir
+        let relative_path = pathdata.path_buf.strip_prefix(proximate_dataset_mount)?;
+
+        let snap_mounts = dataset_collection
+            .map_of_snaps
+            .get(proximate_dataset_mount)
+            .ok_or_else(|| {
+                let msg = format!(
+                    "httm could not find any snapshots for the dataset mounted at {proximate_dataset_mount:?}"
+                );
+                HttmError::new(&msg)
+            })?;
+
+        let res = snap_mounts
+            .par_iter()
+            .flat_map(|snap_mount| {
+                Self::find_in_snap(snap_mount, &snap_mount.join(relative_path), pattern)
+                    .into_iter()
+                    .map(|snap_path| {
+                        let live_path = proximate_dataset_mount.join(
+                            snap_path
+                                .strip_prefix(snap_mount)
+                                .expect("snapshot path should be located within its snap mount"),
+                        );
+                        (live_path, snap_path)
+                    })
+                    .collect::<Vec<(PathBuf, PathBuf)>>()
+            })
+            .collect();
+
+        Ok(res)
+    }
+
+    fn find_in_snap(snap_mount: &Path, search_dir: &Path, pattern: &str) -> Vec<PathBuf> {
+        let mut matches: Vec<PathBuf> = Vec::new();
+
+        walk_tree(search_dir, FIND_MAX_DEPTH, |dir_entry, _depth| {
+            if glob_match(pattern, &dir_entry.file_name().to_string_lossy()) {
+                matches.push(dir_entry.path());
+            }
+
+            true
+        });
+
+        if GLOBAL_CONFIG.opt_debug && matches.is_empty() {
+            eprintln!("DEBUG: No matches found in snapshot: {snap_mount:?}");
+        }
+
+        matches
+    }
+}
@@ -0,0 +1,126 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{InteractiveMode, PrintMode};
+use crate::data::paths::PathData;
+use crate::exec::interactive::{select_restore_view, InteractiveRestore, ViewMode};
+use crate::exec::pipeline::Output;
+use crate::library::history::{History, HistoryEntry, HistoryKind};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{delimiter, print_output_buf};
+use crate::GLOBAL_CONFIG;
+
+pub struct HistoryView;
+
+impl HistoryView {
+    // list all history entries, oldest first, most recent last
+    pub fn exec() -> HttmResult<()> {
+        let entries = History::entries()?;
+
+        if entries.is_empty() {
+            eprintln!("NOTICE: httm has no history of previous selections to display.");
+            return Ok(());
+        }
+
+        let output_buf: String = entries.iter().map(|entry| entry.display() + "\n").collect();
+
+        print_output_buf(output_buf)
+    }
+
+    // select previous selections to print again, or to restore again
+    pub fn select(interactive_mode: &InteractiveMode) -> HttmResult<Output> {
+        // only snapshot versions may be restored, and a query is never a path
+        let entries: Vec<HistoryEntry> = History::entries()?
+            .into_iter()
+            .filter(|entry| match interactive_mode {
+                InteractiveMode::Restore(_) => {
+                    matches!(entry.kind, HistoryKind::Select | HistoryKind::Restore)
+                }
+                InteractiveMode::Select | InteractiveMode::Browse => {
+                    !matches!(entry.kind, HistoryKind::Query)
+                }
+            })
+            .collect();
+
+        if entries.is_empty() {
+            return Err(HttmError::new(
+                "httm has no history of previous selections suitable for this mode. Quitting.",
+            )
+            .into());
+        }
+
+        let selection_buffer: String = entries.iter().map(|entry| entry.display() + "\n").collect();
+
+        // loop until user selects at least one valid entry
+        let selected_entries: Vec<&HistoryEntry> = loop {
+            let requested_lines = select_restore_view(&selection_buffer, &ViewMode::History, true)?;
+
+            // ... we want everything between the quotes
+            let selected_entries: Vec<&HistoryEntry> = requested_lines
+                .iter()
+                .filter_map(|line| line.split_terminator('"').nth(1))
+                .filter_map(|value| entries.iter().rev().find(|entry| entry.value == value))
+                .collect();
+
+            if !selected_entries.is_empty() {
+                break selected_entries;
+            }
+        };
+
+        let selected_values: Vec<String> = selected_entries
+            .iter()
+            .map(|entry| entry.value.clone())
+            .collect();
+
+        match interactive_mode {
+            InteractiveMode::Restore(_) => {
+                let mut live_paths: Vec<PathData> = selected_entries
+                    .iter()
+                    .filter_map(|entry| entry.opt_live_path.as_deref())
+                    .map(PathData::from)
+                    .collect();
+                live_paths.sort_unstable();
+                live_paths.dedup();
+
+                InteractiveRestore::exec(&selected_values, &live_paths)
+            }
+            InteractiveMode::Select | InteractiveMode::Browse => {
+                Ok(Self::print_selections(&selected_values))
+            }
+        }
+    }
+
+    fn print_selections(values: &[String]) -> Output {
+        let delimiter = delimiter();
+
+        let output_buf: String = values
+            .iter()
+            .map(|value| {
+                if matches!(
+                    GLOBAL_CONFIG.print_mode,
+                    PrintMode::RawNewline | PrintMode::RawZero
+                ) {
+                    format!("{value}{delimiter}")
+                } else {
+                    format!("\"{value}\"{delimiter}")
+                }
+            })
+            .collect();
+
+        Output::new(output_buf, 0)
+    }
+}
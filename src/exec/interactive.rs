@@ -15,68 +15,68 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
+use std::io::BufRead;
+use std::os::unix::ffi::OsStrExt;
+use std::process::Command as ExecProcess;
+use std::sync::Mutex;
 use std::thread::JoinHandle;
-use std::{io::Cursor, path::Path, path::PathBuf, thread};
+use std::time::SystemTime;
+use std::{path::Path, path::PathBuf, thread};
 
 use crossbeam_channel::unbounded;
+use hashbrown::HashMap;
+use once_cell::sync::Lazy;
 use skim::prelude::*;
+use which::which;
 
 use crate::config::generate::{
-    ExecMode, InteractiveMode, PrintMode, RestoreMode, RestoreSnapGuard,
+    BrowseFilter, Config, ExecMode, InteractiveMode, PrintMode, RestoreMode, RestoreSnapGuard,
 };
-use crate::data::paths::{PathData, PathMetadata};
+use crate::data::filesystem_info::FilesystemInfo;
+use crate::data::paths::{HashFromFile, PathData, PathMetadata};
+use crate::data::selection::{BrowseRelay, CandidateReceiver, CandidateSender};
 use crate::display_versions::wrapper::VersionsDisplayWrapper;
-use crate::exec::preview::PreviewSelection;
-use crate::exec::recursive::RecursiveSearch;
-use crate::library::results::{HttmError, HttmResult};
-use crate::library::snap_guard::SnapGuard;
+use crate::exec::pipeline::{Output, Selection};
+use crate::exec::recursive::{RecursiveSearch, SkippedDirs};
+use crate::library::copy_progress::CopyProgress;
+use crate::library::history::{History, HistoryKind};
+use crate::library::itemize::ItemizedEntry;
+use crate::library::merge::MergeRestore;
+use crate::library::metadata_changes::MetadataChanges;
+use crate::library::open_files::OpenFiles;
+use crate::library::restore_journal::{JournalMode, RestoreJournal};
+use crate::library::results::{
+    HttmAborted, HttmError, HttmResult, DRY_RUN_CHANGED_EXIT_CODE, NO_VERSIONS_EXIT_CODE,
+    RESTORE_DECLINED_EXIT_CODE,
+};
+use crate::library::resume_marker::ResumeMarker;
+use crate::library::snap_guard::{PrecautionarySnapType, SnapGuard};
+use crate::library::transcript::{Transcript, TranscriptKind};
 use crate::library::utility::{
-    copy_recursive, date_string, delimiter, print_output_buf, user_has_effective_root,
-    user_has_zfs_allow_snap_priv, DateFormat, Never,
+    copy_recursive, date_string, delimiter, display_human_size, restore_dir_times, walk_tree,
+    DateFormat, HttmIsDir, Never,
 };
-use crate::lookup::versions::VersionsMap;
-use crate::GLOBAL_CONFIG;
-
-pub struct InteractiveBrowse;
+use crate::library::zfs::{ZfsCommand, ZfsPermission};
+use crate::lookup::snap_names::SnapNameMap;
+use crate::lookup::versions::{ProximateDatasetAndOptAlts, VersionsMap};
+use crate::ui::picker::picker;
+use crate::{GLOBAL_CONFIG, ROOT_DIRECTORY};
 
-impl InteractiveBrowse {
-    pub fn exec(interactive_mode: &InteractiveMode) -> HttmResult<Vec<PathData>> {
-        let browse_result = InteractiveBrowseResult::new()?;
-
-        // do we return back to our main exec function to print,
-        // or continue down the interactive rabbit hole?
-        match interactive_mode {
-            InteractiveMode::Restore(_) | InteractiveMode::Select => {
-                InteractiveSelect::exec(browse_result, interactive_mode)?;
-                unreachable!()
-            }
-            // InteractiveMode::Browse executes back through fn exec() in main.rs
-            InteractiveMode::Browse => Ok(browse_result.selected_pathdata),
-        }
-    }
-}
+// the files and bytes beneath each snapshot dir to be restored, see InteractiveRestore::tree_summary
+static TREE_SUMMARY_CACHE: Lazy<Mutex<HashMap<PathBuf, (usize, u64)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
 #[derive(Debug)]
 pub struct InteractiveBrowseResult {
     pub selected_pathdata: Vec<PathData>,
-    pub opt_background_handle: Option<JoinHandle<()>>,
+    pub opt_background_handle: Option<JoinHandle<HttmResult<()>>>,
 }
 
 impl InteractiveBrowseResult {
     pub fn new() -> HttmResult<Self> {
         let browse_result = match &GLOBAL_CONFIG.opt_requested_dir {
             // collect string paths from what we get from lookup_view
-            Some(requested_dir) => {
-                let browse_result = Self::browse_view(requested_dir, ViewMode::Browse)?;
-                if browse_result.selected_pathdata.is_empty() {
-                    return Err(HttmError::new(
-                        "None of the selected strings could be converted to paths.",
-                    )
-                    .into());
-                }
-
-                browse_result
-            }
+            Some(requested_dir) => Self::browse(requested_dir)?,
             None => {
                 // go to interactive_select early if user has already requested a file
                 // and we are in the appropriate mode Select or Restore, see struct Config,
@@ -108,60 +108,199 @@ impl InteractiveBrowseResult {
         Ok(browse_result)
     }
 
+    // the background search may fail while the user browses, and, as the search was detached, we learn so only now
+    pub fn join_background(opt_handle: Option<JoinHandle<HttmResult<()>>>) -> HttmResult<()> {
+        match opt_handle.map(|handle| handle.join()) {
+            Some(Ok(res)) => res,
+            Some(Err(_)) => {
+                Err(HttmError::new("Background recursive search thread panicked.").into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    // like a path given on the command line, a dir is browsed, and any other path is selected as is
+    pub fn from_path(pathdata: &PathData) -> HttmResult<Self> {
+        if pathdata.httm_is_dir() {
+            return Self::browse(pathdata);
+        }
+
+        Ok(Self {
+            selected_pathdata: vec![pathdata.clone()],
+            opt_background_handle: None,
+        })
+    }
+
+    fn browse(requested_dir: &PathData) -> HttmResult<Self> {
+        Self::check_snapshot_source(requested_dir)?;
+
+        let browse_result = Self::browse_view(requested_dir)?;
+
+        // let the user know whether any directories were skipped while browsing
+        SkippedDirs::print_summary();
+
+        if browse_result.selected_pathdata.is_empty() {
+            return Err(HttmError::new(
+                "None of the selected strings could be converted to paths.",
+            )
+            .into());
+        }
+
+        Ok(browse_result)
+    }
+
+    // browsing a dir without any snapshots walks the whole tree, only for every preview to be empty,
+    // so we warn the user before we start, and let the user decide whether to continue
+    fn check_snapshot_source(requested_dir: &PathData) -> HttmResult<()> {
+        if Self::has_snapshot_source(requested_dir) {
+            return Ok(());
+        }
+
+        let preview_buffer = format!(
+            "WARNING: {:?} has no snapshot source.  \
+            httm found no dataset with snapshots which contains this path, or is mounted beneath it, \
+            so browse will show no versions.  Continue? (YES/NO)\n\
+            ──────────────────────────────────────────────────────────────────────────────\n\
+            YES\n\
+            NO",
+            requested_dir.path_buf
+        );
+
+        // loop until user consents or doesn't
+        loop {
+            let user_consent = select_restore_view(
+                &preview_buffer,
+                &ViewMode::Browse(GLOBAL_CONFIG.browse_filter, None),
+                false,
+            )?[0]
+                .to_ascii_uppercase();
+
+            match user_consent.as_ref() {
+                "YES" | "Y" => break Ok(()),
+                "NO" | "N" => {
+                    break Err(HttmAborted::new(
+                        "User declined to browse a path without a snapshot source.",
+                    )
+                    .into())
+                }
+                // if not one of our actions, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+    }
+
+    fn has_snapshot_source(requested_dir: &PathData) -> bool {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let has_snaps = |mount: &Path| {
+            dataset_collection
+                .map_of_snaps
+                .get(mount)
+                .map(|snap_mounts| !snap_mounts.is_empty())
+                .unwrap_or(false)
+        };
+
+        let is_covered = ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, requested_dir)
+            .map(|prox_opt_alts| {
+                prox_opt_alts
+                    .datasets_of_interest
+                    .iter()
+                    .any(|dataset| has_snaps(dataset))
+            })
+            .unwrap_or(false);
+
+        if is_covered {
+            return true;
+        }
+
+        // browse recurses, so a dataset, or an alias, beneath the requested dir will have versions to show
+        let has_snaps_beneath =
+            dataset_collection
+                .map_of_snaps
+                .iter()
+                .any(|(mount, snap_mounts)| {
+                    mount.starts_with(&requested_dir.path_buf) && !snap_mounts.is_empty()
+                });
+
+        let has_alias_beneath = dataset_collection
+            .opt_map_of_aliases
+            .as_ref()
+            .map(|map_of_aliases| {
+                map_of_aliases
+                    .keys()
+                    .any(|local_dir| local_dir.starts_with(&requested_dir.path_buf))
+            })
+            .unwrap_or(false);
+
+        has_snaps_beneath || has_alias_beneath
+    }
+
     #[allow(unused_variables)]
-    fn browse_view(requested_dir: &PathData, view_mode: ViewMode) -> HttmResult<Self> {
+    fn browse_view(requested_dir: &PathData) -> HttmResult<Self> {
         // prep thread spawn
         let requested_dir_clone = requested_dir.path_buf.clone();
-        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+        let requested_dir_history = requested_dir.path_buf.clone();
+        let (tx_item, rx_item): (CandidateSender, CandidateReceiver) = unbounded();
         let (hangup_tx, hangup_rx): (Sender<Never>, Receiver<Never>) = bounded(0);
 
         // thread spawn fn enumerate_directory - permits recursion into dirs without blocking
+        // any error of the background search is returned once the search is joined, see join_background
         let background_handle = thread::spawn(move || {
-            // no way to propagate error from closure so exit and explain error here
-            RecursiveSearch::exec(&requested_dir_clone, tx_item.clone(), hangup_rx.clone());
+            RecursiveSearch::exec(&requested_dir_clone, tx_item.clone(), hangup_rx.clone())
         });
 
-        let display_handle = thread::spawn(move || {
+        let display_handle = thread::spawn(move || -> HttmResult<Vec<PathData>> {
             let opt_multi =
                 GLOBAL_CONFIG.opt_last_snap.is_none() || GLOBAL_CONFIG.opt_preview.is_none();
 
-            let header = view_mode.print_header();
-
-            // create the skim component for previews
-            let skim_opts = SkimOptionsBuilder::default()
-                .preview_window(Some("up:50%"))
-                .preview(Some(""))
-                .nosort(true)
-                .exact(GLOBAL_CONFIG.opt_exact)
-                .header(Some(&header))
-                .multi(opt_multi)
-                .regex(false)
-                .build()
-                .expect("Could not initialized skim options for browse_view");
-
-            // run_with() reads and shows items from the thread stream created above
-            let res = match skim::Skim::run_with(&skim_opts, Some(rx_item)) {
-                Some(output) if output.is_abort => {
-                    eprintln!("httm interactive file browse session was aborted.  Quitting.");
-                    std::process::exit(0)
-                }
-                Some(output) => {
-                    // hangup the channel so the background recursive search can gracefully cleanup and exit
-                    drop(hangup_tx);
-
-                    output
-                        .selected_items
-                        .iter()
-                        .map(|i| PathData::from(Path::new(&i.output().to_string())))
-                        .collect()
-                }
-                None => {
-                    return Err(HttmError::new(
-                        "httm interactive file browse session failed.",
-                    ));
+            let (relay, mut rx_skim) = BrowseRelay::new(rx_item, GLOBAL_CONFIG.browse_filter);
+            let mut query = GLOBAL_CONFIG.opt_query.clone();
+            let mut opt_notice: Option<String> = None;
+
+            // reopen the browse view, with the same query, each time the user asks for another filter,
+            // or for a snapshot, and, as a message printed would be drawn over, show how a snapshot went in its header
+            let output = loop {
+                let view_mode = ViewMode::Browse(relay.filter(), opt_notice.take());
+                let output = picker().browse(rx_skim, &view_mode, opt_multi, query.as_deref())?;
+
+                match output.opt_filter_change {
+                    Some(filter_change) => {
+                        rx_skim = relay.change_filter(&filter_change);
+                        query = Some(output.query);
+                    }
+                    None if output.is_snapshot_request => {
+                        opt_notice = Some(Self::snapshot_selected(&output.selected));
+                        rx_skim = relay.refresh();
+                        query = Some(output.query);
+                    }
+                    None => break output,
                 }
             };
 
+            // hangup the channel so the background recursive search can gracefully cleanup and exit
+            drop(hangup_tx);
+
+            History::record(
+                HistoryKind::Query,
+                &output.query,
+                Some(&requested_dir_history),
+            );
+
+            Transcript::record(
+                TranscriptKind::Query,
+                &format!("{:?} in {requested_dir_history:?}", output.query),
+            );
+
+            let res: Vec<PathData> = output
+                .selected
+                .iter()
+                .map(|selected| PathData::from(Path::new(selected)))
+                .collect();
+
+            res.iter().for_each(|pathdata| {
+                Transcript::record(TranscriptKind::Browse, &format!("{:?}", pathdata.path_buf))
+            });
+
             Ok(res)
         });
 
@@ -182,17 +321,69 @@ impl InteractiveBrowseResult {
             Err(_) => Err(HttmError::new("Interactive browse thread panicked.").into()),
         }
     }
+
+    // snapshot the dataset of each highlighted item, once, so a user may "snapshot before I try this",
+    // and, as the user will want to keep browsing, a failure is reported, in the notice returned, but ends nothing
+    fn snapshot_selected(selected: &[String]) -> String {
+        let mut notices: Vec<String> = Vec::new();
+
+        let mut dataset_names: Vec<String> = selected
+            .iter()
+            .filter_map(
+                |selected| match SnapGuard::dataset_name(Path::new(selected)) {
+                    Ok(dataset_name) => Some(dataset_name),
+                    Err(err) => {
+                        notices.push(format!(
+                            "WARN: could not snapshot the dataset of {selected:?}: {err}"
+                        ));
+                        None
+                    }
+                },
+            )
+            .collect();
+
+        dataset_names.sort_unstable();
+        dataset_names.dedup();
+
+        dataset_names.iter().for_each(|dataset_name| {
+            match SnapGuard::new(dataset_name, PrecautionarySnapType::FromBrowse) {
+                Ok(_snap_guard) => {
+                    notices.push(format!("took a snapshot of {dataset_name:?}"));
+                    Transcript::record(
+                        TranscriptKind::Action,
+                        &format!("snapshot of {dataset_name:?}"),
+                    )
+                }
+                Err(err) => {
+                    notices.push(format!("WARN: {err}"));
+                    Transcript::record(
+                        TranscriptKind::Failure,
+                        &format!("snapshot of {dataset_name:?}: {err}"),
+                    );
+                }
+            }
+        });
+
+        // a new snapshot changes no mount table, so each new preview must be told to look again
+        FilesystemInfo::invalidate();
+
+        if notices.is_empty() {
+            return "nothing was selected to snapshot".to_owned();
+        }
+
+        notices.join(" | ")
+    }
 }
 
-struct InteractiveSelect;
+pub struct InteractiveSelect;
 
 impl InteractiveSelect {
-    fn exec(
+    pub fn select(
+        config: &Config,
         browse_result: InteractiveBrowseResult,
+        versions_map: VersionsMap,
         interactive_mode: &InteractiveMode,
-    ) -> HttmResult<()> {
-        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &browse_result.selected_pathdata)?;
-
+    ) -> HttmResult<Selection> {
         // snap and live set has no snaps
         if versions_map.is_empty() {
             let paths: Vec<String> = browse_result
@@ -208,66 +399,212 @@ impl InteractiveSelect {
             return Err(HttmError::new(&msg).into());
         }
 
-        let path_string = if GLOBAL_CONFIG.opt_last_snap.is_some() {
-            Self::last_snap(&browse_result.selected_pathdata, &versions_map)?
+        let path_strings: Vec<String> = if config.opt_last_snap.is_some() {
+            vec![Self::last_snap(
+                &browse_result.selected_pathdata,
+                &versions_map,
+            )?]
         } else {
             // same stuff we do at fn exec, snooze...
-            let display_config =
-                GLOBAL_CONFIG.generate_display_config(&browse_result.selected_pathdata);
+            let display_config = config.generate_display_config(&browse_result.selected_pathdata);
 
             let display_map = VersionsDisplayWrapper::from(&display_config, versions_map);
 
-            let selection_buffer = display_map.to_string();
+            let selection_buffer = display_map.to_formatted()?;
 
             let opt_live_version: Option<String> = browse_result
                 .selected_pathdata
                 .get(0)
                 .map(|pathdata| pathdata.path_buf.to_string_lossy().into_owned());
 
+            // in restore mode, user may select many snapshot versions to restore at once
+            let opt_multi = matches!(interactive_mode, InteractiveMode::Restore(_));
+
             // loop until user selects a valid snapshot version
             loop {
-                // get the file name
-                let requested_file_name = select_restore_view(
+                // get the file names
+                let requested_file_names = select_restore_view(
                     &selection_buffer,
                     &ViewMode::Select(opt_live_version.clone()),
-                    false,
+                    opt_multi,
                 )?;
-                // ... we want everything between the quotes
-                let broken_string: Vec<_> = requested_file_name[0].split_terminator('"').collect();
-                // ... and the file is the 2nd item or the indexed "1" object
-                if let Some(path_string) = broken_string.get(1) {
+
+                let path_strings: Vec<String> = requested_file_names
+                    .iter()
+                    .filter_map(|requested_file_name| {
+                        // ... we want everything between the quotes
+                        let broken_string: Vec<_> =
+                            requested_file_name.split_terminator('"').collect();
+                        // ... and the file is the 2nd item or the indexed "1" object
+                        broken_string
+                            .get(1)
+                            .map(|path_string| path_string.to_string())
+                    })
                     // and cannot select a 'live' version or other invalid value.
-                    if display_map.map.iter().all(|(live_version, _snaps)| {
-                        Path::new(path_string) != live_version.path_buf.as_path()
-                    }) {
-                        // return string from the loop
-                        break (*path_string).to_string();
-                    }
+                    .filter(|path_string| {
+                        display_map.map.iter().all(|(live_version, _snaps)| {
+                            Path::new(path_string) != live_version.path_buf.as_path()
+                        })
+                    })
+                    .collect();
+
+                // return strings from the loop
+                if !path_strings.is_empty() {
+                    break path_strings;
                 }
             }
         };
 
-        if let Some(handle) = browse_result.opt_background_handle {
-            let _ = handle.join();
-        }
+        InteractiveBrowseResult::join_background(browse_result.opt_background_handle)?;
 
-        // continue to interactive_restore or print and exit here?
+        path_strings.iter().for_each(|path_string| {
+            Transcript::record(TranscriptKind::Select, &format!("{path_string:?}"))
+        });
+
+        // we retain the paths selected in browse because we may need
+        // them later during restore if opt_overwrite is selected
         if matches!(interactive_mode, InteractiveMode::Restore(_)) {
-            // one only allow one to select one path string during select
-            // but we retain paths_selected_in_browse because we may need
-            // it later during restore if opt_overwrite is selected
-            Ok(InteractiveRestore::exec(
-                &path_string,
-                &browse_result.selected_pathdata,
-            )?)
+            // a deleted directory need not be restored whole, the user may pick from the files within it
+            let (path_strings, live_paths) =
+                Self::select_within_deleted_dir(path_strings, browse_result.selected_pathdata)?;
+
+            Ok(Selection::Snapshots {
+                path_strings,
+                live_paths,
+            })
         } else {
-            Ok(Self::print_selection(&path_string)?)
+            Ok(Selection::Snapshots {
+                path_strings,
+                live_paths: browse_result.selected_pathdata,
+            })
+        }
+    }
+
+    fn select_within_deleted_dir(
+        path_strings: Vec<String>,
+        paths_selected_in_browse: Vec<PathData>,
+    ) -> HttmResult<(Vec<String>, Vec<PathData>)> {
+        let snap_dir = match (path_strings.as_slice(), paths_selected_in_browse.as_slice()) {
+            ([path_string], [pathdata]) if pathdata.metadata.is_none() => {
+                PathBuf::from(path_string)
+            }
+            _ => return Ok((path_strings, paths_selected_in_browse)),
+        };
+
+        if !snap_dir.is_dir() {
+            return Ok((path_strings, paths_selected_in_browse));
+        }
+
+        // the first entry is the directory itself, selecting it restores the whole tree, as before
+        let mut entries: Vec<PathBuf> = vec![snap_dir.clone()];
+        Self::enumerate_snap_dir(&snap_dir, &mut entries);
+
+        let selection_buffer: String = entries
+            .iter()
+            .map(|entry| format!("\"{}\"\n", entry.to_string_lossy()))
+            .collect();
+
+        // loop until user selects at least one entry
+        let selected: Vec<PathBuf> = loop {
+            let requested_lines =
+                select_restore_view(&selection_buffer, &ViewMode::Select(None), true)?;
+
+            // ... we want everything between the quotes
+            let selected: Vec<PathBuf> = requested_lines
+                .iter()
+                .filter_map(|line| line.split_terminator('"').nth(1))
+                .map(PathBuf::from)
+                .collect();
+
+            if !selected.is_empty() {
+                break selected;
+            }
+        };
+
+        // an entry within another selected dir will already be restored with that dir
+        let selected: Vec<PathBuf> = selected
+            .iter()
+            .filter(|path| {
+                !selected
+                    .iter()
+                    .any(|other| other != *path && path.starts_with(other))
+            })
+            .cloned()
+            .collect();
+
+        // in overwrite mode, each selection's original location is found by way of its live path
+        let live_paths: Vec<PathData> = selected
+            .iter()
+            .map(|snap_path| {
+                InteractiveRestore::live_path(snap_path)
+                    .map(|live_path| PathData::from(live_path.as_path()))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let path_strings = selected
+            .iter()
+            .map(|snap_path| snap_path.to_string_lossy().into_owned())
+            .collect();
+
+        Ok((path_strings, live_paths))
+    }
+
+    // a snapshot is read only, so, unlike a live dir, we simply enumerate everything beneath it
+    fn enumerate_snap_dir(dir: &Path, entries: &mut Vec<PathBuf>) {
+        let mut dir_entries: Vec<(PathBuf, bool)> = match std::fs::read_dir(dir) {
+            Ok(read_dir) => read_dir
+                .flatten()
+                .map(|dir_entry| {
+                    let is_dir = dir_entry
+                        .file_type()
+                        .map(|file_type| file_type.is_dir())
+                        .unwrap_or(false);
+                    (dir_entry.path(), is_dir)
+                })
+                .collect(),
+            Err(_) => return,
+        };
+
+        dir_entries.sort();
+
+        dir_entries.into_iter().for_each(|(path, is_dir)| {
+            entries.push(path.clone());
+
+            if is_dir {
+                Self::enumerate_snap_dir(&path, entries);
+            }
+        });
+    }
+
+    // a live path is only certain to correspond to a selection if a single path was browsed
+    fn opt_single_live_path(paths_selected_in_browse: &[PathData]) -> Option<&Path> {
+        match paths_selected_in_browse {
+            [pathdata] => Some(pathdata.path_buf.as_path()),
+            _ => None,
         }
     }
 
-    fn print_selection(path_string: &str) -> HttmResult<()> {
+    pub fn print_selection(
+        path_strings: &[String],
+        paths_selected_in_browse: &[PathData],
+    ) -> HttmResult<Output> {
+        // one only allow one to select one path string during select
+        let path_string = &path_strings[0];
+
+        History::record(
+            HistoryKind::Select,
+            path_string,
+            Self::opt_single_live_path(paths_selected_in_browse),
+        );
+
         let delimiter = delimiter();
 
+        // a snapshot name is meant to be given directly to a zfs command, and so is never quoted
+        if GLOBAL_CONFIG.opt_print_snap {
+            let snap_name = Self::snap_name(path_string)?;
+            return Ok(Output::new(format!("{snap_name}{delimiter}"), 0));
+        }
+
         let output_buf = if matches!(
             GLOBAL_CONFIG.print_mode,
             PrintMode::RawNewline | PrintMode::RawZero
@@ -277,9 +614,19 @@ impl InteractiveSelect {
             format!("\"{path_string}\"{delimiter}")
         };
 
-        print_output_buf(output_buf)?;
+        Ok(Output::new(output_buf, 0))
+    }
+
+    // reverse maps a snapshot version's location to the ZFS snapshot which contains it, like "pool/dataset@snapname"
+    fn snap_name(path_string: &str) -> HttmResult<String> {
+        let snap_pathdata = PathData::from(Path::new(path_string));
 
-        std::process::exit(0)
+        SnapNameMap::deconstruct_snap_paths(&snap_pathdata).ok_or_else(|| {
+            let msg = format!(
+                "httm could not determine the ZFS snapshot which contains {path_string:?}.  PRINT_SNAP is only available for snapshots of ZFS datasets."
+            );
+            HttmError::new(&msg).into()
+        })
     }
 
     fn last_snap(
@@ -296,14 +643,18 @@ impl InteractiveSelect {
             .flatten()
             .filter(|snap_version| {
                 if GLOBAL_CONFIG.opt_omit_ditto {
-                    snap_version.md_infallible().modify_time
-                        != live_version.md_infallible().modify_time
+                    !VersionsMap::is_ditto(live_version, snap_version, &GLOBAL_CONFIG.ditto_of_type)
                 } else {
                     true
                 }
             })
             .last()
-            .ok_or_else(|| HttmError::new("No last snapshot for the requested input file exists."))?
+            .ok_or_else(|| {
+                HttmError::with_exit_code(
+                    "No last snapshot for the requested input file exists.",
+                    NO_VERSIONS_EXIT_CODE,
+                )
+            })?
             .path_buf
             .to_string_lossy()
             .into_owned();
@@ -312,210 +663,1507 @@ impl InteractiveSelect {
     }
 }
 
-struct InteractiveRestore;
+// with a counter in the restore suffix, how many names we try, before we give up
+const MAX_RESTORE_COUNTER: usize = 9999;
+
+pub struct InteractiveRestore;
 
 impl InteractiveRestore {
-    fn exec(parsed_str: &str, paths_selected_in_browse: &[PathData]) -> HttmResult<()> {
-        // build pathdata from selection buffer parsed string
-        //
-        // request is also sanity check for snap path exists below when we check
-        // if snap_pathdata is_phantom below
-        let snap_pathdata = PathData::from(Path::new(&parsed_str));
+    pub fn exec(
+        parsed_strs: &[String],
+        paths_selected_in_browse: &[PathData],
+    ) -> HttmResult<Output> {
+        // build pairs of snap pathdata from selection buffer parsed strings, and the new
+        // places to send those files
+        let is_multiple = parsed_strs.len() > 1;
 
-        // sanity check -- snap version has good metadata?
-        let snap_path_metadata = snap_pathdata
-            .metadata
-            .ok_or_else(|| HttmError::new("Source location does not exist on disk. Quitting."))?;
+        let restore_pairs: Vec<(PathData, PathBuf)> = parsed_strs
+            .iter()
+            .map(|parsed_str| Self::restore_pair(parsed_str, paths_selected_in_browse, is_multiple))
+            .collect::<HttmResult<_>>()?;
 
-        // build new place to send file
-        let new_file_path_buf = Self::build_new_file_path(
-            paths_selected_in_browse,
-            &snap_pathdata,
-            &snap_path_metadata,
-        )?;
+        Self::check_unique_destinations(&restore_pairs)?;
 
         let should_preserve = Self::should_preserve_attributes();
 
-        // tell the user what we're up to, and get consent
-        let preview_buffer = format!(
-            "httm will copy a file from a snapshot:\n\n\
-            \tfrom: {:?}\n\
-            \tto:   {new_file_path_buf:?}\n\n\
-            Before httm restores this file, it would like your consent. Continue? (YES/NO)\n\
-            ──────────────────────────────────────────────────────────────────────────────\n\
-            YES\n\
-            NO",
-            snap_pathdata.path_buf
-        );
+        // nothing is executed, so there is no need for the user's consent
+        if GLOBAL_CONFIG.opt_print_cmd {
+            return Ok(Self::print_cmds(&restore_pairs, should_preserve));
+        }
+
+        // likewise, nothing is copied in a dry run
+        if GLOBAL_CONFIG.opt_dry_run {
+            return Self::dry_run(&restore_pairs);
+        }
+
+        Self::check_open_files(&restore_pairs)?;
+
+        let opt_live_path = InteractiveSelect::opt_single_live_path(paths_selected_in_browse);
+        let mut restore_pairs = restore_pairs;
+
+        // the content of a file is never touched in metadata only mode, so there is nothing to open or diff,
+        // and the destination is always the live file
+        let actions = if matches!(Self::opt_restore_mode(), Some(RestoreMode::MetadataOnly)) {
+            "YES\nNO"
+        } else if matches!(Self::opt_restore_mode(), Some(RestoreMode::Merge)) {
+            // a merge may leave conflicts, which the user may wish to resolve at once
+            "YES\nYES-AND-OPEN\nNO"
+        } else {
+            "YES\nYES-AND-OPEN\nYES-AND-DIFF\nCHANGE-DESTINATION\nNO"
+        };
 
         // loop until user consents or doesn't
         loop {
+            // tell the user what we're up to, and get consent
+            let preview_buffer = format!(
+                "{}\
+                Before httm restores {}, it would like your consent. Continue? (YES/NO, or another action)\n\
+                ──────────────────────────────────────────────────────────────────────────────\n\
+                {actions}",
+                Self::describe_for_consent(&restore_pairs)?,
+                if restore_pairs.len() == 1 {
+                    "this file"
+                } else {
+                    "these files"
+                }
+            );
+
             let user_consent = select_restore_view(&preview_buffer, &ViewMode::Restore, false)?[0]
                 .to_ascii_uppercase();
 
+            Transcript::record(TranscriptKind::Action, &user_consent);
+
             match user_consent.as_ref() {
+                "YES" | "Y" | "YES-AND-OPEN" | "YES-AND-DIFF"
+                    if !Self::confirm_sensitive(&restore_pairs, &mut std::io::stdin().lock())? =>
+                {
+                    println!("User declined restore.  No files were restored.");
+                    break Ok(Output::new(String::new(), RESTORE_DECLINED_EXIT_CODE));
+                }
                 "YES" | "Y" => {
-                    if matches!(
-                        GLOBAL_CONFIG.exec_mode,
-                        ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(
-                            RestoreSnapGuard::Guarded
-                        )))
-                    ) && (user_has_effective_root().is_ok()
-                        || user_has_zfs_allow_snap_priv(&new_file_path_buf).is_ok())
-                    {
-                        let snap_guard: SnapGuard =
-                            SnapGuard::try_from(new_file_path_buf.as_path())?;
-
-                        if let Err(err) = copy_recursive(
-                            &snap_pathdata.path_buf,
-                            &new_file_path_buf,
-                            should_preserve,
-                        ) {
-                            let msg = format!(
-                                "httm restore failed for the following reason: {}.\n\
-                            Attempting roll back to precautionary pre-execution snapshot.",
-                                err
-                            );
-
-                            eprintln!("{}", msg);
-
-                            snap_guard
-                                .rollback()
-                                .map(|_| println!("Rollback succeeded."))?;
-
-                            std::process::exit(1);
-                        }
-                    } else {
-                        copy_recursive(
-                            &snap_pathdata.path_buf,
-                            &new_file_path_buf,
-                            should_preserve,
-                        )?
-                    }
-
-                    let result_buffer = format!(
-                        "httm copied a file from a snapshot:\n\n\
-                            \tfrom: {:?}\n\
-                            \tto:   {new_file_path_buf:?}\n\n\
-                            Restore completed successfully.",
-                        snap_pathdata.path_buf
-                    );
-
-                    break println!("{result_buffer}");
+                    Self::restore_all(&restore_pairs, should_preserve, opt_live_path)?;
+                    break Ok(Output::default());
+                }
+                "YES-AND-OPEN" => {
+                    Self::restore_all(&restore_pairs, should_preserve, opt_live_path)?;
+                    Self::open_in_editor(&restore_pairs)?;
+                    break Ok(Output::default());
+                }
+                "YES-AND-DIFF" => {
+                    // once restored, the prior version is gone, so we must diff beforehand
+                    let diffs = Self::diff_pairs(&restore_pairs, opt_live_path)?;
+                    Self::restore_all(&restore_pairs, should_preserve, opt_live_path)?;
+                    break Ok(Output::new(diffs, 0));
                 }
-                "NO" | "N" => break println!("User declined restore.  No files were restored."),
-                // if not yes or no, then noop and continue to the next iter of loop
+                "CHANGE-DESTINATION" => {
+                    restore_pairs = Self::change_destinations(restore_pairs)?;
+                    Self::check_unique_destinations(&restore_pairs)?;
+                    Self::check_open_files(&restore_pairs)?;
+                }
+                "NO" | "N" => {
+                    println!("User declined restore.  No files were restored.");
+                    break Ok(Output::new(String::new(), RESTORE_DECLINED_EXIT_CODE));
+                }
+                // if not one of our actions, then noop and continue to the next iter of loop
                 _ => {}
             }
         }
-
-        std::process::exit(0)
     }
 
-    fn should_preserve_attributes() -> bool {
-        matches!(
-            GLOBAL_CONFIG.exec_mode,
-            ExecMode::Interactive(InteractiveMode::Restore(
-                RestoreMode::CopyAndPreserve | RestoreMode::Overwrite(_)
-            ))
-        )
+    // only an overwrite, or a merge, may clobber a system file, a copy always has a new name, see SENSITIVE_PATHS
+    pub fn sensitive_destinations(restore_pairs: &[(PathData, PathBuf)]) -> Vec<&Path> {
+        if !matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(_) | RestoreMode::Merge)
+        ) {
+            return Vec::new();
+        }
+
+        restore_pairs
+            .iter()
+            .map(|(_snap_pathdata, new_file_path_buf)| new_file_path_buf.as_path())
+            .filter(|destination| {
+                GLOBAL_CONFIG
+                    .sensitive_paths
+                    .iter()
+                    .any(|prefix| destination.starts_with(prefix))
+            })
+            .collect()
     }
 
-    fn build_new_file_path(
-        paths_selected_in_browse: &[PathData],
-        snap_pathdata: &PathData,
-        snap_path_metadata: &PathMetadata,
-    ) -> HttmResult<PathBuf> {
-        // build new place to send file
-        if matches!(
-            GLOBAL_CONFIG.exec_mode,
-            ExecMode::Interactive(InteractiveMode::Restore(RestoreMode::Overwrite(_)))
-        ) {
-            // instead of just not naming the new file with extra info (date plus "httm_restored") and shoving that new file
-            // into the pwd, here, we actually look for the original location of the file to make sure we overwrite it.
-            // so, if you were in /etc and wanted to restore /etc/samba/smb.conf, httm will make certain to overwrite
-            // at /etc/samba/smb.conf
-            let opt_original_live_pathdata = paths_selected_in_browse.iter().find_map(|pathdata| {
-                match VersionsMap::new(&GLOBAL_CONFIG, &[pathdata.clone()]).ok() {
-                    // safe to index into snaps, known len of 2 for set
-                    Some(versions_map) => {
-                        versions_map.values().flatten().find_map(|pathdata| {
-                            if pathdata == snap_pathdata {
-                                // SAFETY: safe to index into request, known len of 2 for set,
-                                // keys and values, known len of 1 for request
-                                let original_live_pathdata =
-                                    versions_map.keys().next().unwrap().clone();
-                                Some(original_live_pathdata)
-                            } else {
-                                None
-                            }
-                        })
-                    }
-                    None => None,
-                }
-            });
+    // the user must type the file name of each sensitive destination, as a stronger confirmation than YES
+    pub fn confirm_sensitive(
+        restore_pairs: &[(PathData, PathBuf)],
+        reader: &mut impl BufRead,
+    ) -> HttmResult<bool> {
+        let sensitive_destinations = Self::sensitive_destinations(restore_pairs);
 
-            match opt_original_live_pathdata {
-                Some(pathdata) => Ok(pathdata.path_buf),
-                None => Err(HttmError::new(
-                    "httm unable to determine original file path in overwrite mode.  Quitting.",
-                )
-                .into()),
-            }
-        } else {
-            let snap_filename = snap_pathdata
-                .path_buf
+        if sensitive_destinations.is_empty() {
+            return Ok(true);
+        }
+
+        let destinations_string: String = sensitive_destinations
+            .iter()
+            .map(|destination| format!("{destination:?}\n"))
+            .collect();
+
+        eprintln!(
+            "httm will overwrite the following file/s, beneath a sensitive path:\n\n{destinations_string}\n\
+            To confirm, type the file name of each file, as prompted."
+        );
+
+        for destination in sensitive_destinations {
+            let file_name = destination
                 .file_name()
-                .expect("Could not obtain a file name for the snap file version of path given")
-                .to_string_lossy()
-                .into_owned();
-
-            let new_filename = snap_filename
-                + ".httm_restored."
-                + &date_string(
-                    GLOBAL_CONFIG.requested_utc_offset,
-                    &snap_path_metadata.modify_time,
-                    DateFormat::Timestamp,
+                .map(|file_name| file_name.to_string_lossy())
+                .unwrap_or_default();
+
+            eprint!("Type \"{file_name}\" to overwrite {destination:?}: ");
+
+            let mut input = String::new();
+            reader.read_line(&mut input)?;
+
+            if input.trim() != file_name {
+                eprintln!("The name typed does not match \"{file_name}\".");
+                Transcript::record(
+                    TranscriptKind::Action,
+                    &format!("declined, as the name typed does not match {destination:?}"),
                 );
-            let new_file_dir = GLOBAL_CONFIG.pwd.path_buf.clone();
-            let new_file_path_buf: PathBuf = new_file_dir.join(new_filename);
+                return Ok(false);
+            }
+        }
 
-            // don't let the user rewrite one restore over another in non-overwrite mode
-            if new_file_path_buf.exists() {
-                Err(
-                    HttmError::new("httm will not restore to that file, as a file with the same path name already exists. Quitting.").into(),
+        Ok(true)
+    }
+
+    // only an overwrite may clobber a file some process holds open, a copy always has a new name
+    pub fn check_open_files(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<()> {
+        if !matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(_) | RestoreMode::Merge)
+        ) {
+            return Ok(());
+        }
+
+        let destinations: Vec<&Path> = restore_pairs
+            .iter()
+            .map(|(_snap_pathdata, new_file_path_buf)| new_file_path_buf.as_path())
+            .collect();
+
+        OpenFiles::check(&destinations)
+    }
+
+    // don't let the user restore more than one version to the same place
+    pub fn check_unique_destinations(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<()> {
+        let mut new_file_paths: Vec<&PathBuf> = restore_pairs
+            .iter()
+            .map(|(_snap_pathdata, new_file_path_buf)| new_file_path_buf)
+            .collect();
+        new_file_paths.sort_unstable();
+        new_file_paths.dedup();
+
+        if new_file_paths.len() != restore_pairs.len() {
+            return Err(HttmError::new(
+                "httm will not restore more than one snapshot version to the same location. Quitting.",
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    // asks the user, on the terminal, for a new destination for each file, an empty answer keeps the old
+    fn change_destinations(
+        restore_pairs: Vec<(PathData, PathBuf)>,
+    ) -> HttmResult<Vec<(PathData, PathBuf)>> {
+        restore_pairs
+            .into_iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                eprint!(
+                    "New destination for {:?} (leave empty to keep {:?}): ",
+                    snap_pathdata.path_buf, new_file_path_buf
+                );
+
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+
+                let input = input.trim();
+
+                if input.is_empty() {
+                    return Ok((snap_pathdata, new_file_path_buf));
+                }
+
+                let mut destination = PathBuf::from(input);
+
+                if destination.is_relative() {
+                    destination = GLOBAL_CONFIG.pwd.path_buf.join(destination);
+                }
+
+                // like cp, restoring into an existing dir places the file within that dir
+                if destination.is_dir() {
+                    if let Some(file_name) = snap_pathdata.path_buf.file_name() {
+                        destination.push(file_name);
+                    }
+                }
+
+                Ok((snap_pathdata, destination))
+            })
+            .collect()
+    }
+
+    fn open_in_editor(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<()> {
+        let editor = std::env::var("EDITOR").map_err(|_err| {
+            HttmError::new("httm could not open the restored files, as $EDITOR is not set.")
+        })?;
+
+        // $EDITOR may include its own args, like "code --wait"
+        let mut editor_words = editor.split_whitespace();
+
+        let editor_command = editor_words.next().ok_or_else(|| {
+            HttmError::new("httm could not open the restored files, as $EDITOR is empty.")
+        })?;
+
+        let status = ExecProcess::new(editor_command)
+            .args(editor_words)
+            .args(
+                restore_pairs
+                    .iter()
+                    .map(|(_snap_pathdata, new_file_path_buf)| new_file_path_buf),
+            )
+            .status()?;
+
+        if !status.success() {
+            let msg = format!("httm's $EDITOR, {editor:?}, exited with an error: {status}");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+
+    // compare each snapshot version to what it replaces, else, for a copy, to the live file
+    fn diff_pairs(
+        restore_pairs: &[(PathData, PathBuf)],
+        opt_live_path: Option<&Path>,
+    ) -> HttmResult<String> {
+        let diff_command = which("diff").map_err(|_err| {
+            HttmError::new(
+                "'diff' command not found. Make sure the command 'diff' is in your path.",
+            )
+        })?;
+
+        restore_pairs
+            .iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                let opt_prior = if new_file_path_buf.exists() {
+                    Some(new_file_path_buf.as_path())
+                } else {
+                    opt_live_path.filter(|live_path| live_path.exists())
+                };
+
+                let prior = match opt_prior {
+                    Some(prior) => prior,
+                    None => {
+                        return Ok(format!(
+                            "httm found no prior version of {new_file_path_buf:?} to compare.\n"
+                        ))
+                    }
+                };
+
+                let output = ExecProcess::new(&diff_command)
+                    .args(["-u", "-r"])
+                    .arg(prior)
+                    .arg(&snap_pathdata.path_buf)
+                    .output()?;
+
+                // diff exits 1 when there are differences, anything more is an error
+                if !matches!(output.status.code(), Some(0 | 1)) {
+                    let stderr_string = std::str::from_utf8(&output.stderr)?.trim();
+                    let msg = format!("httm could not diff {prior:?}: {stderr_string}");
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                if output.stdout.is_empty() {
+                    return Ok(format!(
+                        "{new_file_path_buf:?} is unchanged by this restore.\n"
+                    ));
+                }
+
+                Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            })
+            .collect()
+    }
+
+    fn restore_pair(
+        parsed_str: &str,
+        paths_selected_in_browse: &[PathData],
+        is_multiple: bool,
+    ) -> HttmResult<(PathData, PathBuf)> {
+        // build pathdata from selection buffer parsed string
+        //
+        // request is also sanity check for snap path exists below when we check
+        // if snap_pathdata is_phantom below
+        let snap_pathdata = PathData::from(Path::new(&parsed_str));
+
+        // sanity check -- snap version has good metadata?
+        let snap_path_metadata = snap_pathdata
+            .metadata
+            .ok_or_else(|| HttmError::new("Source location does not exist on disk. Quitting."))?;
+
+        // build new place to send file
+        let new_file_path_buf = match &GLOBAL_CONFIG.opt_restore_dest {
+            Some(restore_dest) => {
+                let live_path = Self::live_path(&snap_pathdata.path_buf)?;
+
+                let opt_relative_root = if is_multiple {
+                    Some(
+                        GLOBAL_CONFIG
+                            .opt_requested_dir
+                            .as_ref()
+                            .unwrap_or(&GLOBAL_CONFIG.pwd)
+                            .path_buf
+                            .as_path(),
+                    )
+                } else {
+                    None
+                };
+
+                Self::restore_dest_path(
+                    &snap_pathdata.path_buf,
+                    &live_path,
+                    restore_dest,
+                    opt_relative_root,
+                )?
+            }
+            None => Self::build_new_file_path(
+                paths_selected_in_browse,
+                &snap_pathdata,
+                &snap_path_metadata,
+            )?,
+        };
+
+        Ok((snap_pathdata, new_file_path_buf))
+    }
+
+    // the live path is where the snapshot version would be, if it were on the live dataset
+    pub fn live_path(snap_path: &Path) -> HttmResult<PathBuf> {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .snap_mount_containing(snap_path)
+            .and_then(|(mount, snap_mount)| {
+                snap_path
+                    .strip_prefix(snap_mount)
+                    .ok()
+                    .map(|relative| mount.join(relative))
+            })
+            .ok_or_else(|| {
+                HttmError::new(
+                    "httm unable to determine the live path of the snapshot version.  Quitting.",
+                )
+                .into()
+            })
+    }
+
+    // a single file is placed directly within the restore dest, but multiple files retain their paths relative to a root
+    pub fn restore_dest_path(
+        snap_path: &Path,
+        live_path: &Path,
+        restore_dest: &Path,
+        opt_relative_root: Option<&Path>,
+    ) -> HttmResult<PathBuf> {
+        let relative_path = match opt_relative_root {
+            Some(relative_root) => live_path
+                .strip_prefix(relative_root)
+                .or_else(|_err| live_path.strip_prefix(ROOT_DIRECTORY))
+                .unwrap_or(live_path),
+            None => live_path.file_name().map(Path::new).ok_or_else(|| {
+                HttmError::new("httm could not obtain a file name for the live path.  Quitting.")
+            })?,
+        };
+
+        let new_file_path_buf = restore_dest.join(relative_path);
+
+        if new_file_path_buf.exists() {
+            if !ResumeMarker::is_resumable(snap_path, &new_file_path_buf) {
+                let msg = format!(
+                    "httm will not restore to {new_file_path_buf:?}, as a file with the same path name already exists. Quitting."
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+
+            ResumeMarker::print_resume_notice(snap_path, &new_file_path_buf);
+        }
+
+        Ok(new_file_path_buf)
+    }
+
+    pub fn print_cmds(restore_pairs: &[(PathData, PathBuf)], should_preserve: bool) -> Output {
+        let mut output_buf = String::new();
+
+        if matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(RestoreSnapGuard::Guarded))
+        ) {
+            output_buf += "# NOTE: httm, in guard mode, would take a precautionary snapshot of each dataset before any overwrite\n";
+        }
+
+        // "-a" preserves attributes, and, without "-a", "-P" never follows symlinks, like httm,
+        // while "--attributes-only" never touches the content of an existing file
+        let cp_args = if matches!(Self::opt_restore_mode(), Some(RestoreMode::MetadataOnly)) {
+            "-P --attributes-only --preserve=mode,ownership,xattr"
+        } else if should_preserve {
+            "-a"
+        } else {
+            "-R -P"
+        };
+
+        restore_pairs
+            .iter()
+            .for_each(|(snap_pathdata, new_file_path_buf)| {
+                // a trailing "/." copies the contents of a dir, whether or not the destination exists
+                let src = if snap_pathdata.path_buf.is_dir() {
+                    snap_pathdata.path_buf.join(".")
+                } else {
+                    snap_pathdata.path_buf.clone()
+                };
+
+                let copy_cmd = format!(
+                    "cp {cp_args} -- {} {}",
+                    shell_quote(&src),
+                    shell_quote(new_file_path_buf)
+                );
+
+                output_buf += &match new_file_path_buf.parent() {
+                    Some(parent) if !parent.exists() => {
+                        format!("mkdir -p -- {} && {copy_cmd}\n", shell_quote(parent))
+                    }
+                    _ => format!("{copy_cmd}\n"),
+                };
+            });
+
+        Output::new(output_buf, 0)
+    }
+
+    // like diff, exits with a status which reflects whether anything would change
+    pub fn dry_run(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<Output> {
+        let mut output_buf = String::new();
+
+        if matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(RestoreSnapGuard::Guarded))
+        ) {
+            output_buf += "NOTE: httm, in guard mode, would take a precautionary snapshot of each dataset before any overwrite.\n\n";
+        }
+
+        if matches!(Self::opt_restore_mode(), Some(RestoreMode::MetadataOnly)) {
+            return Self::dry_run_metadata(restore_pairs, output_buf);
+        }
+
+        if matches!(Self::opt_restore_mode(), Some(RestoreMode::Merge)) {
+            return Self::dry_run_merge(restore_pairs, output_buf);
+        }
+
+        if GLOBAL_CONFIG.opt_itemize {
+            return Self::dry_run_itemized(restore_pairs, output_buf);
+        }
+
+        let mut num_changed = 0usize;
+
+        for (snap_pathdata, new_file_path_buf) in restore_pairs {
+            let bytes = if snap_pathdata.path_buf.is_dir() {
+                let (num_files, num_bytes) = Self::tree_summary(&snap_pathdata.path_buf);
+
+                format!(
+                    "{num_bytes} ({}, in {num_files} {})",
+                    display_human_size(num_bytes),
+                    if num_files == 1 { "file" } else { "files" }
+                )
+            } else {
+                let num_bytes = snap_pathdata.md_infallible().size;
+
+                format!("{num_bytes} ({})", display_human_size(num_bytes))
+            };
+
+            let (action, is_changed) = if !new_file_path_buf.exists() {
+                ("create", true)
+            } else if Self::would_change(&snap_pathdata.path_buf, new_file_path_buf) {
+                ("overwrite", true)
+            } else {
+                ("none, identical to the existing file", false)
+            };
+
+            if is_changed {
+                num_changed += 1;
+            }
+
+            output_buf += &format!(
+                "\tfrom:   {:?}\n\
+                \tto:     {new_file_path_buf:?}\n\
+                \tbytes:  {bytes}\n\
+                \taction: {action}\n\n",
+                snap_pathdata.path_buf
+            );
+        }
+
+        output_buf += &format!(
+            "Dry run: httm would change {num_changed} of {} {}.  No files were restored.\n",
+            restore_pairs.len(),
+            if restore_pairs.len() == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        );
+
+        let exit_code = if num_changed == 0 {
+            0
+        } else {
+            DRY_RUN_CHANGED_EXIT_CODE
+        };
+
+        Ok(Output::new(output_buf, exit_code))
+    }
+
+    // one rsync style itemized change code per entry which would change, see ITEMIZE
+    fn dry_run_itemized(
+        restore_pairs: &[(PathData, PathBuf)],
+        mut output_buf: String,
+    ) -> HttmResult<Output> {
+        let num_changed = restore_pairs
+            .iter()
+            .filter(|(snap_pathdata, new_file_path_buf)| {
+                Self::itemize_tree(&snap_pathdata.path_buf, new_file_path_buf, &mut output_buf);
+
+                !new_file_path_buf.exists()
+                    || Self::would_change(&snap_pathdata.path_buf, new_file_path_buf)
+            })
+            .count();
+
+        output_buf += &format!(
+            "Dry run: httm would change {num_changed} of {} {}.  No files were restored.\n",
+            restore_pairs.len(),
+            if restore_pairs.len() == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        );
+
+        let exit_code = if num_changed == 0 {
+            0
+        } else {
+            DRY_RUN_CHANGED_EXIT_CODE
+        };
+
+        Ok(Output::new(output_buf, exit_code))
+    }
+
+    // dirs are merged, not replaced, upon restore, so nothing at the destination is ever deleted
+    fn itemize_tree(src: &Path, dst: &Path, output_buf: &mut String) {
+        let src_entry = match src.symlink_metadata() {
+            Ok(src_md) => ItemizedEntry::from(&src_md),
+            Err(_) => return,
+        };
+
+        let opt_dst_entry = dst
+            .symlink_metadata()
+            .ok()
+            .map(|dst_md| ItemizedEntry::from(&dst_md));
+
+        // only a file which matches in size and modify time needs to be read to know whether it would change
+        let is_content_changed = match opt_dst_entry {
+            Some(dst_entry) if !src_entry.is_dir() && !dst_entry.is_dir() => {
+                Self::would_change(src, dst)
+            }
+            _ => false,
+        };
+
+        let code =
+            ItemizedEntry::itemize(opt_dst_entry.as_ref(), Some(&src_entry), is_content_changed);
+
+        if !ItemizedEntry::is_unchanged(&code) {
+            let slash = if src_entry.is_dir() { "/" } else { "" };
+            *output_buf += &format!("{code} {}{slash}\n", dst.display());
+        }
+
+        if !src_entry.is_dir() {
+            return;
+        }
+
+        if let Ok(dir_entries) = std::fs::read_dir(src) {
+            dir_entries.flatten().for_each(|dir_entry| {
+                Self::itemize_tree(
+                    &dir_entry.path(),
+                    &dst.join(dir_entry.file_name()),
+                    output_buf,
                 )
+            });
+        }
+    }
+
+    fn dry_run_metadata(
+        restore_pairs: &[(PathData, PathBuf)],
+        mut output_buf: String,
+    ) -> HttmResult<Output> {
+        let all_changes: Vec<MetadataChanges> = restore_pairs
+            .iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                MetadataChanges::new(&snap_pathdata.path_buf, new_file_path_buf)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        all_changes.iter().for_each(|changes| {
+            output_buf += &changes.describe();
+            output_buf += "\n";
+        });
+
+        let num_changed = all_changes
+            .iter()
+            .filter(|changes| !changes.is_empty())
+            .count();
+
+        output_buf += &format!(
+            "Dry run: httm would change the metadata of {num_changed} of {} {}.  No metadata was restored.\n",
+            restore_pairs.len(),
+            if restore_pairs.len() == 1 {
+                "file"
+            } else {
+                "files"
+            }
+        );
+
+        let exit_code = if num_changed == 0 {
+            0
+        } else {
+            DRY_RUN_CHANGED_EXIT_CODE
+        };
+
+        Ok(Output::new(output_buf, exit_code))
+    }
+
+    fn dry_run_merge(
+        restore_pairs: &[(PathData, PathBuf)],
+        mut output_buf: String,
+    ) -> HttmResult<Output> {
+        let all_merges: Vec<MergeRestore> = restore_pairs
+            .iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                MergeRestore::new(snap_pathdata, new_file_path_buf)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        all_merges.iter().for_each(|merge| {
+            output_buf += &merge.describe();
+            output_buf += "\n";
+        });
+
+        let num_changed = all_merges
+            .iter()
+            .filter(|merge| !merge.is_unchanged())
+            .count();
+
+        output_buf += &format!(
+            "Dry run: httm would change {num_changed} of {} {}.  No files were merged.\n",
+            restore_pairs.len(),
+            if restore_pairs.len() == 1 {
+                "file"
             } else {
-                Ok(new_file_path_buf)
+                "files"
             }
+        );
+
+        let exit_code = if num_changed == 0 {
+            0
+        } else {
+            DRY_RUN_CHANGED_EXIT_CODE
+        };
+
+        Ok(Output::new(output_buf, exit_code))
+    }
+
+    // would copying the snapshot version change the destination? dirs are merged, not replaced, upon restore,
+    // so only the snapshot side of the tree matters
+    fn would_change(src: &Path, dst: &Path) -> bool {
+        let (src_md, dst_md) = match (src.symlink_metadata(), dst.symlink_metadata()) {
+            (Ok(src_md), Ok(dst_md)) => (src_md, dst_md),
+            _ => return true,
+        };
+
+        if src_md.file_type() != dst_md.file_type() {
+            return true;
+        }
+
+        if src_md.is_symlink() {
+            return src.read_link().ok() != dst.read_link().ok();
+        }
+
+        if src_md.is_dir() {
+            return match std::fs::read_dir(src) {
+                Ok(dir_entries) => dir_entries.flatten().any(|dir_entry| {
+                    Self::would_change(&dir_entry.path(), &dst.join(dir_entry.file_name()))
+                }),
+                Err(_) => true,
+            };
+        }
+
+        if src_md.len() != dst_md.len() {
+            return true;
+        }
+
+        match (HashFromFile::try_from(src), HashFromFile::try_from(dst)) {
+            (Ok(src_hash), Ok(dst_hash)) => src_hash.into_inner() != dst_hash.into_inner(),
+            _ => true,
+        }
+    }
+
+    // what the user consents to, in metadata only mode, is exactly which attributes would change
+    pub fn describe_for_consent(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<String> {
+        if matches!(Self::opt_restore_mode(), Some(RestoreMode::Merge)) {
+            return Self::describe_merges(restore_pairs);
+        }
+
+        if !matches!(Self::opt_restore_mode(), Some(RestoreMode::MetadataOnly)) {
+            return Ok(Self::describe_pairs(restore_pairs, "httm will copy"));
+        }
+
+        let description = if restore_pairs.len() == 1 {
+            "httm will restore the metadata of a file from a snapshot:\n\n".to_owned()
+        } else {
+            format!(
+                "httm will restore the metadata of {} files from snapshots:\n\n",
+                restore_pairs.len()
+            )
+        };
+
+        restore_pairs.iter().try_fold(
+            description,
+            |mut buffer, (snap_pathdata, new_file_path_buf)| {
+                let changes = MetadataChanges::new(&snap_pathdata.path_buf, new_file_path_buf)?;
+
+                buffer += &changes.describe();
+                buffer += "\n";
+                Ok(buffer)
+            },
+        )
+    }
+
+    // what the user consents to, in merge mode, is which files would be left with conflicts
+    fn describe_merges(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<String> {
+        let description = if restore_pairs.len() == 1 {
+            "httm will merge a file from a snapshot:\n\n".to_owned()
+        } else {
+            format!(
+                "httm will merge {} files from snapshots:\n\n",
+                restore_pairs.len()
+            )
+        };
+
+        restore_pairs.iter().try_fold(
+            description,
+            |mut buffer, (snap_pathdata, new_file_path_buf)| {
+                let merge = MergeRestore::new(snap_pathdata, new_file_path_buf)?;
+
+                buffer += &merge.describe();
+                buffer += "\n";
+                Ok(buffer)
+            },
+        )
+    }
+
+    pub fn describe_pairs(restore_pairs: &[(PathData, PathBuf)], verb: &str) -> String {
+        let description = if restore_pairs.len() == 1 {
+            format!("{verb} a file from a snapshot:\n\n")
+        } else {
+            format!("{verb} {} files from snapshots:\n\n", restore_pairs.len())
+        };
+
+        restore_pairs.iter().fold(
+            description,
+            |mut buffer, (snap_pathdata, new_file_path_buf)| {
+                buffer += &format!(
+                    "\tfrom: {:?}\n\
+                    \tto:   {new_file_path_buf:?}\n",
+                    snap_pathdata.path_buf
+                );
+
+                // a dir is restored with its entire tree, so let the user know how much that is
+                if snap_pathdata.path_buf.is_dir() {
+                    let (num_files, num_bytes) = Self::tree_summary(&snap_pathdata.path_buf);
+
+                    buffer += &format!(
+                        "\ttree: {num_files} {}, {}\n",
+                        if num_files == 1 { "file" } else { "files" },
+                        display_human_size(num_bytes)
+                    );
+                }
+
+                buffer += "\n";
+                buffer
+            },
+        )
+    }
+
+    // the consent text, the progress bar, and the success text each want the summary of the same dir,
+    // and, as a snapshot dir never changes, we walk each dir only once, and remember the summary
+    fn tree_summary(dir: &Path) -> (usize, u64) {
+        let mut cache = TREE_SUMMARY_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        *cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| Self::walk_tree_summary(dir))
+    }
+
+    // counts the files and bytes beneath the snapshot side dir
+    fn walk_tree_summary(dir: &Path) -> (usize, u64) {
+        let mut num_files = 0usize;
+        let mut num_bytes = 0u64;
+
+        walk_tree(dir, usize::MAX, |dir_entry, _depth| {
+            match dir_entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => true,
+                Ok(_) => {
+                    num_files += 1;
+                    num_bytes += dir_entry.metadata().map(|md| md.len()).unwrap_or_default();
+                    false
+                }
+                Err(_) => false,
+            }
+        });
+
+        (num_files, num_bytes)
+    }
+
+    fn total_bytes(restore_pairs: &[(PathData, PathBuf)]) -> u64 {
+        restore_pairs
+            .iter()
+            .map(|(snap_pathdata, _new_file_path_buf)| {
+                if snap_pathdata.path_buf.is_dir() {
+                    Self::tree_summary(&snap_pathdata.path_buf).1
+                } else {
+                    snap_pathdata
+                        .path_buf
+                        .metadata()
+                        .map(|md| md.len())
+                        .unwrap_or_default()
+                }
+            })
+            .sum()
+    }
+
+    pub fn restore_all(
+        restore_pairs: &[(PathData, PathBuf)],
+        should_preserve: bool,
+        opt_live_path: Option<&Path>,
+    ) -> HttmResult<()> {
+        // a merge never copies, and so is never guarded
+        if matches!(Self::opt_restore_mode(), Some(RestoreMode::Merge)) {
+            return Self::restore_merge_all(restore_pairs, opt_live_path);
+        }
+
+        if matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(RestoreSnapGuard::Guarded))
+        ) {
+            return Self::restore_all_guarded(restore_pairs, should_preserve, opt_live_path);
+        }
+
+        // the user may ask that an overwrite of a system file be guarded, even when others are not
+        if GLOBAL_CONFIG.opt_guard_sensitive
+            && !Self::sensitive_destinations(restore_pairs).is_empty()
+        {
+            return Self::restore_all_guarded(restore_pairs, should_preserve, opt_live_path);
+        }
+
+        if matches!(Self::opt_restore_mode(), Some(RestoreMode::MetadataOnly)) {
+            return Self::restore_metadata_all(restore_pairs);
+        }
+
+        let journal = Self::new_journal();
+        let progress = CopyProgress::new(Self::total_bytes(restore_pairs));
+
+        let (succeeded, failed): (Vec<_>, Vec<_>) = restore_pairs
+            .iter()
+            .enumerate()
+            .map(|(idx, (snap_pathdata, new_file_path_buf))| {
+                let res = Self::journaled_copy_pair(
+                    &journal,
+                    idx,
+                    &snap_pathdata.path_buf,
+                    new_file_path_buf,
+                    should_preserve,
+                );
+                ((snap_pathdata.clone(), new_file_path_buf.clone()), res)
+            })
+            .partition(|(_pair, res)| res.is_ok());
+
+        drop(progress);
+
+        let _ = RestoreJournal::prune_stashes();
+
+        if !succeeded.is_empty() {
+            let succeeded_pairs: Vec<(PathData, PathBuf)> =
+                succeeded.into_iter().map(|(pair, _res)| pair).collect();
+
+            Self::record_history(&succeeded_pairs, opt_live_path);
+
+            println!(
+                "{}Restore completed successfully.",
+                Self::describe_pairs(&succeeded_pairs, "httm copied")
+            );
+        }
+
+        if !failed.is_empty() {
+            failed
+                .iter()
+                .for_each(|((snap_pathdata, new_file_path_buf), res)| {
+                    if let Err(err) = res {
+                        Transcript::record(
+                            TranscriptKind::Failure,
+                            &format!(
+                                "{:?} to {new_file_path_buf:?}: {err}",
+                                snap_pathdata.path_buf
+                            ),
+                        );
+
+                        eprintln!(
+                            "httm failed to copy a file from a snapshot:\n\n\
+                        \tfrom: {:?}\n\
+                        \tto:   {new_file_path_buf:?}\n\n\
+                        Restore failed for the following reason: {err}.\n",
+                            snap_pathdata.path_buf
+                        )
+                    }
+                });
+
+            let msg = format!(
+                "httm restore failed for {} of {} files.",
+                failed.len(),
+                restore_pairs.len()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+
+    // the content of each live file is untouched, so, unlike a copy, there is nothing to journal or undo
+    fn restore_metadata_all(restore_pairs: &[(PathData, PathBuf)]) -> HttmResult<()> {
+        let all_changes: Vec<MetadataChanges> = restore_pairs
+            .iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                MetadataChanges::new(&snap_pathdata.path_buf, new_file_path_buf)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let failed: Vec<(&MetadataChanges, Box<dyn std::error::Error + Send + Sync>)> = all_changes
+            .iter()
+            .filter(|changes| !changes.is_empty())
+            .filter_map(|changes| changes.apply().err().map(|err| (changes, err)))
+            .collect();
+
+        if !failed.is_empty() {
+            failed.iter().for_each(|(changes, err)| {
+                Transcript::record(
+                    TranscriptKind::Failure,
+                    &format!("{}: {err}", changes.describe()),
+                );
+
+                eprintln!(
+                    "httm failed to restore the metadata of a file from a snapshot:\n\n\
+                    {}\n\
+                    Restore failed for the following reason: {err}.\n",
+                    changes.describe()
+                )
+            });
+
+            let msg = format!(
+                "httm metadata restore failed for {} of {} files.",
+                failed.len(),
+                restore_pairs.len()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Self::record_history(restore_pairs, None);
+
+        println!(
+            "{}Restore completed successfully.",
+            all_changes
+                .iter()
+                .fold(String::new(), |buffer, changes| buffer
+                    + &changes.describe()
+                    + "\n")
+        );
+
+        Ok(())
+    }
+
+    // every merge is computed before any is applied, so that one which cannot be merged stops them all
+    fn restore_merge_all(
+        restore_pairs: &[(PathData, PathBuf)],
+        opt_live_path: Option<&Path>,
+    ) -> HttmResult<()> {
+        let all_merges: Vec<MergeRestore> = restore_pairs
+            .iter()
+            .map(|(snap_pathdata, new_file_path_buf)| {
+                MergeRestore::new(snap_pathdata, new_file_path_buf)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let journal = Self::new_journal();
+
+        all_merges
+            .iter()
+            .zip(restore_pairs.iter())
+            .enumerate()
+            .try_for_each(|(idx, (merge, (snap_pathdata, new_file_path_buf)))| {
+                let opt_pre_hash = RestoreJournal::hash(new_file_path_buf);
+                let opt_stash = journal.stash(idx, new_file_path_buf)?;
+
+                merge.apply()?;
+
+                if let Err(err) = journal.record(
+                    &snap_pathdata.path_buf,
+                    new_file_path_buf,
+                    opt_pre_hash,
+                    opt_stash,
+                ) {
+                    eprintln!("WARN: httm could not record this restore in its journal: {err}");
+                }
+
+                HttmResult::Ok(())
+            })?;
+
+        let _ = RestoreJournal::prune_stashes();
+
+        Self::record_history(restore_pairs, opt_live_path);
+
+        println!(
+            "{}Restore completed successfully.",
+            all_merges.iter().fold(String::new(), |buffer, merge| buffer
+                + &merge.describe()
+                + "\n")
+        );
+
+        let num_conflicts: usize = all_merges.iter().map(MergeRestore::num_conflicts).sum();
+
+        if num_conflicts > 0 {
+            println!(
+                "httm left {num_conflicts} {}, between conflict markers, for the user to resolve.",
+                if num_conflicts == 1 {
+                    "conflict"
+                } else {
+                    "conflicts"
+                }
+            );
+        }
+
+        Ok(())
+    }
+
+    // restores a single snapshot version, journaled like any other restore, but without a word to stdout,
+    // for those, like the robot protocol, which report for themselves
+    pub fn restore_quietly(
+        snap_pathdata: &PathData,
+        new_file_path_buf: &Path,
+        journal_mode: JournalMode,
+        should_preserve: bool,
+    ) -> HttmResult<()> {
+        let journal = RestoreJournal::new(journal_mode);
+
+        let res = Self::journaled_copy_pair(
+            &journal,
+            0,
+            &snap_pathdata.path_buf,
+            new_file_path_buf,
+            should_preserve,
+        );
+
+        let _ = RestoreJournal::prune_stashes();
+
+        res
+    }
+
+    fn restore_all_guarded(
+        restore_pairs: &[(PathData, PathBuf)],
+        should_preserve: bool,
+        opt_live_path: Option<&Path>,
+    ) -> HttmResult<()> {
+        // take only one precautionary snapshot per dataset, before any overwrite action occurs
+        let mut snap_guards: Vec<(PathBuf, SnapGuard)> = Vec::new();
+
+        let opt_zfs_command = ZfsCommand::new().ok();
+
+        for (_snap_pathdata, new_file_path_buf) in restore_pairs {
+            let is_capable = match &opt_zfs_command {
+                Some(zfs_command) => {
+                    zfs_command.is_capable(new_file_path_buf, ZfsPermission::Snapshot)
+                }
+                None => false,
+            };
+
+            if !is_capable {
+                continue;
+            }
+
+            let new_file_pathdata = PathData::from(new_file_path_buf.as_path());
+            let dataset_mount = new_file_pathdata
+                .proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)?
+                .to_path_buf();
+
+            if snap_guards
+                .iter()
+                .all(|(mount, _guard)| mount != &dataset_mount)
+            {
+                let snap_guard: SnapGuard = SnapGuard::try_from(new_file_path_buf.as_path())?;
+                snap_guards.push((dataset_mount, snap_guard));
+            }
+        }
+
+        let journal = Self::new_journal();
+        let progress = CopyProgress::new(Self::total_bytes(restore_pairs));
+
+        for (idx, (snap_pathdata, new_file_path_buf)) in restore_pairs.iter().enumerate() {
+            if let Err(err) = Self::journaled_copy_pair(
+                &journal,
+                idx,
+                &snap_pathdata.path_buf,
+                new_file_path_buf,
+                should_preserve,
+            ) {
+                // the progress bar must be cleared before we print anything else
+                drop(progress);
+
+                let msg = format!(
+                    "httm restore failed for the following reason: {}.\n\
+                Attempting roll back to precautionary pre-execution snapshots.",
+                    err
+                );
+
+                eprintln!("{}", msg);
+
+                Transcript::record(
+                    TranscriptKind::Failure,
+                    &format!(
+                        "{:?} to {new_file_path_buf:?}: {err}",
+                        snap_pathdata.path_buf
+                    ),
+                );
+
+                snap_guards
+                    .iter()
+                    .try_for_each(|(_mount, snap_guard)| snap_guard.rollback())
+                    .map(|_| println!("Rollback succeeded."))?;
+
+                Transcript::record(
+                    TranscriptKind::End,
+                    "rolled back to precautionary pre-execution snapshots",
+                );
+
+                let _ = RestoreJournal::prune_stashes();
+
+                return Err(HttmError::new(
+                    "httm restore failed, and was rolled back to the precautionary pre-execution snapshots.",
+                )
+                .into());
+            }
+        }
+
+        drop(progress);
+
+        let _ = RestoreJournal::prune_stashes();
+
+        Self::record_history(restore_pairs, opt_live_path);
+
+        println!(
+            "{}Restore completed successfully.",
+            Self::describe_pairs(restore_pairs, "httm copied")
+        );
+
+        Ok(())
+    }
+
+    fn new_journal() -> RestoreJournal {
+        match Self::opt_restore_mode() {
+            Some(RestoreMode::Overwrite(_) | RestoreMode::Merge) => {
+                RestoreJournal::new(JournalMode::Overwrite)
+            }
+            _ => RestoreJournal::new(JournalMode::Copy),
+        }
+    }
+
+    // every restore is journaled, and any file an overwrite would replace is first stashed,
+    // so that "--undo-last-restore" may put it back
+    fn journaled_copy_pair(
+        journal: &RestoreJournal,
+        idx: usize,
+        src: &Path,
+        dst: &Path,
+        should_preserve: bool,
+    ) -> HttmResult<()> {
+        let opt_pre_hash = RestoreJournal::hash(dst);
+        let opt_stash = journal.stash(idx, dst)?;
+
+        Self::copy_pair(src, dst, should_preserve)?;
+
+        if let Err(err) = journal.record(src, dst, opt_pre_hash, opt_stash) {
+            eprintln!("WARN: httm could not record this restore in its journal: {err}");
+        }
+
+        Ok(())
+    }
+
+    fn copy_pair(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+        // the marker remains, should we be interrupted, so the same restore may be resumed
+        ResumeMarker::create(src, dst)?;
+
+        copy_recursive(src, dst, should_preserve)?;
+
+        if should_preserve || GLOBAL_CONFIG.opt_dir_times {
+            restore_dir_times(src, dst)?;
+        }
+
+        ResumeMarker::remove(dst)?;
+
+        Ok(())
+    }
+
+    fn record_history(restore_pairs: &[(PathData, PathBuf)], opt_live_path: Option<&Path>) {
+        let is_overwrite = matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::Overwrite(_) | RestoreMode::MetadataOnly | RestoreMode::Merge)
+        );
+
+        restore_pairs
+            .iter()
+            .for_each(|(snap_pathdata, new_file_path_buf)| {
+                // in overwrite mode, the new file path is always the live path
+                let opt_live_path = if is_overwrite {
+                    Some(new_file_path_buf.as_path())
+                } else {
+                    opt_live_path
+                };
+
+                History::record(
+                    HistoryKind::Restore,
+                    &snap_pathdata.path_buf.to_string_lossy(),
+                    opt_live_path,
+                );
+
+                Transcript::record(
+                    TranscriptKind::Restore,
+                    &format!("{:?} to {new_file_path_buf:?}", snap_pathdata.path_buf),
+                );
+            });
+    }
+
+    pub fn should_preserve_attributes() -> bool {
+        matches!(
+            Self::opt_restore_mode(),
+            Some(RestoreMode::CopyAndPreserve | RestoreMode::Overwrite(_))
+        )
+    }
+
+    // the interactive and batch restore modes share a restore mode
+    fn opt_restore_mode() -> Option<&'static RestoreMode> {
+        match &GLOBAL_CONFIG.exec_mode {
+            ExecMode::Interactive(InteractiveMode::Restore(restore_mode)) => Some(restore_mode),
+            ExecMode::BatchRestore(batch_config) => Some(&batch_config.restore_mode),
+            _ => None,
+        }
+    }
+
+    // a copy is named per the user's restore suffix, and, should that name be taken, and the suffix have
+    // a counter, by the first name not yet taken, see RestoreSuffix
+    pub fn restored_file_path(
+        snap_path: &Path,
+        modify_time: &SystemTime,
+        new_file_dir: &Path,
+    ) -> HttmResult<PathBuf> {
+        let snap_filename = snap_path
+            .file_name()
+            .ok_or_else(|| {
+                HttmError::new("httm could not obtain a file name for the snapshot version.")
+            })?
+            .to_string_lossy();
+
+        let date = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            modify_time,
+            DateFormat::Timestamp,
+        );
+
+        let restore_suffix = &GLOBAL_CONFIG.restore_suffix;
+        let max_counter = if restore_suffix.has_counter() {
+            MAX_RESTORE_COUNTER
+        } else {
+            1
+        };
+
+        let mut new_file_path_buf = PathBuf::new();
+
+        for counter in 1..=max_counter {
+            new_file_path_buf =
+                new_file_dir.join(restore_suffix.render(&snap_filename, &date, counter));
+
+            // don't let the user rewrite one restore over another in non-overwrite mode,
+            // unless resuming that same restore
+            if !new_file_path_buf.exists() {
+                return Ok(new_file_path_buf);
+            }
+
+            if ResumeMarker::is_resumable(snap_path, &new_file_path_buf) {
+                ResumeMarker::print_resume_notice(snap_path, &new_file_path_buf);
+                return Ok(new_file_path_buf);
+            }
+        }
+
+        let msg = if restore_suffix.has_counter() {
+            format!(
+                "httm could not find a name for a copy of {snap_path:?}, per the RESTORE_SUFFIX given, which is not already taken."
+            )
+        } else {
+            format!(
+                "httm will not restore to {new_file_path_buf:?}, as a file with the same path name already exists."
+            )
+        };
+
+        Err(HttmError::new(&msg).into())
+    }
+
+    fn build_new_file_path(
+        paths_selected_in_browse: &[PathData],
+        snap_pathdata: &PathData,
+        snap_path_metadata: &PathMetadata,
+    ) -> HttmResult<PathBuf> {
+        // build new place to send file
+        if matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(
+                RestoreMode::Overwrite(_) | RestoreMode::MetadataOnly | RestoreMode::Merge
+            ))
+        ) {
+            // instead of just not naming the new file with extra info (date plus "httm_restored") and shoving that new file
+            // into the pwd, here, we actually look for the original location of the file to make sure we overwrite it.
+            // so, if you were in /etc and wanted to restore /etc/samba/smb.conf, httm will make certain to overwrite
+            // at /etc/samba/smb.conf
+            let opt_original_live_pathdata = paths_selected_in_browse.iter().find_map(|pathdata| {
+                match VersionsMap::new(&GLOBAL_CONFIG, &[pathdata.clone()]).ok() {
+                    // safe to index into snaps, known len of 2 for set
+                    Some(versions_map) => {
+                        versions_map.values().flatten().find_map(|pathdata| {
+                            if pathdata == snap_pathdata {
+                                // SAFETY: safe to index into request, known len of 2 for set,
+                                // keys and values, known len of 1 for request
+                                let original_live_pathdata =
+                                    versions_map.keys().next().unwrap().clone();
+                                Some(original_live_pathdata)
+                            } else {
+                                None
+                            }
+                        })
+                    }
+                    None => None,
+                }
+            });
+
+            match opt_original_live_pathdata {
+                Some(pathdata) => Ok(pathdata.path_buf),
+                None => Err(HttmError::new(
+                    "httm unable to determine original file path in overwrite mode.  Quitting.",
+                )
+                .into()),
+            }
+        } else {
+            Self::restored_file_path(
+                &snap_pathdata.path_buf,
+                &snap_path_metadata.modify_time,
+                &GLOBAL_CONFIG.pwd.path_buf,
+            )
+        }
+    }
+}
+
+// single quote a path for the shell, where the only char requiring an escape is the single quote itself,
+// but a path which is not UTF-8 is quoted byte by byte, with $'\xHH' escapes, as a lossy conversion
+// would print a command which copies some other path
+fn shell_quote(path: &Path) -> String {
+    match path.to_str() {
+        Some(utf8) => format!("'{}'", utf8.replace('\'', "'\\''")),
+        None => {
+            let escaped: String = path
+                .as_os_str()
+                .as_bytes()
+                .iter()
+                .map(|byte| match byte {
+                    b'\'' | b'\\' => format!("\\{}", *byte as char),
+                    0x20..=0x7e => (*byte as char).to_string(),
+                    _ => format!("\\x{byte:02x}"),
+                })
+                .collect();
+
+            format!("$'{escaped}'")
         }
     }
 }
 
 pub enum ViewMode {
-    Browse,
+    // the notice is how the last snapshot request went, if any, see snapshot_selected
+    Browse(BrowseFilter, Option<String>),
     Select(Option<String>),
     Restore,
     Purge,
+    RollForward,
+    History,
+    Bookmarks,
+    SnapshotSelect,
 }
 
 impl ViewMode {
-    fn print_header(&self) -> String {
+    pub fn print_header(&self) -> String {
         format!(
             "PREVIEW UP: shift+up | PREVIEW DOWN: shift+down | {}\n\
-        PAGE UP:    page up  | PAGE DOWN:    page down \n\
-        EXIT:       esc      | SELECT:       enter      | SELECT, MULTIPLE: shift+tab\n\
+        PAGE UP:    page up  | PAGE DOWN:    page down {}\n\
+        EXIT:       esc      | SELECT:       enter      | SELECT, MULTIPLE: shift+tab{}\n\
         ──────────────────────────────────────────────────────────────────────────────",
-            self.print_mode()
+            self.print_mode(),
+            self.print_filter(),
+            self.print_notice()
         )
     }
 
-    fn print_mode(&self) -> &str {
+    // a line of its own, if there is any notice to show
+    pub fn print_notice(&self) -> String {
+        match self {
+            ViewMode::Browse(_, Some(notice)) => format!("\nSNAPSHOT:   {notice}"),
+            _ => String::new(),
+        }
+    }
+
+    // only the browse view may be filtered
+    pub fn print_filter(&self) -> String {
         match self {
-            ViewMode::Browse => "====> [ Browse Mode ] <====",
+            ViewMode::Browse(filter, _) => {
+                format!(
+                    "  | FILTER: ctrl-o, alt-1..4 [ {} ] | SNAPSHOT: ctrl-s",
+                    filter.as_str()
+                )
+            }
+            _ => String::new(),
+        }
+    }
+
+    pub fn print_mode(&self) -> &str {
+        match self {
+            ViewMode::Browse(..) => "====> [ Browse Mode ] <====",
             ViewMode::Select(_) => "====> [ Select Mode ] <====",
             ViewMode::Restore => "====> [ Restore Mode ] <====",
             ViewMode::Purge => "====> [ Purge Mode ] <====",
+            ViewMode::RollForward => "====> [ Roll Forward Mode ] <====",
+            ViewMode::History => "====> [ History Mode ] <====",
+            ViewMode::Bookmarks => "====> [ Bookmarks Mode ] <====",
+            ViewMode::SnapshotSelect => "====> [ Snapshot Select Mode ] <====",
         }
     }
 }
@@ -525,47 +2173,7 @@ pub fn select_restore_view(
     view_mode: &ViewMode,
     multi: bool,
 ) -> HttmResult<Vec<String>> {
-    let preview_selection = PreviewSelection::new(view_mode)?;
-
-    let header = view_mode.print_header();
-
-    // build our browse view - less to do than before - no previews, looking through one 'lil buffer
-    let skim_opts = SkimOptionsBuilder::default()
-        .preview_window(preview_selection.opt_preview_window.as_deref())
-        .preview(preview_selection.opt_preview_command.as_deref())
-        .disabled(true)
-        .tac(true)
-        .nosort(true)
-        .tabstop(Some("4"))
-        .exact(true)
-        .multi(multi)
-        .regex(false)
-        .tiebreak(Some("length,index".to_string()))
-        .header(Some(&header))
-        .build()
-        .expect("Could not initialized skim options for select_restore_view");
-
-    let item_reader_opts = SkimItemReaderOption::default().ansi(true);
-    let item_reader = SkimItemReader::new(item_reader_opts);
-
-    let (items, _opt_handle) =
-        item_reader.of_bufread(Box::new(Cursor::new(preview_buffer.trim().to_owned())));
-
-    // run_with() reads and shows items from the thread stream created above
-    let res = match skim::Skim::run_with(&skim_opts, Some(items)) {
-        Some(output) if output.is_abort => {
-            eprintln!("httm select/restore/purge session was aborted.  Quitting.");
-            std::process::exit(0);
-        }
-        Some(output) => output
-            .selected_items
-            .iter()
-            .map(|i| i.output().into_owned())
-            .collect(),
-        None => {
-            return Err(HttmError::new("httm select/restore/purge session failed.").into());
-        }
-    };
-
-    Ok(res)
+    picker()
+        .select(preview_buffer, view_mode, multi)
+        .map(|output| output.selected)
 }
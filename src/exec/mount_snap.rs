@@ -0,0 +1,270 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs::{read_to_string, DirBuilder};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+
+use nix::unistd::geteuid;
+
+use crate::config::generate::{MountSnapAction, PrintMode};
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{delimiter, print_output_buf};
+use crate::lookup::snap_names::SnapNameMap;
+use crate::parse::aliases::FilesystemType;
+use crate::GLOBAL_CONFIG;
+
+const MOUNT_DIR_PREFIX: &str = "httm_snap_mount_";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+pub struct MountSnap;
+
+impl MountSnap {
+    pub fn exec(mount_snap_action: &MountSnapAction) -> HttmResult<()> {
+        match mount_snap_action {
+            MountSnapAction::Mount => Self::mount(&GLOBAL_CONFIG.paths),
+            MountSnapAction::Unmount => Self::unmount(&GLOBAL_CONFIG.paths),
+        }
+    }
+
+    fn mount(snap_versions: &[PathData]) -> HttmResult<()> {
+        let mount_command = which::which("mount").map_err(|_err| {
+            HttmError::new(
+                "'mount' command not found. Make sure the command 'mount' is in your path.",
+            )
+        })?;
+
+        // key: snap root, val: opt ZFS snapshot name
+        // more than one version may be located on the same snapshot, but we only mount each snapshot once
+        let snaps_to_mount: BTreeMap<PathBuf, Option<String>> = snap_versions
+            .iter()
+            .map(|pathdata| {
+                let (snap_root, fs_type) = Self::snap_root(pathdata)?;
+
+                let opt_zfs_snap_name = match fs_type {
+                    FilesystemType::Zfs => SnapNameMap::deconstruct_snap_paths(pathdata),
+                    FilesystemType::Btrfs
+                    | FilesystemType::Nilfs2
+                    | FilesystemType::SnapshotDir(_) => None,
+                };
+
+                Ok((snap_root, opt_zfs_snap_name))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let output_buf: String = snaps_to_mount
+            .iter()
+            .map(|(snap_root, opt_zfs_snap_name)| {
+                let mount_dir = Self::mount_dir(snap_root, opt_zfs_snap_name)?;
+
+                let mut process_args: Vec<&OsStr> = match opt_zfs_snap_name {
+                    Some(zfs_snap_name) => {
+                        vec![OsStr::new("-t"), OsStr::new("zfs"), OsStr::new(zfs_snap_name)]
+                    }
+                    None => vec![OsStr::new("--bind"), snap_root.as_os_str()],
+                };
+
+                process_args.push(mount_dir.as_os_str());
+
+                let process_output = ExecProcess::new(&mount_command)
+                    .args(&process_args)
+                    .output()?;
+                let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+                // stderr_string is a string not an error, so here we build an err or output
+                if !process_output.status.success() || !stderr_string.is_empty() {
+                    // clean up after ourselves, the dir is empty if the mount failed
+                    let _ = std::fs::remove_dir(&mount_dir);
+
+                    let msg = if stderr_string.contains("must be superuser")
+                        || stderr_string.contains("permission denied")
+                    {
+                        "httm must have root privileges to mount a snapshot".to_owned()
+                    } else {
+                        format!(
+                            "httm was unable to mount the snapshot {snap_root:?}. The 'mount' command issued the following error: {stderr_string}"
+                        )
+                    };
+
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                let res = if matches!(
+                    GLOBAL_CONFIG.print_mode,
+                    PrintMode::RawNewline | PrintMode::RawZero
+                ) {
+                    let delimiter = delimiter();
+                    format!("{}{delimiter}", mount_dir.display())
+                } else {
+                    format!(
+                        "httm mounted the snapshot {snap_root:?} at: {}\n",
+                        mount_dir.display()
+                    )
+                };
+
+                Ok(res)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        print_output_buf(output_buf)
+    }
+
+    fn unmount(mount_dirs: &[PathData]) -> HttmResult<()> {
+        let umount_command = which::which("umount").map_err(|_err| {
+            HttmError::new(
+                "'umount' command not found. Make sure the command 'umount' is in your path.",
+            )
+        })?;
+
+        let output_buf: String = mount_dirs
+            .iter()
+            .map(|pathdata| {
+                let mount_dir = &pathdata.path_buf;
+
+                // only unmount those dirs we mounted ourselves
+                if !Self::is_httm_mount_dir(mount_dir)? {
+                    let msg = format!(
+                        "{mount_dir:?} is not a snapshot mount created by httm, and httm will not unmount it."
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                let process_output = ExecProcess::new(&umount_command).arg(mount_dir).output()?;
+                let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+                if !process_output.status.success() || !stderr_string.is_empty() {
+                    let msg = format!(
+                        "httm was unable to unmount {mount_dir:?}. The 'umount' command issued the following error: {stderr_string}"
+                    );
+                    return Err(HttmError::new(&msg).into());
+                }
+
+                // remove_dir only removes an empty dir, so we won't remove anything still mounted
+                std::fs::remove_dir(mount_dir)?;
+
+                let res = if matches!(
+                    GLOBAL_CONFIG.print_mode,
+                    PrintMode::RawNewline | PrintMode::RawZero
+                ) {
+                    let delimiter = delimiter();
+                    format!("{}{delimiter}", mount_dir.display())
+                } else {
+                    format!("httm unmounted: {}\n", mount_dir.display())
+                };
+
+                Ok(res)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        print_output_buf(output_buf)
+    }
+
+    // find the snapshot which contains the snapshot version, that is, the snap mount which is its longest prefix
+    fn snap_root(pathdata: &PathData) -> HttmResult<(PathBuf, FilesystemType)> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        dataset_collection
+            .snap_mount_containing(&pathdata.path_buf)
+            .and_then(|(mount, snap_mount)| {
+                dataset_collection
+                    .map_of_datasets
+                    .get(mount)
+                    .map(|md| (snap_mount.clone(), md.fs_type.clone()))
+            })
+            .ok_or_else(|| {
+                let msg = format!(
+                    "httm could not determine the snapshot which contains {:?}.  Is this path a snapshot version?",
+                    pathdata.path_buf
+                );
+                HttmError::new(&msg).into()
+            })
+    }
+
+    // a new dir unique to the snapshot, like "httm_snap_mount_rpool_home@snap_2023", which is only created if nothing,
+    // not even a symlink, exists at its path, and which only its owner may enter, so no other user may
+    // choose, or redirect, where we mount
+    fn mount_dir(snap_root: &Path, opt_zfs_snap_name: &Option<String>) -> HttmResult<PathBuf> {
+        let unique_name = match opt_zfs_snap_name {
+            Some(zfs_snap_name) => zfs_snap_name.replace('/', "_"),
+            None => snap_root
+                .to_string_lossy()
+                .trim_start_matches('/')
+                .replace('/', "_"),
+        };
+
+        let mount_dir = std::env::temp_dir().join(format!("{MOUNT_DIR_PREFIX}{unique_name}"));
+
+        // unlike create_dir_all, create never succeeds upon a path which already exists
+        DirBuilder::new()
+            .mode(0o700)
+            .create(&mount_dir)
+            .map_err(|err| {
+                let msg = format!(
+                    "httm could not create the directory {mount_dir:?} upon which to mount the snapshot {snap_root:?}, \
+                    perhaps because the snapshot is already mounted there: {err}"
+                );
+                HttmError::new(&msg)
+            })?;
+
+        Ok(mount_dir)
+    }
+
+    // a dir we mounted is a dir, not a symlink, within the temp dir, with our prefix, which we own, and which
+    // only we may enter, and upon which something is mounted, just as mount_dir and mount left it
+    fn is_httm_mount_dir(mount_dir: &Path) -> HttmResult<bool> {
+        let is_named_as_ours = mount_dir.parent() == Some(std::env::temp_dir().as_path())
+            && mount_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().starts_with(MOUNT_DIR_PREFIX))
+                .unwrap_or(false);
+
+        if !is_named_as_ours {
+            return Ok(false);
+        }
+
+        let md = match std::fs::symlink_metadata(mount_dir) {
+            Ok(md) => md,
+            Err(_) => return Ok(false),
+        };
+
+        if !md.is_dir() || md.uid() != geteuid().as_raw() || md.mode() & 0o777 != 0o700 {
+            return Ok(false);
+        }
+
+        // the fifth field of each line of mountinfo is the mount point, with spaces and the like octal escaped
+        let mountinfo = read_to_string(MOUNTINFO_PATH)?;
+
+        let is_mounted = mountinfo
+            .lines()
+            .filter_map(|line| line.split(' ').nth(4))
+            .any(|mount_point| Path::new(&Self::unescape_mountinfo(mount_point)) == mount_dir);
+
+        Ok(is_mounted)
+    }
+
+    fn unescape_mountinfo(field: &str) -> String {
+        field
+            .replace("\\040", " ")
+            .replace("\\011", "\t")
+            .replace("\\012", "\n")
+            .replace("\\134", "\\")
+    }
+}
@@ -0,0 +1,206 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use crate::config::generate::{Config, InteractiveMode};
+use crate::data::paths::PathData;
+use crate::display_versions::wrapper::VersionsDisplayWrapper;
+use crate::exec::bookmarks::BookmarkView;
+use crate::exec::history::HistoryView;
+use crate::exec::interactive::{InteractiveBrowseResult, InteractiveRestore, InteractiveSelect};
+use crate::library::history::{History, HistoryKind};
+use crate::library::results::{HttmResult, NO_VERSIONS_EXIT_CODE, SUCCESS_EXIT_CODE};
+use crate::library::transcript::{Transcript, TranscriptKind};
+use crate::library::utility::print_output_buf;
+use crate::library::version_index::VersionIndex;
+use crate::lookup::versions::VersionsMap;
+
+// the display and interactive modes are each a pipeline of the same stages:
+// gather paths -> resolve versions -> select -> act -> output
+//
+// each stage takes only the config, and the typed output of the stage before, so modes may share, or replace,
+// any one stage.
+// the output stage prints whatever buffer the mode leaves, and exits with its status, but an act which
+// may take a while, like a restore, or a precautionary snapshot, reports its progress as it goes
+pub struct Pipeline;
+
+// the paths of interest, whether given as input, or browsed
+pub struct Gathered {
+    pub paths: Vec<PathData>,
+    pub opt_browse_result: Option<InteractiveBrowseResult>,
+}
+
+// what, of the versions of those paths, the user would like to act upon
+pub enum Selection {
+    // all versions, as displayed in the display and browse modes
+    Versions(VersionsMap),
+    // snapshot versions picked by the user, and the live paths browsed, which, in overwrite mode,
+    // locate where each version should be restored
+    Snapshots {
+        path_strings: Vec<String>,
+        live_paths: Vec<PathData>,
+    },
+}
+
+// what remains to do once a mode has acted: print a buffer, and exit with a status
+#[derive(Debug, Default)]
+pub struct Output {
+    pub buffer: String,
+    pub exit_code: i32,
+}
+
+impl Output {
+    pub fn new(buffer: String, exit_code: i32) -> Self {
+        Self { buffer, exit_code }
+    }
+}
+
+impl Pipeline {
+    pub fn exec(config: &Config, opt_interactive_mode: Option<&InteractiveMode>) -> HttmResult<()> {
+        // select or restore from previous sessions, instead of browsing anew
+        if let Some(interactive_mode) = opt_interactive_mode {
+            if config.opt_history {
+                let output = HistoryView::select(interactive_mode)?;
+                return Self::output(output);
+            }
+        }
+
+        let gathered = Self::gather(config, opt_interactive_mode)?;
+        let versions_map = Self::resolve(config, &gathered)?;
+        let selection = Self::select(config, gathered, versions_map, opt_interactive_mode)?;
+        let output = Self::act(config, selection, opt_interactive_mode)?;
+
+        Self::output(output)
+    }
+
+    pub fn gather(
+        config: &Config,
+        opt_interactive_mode: Option<&InteractiveMode>,
+    ) -> HttmResult<Gathered> {
+        match opt_interactive_mode {
+            Some(_) => {
+                // a bookmarked path takes the place of the path given, see BookmarkView
+                let browse_result = if config.opt_bookmarks {
+                    InteractiveBrowseResult::from_path(&BookmarkView::select()?)?
+                } else {
+                    InteractiveBrowseResult::new()?
+                };
+
+                Ok(Gathered {
+                    paths: browse_result.selected_pathdata.clone(),
+                    opt_browse_result: Some(browse_result),
+                })
+            }
+            None => Ok(Gathered {
+                paths: config.paths.clone(),
+                opt_browse_result: None,
+            }),
+        }
+    }
+
+    pub fn resolve(config: &Config, gathered: &Gathered) -> HttmResult<VersionsMap> {
+        VersionsMap::new(config, &gathered.paths)
+    }
+
+    pub fn select(
+        config: &Config,
+        gathered: Gathered,
+        versions_map: VersionsMap,
+        opt_interactive_mode: Option<&InteractiveMode>,
+    ) -> HttmResult<Selection> {
+        match (opt_interactive_mode, gathered.opt_browse_result) {
+            (
+                Some(interactive_mode @ (InteractiveMode::Restore(_) | InteractiveMode::Select)),
+                Some(browse_result),
+            ) => InteractiveSelect::select(config, browse_result, versions_map, interactive_mode),
+            (Some(InteractiveMode::Browse), opt_browse_result) => {
+                InteractiveBrowseResult::join_background(
+                    opt_browse_result.and_then(|browse_result| browse_result.opt_background_handle),
+                )?;
+
+                gathered.paths.iter().for_each(|pathdata| {
+                    History::record(
+                        HistoryKind::Browse,
+                        &pathdata.path_buf.to_string_lossy(),
+                        None,
+                    );
+
+                    Transcript::record(TranscriptKind::Browse, &format!("{:?}", pathdata.path_buf));
+                });
+
+                Ok(Selection::Versions(versions_map))
+            }
+            _ => Ok(Selection::Versions(versions_map)),
+        }
+    }
+
+    pub fn act(
+        config: &Config,
+        selection: Selection,
+        opt_interactive_mode: Option<&InteractiveMode>,
+    ) -> HttmResult<Output> {
+        match (selection, opt_interactive_mode) {
+            (Selection::Versions(versions_map), _) => {
+                // only the display modes, not an interactive browse, exit upon no snapshot versions
+                let exit_code =
+                    if opt_interactive_mode.is_none() && versions_map.is_no_snap_versions(config) {
+                        NO_VERSIONS_EXIT_CODE
+                    } else {
+                        SUCCESS_EXIT_CODE
+                    };
+
+                let output_buf =
+                    VersionsDisplayWrapper::from(config, versions_map).to_formatted()?;
+
+                Ok(Output::new(output_buf, exit_code))
+            }
+            (
+                Selection::Snapshots {
+                    path_strings,
+                    live_paths,
+                },
+                Some(InteractiveMode::Restore(_)),
+            ) => InteractiveRestore::exec(&path_strings, &live_paths),
+            (
+                Selection::Snapshots {
+                    path_strings,
+                    live_paths,
+                },
+                _,
+            ) => InteractiveSelect::print_selection(&path_strings, &live_paths),
+        }
+    }
+
+    pub fn output(output: Output) -> HttmResult<()> {
+        if !output.buffer.is_empty() {
+            print_output_buf(output.buffer)?;
+        }
+
+        if output.exit_code != 0 {
+            if VersionIndex::is_enabled() {
+                let _ = VersionIndex::flush();
+            }
+
+            Transcript::record(
+                TranscriptKind::End,
+                &format!("completed with exit code {}", output.exit_code),
+            );
+            std::process::exit(output.exit_code)
+        }
+
+        Ok(())
+    }
+}
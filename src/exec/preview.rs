@@ -15,17 +15,42 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use std::time::{Duration, SystemTime};
 
 use which::which;
 
+use crate::config::generate::PreviewSandbox;
 use crate::exec::interactive::ViewMode;
 use crate::library::results::{HttmError, HttmResult};
-use crate::GLOBAL_CONFIG;
+use crate::library::utility::{date_string, display_human_size, DateFormat};
+use crate::{BTRFS_SNAPPER_SUFFIX, GLOBAL_CONFIG};
+
+// the whole filesystem, read-only, with a private /tmp, no network, and no terminal to inject input into
+const BWRAP_ARGS: [&str; 13] = [
+    "--ro-bind",
+    "/",
+    "/",
+    "--dev",
+    "/dev",
+    "--proc",
+    "/proc",
+    "--tmpfs",
+    "/tmp",
+    "--unshare-all",
+    "--die-with-parent",
+    "--new-session",
+    "--",
+];
 
 pub struct PreviewSelection {
     pub opt_preview_window: Option<String>,
     pub opt_preview_command: Option<String>,
+    pub opt_live_version: Option<String>,
 }
 
 impl PreviewSelection {
@@ -45,35 +70,73 @@ impl PreviewSelection {
                         defined_command,
                         opt_live_version,
                     )?),
+                    opt_live_version: opt_live_version.clone(),
                 }
             }
             _ => PreviewSelection {
                 opt_preview_window: Some(String::new()),
                 opt_preview_command: None,
+                opt_live_version: None,
             },
         };
 
         Ok(res)
     }
 
+    // with no live version to compare, we preview the snapshot file's contents: text is syntax highlighted,
+    // according to its extension, if "bat" is available, and binary files are shown as a hexdump
+    fn content_preview_command() -> HttmResult<String> {
+        let text_command = match ["bat", "batcat"]
+            .into_iter()
+            .find(|highlighter| which(highlighter).is_ok())
+        {
+            // Debian and Ubuntu install "bat" as "batcat"
+            Some(highlighter) => {
+                format!("{highlighter} --color=always --style=plain --paging=never \"$snap_file\"")
+            }
+            None => match which("cat") {
+                Ok(_) => "cat \"$snap_file\"".to_string(),
+                Err(_) => {
+                    return Err(HttmError::new(
+                        "'cat' executable could not be found in the user's PATH. 'cat' is necessary for executing a bare preview command.",
+                    )
+                    .into())
+                }
+            },
+        };
+
+        let opt_hex_command = [
+            ("hexyl", "hexyl \"$snap_file\""),
+            ("xxd", "xxd \"$snap_file\""),
+            ("hexdump", "hexdump -C \"$snap_file\""),
+        ]
+        .into_iter()
+        .find(|(executable, _command)| which(executable).is_ok())
+        .map(|(_executable, command)| command);
+
+        // "grep -I" won't match a binary file, and an empty file is not binary
+        let command = match opt_hex_command {
+            Some(hex_command) if which("grep").is_ok() => format!(
+                "if test ! -s \"$snap_file\" || grep -Iq . \"$snap_file\"; then {text_command}; else {hex_command}; fi"
+            ),
+            _ => text_command,
+        };
+
+        Ok(command)
+    }
+
     fn parse_preview_command(
         defined_command: &str,
         opt_live_version: &Option<String>,
     ) -> HttmResult<String> {
         let command = if defined_command == "default" {
             match opt_live_version {
-                Some(live_version) if PathBuf::from(live_version).exists() && which("bowie").is_ok() => {
+                Some(live_version)
+                    if PathBuf::from(live_version).exists() && which("bowie").is_ok() =>
+                {
                     format!("bowie --direct \"$snap_file\" \"{live_version}\"")
-                },
-                _ => match which("cat") {
-                    Ok(_) => "cat \"$snap_file\"".to_string(),
-                    Err(_) => {
-                        return Err(HttmError::new(
-                            "'cat' executable could not be found in the user's PATH. 'cat' is necessary for executing a bare preview command.",
-                        )
-                        .into())
-                    }
-                },
+                }
+                _ => Self::content_preview_command()?,
             }
         } else {
             match defined_command.split_ascii_whitespace().next() {
@@ -117,19 +180,177 @@ impl PreviewSelection {
             }
         };
 
-        let res = match which("cut") {
-            Ok(_) => {
-                format!(
-                    "snap_file=\"`echo {{}} | cut -d'\"' -f2`\"; if test -f \"$snap_file\" || test -d \"$snap_file\" || test -L \"$snap_file\"; then exec 0<&-; {command} 2>&1; fi"
-                )
+        Ok(command)
+    }
+
+    // the preview of a line of the select view: a metadata header, generated here,
+    // followed by the output of the preview command
+    pub fn preview(&self, line: &str) -> String {
+        // the path of a version is the first quoted string of its line, if quoted at all
+        let snap_path = Path::new(line.split('"').nth(1).unwrap_or(line).trim());
+
+        let snap_md = match snap_path.symlink_metadata() {
+            Ok(snap_md) => snap_md,
+            Err(_) => return String::new(),
+        };
+
+        let mut buffer = self.metadata_header(snap_path, &snap_md);
+
+        if let Some(command) = &self.opt_preview_command {
+            buffer += &Self::run_preview_command(command, snap_path);
+        }
+
+        buffer
+    }
+
+    fn metadata_header(&self, snap_path: &Path, snap_md: &Metadata) -> String {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let (snapshot, dataset) = match dataset_collection.snap_mount_containing(snap_path) {
+            Some((mount, snap_mount)) => {
+                // btrfs snapper snapshots are named by the dir containing the "snapshot" dir
+                let opt_snap_name = match snap_mount.file_name() {
+                    Some(file_name) if file_name == BTRFS_SNAPPER_SUFFIX => {
+                        snap_mount.parent().and_then(Path::file_name)
+                    }
+                    opt_file_name => opt_file_name,
+                };
+
+                let snapshot = opt_snap_name
+                    .map(|snap_name| snap_name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "unknown".to_owned());
+
+                let dataset = match dataset_collection.map_of_datasets.get(mount) {
+                    Some(dataset_md) => {
+                        format!("{} ({:?})", dataset_md.source.to_string_lossy(), mount)
+                    }
+                    None => format!("{mount:?}"),
+                };
+
+                (snapshot, dataset)
+            }
+            None => ("unknown".to_owned(), "unknown".to_owned()),
+        };
+
+        let modify_time = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &snap_md.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            DateFormat::Display,
+        );
+
+        format!(
+            "snapshot: {snapshot}\n\
+            dataset:  {dataset}\n\
+            modified: {modify_time}\n\
+            size:     {}\n\
+            mode:     {}\n\
+            owner:    {}:{}\n\
+            vs. live: {}\n\
+            ──────────────────────────────────────────────────────────────────────────────\n",
+            display_human_size(snap_md.len()),
+            Self::mode_string(snap_md),
+            snap_md.uid(),
+            snap_md.gid(),
+            self.live_delta(snap_md)
+        )
+    }
+
+    // like "ls -l", eg. "-rw-r--r-- (644)"
+    fn mode_string(snap_md: &Metadata) -> String {
+        let file_type = if snap_md.is_dir() {
+            'd'
+        } else if snap_md.is_symlink() {
+            'l'
+        } else {
+            '-'
+        };
+
+        let mode = snap_md.mode();
+
+        let permissions: String = [
+            (0o400, 'r'),
+            (0o200, 'w'),
+            (0o100, 'x'),
+            (0o040, 'r'),
+            (0o020, 'w'),
+            (0o010, 'x'),
+            (0o004, 'r'),
+            (0o002, 'w'),
+            (0o001, 'x'),
+        ]
+        .into_iter()
+        .map(|(bit, c)| if mode & bit != 0 { c } else { '-' })
+        .collect();
+
+        format!("{file_type}{permissions} ({:o})", mode & 0o7777)
+    }
+
+    fn live_delta(&self, snap_md: &Metadata) -> String {
+        let live_md = match self
+            .opt_live_version
+            .as_ref()
+            .and_then(|live_version| Path::new(live_version).symlink_metadata().ok())
+        {
+            Some(live_md) => live_md,
+            None => return "no live version exists".to_owned(),
+        };
+
+        let size_delta = match snap_md.len().cmp(&live_md.len()) {
+            Ordering::Equal => "same size".to_owned(),
+            Ordering::Less => format!(
+                "{} smaller",
+                display_human_size(live_md.len() - snap_md.len())
+            ),
+            Ordering::Greater => format!(
+                "{} larger",
+                display_human_size(snap_md.len() - live_md.len())
+            ),
+        };
+
+        let time_delta = match (snap_md.modified(), live_md.modified()) {
+            (Ok(snap_time), Ok(live_time)) => match live_time.duration_since(snap_time) {
+                Ok(duration) if duration.is_zero() => "same modify time".to_owned(),
+                Ok(duration) => format!("modified {} before", Self::human_duration(duration)),
+                Err(err) => format!("modified {} after", Self::human_duration(err.duration())),
+            },
+            _ => "unknown modify time".to_owned(),
+        };
+
+        format!("{size_delta}, {time_delta}")
+    }
+
+    // only the two largest units, like "3d 4h", are precise enough for a preview
+    fn human_duration(duration: Duration) -> String {
+        let secs = duration.as_secs();
+
+        match (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60) {
+            (0, 0, 0) => format!("{secs}s"),
+            (0, 0, mins) => format!("{mins}m {}s", secs % 60),
+            (0, hours, mins) => format!("{hours}h {mins}m"),
+            (days, hours, _mins) => format!("{days}d {hours}h"),
+        }
+    }
+
+    fn run_preview_command(command: &str, snap_path: &Path) -> String {
+        // close stdin, so a command like "cat" can't wait upon it
+        let script = format!("exec 0<&-; {command} 2>&1");
+
+        let mut process = match &GLOBAL_CONFIG.opt_preview_sandbox {
+            Some(PreviewSandbox::Bwrap(bwrap)) => {
+                let mut process = ExecProcess::new(bwrap);
+                process.args(BWRAP_ARGS).arg("sh").arg("-c").arg(script);
+                process
             }
-            Err(_) => {
-                return Err(
-                    HttmError::new("'cut' executable could not be found in the user's PATH. 'cut' is necessary for executing a preview command.").into(),
-                )
+            None => {
+                let mut process = ExecProcess::new("sh");
+                process.arg("-c").arg(script);
+                process
             }
         };
 
-        Ok(res)
+        match process.env("snap_file", snap_path).output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+            Err(err) => format!("httm could not execute the preview command: {err}"),
+        }
     }
 }
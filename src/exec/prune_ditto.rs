@@ -0,0 +1,235 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+
+use crate::data::paths::PathData;
+use crate::exec::interactive::{select_restore_view, ViewMode};
+use crate::exec::pipeline::{Output, Pipeline};
+use crate::library::results::{HttmError, HttmResult, DRY_RUN_CHANGED_EXIT_CODE};
+use crate::library::zfs::{ZfsCommand, ZfsPermission};
+use crate::lookup::versions::{ProximateDatasetAndOptAlts, VersionsMap};
+use crate::parse::aliases::FilesystemType;
+use crate::GLOBAL_CONFIG;
+
+// a snapshot which may be destroyed, and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PruneTarget {
+    // "pool/dataset@snapname"
+    Zfs(String),
+    // the snapshot subvolume
+    Btrfs(PathBuf),
+}
+
+impl std::fmt::Display for PruneTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PruneTarget::Zfs(snap_name) => write!(f, "{snap_name}"),
+            PruneTarget::Btrfs(subvolume) => write!(f, "{}", subvolume.display()),
+        }
+    }
+}
+
+pub struct PruneDitto;
+
+impl PruneDitto {
+    pub fn exec() -> HttmResult<()> {
+        let targets = Self::targets()?;
+
+        if targets.is_empty() {
+            eprintln!("NOTICE: httm found no snapshot in which each version of the input file/s is identical to the live version.  No snapshots were destroyed.");
+            return Ok(());
+        }
+
+        let file_names_string: String = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| format!("{:?}\n", pathdata.path_buf))
+            .collect();
+
+        let targets_string: String = targets.iter().map(|target| format!("{target}\n")).collect();
+
+        // like diff, exits with a status which reflects whether anything would change
+        if GLOBAL_CONFIG.opt_dry_run {
+            let output_buf = format!(
+                "Dry run: httm would destroy the following snapshot/s, in which each version of the input file/s is identical to the live version:\n\n{targets_string}"
+            );
+
+            return Pipeline::output(Output::new(output_buf, DRY_RUN_CHANGED_EXIT_CODE));
+        }
+
+        let preview_buffer = format!(
+            "In the following snapshot/s, each version of the following file/s is identical to the live version:\n\n{file_names_string}\n\
+            httm will destroy the following snapshot/s:\n\n{targets_string}\n\
+            Destroying a snapshot destroys everything the snapshot contains, not just the versions of these files.\n\
+            Before httm destroys these snapshot/s, it would like your consent. Continue? (YES/NO)\n\
+            ─────────────────────────────────────────────────────────────────────────────\n\
+            YES\n\
+            NO"
+        );
+
+        // loop until user consents or doesn't
+        loop {
+            let user_consent = select_restore_view(&preview_buffer, &ViewMode::Purge, false)?[0]
+                .to_ascii_uppercase();
+
+            match user_consent.as_ref() {
+                "YES" | "Y" => {
+                    targets.iter().try_for_each(Self::destroy)?;
+
+                    eprintln!("httm destroyed the following snapshot/s:\n\n{targets_string}\nPrune completed successfully.");
+                    return Ok(());
+                }
+                "NO" | "N" => {
+                    eprintln!("User declined prune.  No snapshots were destroyed.");
+                    return Ok(());
+                }
+                // if not yes or no, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+    }
+
+    // a snapshot is only a target when every input file, whose dataset the snapshot is of, has a version
+    // upon the snapshot, and each such version is identical to the live version, see OMIT_DITTO
+    fn targets() -> HttmResult<Vec<PruneTarget>> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        // key: snap mount, val: the target, and whether every version upon the snapshot is a ditto
+        let mut snapshots: BTreeMap<&PathBuf, (Option<PruneTarget>, bool)> = BTreeMap::new();
+
+        for pathdata in GLOBAL_CONFIG.paths.iter() {
+            if pathdata.metadata.is_none() {
+                let msg = format!(
+                    "httm can only compare snapshot versions to a live version which exists, and {:?} does not.",
+                    pathdata.path_buf
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+
+            let prox_opt_alts = ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, pathdata)?;
+
+            for bundle in prox_opt_alts.into_search_bundles() {
+                let opt_dataset_info = dataset_collection
+                    .map_of_datasets
+                    .get(bundle.dataset_of_interest);
+
+                for snap_mount in bundle.snap_mounts {
+                    let is_ditto = Self::is_ditto(pathdata, &snap_mount.join(bundle.relative_path));
+
+                    let entry = snapshots.entry(snap_mount).or_insert_with(|| {
+                        let opt_target = opt_dataset_info.and_then(|dataset_info| {
+                            Self::target(snap_mount, &dataset_info.fs_type, &dataset_info.source)
+                        });
+
+                        (opt_target, true)
+                    });
+
+                    entry.1 &= is_ditto;
+                }
+            }
+        }
+
+        let targets = snapshots
+            .into_iter()
+            .filter(|(_snap_mount, (_opt_target, is_all_ditto))| *is_all_ditto)
+            .filter_map(|(snap_mount, (opt_target, _is_all_ditto))| {
+                if opt_target.is_none() {
+                    eprintln!("WARNING: httm can only destroy ZFS snapshots and btrfs snapshot subvolumes, and so will skip {snap_mount:?}.");
+                }
+
+                opt_target
+            })
+            .collect();
+
+        Ok(targets)
+    }
+
+    // a snapshot which does not contain the file at all is of interest, as it differs from the live file
+    fn is_ditto(live_pathdata: &PathData, snap_path: &Path) -> bool {
+        match snap_path.symlink_metadata() {
+            Ok(md) => VersionsMap::is_ditto(
+                live_pathdata,
+                &PathData::new(snap_path, Some(md)),
+                &GLOBAL_CONFIG.ditto_of_type,
+            ),
+            Err(_) => false,
+        }
+    }
+
+    fn target(snap_mount: &Path, fs_type: &FilesystemType, source: &Path) -> Option<PruneTarget> {
+        match fs_type {
+            FilesystemType::Zfs => snap_mount.file_name().map(|snap_name| {
+                PruneTarget::Zfs(format!(
+                    "{}@{}",
+                    source.to_string_lossy(),
+                    snap_name.to_string_lossy()
+                ))
+            }),
+            FilesystemType::Btrfs => Some(PruneTarget::Btrfs(snap_mount.to_path_buf())),
+            FilesystemType::Nilfs2 | FilesystemType::SnapshotDir(_) => None,
+        }
+    }
+
+    fn destroy(target: &PruneTarget) -> HttmResult<()> {
+        let (command_name, args): (&str, Vec<String>) = match target {
+            PruneTarget::Zfs(snap_name) => ("zfs", vec!["destroy".to_owned(), snap_name.clone()]),
+            PruneTarget::Btrfs(subvolume) => (
+                "btrfs",
+                vec![
+                    "subvolume".to_owned(),
+                    "delete".to_owned(),
+                    subvolume.to_string_lossy().into_owned(),
+                ],
+            ),
+        };
+
+        // a btrfs snapshot subvolume may only be deleted by root, or by its owner, with user_subvol_rm_allowed
+        let process_output = match target {
+            PruneTarget::Zfs(_) => ZfsCommand::new()?.exec(ZfsPermission::Destroy, &args)?,
+            PruneTarget::Btrfs(_) => {
+                let command = which::which(command_name).map_err(|_err| {
+                    let msg = format!("'{command_name}' command not found. Make sure the command '{command_name}' is in your path.");
+                    HttmError::new(&msg)
+                })?;
+
+                ExecProcess::new(command).args(&args).output()?
+            }
+        };
+
+        // btrfs reports its progress upon stdout, and any error upon stderr, along with a non-zero status
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+            let msg = if stderr_string
+                .to_ascii_lowercase()
+                .contains("permission denied")
+                || stderr_string.contains("Operation not permitted")
+            {
+                "httm must have root privileges, or 'zfs allow' privileges, to destroy a snapshot filesystem, see also ESCALATE".to_owned()
+            } else {
+                format!("httm was unable to destroy {target}. The '{command_name}' command issued the following error: {stderr_string}")
+            };
+
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+}
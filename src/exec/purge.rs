@@ -15,11 +15,10 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::process::Command as ExecProcess;
-
 use crate::config::generate::ListSnapsFilters;
 use crate::exec::interactive::{select_restore_view, ViewMode};
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::zfs::{ZfsCommand, ZfsPermission};
 use crate::lookup::snap_names::SnapNameMap;
 use crate::lookup::versions::VersionsMap;
 
@@ -97,23 +96,21 @@ impl PurgeSnaps {
             }
         }
 
-        std::process::exit(0)
+        Ok(())
     }
 
     fn purge_snaps(snap_name_map: &SnapNameMap) -> HttmResult<()> {
-        let zfs_command = which::which("zfs").map_err(|_err| {
-            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
-        })?;
+        let zfs_command = ZfsCommand::new()?;
         snap_name_map.values().flatten().try_for_each( |snapshot_name| {
             let process_args = vec!["destroy".to_owned(), snapshot_name.clone()];
 
-            let process_output = ExecProcess::new(&zfs_command).args(&process_args).output()?;
+            let process_output = zfs_command.exec(ZfsPermission::Destroy, &process_args)?;
             let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
             // stderr_string is a string not an error, so here we build an err or output
             if !stderr_string.is_empty() {
                 let msg = if stderr_string.contains("cannot destroy snapshots: permission denied") {
-                    "httm must have root privileges to destroy a snapshot filesystem".to_owned()
+                    "httm must have root privileges, or 'zfs allow' privileges, to destroy a snapshot filesystem, see also ESCALATE".to_owned()
                 } else {
                     "httm was unable to destroy snapshots. The 'zfs' command issued the following error: ".to_owned() + stderr_string
                 };
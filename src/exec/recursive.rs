@@ -16,6 +16,7 @@
 // that was distributed with this source code.
 
 use std::os::unix::fs::MetadataExt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::{fs::read_dir, path::Path, sync::Arc};
 
 use once_cell::sync::Lazy;
@@ -24,12 +25,14 @@ use skim::prelude::*;
 
 use crate::config::generate::{DeletedMode, ExecMode};
 use crate::data::paths::{BasicDirEntryInfo, PathData};
-use crate::data::selection::SelectionCandidate;
+use crate::data::selection::{CandidateReceiver, CandidateSender, SelectionCandidate};
 use crate::display_versions::wrapper::VersionsDisplayWrapper;
 use crate::exec::deleted::SpawnDeletedThread;
+use crate::library::exclusions::Exclusions;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::is_channel_closed;
 use crate::library::utility::{print_output_buf, HttmIsDir, Never};
+use crate::parse::aliases::FilesystemType;
 use crate::parse::mounts::MaxLen;
 use crate::VersionsMap;
 use crate::GLOBAL_CONFIG;
@@ -46,6 +49,102 @@ static OPT_REQUESTED_DIR_DEV: Lazy<u64> = Lazy::new(|| {
         .dev()
 });
 
+// count of directories we could not enter during recursion, usually because
+// we lack permissions to view (EACCES), or because of a symlink loop (ELOOP)
+static SKIPPED_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct SkippedDirs;
+
+impl SkippedDirs {
+    fn increment(path: &Path, error: &std::io::Error) {
+        let count = SKIPPED_DIRS.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if GLOBAL_CONFIG.opt_debug {
+            eprintln!("DEBUG: Skipping directory {path:?}: {error}");
+        }
+
+        if let ExecMode::NonInteractiveRecursive(progress_bar) = &GLOBAL_CONFIG.exec_mode {
+            progress_bar.set_message(format!("{count} directories skipped"));
+        }
+    }
+
+    pub fn count() -> usize {
+        SKIPPED_DIRS.load(Ordering::Relaxed)
+    }
+
+    // a running count for the browse view, as directories may be skipped while the user browses
+    pub fn status_line() -> Option<String> {
+        match Self::count() {
+            0 => None,
+            count => Some(format!(
+                "SKIPPED: {count} directories which could not be read, so far (see \"--debug\")"
+            )),
+        }
+    }
+
+    pub fn print_summary() {
+        let count = Self::count();
+
+        if count != 0 {
+            eprintln!(
+                "NOTICE: httm skipped {count} directories which could not be read during this search, \
+            likely because of insufficient permissions.  Perhaps try again with elevated privileges, \
+            or use \"--debug\" to view which directories were skipped."
+            );
+        }
+    }
+}
+
+// count of entries sent or printed, so that we may stop once we reach the user's max results
+static RESULTS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub struct SearchLimits;
+
+impl SearchLimits {
+    // whether we may enter a directory, and show its entries, per the user's max depth,
+    // entries of the requested dir are at depth 1, so we may always enter the requested dir itself
+    pub fn is_within_depth(dir: &Path) -> bool {
+        match (
+            GLOBAL_CONFIG.opt_max_depth,
+            GLOBAL_CONFIG.opt_requested_dir.as_ref(),
+        ) {
+            (Some(max_depth), Some(requested_dir)) => dir
+                .strip_prefix(&requested_dir.path_buf)
+                .map(|relative| relative.components().count() < max_depth)
+                .unwrap_or(true),
+            _ => true,
+        }
+    }
+
+    pub fn is_max_results_reached() -> bool {
+        match GLOBAL_CONFIG.opt_max_results {
+            Some(max_results) => RESULTS_COUNT.load(Ordering::Relaxed) >= max_results,
+            None => false,
+        }
+    }
+
+    // claims as many of the requested entries as remain before the max, and returns how many were claimed
+    fn claim_results(requested: usize) -> usize {
+        match GLOBAL_CONFIG.opt_max_results {
+            Some(max_results) => {
+                let prior = RESULTS_COUNT.fetch_add(requested, Ordering::Relaxed);
+                max_results.saturating_sub(prior).min(requested)
+            }
+            None => requested,
+        }
+    }
+
+    pub fn print_summary() {
+        if let Some(max_results) = GLOBAL_CONFIG.opt_max_results {
+            if Self::is_max_results_reached() {
+                eprintln!(
+                    "NOTICE: httm stopped searching after {max_results} results, per the MAX_RESULTS value given."
+                );
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum PathProvenance {
     FromLiveDataset,
@@ -55,7 +154,11 @@ pub enum PathProvenance {
 pub struct RecursiveSearch;
 
 impl RecursiveSearch {
-    pub fn exec(requested_dir: &Path, skim_tx: SkimItemSender, hangup_rx: Receiver<Never>) {
+    pub fn exec(
+        requested_dir: &Path,
+        skim_tx: CandidateSender,
+        hangup_rx: Receiver<Never>,
+    ) -> HttmResult<()> {
         if GLOBAL_CONFIG.opt_deleted_mode.is_some() {
             // thread pool allows deleted to have its own scope, which means
             // all threads must complete before the scope exits.  this is important
@@ -75,17 +178,13 @@ impl RecursiveSearch {
 
     fn run_enumerate_loop(
         requested_dir: &Path,
-        skim_tx: SkimItemSender,
+        skim_tx: CandidateSender,
         hangup_rx: Receiver<Never>,
         opt_deleted_scope: Option<&Scope>,
-    ) {
+    ) -> HttmResult<()> {
         // this runs the main loop for live file searches, see the referenced struct below
-        // we are in our own detached system thread, so print error and exit if error trickles up
+        // an interactive search runs in its own detached thread, so any error is only returned once it is joined
         RecursiveMainLoop::exec(requested_dir, opt_deleted_scope, &skim_tx, &hangup_rx)
-            .unwrap_or_else(|error| {
-                eprintln!("Error: {error}");
-                std::process::exit(1)
-            });
     }
 }
 
@@ -96,7 +195,7 @@ impl RecursiveMainLoop {
     fn exec(
         requested_dir: &Path,
         opt_deleted_scope: Option<&Scope>,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
         hangup_rx: &Receiver<Never>,
     ) -> HttmResult<()> {
         // runs once for non-recursive but also "primes the pump"
@@ -112,16 +211,24 @@ impl RecursiveMainLoop {
                 // check -- should deleted threads keep working?
                 // exit/error on disconnected channel, which closes
                 // at end of browse scope
-                if is_channel_closed(hangup_rx) {
+                if is_channel_closed(hangup_rx) || SearchLimits::is_max_results_reached() {
                     break;
                 }
 
+                if !SearchLimits::is_within_depth(&item.path) {
+                    continue;
+                }
+
                 // no errors will be propagated in recursive mode
-                // far too likely to run into a dir we don't have permissions to view
-                if let Ok(items) =
-                    Self::enter_directory(&item.path, opt_deleted_scope, skim_tx, hangup_rx)
-                {
-                    queue.extend(items)
+                // far too likely to run into a dir we don't have permissions to view,
+                // so we count such dirs, skip them, and continue
+                match Self::enter_directory(&item.path, opt_deleted_scope, skim_tx, hangup_rx) {
+                    Ok(items) => queue.extend(items),
+                    Err(error) => {
+                        if let Some(io_error) = error.downcast_ref::<std::io::Error>() {
+                            SkippedDirs::increment(&item.path, io_error)
+                        }
+                    }
                 }
             }
         }
@@ -132,7 +239,7 @@ impl RecursiveMainLoop {
     fn enter_directory(
         requested_dir: &Path,
         opt_deleted_scope: Option<&Scope>,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
         hangup_rx: &Receiver<Never>,
     ) -> HttmResult<Vec<BasicDirEntryInfo>> {
         // combined entries will be sent or printed, but we need the vec_dirs to recurse
@@ -147,7 +254,11 @@ impl RecursiveMainLoop {
             skim_tx,
         )?;
 
-        if let Some(deleted_scope) = opt_deleted_scope {
+        // ghosts are sent immediately after the live entries of the same directory, so we don't spawn,
+        // and, as with a deleted thread, errors are not propagated
+        if GLOBAL_CONFIG.opt_ghosts {
+            let _ = SpawnDeletedThread::enter_directory(requested_dir, skim_tx, hangup_rx);
+        } else if let Some(deleted_scope) = opt_deleted_scope {
             SpawnDeletedThread::exec(requested_dir, deleted_scope, skim_tx, hangup_rx);
         }
 
@@ -163,7 +274,7 @@ impl SharedRecursive {
         vec_dirs: &[BasicDirEntryInfo],
         is_phantom: PathProvenance,
         requested_dir: &Path,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
     ) -> HttmResult<()> {
         let mut combined = vec_files;
         combined.extend_from_slice(vec_dirs);
@@ -173,7 +284,8 @@ impl SharedRecursive {
                 // live - not phantom
                 match GLOBAL_CONFIG.opt_deleted_mode {
                     Some(DeletedMode::Only) => return Ok(()),
-                    Some(DeletedMode::DepthOfOne | DeletedMode::All) | None => {
+                    Some(DeletedMode::DepthOfOne | DeletedMode::All | DeletedMode::Depth)
+                    | None => {
                         // never show live files is display recursive/deleted only file mode
                         if matches!(
                             GLOBAL_CONFIG.exec_mode,
@@ -214,6 +326,15 @@ impl SharedRecursive {
                     return false;
                 }
 
+                let is_dir = entry
+                    .filetype()
+                    .map(|file_type| file_type.is_dir())
+                    .unwrap_or(false);
+
+                if Exclusions::is_excluded(&entry.path, is_dir) {
+                    return false;
+                }
+
                 if GLOBAL_CONFIG.opt_one_filesystem {
                     if let Some(requested_dir_dev) = Lazy::get(&OPT_REQUESTED_DIR_DEV) {
                         match entry.path.symlink_metadata() {
@@ -254,7 +375,7 @@ impl SharedRecursive {
         entry.httm_is_dir()
     }
 
-    fn is_filter_dir(entry: &BasicDirEntryInfo) -> bool {
+    pub fn is_filter_dir(entry: &BasicDirEntryInfo) -> bool {
         // FYI path is always a relative path, but no need to canonicalize as
         // partial eq for paths is comparison of components iter
         let path = entry.path.as_path();
@@ -266,6 +387,18 @@ impl SharedRecursive {
             return true;
         }
 
+        // is the snapshot dir of a network share?
+        if let Some(dataset_md) = path
+            .parent()
+            .and_then(|parent| GLOBAL_CONFIG.dataset_collection.map_of_datasets.get(parent))
+        {
+            if let FilesystemType::SnapshotDir(snapshot_dir) = dataset_md.fs_type {
+                if path.ends_with(snapshot_dir) {
+                    return true;
+                }
+            }
+        }
+
         // is a common btrfs snapshot dir?
         if let Some(common_snap_dir) = &GLOBAL_CONFIG.dataset_collection.opt_common_snap_dir {
             if path == *common_snap_dir {
@@ -306,16 +439,29 @@ impl SharedRecursive {
     }
 
     fn display_or_transmit(
-        entries: Vec<BasicDirEntryInfo>,
+        mut entries: Vec<BasicDirEntryInfo>,
         is_phantom: PathProvenance,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
     ) -> HttmResult<()> {
+        // entries beyond the user's max results are never shown
+        if !entries.is_empty() {
+            entries.truncate(SearchLimits::claim_results(entries.len()));
+
+            if entries.is_empty() {
+                return Ok(());
+            }
+        }
+
         // send to the interactive view, or print directly, never return back
         match &GLOBAL_CONFIG.exec_mode {
             ExecMode::Interactive(_) => Self::transmit(entries, is_phantom, skim_tx)?,
             ExecMode::NonInteractiveRecursive(progress_bar) => {
+                // deleted "depth" mode also descends, if only into deleted dirs
+                let is_descending = GLOBAL_CONFIG.opt_recursive
+                    || matches!(GLOBAL_CONFIG.opt_deleted_mode, Some(DeletedMode::Depth));
+
                 if entries.is_empty() {
-                    if GLOBAL_CONFIG.opt_recursive {
+                    if is_descending {
                         progress_bar.tick();
                     } else {
                         eprintln!(
@@ -327,7 +473,7 @@ impl SharedRecursive {
                     NonInteractiveRecursiveWrapper::print(entries)?;
 
                     // keeps spinner from squashing last line of output
-                    if GLOBAL_CONFIG.opt_recursive {
+                    if is_descending {
                         eprintln!();
                     }
                 }
@@ -341,7 +487,7 @@ impl SharedRecursive {
     fn transmit(
         entries: Vec<BasicDirEntryInfo>,
         is_phantom: PathProvenance,
-        skim_tx: &SkimItemSender,
+        skim_tx: &CandidateSender,
     ) -> HttmResult<()> {
         // don't want a par_iter here because it will block and wait for all
         // results, instead of printing and recursing into the subsequent dirs
@@ -362,12 +508,19 @@ impl NonInteractiveRecursiveWrapper {
     #[allow(unused_variables)]
     pub fn exec() -> HttmResult<()> {
         // won't be sending anything anywhere, this just allows us to reuse enumerate_directory
-        let (dummy_skim_tx, _): (SkimItemSender, SkimItemReceiver) = unbounded();
+        let (dummy_skim_tx, _): (CandidateSender, CandidateReceiver) = unbounded();
         let (hangup_tx, hangup_rx): (Sender<Never>, Receiver<Never>) = bounded(0);
 
         match &GLOBAL_CONFIG.opt_requested_dir {
             Some(requested_dir) => {
-                RecursiveSearch::exec(&requested_dir.path_buf, dummy_skim_tx, hangup_rx);
+                RecursiveSearch::exec(&requested_dir.path_buf, dummy_skim_tx, hangup_rx)?;
+
+                if let ExecMode::NonInteractiveRecursive(progress_bar) = &GLOBAL_CONFIG.exec_mode {
+                    progress_bar.finish_and_clear();
+                }
+
+                SkippedDirs::print_summary();
+                SearchLimits::print_summary();
             }
             None => {
                 return Err(HttmError::new(
@@ -384,7 +537,8 @@ impl NonInteractiveRecursiveWrapper {
         let pseudo_live_set: Vec<PathData> = entries.into_iter().map(PathData::from).collect();
 
         let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &pseudo_live_set)?;
-        let output_buf = VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string();
+        let output_buf =
+            VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_formatted()?;
 
         print_output_buf(output_buf)
     }
@@ -0,0 +1,265 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::data::paths::PathData;
+use crate::exec::interactive::InteractiveRestore;
+use crate::library::history::{History, HistoryKind};
+use crate::library::open_files::OpenFiles;
+use crate::library::restore_journal::JournalMode;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::print_output_buf;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+// should the requests or responses change, in a way a front end would notice, this number changes
+const ROBOT_PROTOCOL_VERSION: u64 = 1;
+const DEFAULT_PREVIEW_BYTES: u64 = 65_536;
+
+// a session of requests and responses, for GUI wrappers and editor plugins, so that a front end need not scrape
+// the interactive views: each request is a JSON object on its own line of stdin, like
+// {"id": 1, "cmd": "list-versions", "path": "/etc/hosts"}, and each response is a JSON object on its own line of
+// stdout, which echoes the request's "id", and reports "ok" as true, with the result, or as false, with an "error"
+pub struct RobotSession;
+
+impl RobotSession {
+    pub fn exec() -> HttmResult<()> {
+        // the first line lets a front end know we are ready, and which protocol we speak
+        Self::respond(&json!({
+            "ok": true,
+            "protocol": ROBOT_PROTOCOL_VERSION,
+            "version": env!("CARGO_PKG_VERSION"),
+        }))?;
+
+        let stdin = std::io::stdin();
+
+        for line in stdin.lock().lines() {
+            let line = line?;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let request: Value = match serde_json::from_str(&line) {
+                Ok(request) => request,
+                Err(err) => {
+                    Self::respond(&Self::error(
+                        Value::Null,
+                        &format!("Invalid request: {err}"),
+                    ))?;
+                    continue;
+                }
+            };
+
+            let id = request.get("id").cloned().unwrap_or(Value::Null);
+            let cmd = request
+                .get("cmd")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+
+            let res = match cmd {
+                "list-versions" => Self::list_versions(&request),
+                "preview" => Self::preview(&request),
+                "restore" => Self::restore(&request),
+                "quit" => {
+                    Self::respond(&json!({ "id": id, "ok": true }))?;
+                    break;
+                }
+                _ => Err(HttmError::new(&format!(
+                    "Unknown command {cmd:?}.  Possible commands are: \"list-versions\", \"preview\", \"restore\", and \"quit\"."
+                ))
+                .into()),
+            };
+
+            let response = match res {
+                Ok(mut result) => {
+                    result.insert("id".to_owned(), id);
+                    result.insert("ok".to_owned(), Value::Bool(true));
+                    Value::Object(result)
+                }
+                Err(err) => Self::error(id, &err.to_string()),
+            };
+
+            Self::respond(&response)?;
+        }
+
+        Ok(())
+    }
+
+    // every response is a single line, so a front end may read one line per request
+    fn respond(response: &Value) -> HttmResult<()> {
+        print_output_buf(response.to_string() + "\n")
+    }
+
+    fn error(id: Value, msg: &str) -> Value {
+        json!({ "id": id, "ok": false, "error": msg })
+    }
+
+    fn path_field(request: &Value, field: &str) -> HttmResult<PathBuf> {
+        match request.get(field).and_then(Value::as_str) {
+            Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+            _ => {
+                let msg = format!("This request requires a \"{field}\" field, a path.");
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    // {"id": 1, "cmd": "list-versions", "path": "/etc/hosts"} returns the live version, and each unique snapshot version,
+    // oldest first, each with its path and metadata, as with "--json"
+    fn list_versions(request: &Value) -> HttmResult<Map<String, Value>> {
+        let pathdata = PathData::from(Self::path_field(request, "path")?);
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &[pathdata])?;
+
+        let mut result = Map::new();
+
+        if let Some((live_version, snaps)) = versions_map.iter().next() {
+            result.insert("live".to_owned(), serde_json::to_value(live_version)?);
+            result.insert("versions".to_owned(), serde_json::to_value(snaps)?);
+        }
+
+        Ok(result)
+    }
+
+    // {"id": 2, "cmd": "preview", "path": "/.zfs/snapshot/snap_1/etc/hosts", "max_bytes": 4096} returns the beginning
+    // of the content of any version, as text, and whether the content was truncated
+    fn preview(request: &Value) -> HttmResult<Map<String, Value>> {
+        let path = Self::path_field(request, "path")?;
+
+        let max_bytes = request
+            .get("max_bytes")
+            .and_then(Value::as_u64)
+            .unwrap_or(DEFAULT_PREVIEW_BYTES);
+
+        if path.is_dir() {
+            let msg = format!(
+                "{path:?} is a directory, and only the content of a file may be previewed."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let mut content = Vec::new();
+
+        // read one more byte than requested, so we know whether there is more
+        File::open(&path)?
+            .take(max_bytes.saturating_add(1))
+            .read_to_end(&mut content)?;
+
+        let is_truncated = content.len() as u64 > max_bytes;
+        content.truncate(max_bytes as usize);
+
+        let mut result = Map::new();
+        result.insert(
+            "content".to_owned(),
+            Value::String(String::from_utf8_lossy(&content).into_owned()),
+        );
+        result.insert("is_truncated".to_owned(), Value::Bool(is_truncated));
+
+        Ok(result)
+    }
+
+    // {"id": 3, "cmd": "restore", "path": "/.zfs/snapshot/snap_1/etc/hosts", "mode": "copy"} restores a snapshot version,
+    // where the mode is one of "copy", the default, "copy-and-preserve", or "overwrite", a copy is placed beside the
+    // live file, unless a "destination" directory is given, and an overwrite refuses to replace a file held open,
+    // unless "force" is true, a front end is expected to have asked for the user's consent
+    fn restore(request: &Value) -> HttmResult<Map<String, Value>> {
+        let snap_path = Self::path_field(request, "path")?;
+        let snap_pathdata = PathData::from(&snap_path);
+
+        let snap_metadata = match snap_pathdata.metadata {
+            Some(metadata) => metadata,
+            None => {
+                let msg = format!("The snapshot version {snap_path:?} does not exist.");
+                return Err(HttmError::new(&msg).into());
+            }
+        };
+
+        let live_path = InteractiveRestore::live_path(&snap_path)?;
+        let mode = request
+            .get("mode")
+            .and_then(Value::as_str)
+            .unwrap_or("copy");
+        let is_forced = request
+            .get("force")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let (new_file_path_buf, journal_mode, should_preserve) = match mode {
+            "overwrite" => {
+                if !is_forced {
+                    OpenFiles::check(&[live_path.as_path()])?;
+                }
+
+                (live_path.clone(), JournalMode::Overwrite, true)
+            }
+            "copy" | "copy-and-preserve" => {
+                let new_file_dir = match request.get("destination") {
+                    Some(_) => Self::path_field(request, "destination")?,
+                    None => live_path
+                        .parent()
+                        .unwrap_or_else(|| Path::new("/"))
+                        .to_path_buf(),
+                };
+
+                let new_file_path_buf = InteractiveRestore::restored_file_path(
+                    &snap_path,
+                    &snap_metadata.modify_time,
+                    &new_file_dir,
+                )?;
+
+                (
+                    new_file_path_buf,
+                    JournalMode::Copy,
+                    mode == "copy-and-preserve",
+                )
+            }
+            _ => {
+                let msg = format!(
+                    "Unknown restore mode {mode:?}.  Possible modes are: \"copy\", \"copy-and-preserve\", and \"overwrite\"."
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+        };
+
+        InteractiveRestore::restore_quietly(
+            &snap_pathdata,
+            &new_file_path_buf,
+            journal_mode,
+            should_preserve,
+        )?;
+
+        History::record(
+            HistoryKind::Restore,
+            &snap_path.to_string_lossy(),
+            Some(&live_path),
+        );
+
+        let mut result = Map::new();
+        result.insert(
+            "destination".to_owned(),
+            Value::String(new_file_path_buf.to_string_lossy().into_owned()),
+        );
+
+        Ok(result)
+    }
+}
@@ -34,6 +34,7 @@ use which::which;
 use crate::config::generate::RollForwardConfig;
 use crate::data::paths::BasicDirEntryInfo;
 use crate::data::paths::PathData;
+use crate::exec::interactive::{select_restore_view, ViewMode};
 use crate::library::iter_extensions::HttmIter;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::snap_guard::{PrecautionarySnapType, SnapGuard};
@@ -148,8 +149,17 @@ impl RollForward {
     }
 
     pub fn exec(&self) -> HttmResult<()> {
+        if GLOBAL_CONFIG.opt_dry_run {
+            return self.dry_run();
+        }
+
         user_has_effective_root()?;
 
+        if !self.user_consent()? {
+            println!("User declined roll forward.  No files were altered.");
+            return Ok(());
+        }
+
         let snap_guard: SnapGuard =
             SnapGuard::new(&self.dataset_name, PrecautionarySnapType::PreRollForward)?;
 
@@ -169,7 +179,10 @@ impl RollForward {
                     .rollback()
                     .map(|_| println!("Rollback succeeded."))?;
 
-                std::process::exit(1)
+                return Err(HttmError::new(
+                    "httm roll forward failed, and was rolled back to the precautionary pre-execution snapshot.",
+                )
+                .into());
             }
         };
 
@@ -180,6 +193,89 @@ impl RollForward {
         .map(|_res| ())
     }
 
+    fn user_consent(&self) -> HttmResult<bool> {
+        // tell the user what we're up to, and get consent
+        let preview_buffer = format!(
+            "httm will roll forward a dataset to the state of a snapshot:\n\n\
+            \tdataset:  {:?}\n\
+            \tsnapshot: {:?}\n\n\
+            httm will take a precautionary snapshot before and after the roll forward.\n\
+            Before httm alters any files, it would like your consent. Continue? (YES/NO)\n\
+            ──────────────────────────────────────────────────────────────────────────────\n\
+            YES\n\
+            NO",
+            self.proximate_dataset_mount, self.roll_config.full_snap_name
+        );
+
+        // loop until user consents or doesn't
+        loop {
+            let view_mode = ViewMode::RollForward;
+            let user_consent =
+                select_restore_view(&preview_buffer, &view_mode, false)?[0].to_ascii_uppercase();
+
+            match user_consent.as_ref() {
+                "YES" | "Y" => return Ok(true),
+                "NO" | "N" => return Ok(false),
+                // if not yes or no, then noop and continue to the next iter of loop
+                _ => {}
+            }
+        }
+    }
+
+    fn dry_run(&self) -> HttmResult<()> {
+        let group_map = self.diff_events()?;
+
+        eprintln!("Dry run: httm would take the following actions.  Hard links are not shown.");
+        group_map
+            .values()
+            .flat_map(|values| values.iter().max_by_key(|event| event.time))
+            .try_for_each(|event| self.dry_run_action(event))
+    }
+
+    fn dry_run_action(&self, event: &DiffEvent) -> HttmResult<()> {
+        let snap_file_path = self
+            .snap_path(&event.path_buf)
+            .ok_or_else(|| HttmError::new("Could not obtain snap file path for live version."))?;
+
+        let print_overwrite_or_remove = |src: &Path, dst: &Path| {
+            if src.exists() {
+                eprintln!("{}: {:?} -> {:?}", Blue.paint("Restore  "), src, dst);
+            } else if dst.exists() {
+                eprintln!("{}: {:?} -> 🗑️", Red.paint("Remove   "), dst);
+            }
+        };
+
+        match &event.diff_type {
+            DiffType::Removed | DiffType::Modified => {
+                eprintln!(
+                    "{}: {:?} -> {:?}",
+                    Blue.paint("Restore  "),
+                    snap_file_path,
+                    event.path_buf
+                );
+            }
+            DiffType::Created => print_overwrite_or_remove(&snap_file_path, &event.path_buf),
+            DiffType::Renamed(new_file_name) => {
+                let snap_new_file_name = self.snap_path(new_file_name).ok_or_else(|| {
+                    HttmError::new("Could not obtain snap file path for live version.")
+                })?;
+
+                print_overwrite_or_remove(&snap_new_file_name, new_file_name);
+
+                if snap_file_path.exists() {
+                    eprintln!(
+                        "{}: {:?} -> {:?}",
+                        Blue.paint("Restore  "),
+                        snap_file_path,
+                        event.path_buf
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     fn zfs_diff_std_err(opt_stderr: Option<ChildStderr>) -> HttmResult<String> {
         let mut buf = String::new();
 
@@ -193,6 +289,35 @@ impl RollForward {
     fn roll_forward(&self) -> HttmResult<()> {
         let (snap_handle, live_handle) = self.spawn_preserve_links();
 
+        let group_map = self.diff_events()?;
+
+        // need to wait for these to finish before executing any diff_action
+        let snap_map = snap_handle
+            .join()
+            .map_err(|_err| HttmError::new("Thread panicked!"))??;
+
+        let live_map = live_handle
+            .join()
+            .map_err(|_err| HttmError::new("Thread panicked!"))??;
+
+        let preserve_hard_links = PreserveHardLinks::new(&live_map, &snap_map, self.to_owned())?;
+        let exclusions = preserve_hard_links.exec()?;
+
+        // into iter and reverse because we want to go largest first
+        eprintln!("Reversing 'zfs diff' actions.");
+        group_map
+            .par_iter()
+            .filter(|(key, _values)| !exclusions.contains(key.as_path()))
+            .flat_map(|(_key, values)| values.iter().max_by_key(|event| event.time))
+            .try_for_each(|event| match &event.diff_type {
+                DiffType::Renamed(new_file) if exclusions.contains(new_file) => Ok(()),
+                _ => self.diff_action(event),
+            })?;
+
+        self.verify()
+    }
+
+    fn diff_events(&self) -> HttmResult<HashMap<PathBuf, Vec<DiffEvent>>> {
         let mut process_handle = self.zfs_diff_cmd()?;
 
         let opt_stderr = process_handle.stderr.take();
@@ -238,30 +363,7 @@ impl RollForward {
             return Err(HttmError::new(&msg).into());
         }
 
-        // need to wait for these to finish before executing any diff_action
-        let snap_map = snap_handle
-            .join()
-            .map_err(|_err| HttmError::new("Thread panicked!"))??;
-
-        let live_map = live_handle
-            .join()
-            .map_err(|_err| HttmError::new("Thread panicked!"))??;
-
-        let preserve_hard_links = PreserveHardLinks::new(&live_map, &snap_map, self.to_owned())?;
-        let exclusions = preserve_hard_links.exec()?;
-
-        // into iter and reverse because we want to go largest first
-        eprintln!("Reversing 'zfs diff' actions.");
-        group_map
-            .par_iter()
-            .filter(|(key, _values)| !exclusions.contains(key.as_path()))
-            .flat_map(|(_key, values)| values.iter().max_by_key(|event| event.time))
-            .try_for_each(|event| match &event.diff_type {
-                DiffType::Renamed(new_file) if exclusions.contains(new_file) => Ok(()),
-                _ => self.diff_action(event),
-            })?;
-
-        self.verify()
+        Ok(group_map)
     }
 
     fn verify(&self) -> HttmResult<()> {
@@ -0,0 +1,263 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::config::generate::{Config, MountDisplay};
+use crate::data::filesystem_info::FilesystemInfo;
+use crate::data::paths::PathData;
+use crate::exec::snap_mounts::SnapshotMounts;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, print_output_buf, DateFormat};
+use crate::lookup::file_mounts::MountsForFiles;
+use crate::lookup::versions::VersionsMap;
+use crate::parse::aliases::FilesystemType;
+use crate::parse::mounts::DatasetMetadata;
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+
+const SELF_TEST_SNAPSHOT_SUFFIX: &str = "httmSelfTest";
+// each content is of a different length, so the two versions differ, whatever the resolution of the modify time
+const ORIGINAL_CONTENTS: &str = "httm self-test: the original contents\n";
+const MODIFIED_CONTENTS: &str = "httm self-test: the modified contents, after the snapshot\n";
+
+// a guided end to end check, for new users, of whether httm, as configured, and with the user's permissions,
+// can find the snapshot versions of a file in the directory given
+pub struct SelfTest {
+    dir: PathBuf,
+    scratch_file: PathBuf,
+    is_failed: bool,
+}
+
+impl SelfTest {
+    pub fn exec() -> HttmResult<()> {
+        let dir = match GLOBAL_CONFIG.paths.as_slice() {
+            [pathdata] if pathdata.path_buf.is_dir() => pathdata.path_buf.clone(),
+            _ => {
+                return Err(HttmError::new(
+                    "SELF_TEST requires exactly one input directory, in which to create a scratch file.",
+                )
+                .into())
+            }
+        };
+
+        let timestamp = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Timestamp,
+        );
+
+        let mut self_test = Self {
+            scratch_file: dir.join(format!(".httm_self_test_{timestamp}")),
+            dir,
+            is_failed: false,
+        };
+
+        print_output_buf(format!("httm self-test of {:?}:\n", self_test.dir))?;
+
+        let res = self_test.run();
+
+        // whatever happened, the live scratch file should not remain
+        if self_test.scratch_file.exists() {
+            match std::fs::remove_file(&self_test.scratch_file) {
+                Ok(_) => self_test.report_ok("removed the scratch file")?,
+                Err(err) => self_test.report_fail(&format!(
+                    "could not remove the scratch file {:?}: {err}",
+                    self_test.scratch_file
+                ))?,
+            }
+        }
+
+        res?;
+
+        if self_test.is_failed {
+            return Err(HttmError::new(
+                "httm self-test failed.  Please see the steps marked FAIL above.",
+            )
+            .into());
+        }
+
+        print_output_buf("httm self-test passed.\n".to_owned())
+    }
+
+    fn run(&mut self) -> HttmResult<()> {
+        if let Err(err) = Self::write_scratch_file(&self.scratch_file, ORIGINAL_CONTENTS, true) {
+            return self.report_fail(&format!(
+                "could not create a scratch file in {:?}, is the directory writable? {err}",
+                self.dir
+            ));
+        }
+
+        self.report_ok(&format!("created a scratch file: {:?}", self.scratch_file))?;
+
+        let scratch_pathdata = PathData::from(self.scratch_file.as_path());
+
+        let dataset_mount = match scratch_pathdata
+            .proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)
+        {
+            Ok(dataset_mount) => dataset_mount.to_path_buf(),
+            Err(err) => return self.report_fail(&err.to_string()),
+        };
+
+        let dataset_info = match GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(&dataset_mount)
+        {
+            Some(dataset_info) => dataset_info.clone(),
+            None => {
+                return self.report_fail(&format!(
+                    "httm could not find the dataset mounted at {dataset_mount:?}"
+                ))
+            }
+        };
+
+        let fs_type = match dataset_info.fs_type {
+            FilesystemType::Zfs => "zfs",
+            FilesystemType::Btrfs => "btrfs",
+            FilesystemType::Nilfs2 => "nilfs2",
+            FilesystemType::SnapshotDir(_) => "snapshot-dir",
+        };
+
+        self.report_ok(&format!(
+            "the scratch file resides on the {fs_type} dataset {:?}, mounted at {dataset_mount:?}",
+            dataset_info.source
+        ))?;
+
+        let num_snaps = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_snaps
+            .get(&dataset_mount)
+            .map(Vec::len)
+            .unwrap_or_default();
+
+        self.report_ok(&format!(
+            "httm can see {num_snaps} existing snapshot(s) of this dataset"
+        ))?;
+
+        // only ZFS snapshots may be taken by httm, see SNAPSHOT, so elsewhere, the user must take one themselves
+        if !matches!(dataset_info.fs_type, FilesystemType::Zfs) {
+            return self.report_skip(
+                "httm can only take snapshots of ZFS datasets, so the remaining steps were skipped.  \
+                To finish the test yourself, snapshot this dataset, modify a file, and run httm upon that file",
+            );
+        }
+
+        let snapshot_names = match SnapshotMounts::snapshot_mounts(
+            &MountsForFiles::new(&MountDisplay::Target),
+            SELF_TEST_SNAPSHOT_SUFFIX,
+        ) {
+            Ok(snapshot_names) => snapshot_names,
+            Err(err) => {
+                return self.report_skip(&format!(
+                    "could not take a snapshot, so the remaining steps were skipped.  \
+                    Perhaps try again with superuser or 'zfs allow' privileges? {err}"
+                ))
+            }
+        };
+
+        self.report_ok("took a snapshot of the dataset")?;
+
+        if let Err(err) = Self::write_scratch_file(&self.scratch_file, MODIFIED_CONTENTS, false) {
+            return self.report_fail(&format!("could not modify the scratch file: {err}"));
+        }
+
+        self.report_ok("modified the scratch file")?;
+
+        self.verify_versions(&scratch_pathdata.path_buf, &dataset_mount, &dataset_info)?;
+
+        print_output_buf(format!(
+            "The snapshot taken by the self-test remains.  Should you wish to remove it: zfs destroy {}\n",
+            snapshot_names.join(" ")
+        ))
+    }
+
+    // the snapshot just taken is not among those httm found at startup, so we look again, as WATCH does
+    fn verify_versions(
+        &mut self,
+        scratch_file: &Path,
+        dataset_mount: &Path,
+        dataset_info: &DatasetMetadata,
+    ) -> HttmResult<()> {
+        let snap_mounts = MapOfSnaps::snap_mounts(dataset_mount, dataset_info)?;
+
+        let mut dataset_collection: FilesystemInfo = (*GLOBAL_CONFIG.dataset_collection).clone();
+        dataset_collection.map_of_snaps = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_snaps
+            .with_snap_mounts(dataset_mount, snap_mounts);
+
+        let mut config: Config = (*GLOBAL_CONFIG).clone();
+        config.dataset_collection = Arc::new(dataset_collection);
+
+        let live_version = PathData::from(scratch_file);
+        let versions_map = VersionsMap::new(&config, std::slice::from_ref(&live_version))?;
+
+        let snaps: &[PathData] = versions_map
+            .get(&live_version)
+            .map(|snaps| snaps.as_slice())
+            .unwrap_or_default();
+
+        let is_original_found = snaps.iter().any(|snap| {
+            std::fs::read_to_string(&snap.path_buf).ok().as_deref() == Some(ORIGINAL_CONTENTS)
+        });
+
+        let is_modified_live =
+            std::fs::read_to_string(scratch_file).ok().as_deref() == Some(MODIFIED_CONTENTS);
+
+        if !is_original_found {
+            return self.report_fail(&format!(
+                "httm found {} snapshot version(s) of the scratch file, but none with its original contents",
+                snaps.len()
+            ));
+        }
+
+        if !is_modified_live {
+            return self.report_fail("the live scratch file does not have its modified contents");
+        }
+
+        self.report_ok("httm found both the snapshot version, and the modified live version, of the scratch file")
+    }
+
+    fn write_scratch_file(path: &Path, contents: &str, is_new: bool) -> std::io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create_new(is_new)
+            .open(path)?;
+
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    }
+
+    fn report_ok(&self, msg: &str) -> HttmResult<()> {
+        print_output_buf(format!("[  OK  ] {msg}\n"))
+    }
+
+    fn report_skip(&self, msg: &str) -> HttmResult<()> {
+        print_output_buf(format!("[ SKIP ] {msg}\n"))
+    }
+
+    fn report_fail(&mut self, msg: &str) -> HttmResult<()> {
+        self.is_failed = true;
+        print_output_buf(format!("[ FAIL ] {msg}\n"))
+    }
+}
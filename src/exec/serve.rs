@@ -0,0 +1,303 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::os::unix::ffi::OsStringExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, Receiver};
+use serde_json::json;
+
+use crate::data::paths::PathData;
+use crate::display_versions::wrapper::VersionsDisplayWrapper;
+use crate::library::results::{
+    HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE, NO_VERSIONS_EXIT_CODE,
+};
+use crate::lookup::deleted::DeletedFiles;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+// a client which never finishes its request should not hold a thread forever
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+// connections are handled by a fixed number of workers, and, once as many more are waiting,
+// we accept no more until a worker is free, so many connections cannot exhaust threads or memory
+const NUM_WORKERS: usize = 8;
+// no request we serve needs a longer line, or more headers, than this
+const MAX_LINE_LEN: u64 = 8192;
+const MAX_NUM_HEADERS: usize = 100;
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self {
+            status: 200,
+            reason: "OK",
+            body,
+        }
+    }
+
+    // errors are JSON too, so a client need only ever parse JSON
+    fn error(status: u16, reason: &'static str, msg: &str) -> Self {
+        Self {
+            status,
+            reason,
+            body: json!({ "error": msg }).to_string() + "\n",
+        }
+    }
+
+    // a path with no versions, or upon no snapshot-capable filesystem, is simply not found
+    fn from_error(err: &(dyn std::error::Error + Send + Sync + 'static)) -> Self {
+        match err.downcast_ref::<HttmError>() {
+            Some(httm_error)
+                if httm_error.exit_code == NO_VERSIONS_EXIT_CODE
+                    || httm_error.exit_code == NO_SNAPSHOT_SOURCE_EXIT_CODE =>
+            {
+                Self::error(404, "Not Found", &err.to_string())
+            }
+            _ => Self::error(500, "Internal Server Error", &err.to_string()),
+        }
+    }
+}
+
+// versions, as JSON, over HTTP, for web dashboards and other hosts, see SERVE.  every request is a GET, as nothing
+// may be changed, and, as every response is small, each connection is closed after its response.  a non-loopback
+// address is only served upon SERVE_REMOTE
+pub struct ServeVersions;
+
+impl ServeVersions {
+    pub fn exec(socket_addr: &SocketAddr) -> HttmResult<()> {
+        let listener = TcpListener::bind(socket_addr)?;
+
+        eprintln!(
+            "httm is serving snapshot versions at http://{}",
+            listener.local_addr()?
+        );
+
+        let (stream_tx, stream_rx) = bounded::<TcpStream>(NUM_WORKERS);
+
+        for _ in 0..NUM_WORKERS {
+            let stream_rx = stream_rx.clone();
+            std::thread::spawn(move || Self::work(stream_rx));
+        }
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => stream_tx.send(stream)?,
+                Err(err) => eprintln!("WARN: httm could not accept a connection: {err}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn work(stream_rx: Receiver<TcpStream>) {
+        while let Ok(stream) = stream_rx.recv() {
+            if let Err(err) = Self::handle(stream) {
+                eprintln!("WARN: httm could not respond to a request: {err}");
+            }
+        }
+    }
+
+    fn handle(mut stream: TcpStream) -> HttmResult<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(REQUEST_TIMEOUT_SECS)))?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let response = match Self::read_request_line(&mut reader)? {
+            Some(request_line) => Self::route(&request_line),
+            None => Response::error(
+                431,
+                "Request Header Fields Too Large",
+                "The request line, or its headers, are too long.",
+            ),
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\n\
+            Content-Type: application/json\r\n\
+            Content-Length: {}\r\n\
+            Connection: close\r\n\r\n\
+            {}",
+            response.status,
+            response.reason,
+            response.body.len(),
+            response.body
+        )?;
+
+        stream.flush()?;
+
+        Ok(())
+    }
+
+    // None, should the request line, or any header, be too long, or should there be too many headers
+    fn read_request_line(reader: &mut BufReader<TcpStream>) -> HttmResult<Option<String>> {
+        let request_line = match Self::read_line(reader)? {
+            Some(request_line) => request_line,
+            None => return Ok(None),
+        };
+
+        // we have no use for any header, but must read them all before we respond
+        for _ in 0..MAX_NUM_HEADERS {
+            match Self::read_line(reader)? {
+                Some(header) if header.trim().is_empty() => return Ok(Some(request_line)),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn read_line(reader: &mut BufReader<TcpStream>) -> HttmResult<Option<String>> {
+        let mut line = String::new();
+
+        let len = reader.by_ref().take(MAX_LINE_LEN).read_line(&mut line)?;
+
+        // a line which fills the limit without its end is too long, but a closed connection is simply an empty line
+        if len as u64 == MAX_LINE_LEN && !line.ends_with('\n') {
+            return Ok(None);
+        }
+
+        Ok(Some(line))
+    }
+
+    fn route(request_line: &str) -> Response {
+        let mut split = request_line.split_whitespace();
+
+        let (method, target) = match (split.next(), split.next()) {
+            (Some(method), Some(target)) => (method, target),
+            _ => return Response::error(400, "Bad Request", "The request is malformed."),
+        };
+
+        if method != "GET" {
+            return Response::error(
+                405,
+                "Method Not Allowed",
+                "Only GET requests are supported.",
+            );
+        }
+
+        let (endpoint, query) = target.split_once('?').unwrap_or((target, ""));
+
+        let params = match Self::params(query) {
+            Some(params) => params,
+            None => return Response::error(400, "Bad Request", "The query string is malformed."),
+        };
+
+        let res = match endpoint {
+            "/versions" => Self::paths_param(&params, "path").and_then(Self::versions),
+            "/deleted" => Self::paths_param(&params, "dir").and_then(|dirs| match dirs.as_slice() {
+                [dir] if dir.is_dir() => Self::deleted(dir),
+                _ => Err(HttmError::new("\"/deleted\" requires a single existing directory, \"dir\".").into()),
+            }),
+            _ => {
+                return Response::error(
+                    404,
+                    "Not Found",
+                    "Unknown endpoint.  Possible endpoints are: \"/versions?path=PATH\", and \"/deleted?dir=DIR\".",
+                )
+            }
+        };
+
+        match res {
+            Ok(body) => Response::ok(body),
+            Err(err) => Response::from_error(err.as_ref()),
+        }
+    }
+
+    // each key may be repeated, and each value is percent-encoded, and may not be UTF-8, as a path need not be
+    fn params(query: &str) -> Option<BTreeMap<String, Vec<PathBuf>>> {
+        let mut params: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+            let key = String::from_utf8(Self::percent_decode(key)?).ok()?;
+            let value = PathBuf::from(OsString::from_vec(Self::percent_decode(value)?));
+
+            params.entry(key).or_default().push(value);
+        }
+
+        Some(params)
+    }
+
+    fn percent_decode(encoded: &str) -> Option<Vec<u8>> {
+        let mut decoded = Vec::with_capacity(encoded.len());
+        let mut bytes = encoded.bytes();
+
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'%' => {
+                    let hex = [bytes.next()?, bytes.next()?];
+                    let hex_str = std::str::from_utf8(&hex).ok()?;
+                    decoded.push(u8::from_str_radix(hex_str, 16).ok()?);
+                }
+                b'+' => decoded.push(b' '),
+                _ => decoded.push(byte),
+            }
+        }
+
+        Some(decoded)
+    }
+
+    // there is no working directory to which a relative path might be relative
+    fn paths_param(params: &BTreeMap<String, Vec<PathBuf>>, key: &str) -> HttmResult<Vec<PathBuf>> {
+        match params.get(key) {
+            Some(paths) if paths.iter().all(|path| path.is_absolute()) => Ok(paths.clone()),
+            Some(_) => {
+                Err(HttmError::new(&format!("Each \"{key}\" must be an absolute path.")).into())
+            }
+            None => Err(HttmError::new(&format!("The \"{key}\" parameter is required.")).into()),
+        }
+    }
+
+    fn versions(paths: Vec<PathBuf>) -> HttmResult<String> {
+        let path_set: Vec<PathData> = paths.iter().map(PathData::from).collect();
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &path_set)?;
+
+        VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_json()
+    }
+
+    // the versions of each deleted file, just as "--deleted=only --json" would display them
+    fn deleted(dir: &Path) -> HttmResult<String> {
+        let path_set: Vec<PathData> = DeletedFiles::try_from(dir)?
+            .into_inner()
+            .into_iter()
+            .map(|basic_info| PathData::from(dir.join(basic_info.filename())))
+            .collect();
+
+        // a directory from which nothing has been deleted is not an error
+        let versions_map = if path_set.is_empty() {
+            VersionsMap::from(BTreeMap::new())
+        } else {
+            VersionsMap::new(&GLOBAL_CONFIG, &path_set)?
+        };
+
+        VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_json()
+    }
+}
@@ -0,0 +1,177 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+
+use hashbrown::HashSet;
+
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::results::HttmResult;
+use crate::library::utility::{display_human_size, print_output_buf};
+use crate::lookup::versions::VersionsMap;
+use crate::parse::aliases::FilesystemType;
+use crate::parse::mounts::MountType;
+use crate::GLOBAL_CONFIG;
+
+struct FileSpace<'a> {
+    live_version: &'a PathData,
+    num_unique: usize,
+    unique_bytes: u64,
+}
+
+impl<'a> FileSpace<'a> {
+    // each distinct size and modify time is a version the snapshots alone keep, unless it is also the live version.
+    // as unchanged blocks are shared, this is the most, not the least, which pruning these versions could free
+    fn new(live_version: &'a PathData, snaps: &[PathData]) -> Self {
+        let unique: HashSet<PathMetadata> = snaps
+            .iter()
+            .filter_map(|snap| snap.metadata)
+            .filter(|md| Some(md) != live_version.metadata.as_ref())
+            .collect();
+
+        Self {
+            live_version,
+            num_unique: unique.len(),
+            unique_bytes: unique.iter().map(|md| md.size).sum(),
+        }
+    }
+}
+
+pub struct SizeReport;
+
+impl SizeReport {
+    pub fn exec() -> HttmResult<()> {
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        let mut file_spaces: Vec<FileSpace> = versions_map
+            .iter()
+            .filter(|(live_version, _snaps)| {
+                if live_version.path_buf.is_dir() {
+                    eprintln!(
+                        "WARN: {:?} is a directory, skipping.  To report on a directory, pipe its files from \"find\".",
+                        live_version.path_buf
+                    );
+                    return false;
+                }
+
+                true
+            })
+            .map(|(live_version, snaps)| FileSpace::new(live_version, snaps))
+            .collect();
+
+        // the files most responsible for snapshot bloat first
+        file_spaces.sort_by(|a, b| {
+            b.unique_bytes
+                .cmp(&a.unique_bytes)
+                .then_with(|| a.live_version.cmp(b.live_version))
+        });
+
+        let total_bytes: u64 = file_spaces.iter().map(|space| space.unique_bytes).sum();
+
+        let sizes: Vec<String> = file_spaces
+            .iter()
+            .map(|space| display_human_size(space.unique_bytes))
+            .collect();
+
+        let size_width = sizes.iter().map(String::len).max().unwrap_or_default();
+
+        let mut output_buf: String = file_spaces
+            .iter()
+            .zip(sizes.iter())
+            .map(|(space, size)| {
+                format!(
+                    "{size:>size_width$}  {:>4} versions  {:?}\n",
+                    space.num_unique,
+                    GLOBAL_CONFIG.display_path(&space.live_version.path_buf)
+                )
+            })
+            .collect();
+
+        output_buf += &format!(
+            "\nIn total, snapshots keep at most {} of unique versions of these {} files.\n",
+            display_human_size(total_bytes),
+            file_spaces.len()
+        );
+
+        output_buf += &Self::zfs_used_by_snapshots(&file_spaces);
+
+        print_output_buf(output_buf)
+    }
+
+    // ZFS knows exactly how much space the snapshots of each dataset occupy, which puts the total above in context
+    fn zfs_used_by_snapshots(file_spaces: &[FileSpace]) -> String {
+        let map_of_datasets = &GLOBAL_CONFIG.dataset_collection.map_of_datasets;
+
+        let mut dataset_mounts: Vec<&Path> = file_spaces
+            .iter()
+            .filter_map(|space| space.live_version.proximate_dataset(map_of_datasets).ok())
+            .collect();
+
+        dataset_mounts.sort_unstable();
+        dataset_mounts.dedup();
+
+        let dataset_names: Vec<(&Path, &PathBuf)> = dataset_mounts
+            .into_iter()
+            .filter_map(|mount| {
+                map_of_datasets
+                    .get(mount)
+                    .filter(|dataset_info| {
+                        dataset_info.fs_type == FilesystemType::Zfs
+                            && dataset_info.mount_type == MountType::Local
+                    })
+                    .map(|dataset_info| (mount, &dataset_info.source))
+            })
+            .collect();
+
+        if dataset_names.is_empty() {
+            return String::new();
+        }
+
+        let zfs_command = match which::which("zfs") {
+            Ok(zfs_command) => zfs_command,
+            Err(_) => return String::new(),
+        };
+
+        dataset_names
+            .into_iter()
+            .filter_map(|(mount, dataset_name)| {
+                let process_output = ExecProcess::new(&zfs_command)
+                    .arg("get")
+                    .arg("-H")
+                    .arg("-p")
+                    .arg("-o")
+                    .arg("value")
+                    .arg("usedbysnapshots")
+                    .arg(dataset_name)
+                    .output()
+                    .ok()?;
+
+                let used_by_snapshots = std::str::from_utf8(&process_output.stdout)
+                    .ok()?
+                    .trim()
+                    .parse::<u64>()
+                    .ok()?;
+
+                Some(format!(
+                    "The snapshots of the dataset {dataset_name:?}, mounted at {mount:?}, occupy {} in total.\n",
+                    display_human_size(used_by_snapshots)
+                ))
+            })
+            .collect()
+    }
+}
@@ -17,12 +17,11 @@
 
 use std::{collections::BTreeMap, time::SystemTime};
 
-use std::process::Command as ExecProcess;
-
 use crate::config::generate::{MountDisplay, PrintMode};
 use crate::library::iter_extensions::HttmIter;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{date_string, delimiter, print_output_buf, DateFormat};
+use crate::library::zfs::{ZfsCommand, ZfsPermission};
 use crate::lookup::file_mounts::MountsForFiles;
 use crate::parse::aliases::FilesystemType;
 use crate::GLOBAL_CONFIG;
@@ -33,29 +32,30 @@ impl SnapshotMounts {
     pub fn exec(requested_snapshot_suffix: &str) -> HttmResult<()> {
         let mounts_for_files: MountsForFiles = MountsForFiles::new(&MountDisplay::Target);
 
-        Self::snapshot_mounts(&mounts_for_files, requested_snapshot_suffix)
+        Self::snapshot_mounts(&mounts_for_files, requested_snapshot_suffix)?;
+
+        Ok(())
     }
 
-    fn snapshot_mounts(
+    // returns the names of the snapshots taken, see SelfTest
+    pub fn snapshot_mounts(
         mounts_for_files: &MountsForFiles,
         requested_snapshot_suffix: &str,
-    ) -> HttmResult<()> {
-        let zfs_command = which::which("zfs").map_err(|_err| {
-            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
-        })?;
+    ) -> HttmResult<Vec<String>> {
+        let zfs_command = ZfsCommand::new()?;
         let map_snapshot_names = Self::snapshot_names(mounts_for_files, requested_snapshot_suffix)?;
 
         map_snapshot_names.iter().try_for_each( |(_pool_name, snapshot_names)| {
             let mut process_args = vec!["snapshot".to_owned()];
             process_args.extend_from_slice(snapshot_names);
 
-            let process_output = ExecProcess::new(&zfs_command).args(&process_args).output()?;
+            let process_output = zfs_command.exec(ZfsPermission::Snapshot, &process_args)?;
             let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
             // stderr_string is a string not an error, so here we build an err or output
             if !stderr_string.is_empty() {
                 let msg = if stderr_string.contains("cannot create snapshots : permission denied") {
-                    "httm must have root privileges to snapshot a filesystem".to_owned()
+                    "httm must have root privileges, or 'zfs allow' privileges, to snapshot a filesystem, see also ESCALATE".to_owned()
                 } else {
                     "httm was unable to take snapshots. The 'zfs' command issued the following error: ".to_owned() + stderr_string
                 };
@@ -77,7 +77,7 @@ impl SnapshotMounts {
             }
         })?;
 
-        Ok(())
+        Ok(map_snapshot_names.into_values().flatten().collect())
     }
 
     fn snapshot_names(
@@ -131,14 +131,17 @@ impl SnapshotMounts {
         // why all this garbage with BTreeMaps, etc.? ZFS will not allow one to take snapshots
         // with the same name, at the same time, across pools.  Since we don't really care, we break
         // the snapshots into groups by pool name and then just take snapshots for each pool
-        let map_snapshot_names: BTreeMap<String, Vec<String>> = vec_snapshot_names
+        let pools_and_snapshot_names: Vec<(String, String)> = vec_snapshot_names
             .into_iter()
-            .into_group_map_by(|snapshot_name| {
-                Self::pool_from_snap_name(snapshot_name).unwrap_or_else(|err| {
-                    eprintln!("{}", err);
-                    std::process::exit(1)
-                })
+            .map(|snapshot_name| {
+                Self::pool_from_snap_name(&snapshot_name)
+                    .map(|pool_name| (pool_name, snapshot_name))
             })
+            .collect::<HttmResult<_>>()?;
+
+        let map_snapshot_names: BTreeMap<String, Vec<String>> = pools_and_snapshot_names
+            .into_iter()
+            .into_group_map()
             .iter_mut()
             .map(|(key, group)| {
                 group.sort();
@@ -0,0 +1,235 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::exec::interactive::{select_restore_view, ViewMode};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{
+    delimiter, display_human_size, display_width, pad_to_width, print_output_buf,
+};
+use crate::parse::aliases::FilesystemType;
+use crate::GLOBAL_CONFIG;
+
+// a snapshot of the dataset of a requested path, as listed, independent of the versions of any file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SnapshotEntry {
+    // for ZFS, "pool/dataset@snapname", otherwise, the location of the snapshot
+    name: String,
+    opt_creation: Option<SystemTime>,
+    opt_used: Option<u64>,
+}
+
+pub struct SnapshotSelect;
+
+impl SnapshotSelect {
+    pub fn exec() -> HttmResult<()> {
+        let mut dataset_mounts: Vec<&Path> = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(|pathdata| {
+                pathdata.proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)
+            })
+            .collect::<HttmResult<_>>()?;
+
+        // many paths may share a dataset, and each snapshot need only be listed once
+        dataset_mounts.sort_unstable();
+        dataset_mounts.dedup();
+
+        let entries: Vec<SnapshotEntry> = dataset_mounts
+            .into_iter()
+            .map(Self::snapshot_entries)
+            .collect::<HttmResult<Vec<Vec<SnapshotEntry>>>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        if entries.is_empty() {
+            return Err(HttmError::new(
+                "httm could not find any snapshots of the dataset of any path requested.",
+            )
+            .into());
+        }
+
+        let lines = Self::display_lines(&entries);
+
+        let selection_buffer: String = lines.iter().map(|line| format!("{line}\n")).collect();
+
+        // loop until user selects at least one snapshot
+        let selected: Vec<&SnapshotEntry> = loop {
+            let requested_lines =
+                select_restore_view(&selection_buffer, &ViewMode::SnapshotSelect, true)?;
+
+            let selected: Vec<&SnapshotEntry> = requested_lines
+                .iter()
+                .filter_map(|requested_line| {
+                    lines
+                        .iter()
+                        .position(|line| line.trim_end() == requested_line.trim_end())
+                        .map(|idx| &entries[idx])
+                })
+                .collect();
+
+            if !selected.is_empty() {
+                break selected;
+            }
+        };
+
+        let delimiter = delimiter();
+
+        let output_buf: String = selected
+            .iter()
+            .map(|entry| format!("{}{delimiter}", entry.name))
+            .collect();
+
+        print_output_buf(output_buf)
+    }
+
+    fn snapshot_entries(dataset_mount: &Path) -> HttmResult<Vec<SnapshotEntry>> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        let dataset_info = dataset_collection
+            .map_of_datasets
+            .get(dataset_mount)
+            .ok_or_else(|| {
+                let msg = format!("httm could not find the dataset mounted at {dataset_mount:?}");
+                HttmError::new(&msg)
+            })?;
+
+        // ZFS knows the creation time and the space used by each snapshot, other filesystems are asked less of
+        if let FilesystemType::Zfs = dataset_info.fs_type {
+            return Self::zfs_snapshot_entries(&dataset_info.source.to_string_lossy());
+        }
+
+        let snap_mounts: &[PathBuf] = dataset_collection
+            .map_of_snaps
+            .get(dataset_mount)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        let mut entries: Vec<SnapshotEntry> = snap_mounts
+            .iter()
+            .map(|snap_mount| SnapshotEntry {
+                name: snap_mount.to_string_lossy().into_owned(),
+                opt_creation: snap_mount
+                    .symlink_metadata()
+                    .and_then(|md| md.modified())
+                    .ok(),
+                opt_used: None,
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| entry.opt_creation);
+
+        Ok(entries)
+    }
+
+    fn zfs_snapshot_entries(dataset_name: &str) -> HttmResult<Vec<SnapshotEntry>> {
+        let zfs_command = which::which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        // exact values, in seconds and bytes, are easier to parse, and are sorted, oldest first, by creation
+        let process_output = ExecProcess::new(zfs_command)
+            .arg("list")
+            .arg("-H")
+            .arg("-p")
+            .arg("-t")
+            .arg("snapshot")
+            .arg("-d")
+            .arg("1")
+            .arg("-s")
+            .arg("creation")
+            .arg("-o")
+            .arg("name,creation,used")
+            .arg(dataset_name)
+            .output()?;
+
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        if !stderr_string.is_empty() {
+            let msg =
+                "httm was unable to list snapshots. The 'zfs' command issued the following error: "
+                    .to_owned()
+                    + stderr_string;
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        // each line is tab delimited: name, creation, used
+        let entries = stdout_string
+            .lines()
+            .filter_map(|line| {
+                let mut split = line.split('\t');
+
+                let name = split.next()?;
+                let opt_creation = split
+                    .next()
+                    .and_then(|creation| creation.parse::<u64>().ok())
+                    .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+                let opt_used = split.next().and_then(|used| used.parse::<u64>().ok());
+
+                Some(SnapshotEntry {
+                    name: name.to_owned(),
+                    opt_creation,
+                    opt_used,
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    // one line per snapshot: name, creation time, and used space, each column padded to the widest
+    fn display_lines(entries: &[SnapshotEntry]) -> Vec<String> {
+        let columns: Vec<(String, String, String)> = entries
+            .iter()
+            .map(|entry| {
+                let creation = entry
+                    .opt_creation
+                    .map(|creation| GLOBAL_CONFIG.display_date(&creation))
+                    .unwrap_or_else(|| "-".to_owned());
+                let used = entry
+                    .opt_used
+                    .map(display_human_size)
+                    .unwrap_or_else(|| "-".to_owned());
+
+                (entry.name.clone(), creation, used)
+            })
+            .collect();
+
+        let name_width = columns.iter().map(|(name, _, _)| display_width(name)).max();
+        let creation_width = columns
+            .iter()
+            .map(|(_, creation, _)| display_width(creation))
+            .max();
+
+        columns
+            .into_iter()
+            .map(|(name, creation, used)| {
+                format!(
+                    "{}  {}  {used}",
+                    pad_to_width(&name, name_width.unwrap_or_default()),
+                    pad_to_width(&creation, creation_width.unwrap_or_default())
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::data::paths::{PathData, PathMetadata};
+use crate::exec::digest::DigestOfSnaps;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{display_human_size, print_output_buf};
+use crate::lookup::versions::{ProximateDatasetAndOptAlts, RelativePathAndSnapMounts};
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+
+enum Transition {
+    Created,
+    Modified,
+    Deleted,
+    Reappeared,
+}
+
+impl Transition {
+    // what changed between one snapshot and the next, if anything, where None is a file which is not present
+    fn new(
+        opt_previous: Option<&PathMetadata>,
+        opt_current: Option<&PathMetadata>,
+        has_existed: bool,
+    ) -> Option<Self> {
+        match (opt_previous, opt_current) {
+            (None, Some(_)) if has_existed => Some(Self::Reappeared),
+            (None, Some(_)) => Some(Self::Created),
+            (Some(_), None) => Some(Self::Deleted),
+            (Some(previous), Some(current)) if previous != current => Some(Self::Modified),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "CREATED",
+            Self::Modified => "MODIFIED",
+            Self::Deleted => "DELETED",
+            Self::Reappeared => "REAPPEARED",
+        }
+    }
+}
+
+pub struct TimelineView;
+
+impl TimelineView {
+    pub fn exec() -> HttmResult<()> {
+        let output_buf: String = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(Self::timeline)
+            .collect::<HttmResult<Vec<String>>>()?
+            .join("\n");
+
+        print_output_buf(output_buf)
+    }
+
+    fn timeline(pathdata: &PathData) -> HttmResult<String> {
+        let bundles: Vec<RelativePathAndSnapMounts> =
+            ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, pathdata)?
+                .into_search_bundles()
+                .collect();
+
+        if bundles.is_empty() {
+            let msg = format!(
+                "httm could not find any snapshots which might contain {:?}.",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        // each dataset of interest, see "--alt-replicated", has its own snapshots, and so its own timeline
+        let timelines: Vec<String> = bundles
+            .iter()
+            .map(|bundle| Self::bundle_timeline(pathdata, bundle))
+            .collect::<HttmResult<_>>()?;
+
+        Ok(timelines.join("\n"))
+    }
+
+    fn bundle_timeline(
+        pathdata: &PathData,
+        bundle: &RelativePathAndSnapMounts,
+    ) -> HttmResult<String> {
+        let oldest_first = Self::oldest_first(bundle);
+        let snap_mounts: Vec<&PathBuf> = oldest_first
+            .iter()
+            .map(|(_opt_mtime, snap_mount)| snap_mount)
+            .collect();
+
+        // unlike a search for versions, every snapshot is probed, as a snapshot without the file is itself a transition
+        let probed = bundle.probe(&snap_mounts)?;
+
+        let mut buffer = format!(
+            "Timeline of {:?}, across {} snapshots of the dataset mounted at {:?}:\n",
+            GLOBAL_CONFIG.display_path(&pathdata.path_buf),
+            probed.len(),
+            bundle.dataset_of_interest
+        );
+
+        let mut opt_previous: Option<PathMetadata> = None;
+        let mut has_existed = false;
+        let mut num_transitions = 0usize;
+
+        oldest_first.iter().zip(probed.iter()).for_each(
+            |((opt_mtime, snap_mount), (_snap_mount, opt_version))| {
+                let opt_current = opt_version.as_ref().and_then(|version| version.metadata);
+
+                if let Some(transition) =
+                    Transition::new(opt_previous.as_ref(), opt_current.as_ref(), has_existed)
+                {
+                    let snap_date = opt_mtime
+                        .map(|mtime| GLOBAL_CONFIG.display_date(&mtime))
+                        .unwrap_or_else(|| "unknown date".to_owned());
+
+                    buffer += &Self::transition_line(
+                        &transition,
+                        &format!("{} ({snap_date})", DigestOfSnaps::snap_name(snap_mount)),
+                        opt_previous.as_ref(),
+                        opt_current.as_ref(),
+                    );
+
+                    num_transitions += 1;
+                }
+
+                has_existed |= opt_current.is_some();
+                opt_previous = opt_current;
+            },
+        );
+
+        // and, last of all, whatever has happened since the latest snapshot
+        if let Some(transition) = Transition::new(
+            opt_previous.as_ref(),
+            pathdata.metadata.as_ref(),
+            has_existed,
+        ) {
+            buffer += &Self::transition_line(
+                &transition,
+                "live version",
+                opt_previous.as_ref(),
+                pathdata.metadata.as_ref(),
+            );
+
+            num_transitions += 1;
+        }
+
+        if num_transitions == 0 {
+            buffer += "\t(neither the live version nor any snapshot contains this file)\n";
+        }
+
+        Ok(buffer)
+    }
+
+    fn transition_line(
+        transition: &Transition,
+        location: &str,
+        opt_previous: Option<&PathMetadata>,
+        opt_current: Option<&PathMetadata>,
+    ) -> String {
+        let details = match (transition, opt_previous, opt_current) {
+            (Transition::Modified, Some(previous), Some(current)) => format!(
+                "{}, {}, last modified {}",
+                display_human_size(current.size),
+                Self::size_delta(previous.size, current.size),
+                GLOBAL_CONFIG.display_date(&current.modify_time)
+            ),
+            (_, _, Some(current)) => format!(
+                "{}, last modified {}",
+                display_human_size(current.size),
+                GLOBAL_CONFIG.display_date(&current.modify_time)
+            ),
+            (_, _, None) => "no longer present".to_owned(),
+        };
+
+        format!("\t{:<10} {location}: {details}\n", transition.as_str())
+    }
+
+    fn size_delta(previous_size: u64, current_size: u64) -> String {
+        match current_size.cmp(&previous_size) {
+            std::cmp::Ordering::Greater => {
+                format!("+{}", display_human_size(current_size - previous_size))
+            }
+            std::cmp::Ordering::Less => {
+                format!("-{}", display_human_size(previous_size - current_size))
+            }
+            std::cmp::Ordering::Equal => "same size".to_owned(),
+        }
+    }
+
+    // when the provider knows in which order its snapshots were created, see SnapOrderSource, we use that order,
+    // otherwise, like digest, we order snapshots by the modify time of each snap mount
+    fn oldest_first(bundle: &RelativePathAndSnapMounts) -> Vec<(Option<SystemTime>, PathBuf)> {
+        let snap_mount_mtime = |snap_mount: &PathBuf| {
+            snap_mount
+                .symlink_metadata()
+                .and_then(|md| md.modified())
+                .ok()
+        };
+
+        if let Some(newest_first) = MapOfSnaps::newest_first(
+            &GLOBAL_CONFIG.dataset_collection.map_of_datasets,
+            bundle.dataset_of_interest,
+            bundle.snap_mounts,
+        ) {
+            return newest_first
+                .iter()
+                .rev()
+                .map(|snap_mount| (snap_mount_mtime(snap_mount), snap_mount.clone()))
+                .collect();
+        }
+
+        let mut oldest_first: Vec<(Option<SystemTime>, PathBuf)> = bundle
+            .snap_mounts
+            .iter()
+            .map(|snap_mount| (snap_mount_mtime(snap_mount), snap_mount.clone()))
+            .collect();
+
+        oldest_first.sort_unstable_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        oldest_first
+    }
+}
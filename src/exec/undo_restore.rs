@@ -0,0 +1,174 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::library::restore_journal::{JournalEntry, JournalMode, RestoreJournal};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::copy_recursive;
+
+pub struct UndoRestore;
+
+impl UndoRestore {
+    pub fn exec() -> HttmResult<()> {
+        let entries = RestoreJournal::entries()?;
+
+        let undone_sessions: HashSet<&str> = entries
+            .iter()
+            .filter(|entry| matches!(entry, JournalEntry::Undo { .. }))
+            .map(|entry| entry.session())
+            .collect();
+
+        // the most recent overwrite restore which has not already been undone
+        let last_session = entries
+            .iter()
+            .rev()
+            .find(|entry| {
+                matches!(
+                    entry,
+                    JournalEntry::Restore {
+                        mode: JournalMode::Overwrite,
+                        ..
+                    }
+                ) && !undone_sessions.contains(entry.session())
+            })
+            .map(|entry| entry.session().to_owned())
+            .ok_or_else(|| {
+                HttmError::new(
+                    "httm has no record of an overwrite restore which may be undone. Quitting.",
+                )
+            })?;
+
+        let mut num_reverted = 0usize;
+        let mut num_skipped = 0usize;
+
+        // revert in the reverse order of the restore, should the same path have been restored twice
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.session() == last_session)
+            .for_each(|entry| {
+                if let JournalEntry::Restore {
+                    destination,
+                    opt_pre_hash,
+                    opt_post_hash,
+                    opt_stash,
+                    ..
+                } = entry
+                {
+                    match Self::revert_entry(
+                        destination,
+                        opt_pre_hash,
+                        opt_post_hash,
+                        opt_stash.as_deref(),
+                    ) {
+                        Ok(_) => {
+                            println!("httm reverted: {destination:?}");
+                            num_reverted += 1;
+                        }
+                        Err(err) => {
+                            eprintln!("WARN: httm skipped {destination:?}: {err}");
+                            num_skipped += 1;
+                        }
+                    }
+                }
+            });
+
+        // a session with any skipped entry is not undone, so that, once the user has dealt with whatever
+        // caused the skip, the user may undo the remainder
+        if num_skipped > 0 {
+            let msg = format!(
+                "httm reverted {num_reverted} file/s, but skipped {num_skipped} file/s, of the last overwrite restore.  \
+                Once those are dealt with, undo may be run again to revert the remainder."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        RestoreJournal::record_undo(&last_session)?;
+
+        println!("Undo of the last overwrite restore completed successfully.");
+
+        Ok(())
+    }
+
+    fn revert_entry(
+        destination: &Path,
+        opt_pre_hash: &Option<String>,
+        opt_post_hash: &Option<String>,
+        opt_stash: Option<&Path>,
+    ) -> HttmResult<()> {
+        // a session with skipped entries may be undone again, and an entry already reverted is left as is
+        let is_reverted = match opt_stash {
+            Some(_) => opt_pre_hash.is_some() && &RestoreJournal::hash(destination) == opt_pre_hash,
+            None => destination.symlink_metadata().is_err(),
+        };
+
+        if is_reverted {
+            return Ok(());
+        }
+
+        // never clobber changes made after the restore, and, should we have no record of what was restored,
+        // like a dir restored before dirs were hashed, we can't know whether there were any changes
+        match opt_post_hash {
+            Some(_) if &RestoreJournal::hash(destination) != opt_post_hash => {
+                return Err(
+                    HttmError::new("the file has been modified since it was restored").into(),
+                );
+            }
+            None if destination.symlink_metadata().is_ok() => {
+                return Err(HttmError::new(
+                    "httm has no record of what was restored, so can't know whether it has been modified since",
+                )
+                .into());
+            }
+            _ => {}
+        }
+
+        match opt_stash {
+            Some(stash) => {
+                if stash.symlink_metadata().is_err() {
+                    return Err(HttmError::new("its pre-restore stash no longer exists").into());
+                }
+
+                Self::remove_path(destination)?;
+                copy_recursive(stash, destination, true)?;
+
+                if opt_pre_hash.is_some() && &RestoreJournal::hash(destination) != opt_pre_hash {
+                    return Err(HttmError::new(
+                        "the content put back does not match the content recorded before the restore",
+                    )
+                    .into());
+                }
+            }
+            // nothing existed at the destination before the restore, so undo is a removal
+            None => Self::remove_path(destination)?,
+        }
+
+        Ok(())
+    }
+
+    fn remove_path(path: &Path) -> HttmResult<()> {
+        match path.symlink_metadata() {
+            Ok(md) if md.is_dir() => std::fs::remove_dir_all(path)?,
+            Ok(_) => std::fs::remove_file(path)?,
+            Err(_) => {}
+        }
+
+        Ok(())
+    }
+}
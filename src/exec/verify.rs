@@ -0,0 +1,132 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use rayon::prelude::*;
+
+use crate::data::paths::{HashFromFile, PathData};
+use crate::library::results::{HttmError, HttmResult, VERIFY_MISMATCH_EXIT_CODE};
+use crate::library::utility::print_output_buf;
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+enum Verified {
+    // the same size, modify time, and contents
+    Identical,
+    // modified as any file is, with a new modify time or size
+    Modified,
+    // the same size and modify time, but not the same contents, which a write would never leave behind
+    Mismatched(PathData),
+    Unverified,
+}
+
+pub struct VerifyFiles;
+
+impl VerifyFiles {
+    pub fn exec() -> HttmResult<()> {
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
+
+        let results: Vec<(&PathData, Verified)> = versions_map
+            .par_iter()
+            .map(|(live_version, snaps)| (live_version, Self::verify(live_version, snaps)))
+            .collect();
+
+        let count = |is_kind: fn(&Verified) -> bool| {
+            results
+                .iter()
+                .filter(|(_live_version, verified)| is_kind(verified))
+                .count()
+        };
+
+        let num_identical = count(|verified| matches!(verified, Verified::Identical));
+        let num_modified = count(|verified| matches!(verified, Verified::Modified));
+        let num_unverified = count(|verified| matches!(verified, Verified::Unverified));
+
+        let mismatched: Vec<String> = results
+            .iter()
+            .filter_map(|(live_version, verified)| match verified {
+                Verified::Mismatched(snap) => Some(format!(
+                    "MISMATCH: {:?} differs from its latest snapshot version {:?}, though both have the same size and modify time.\n",
+                    live_version.path_buf, snap.path_buf
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let output_buf = format!(
+            "{}httm verified {} files: {num_identical} identical, {num_modified} modified since the latest snapshot, \
+            {} mismatched, and {num_unverified} which could not be verified.\n",
+            mismatched.concat(),
+            results.len(),
+            mismatched.len(),
+        );
+
+        print_output_buf(output_buf)?;
+
+        if !mismatched.is_empty() {
+            let msg = format!(
+                "httm found {} file(s) whose contents changed without any change to their modify time or size, \
+                which may indicate silent corruption, or a modification which was meant to go unnoticed.",
+                mismatched.len()
+            );
+            return Err(HttmError::with_exit_code(&msg, VERIFY_MISMATCH_EXIT_CODE).into());
+        }
+
+        Ok(())
+    }
+
+    fn verify(live_version: &PathData, snaps: &[PathData]) -> Verified {
+        let live_path = &live_version.path_buf;
+
+        if !live_path.is_file() {
+            eprintln!("WARN: {live_path:?} is not a regular file, skipping.");
+            return Verified::Unverified;
+        }
+
+        // versions are ordered oldest to newest
+        let latest_snap = match snaps.last() {
+            Some(snap) => snap,
+            None => {
+                eprintln!("WARN: {live_path:?} has no snapshot version, skipping.");
+                return Verified::Unverified;
+            }
+        };
+
+        // only a file which ought to be unchanged need be read
+        if live_version.metadata != latest_snap.metadata {
+            return Verified::Modified;
+        }
+
+        let (live_hash, snap_hash) = rayon::join(
+            || HashFromFile::try_from(live_path.as_path()),
+            || HashFromFile::try_from(latest_snap.path_buf.as_path()),
+        );
+
+        match (live_hash, snap_hash) {
+            (Ok(live_hash), Ok(snap_hash)) => {
+                if live_hash.into_inner() == snap_hash.into_inner() {
+                    Verified::Identical
+                } else {
+                    Verified::Mismatched(latest_snap.clone())
+                }
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                eprintln!("WARN: httm could not read {live_path:?}, or its latest snapshot version: {err}");
+                Verified::Unverified
+            }
+        }
+    }
+}
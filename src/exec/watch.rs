@@ -0,0 +1,514 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::data::paths::PathData;
+use crate::exec::digest::{DigestOfSnaps, SnapTree};
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{date_string, print_output_buf, DateFormat};
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WatchEventKind {
+    // a new snapshot contains a new or changed version of a file
+    Version,
+    // a file, of which a snapshot version exists, is gone
+    Deleted,
+}
+
+impl WatchEventKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WatchEventKind::Version => "version",
+            WatchEventKind::Deleted => "deleted",
+        }
+    }
+}
+
+struct WatchEvent {
+    kind: WatchEventKind,
+    date: SystemTime,
+    live_path: PathBuf,
+    snap_path: PathBuf,
+}
+
+impl Serialize for WatchEvent {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("WatchEvent", 4)?;
+
+        // JSON may be read on another machine, so we include the offset
+        let date = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &self.date,
+            DateFormat::Iso8601,
+        );
+
+        state.serialize_field("event", self.kind.as_str())?;
+        state.serialize_field("date", &date)?;
+        state.serialize_field("live_path", GLOBAL_CONFIG.display_path(&self.live_path))?;
+        state.serialize_field("snapshot_path", &self.snap_path)?;
+        state.end()
+    }
+}
+
+impl WatchEvent {
+    // each event is a single line, so a dashboard may read events as they arrive, one JSON object per line
+    fn to_line(&self) -> String {
+        if GLOBAL_CONFIG.opt_json {
+            return serde_json::to_string(self).unwrap_or_default() + "\n";
+        }
+
+        let date = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &self.date,
+            DateFormat::Display,
+        );
+
+        // a new version may be found at its snapshot path, and a deleted file at its live path
+        let path = match self.kind {
+            WatchEventKind::Version => self.snap_path.as_path(),
+            WatchEventKind::Deleted => GLOBAL_CONFIG.display_path(&self.live_path),
+        };
+
+        format!(
+            "{date}  {:<7}  \"{}\"\n",
+            self.kind.as_str(),
+            path.to_string_lossy()
+        )
+    }
+}
+
+// a directory being watched, and what we knew of its snapshots when last we looked
+struct WatchedDir {
+    dir: PathBuf,
+    dataset_mount: PathBuf,
+    relative_path: PathBuf,
+    known_snaps: HashSet<PathBuf>,
+    opt_latest_snap: Option<PathBuf>,
+    latest_tree: SnapTree,
+    // deletions already reported as they happened, so they are not reported again with the next snapshot
+    reported_deleted: HashSet<PathBuf>,
+}
+
+impl WatchedDir {
+    fn new(pathdata: &PathData) -> HttmResult<Self> {
+        if !pathdata.path_buf.is_dir() {
+            let msg = format!(
+                "WATCH requires a directory, and {:?} is not a directory.",
+                pathdata.path_buf
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let dataset_mount = pathdata
+            .proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)?
+            .to_path_buf();
+
+        let relative_path = pathdata
+            .path_buf
+            .strip_prefix(&dataset_mount)?
+            .to_path_buf();
+
+        let mut watched_dir = Self {
+            dir: pathdata.path_buf.clone(),
+            dataset_mount,
+            relative_path,
+            known_snaps: HashSet::new(),
+            opt_latest_snap: None,
+            latest_tree: SnapTree::new(),
+            reported_deleted: HashSet::new(),
+        };
+
+        // only the latest snapshot, as of now, need be walked, everything before is old news
+        let mut oldest_first = watched_dir.new_snaps()?;
+
+        if let Some((_opt_time, latest_snap)) = oldest_first.pop() {
+            watched_dir.latest_tree =
+                DigestOfSnaps::snap_tree(&latest_snap.join(&watched_dir.relative_path));
+            watched_dir.opt_latest_snap = Some(latest_snap);
+        }
+
+        Ok(watched_dir)
+    }
+
+    // snapshots we have not seen before, oldest first, each with the time it was taken, where known, see
+    // MapOfSnaps::oldest_first, and forget those which no longer exist
+    fn new_snaps(&mut self) -> HttmResult<Vec<(Option<SystemTime>, PathBuf)>> {
+        let dataset_info = GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(&self.dataset_mount)
+            .ok_or_else(|| {
+                let msg = format!(
+                    "httm could not find the dataset mounted at {:?}",
+                    self.dataset_mount
+                );
+                HttmError::new(&msg)
+            })?;
+
+        let snap_mounts = MapOfSnaps::snap_mounts(&self.dataset_mount, dataset_info)?;
+
+        // like the digest, we order snapshots in the order in which they were created, so the latest is truly the latest
+        let new_snaps: Vec<(Option<SystemTime>, PathBuf)> = MapOfSnaps::oldest_first(
+            &GLOBAL_CONFIG.dataset_collection.map_of_datasets,
+            &self.dataset_mount,
+            &snap_mounts,
+        )
+        .oldest_first
+        .into_iter()
+        .filter(|(_opt_time, snap_mount)| !self.known_snaps.contains(snap_mount))
+        .collect();
+
+        self.known_snaps = snap_mounts.into_iter().collect();
+
+        Ok(new_snaps)
+    }
+
+    fn poll(&mut self) -> HttmResult<Vec<WatchEvent>> {
+        let mut events = Vec::new();
+
+        for (opt_time, snap_mount) in self.new_snaps()? {
+            let snap_dir = snap_mount.join(&self.relative_path);
+            let tree = DigestOfSnaps::snap_tree(&snap_dir);
+            let date = opt_time.unwrap_or_else(SystemTime::now);
+
+            DigestOfSnaps::compare(&self.latest_tree, &tree)
+                .into_iter()
+                .for_each(|(marker, relpath)| {
+                    let live_path = self.dir.join(&relpath);
+
+                    match marker {
+                        '-' => {
+                            let is_file = self
+                                .latest_tree
+                                .get(&relpath)
+                                .map(|entry| !entry.is_dir)
+                                .unwrap_or(false);
+
+                            if !is_file || self.reported_deleted.remove(&live_path) {
+                                return;
+                            }
+
+                            if let Some(latest_snap) = &self.opt_latest_snap {
+                                events.push(WatchEvent {
+                                    kind: WatchEventKind::Deleted,
+                                    date,
+                                    live_path,
+                                    snap_path: latest_snap.join(&self.relative_path).join(&relpath),
+                                });
+                            }
+                        }
+                        _ => {
+                            let is_file = tree
+                                .get(&relpath)
+                                .map(|entry| !entry.is_dir)
+                                .unwrap_or(false);
+
+                            if is_file {
+                                events.push(WatchEvent {
+                                    kind: WatchEventKind::Version,
+                                    date,
+                                    live_path,
+                                    snap_path: snap_dir.join(&relpath),
+                                });
+                            }
+                        }
+                    }
+                });
+
+            self.latest_tree = tree;
+            self.opt_latest_snap = Some(snap_mount);
+        }
+
+        Ok(events)
+    }
+
+    // a live file deleted is only of interest if a snapshot version of it exists
+    fn live_deleted(&mut self, live_path: &Path) -> Option<WatchEvent> {
+        if live_path.symlink_metadata().is_ok() {
+            return None;
+        }
+
+        let relpath = live_path.strip_prefix(&self.dir).ok()?;
+
+        let is_file = self
+            .latest_tree
+            .get(relpath)
+            .map(|entry| !entry.is_dir)
+            .unwrap_or(false);
+
+        if !is_file {
+            return None;
+        }
+
+        let snap_path = self
+            .opt_latest_snap
+            .as_ref()?
+            .join(&self.relative_path)
+            .join(relpath);
+
+        self.reported_deleted.insert(live_path.to_path_buf());
+
+        Some(WatchEvent {
+            kind: WatchEventKind::Deleted,
+            date: SystemTime::now(),
+            live_path: live_path.to_path_buf(),
+            snap_path,
+        })
+    }
+}
+
+pub struct WatchSnaps;
+
+impl WatchSnaps {
+    pub fn exec(interval_secs: usize) -> HttmResult<()> {
+        let mut watched_dirs: Vec<WatchedDir> = GLOBAL_CONFIG
+            .paths
+            .iter()
+            .map(WatchedDir::new)
+            .collect::<HttmResult<_>>()?;
+
+        let dirs: Vec<&Path> = watched_dirs
+            .iter()
+            .map(|watched| watched.dir.as_path())
+            .collect();
+        let mut live_watch = LiveWatch::new(&dirs);
+
+        eprintln!(
+            "httm is watching for new snapshot versions, and deleted files, every {interval_secs} second(s).  Press Ctrl-C to quit."
+        );
+
+        let interval = Duration::from_secs(interval_secs as u64);
+        let mut next_poll = Instant::now() + interval;
+
+        loop {
+            let timeout = next_poll.saturating_duration_since(Instant::now());
+
+            let mut events: Vec<WatchEvent> = live_watch
+                .wait(timeout)
+                .iter()
+                .filter_map(|live_path| {
+                    watched_dirs
+                        .iter_mut()
+                        .find(|watched| live_path.starts_with(&watched.dir))
+                        .and_then(|watched| watched.live_deleted(live_path))
+                })
+                .collect();
+
+            if Instant::now() >= next_poll {
+                watched_dirs
+                    .iter_mut()
+                    .for_each(|watched| match watched.poll() {
+                        Ok(new_events) => events.extend(new_events),
+                        // a watch runs for a long time, so a passing error should not end it
+                        Err(err) => eprintln!(
+                            "WARNING: httm could not search for new snapshots of {:?}: {err}",
+                            watched.dir
+                        ),
+                    });
+
+                next_poll = Instant::now() + interval;
+            }
+
+            if !events.is_empty() {
+                let output_buf: String = events.iter().map(WatchEvent::to_line).collect();
+                print_output_buf(output_buf)?;
+            }
+        }
+    }
+}
+
+// on Linux, inotify tells us of a deleted file at once, elsewhere, we only learn of it with the next snapshot
+#[cfg(target_os = "linux")]
+struct LiveWatch {
+    opt_fd: Option<i32>,
+    watch_dirs: std::collections::HashMap<i32, PathBuf>,
+}
+
+// inotify watches are a limited resource, and a watch is required for each directory
+#[cfg(target_os = "linux")]
+const MAX_LIVE_WATCHES: usize = 8192;
+
+#[cfg(target_os = "linux")]
+impl LiveWatch {
+    fn new(dirs: &[&Path]) -> Self {
+        let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+
+        let mut live_watch = Self {
+            opt_fd: Some(fd).filter(|fd| *fd >= 0),
+            watch_dirs: std::collections::HashMap::new(),
+        };
+
+        dirs.iter().for_each(|dir| live_watch.add_tree(dir));
+
+        live_watch
+    }
+
+    fn add_tree(&mut self, dir: &Path) {
+        let mut queue: Vec<PathBuf> = vec![dir.to_path_buf()];
+
+        while let Some(item) = queue.pop() {
+            if self.watch_dirs.len() >= MAX_LIVE_WATCHES {
+                return;
+            }
+
+            self.add_watch(&item);
+
+            if let Ok(dir_entries) = std::fs::read_dir(&item) {
+                dir_entries
+                    .flatten()
+                    .filter(|dir_entry| {
+                        dir_entry
+                            .file_type()
+                            .map(|file_type| file_type.is_dir())
+                            .unwrap_or(false)
+                    })
+                    .for_each(|dir_entry| queue.push(dir_entry.path()));
+            }
+        }
+    }
+
+    fn add_watch(&mut self, dir: &Path) {
+        use std::os::unix::ffi::OsStrExt;
+
+        let fd = match self.opt_fd {
+            Some(fd) => fd,
+            None => return,
+        };
+
+        let c_dir = match std::ffi::CString::new(dir.as_os_str().as_bytes()) {
+            Ok(c_dir) => c_dir,
+            Err(_) => return,
+        };
+
+        let mask = libc::IN_DELETE | libc::IN_MOVED_FROM | libc::IN_CREATE | libc::IN_ONLYDIR;
+        let wd = unsafe { libc::inotify_add_watch(fd, c_dir.as_ptr(), mask) };
+
+        if wd >= 0 {
+            self.watch_dirs.insert(wd, dir.to_path_buf());
+        }
+    }
+
+    // wait, at most until the timeout, for files to be deleted, and return the paths of those deleted
+    fn wait(&mut self, timeout: Duration) -> Vec<PathBuf> {
+        let fd = match self.opt_fd {
+            Some(fd) => fd,
+            None => {
+                std::thread::sleep(timeout);
+                return Vec::new();
+            }
+        };
+
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+
+        if unsafe { libc::poll(&mut poll_fd, 1, timeout_ms) } <= 0 {
+            return Vec::new();
+        }
+
+        let mut buffer = [0u8; 64 * 1024];
+        let num_read = unsafe { libc::read(fd, buffer.as_mut_ptr().cast(), buffer.len()) };
+
+        if num_read <= 0 {
+            return Vec::new();
+        }
+
+        let header_len = std::mem::size_of::<libc::inotify_event>();
+        let mut deleted = Vec::new();
+        let mut new_dirs = Vec::new();
+        let mut offset = 0usize;
+
+        // each event is a header followed by a nul padded name
+        while offset + header_len <= num_read as usize {
+            let event: libc::inotify_event = unsafe {
+                std::ptr::read_unaligned(buffer[offset..].as_ptr().cast::<libc::inotify_event>())
+            };
+
+            let name_start = offset + header_len;
+            let name_end = (name_start + event.len as usize).min(num_read as usize);
+
+            offset = name_end;
+
+            let name: Vec<u8> = buffer[name_start..name_end]
+                .iter()
+                .take_while(|byte| **byte != 0)
+                .copied()
+                .collect();
+
+            let path = match self.watch_dirs.get(&event.wd) {
+                Some(dir) if !name.is_empty() => {
+                    use std::os::unix::ffi::OsStrExt;
+                    dir.join(std::ffi::OsStr::from_bytes(&name))
+                }
+                _ => continue,
+            };
+
+            let is_dir = event.mask & libc::IN_ISDIR != 0;
+
+            if is_dir && event.mask & libc::IN_CREATE != 0 {
+                new_dirs.push(path);
+            } else if !is_dir && event.mask & (libc::IN_DELETE | libc::IN_MOVED_FROM) != 0 {
+                deleted.push(path);
+            }
+        }
+
+        new_dirs.iter().for_each(|dir| self.add_tree(dir));
+
+        deleted
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for LiveWatch {
+    fn drop(&mut self) {
+        if let Some(fd) = self.opt_fd {
+            unsafe {
+                libc::close(fd);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct LiveWatch;
+
+#[cfg(not(target_os = "linux"))]
+impl LiveWatch {
+    fn new(_dirs: &[&Path]) -> Self {
+        Self
+    }
+
+    fn wait(&mut self, timeout: Duration) -> Vec<PathBuf> {
+        std::thread::sleep(timeout);
+        Vec::new()
+    }
+}
@@ -0,0 +1,207 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+// the library's stable surface is Versions, Deleted, PathData, and set_config, with the Config it takes, see library::api.
+// every module below is public only so that the httm binary may use it, and so is hidden from the docs, as it may change at any time
+#[doc(hidden)]
+pub mod data {
+    pub mod filesystem_info;
+    pub mod paths;
+    pub mod selection;
+}
+#[doc(hidden)]
+pub mod display_map {
+    pub mod datasets;
+    pub mod format;
+}
+#[doc(hidden)]
+pub mod display_versions {
+    pub mod custom_format;
+    pub mod delimited;
+    pub mod format;
+    pub mod group_identical;
+    pub mod num_versions;
+    pub mod stream;
+    pub mod wrapper;
+}
+#[doc(hidden)]
+pub mod exec {
+    pub mod batch_restore;
+    pub mod bookmarks;
+    pub mod cat;
+    pub mod churn;
+    pub mod complete;
+    pub mod dedup_ratio;
+    pub mod deleted;
+    pub mod diff;
+    pub mod digest;
+    pub mod find;
+    pub mod history;
+    pub mod interactive;
+    pub mod mount_snap;
+    pub mod pipeline;
+    pub mod preview;
+    pub mod prune_ditto;
+    pub mod purge;
+    pub mod recursive;
+    pub mod robot;
+    pub mod roll_forward;
+    pub mod self_test;
+    pub mod serve;
+    pub mod size_report;
+    pub mod snap_mounts;
+    pub mod snap_select;
+    pub mod timeline;
+    pub mod undo_restore;
+    pub mod verify;
+    pub mod watch;
+}
+#[doc(hidden)]
+pub mod config {
+    pub mod config_file;
+    pub mod generate;
+    pub mod install_hot_keys;
+}
+#[doc(hidden)]
+pub mod library {
+    pub mod api;
+    pub mod bookmarks;
+    pub mod copy_progress;
+    pub mod diff_copy;
+    pub mod exclusions;
+    pub mod history;
+    pub mod httm_dirs;
+    pub mod itemize;
+    pub mod iter_extensions;
+    pub mod merge;
+    pub mod metadata_changes;
+    pub mod network_io;
+    pub mod open_files;
+    pub mod restore_journal;
+    pub mod results;
+    pub mod resume_marker;
+    pub mod snap_guard;
+    pub mod transcript;
+    pub mod unified_diff;
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    pub mod uring;
+    pub mod utility;
+    pub mod version_index;
+    pub mod zfs;
+}
+#[doc(hidden)]
+pub mod lookup {
+    pub mod annotations;
+    pub mod deleted;
+    pub mod diffstat;
+    pub mod file_mounts;
+    pub mod object_versions;
+    pub mod remote;
+    pub mod snap_names;
+    pub mod versions;
+}
+#[doc(hidden)]
+pub mod parse {
+    pub mod aliases;
+    pub mod alts;
+    pub mod mounts;
+    pub mod provenance;
+    pub mod replication;
+    pub mod snaps;
+}
+#[doc(hidden)]
+pub mod ui {
+    #[cfg(feature = "native-ui")]
+    pub mod native;
+    pub mod picker;
+    pub mod skim_picker;
+}
+
+use once_cell::sync::{Lazy, OnceCell};
+
+use crate::library::results::{HttmError, HttmResult, ERROR_EXIT_CODE};
+
+pub use crate::config::generate::Config;
+pub use crate::data::paths::PathData;
+pub use crate::library::api::{Deleted, Versions};
+
+pub(crate) use crate::display_versions::wrapper::VersionsDisplayWrapper;
+
+#[doc(hidden)]
+pub use crate::library::utility::print_output_buf;
+#[doc(hidden)]
+pub use crate::lookup::file_mounts::MountsForFiles;
+#[doc(hidden)]
+pub use crate::lookup::snap_names::SnapNameMap;
+#[doc(hidden)]
+pub use crate::lookup::versions::VersionsMap;
+
+pub(crate) const ZFS_HIDDEN_DIRECTORY: &str = ".zfs";
+pub(crate) const ZFS_SNAPSHOT_DIRECTORY: &str = ".zfs/snapshot";
+pub(crate) const BTRFS_SNAPPER_HIDDEN_DIRECTORY: &str = ".snapshots";
+pub(crate) const BTRFS_SNAPPER_SUFFIX: &str = "snapshot";
+// NAS appliances (NetApp, Isilon, etc.) expose snapshots of a share at the share root
+pub(crate) const NETWORK_SNAPSHOT_DIRECTORIES: [&str; 2] = [".snapshot", "~snapshot"];
+pub(crate) const ROOT_DIRECTORY: &str = "/";
+pub(crate) const NILFS2_SNAPSHOT_ID_KEY: &str = "cp=";
+
+// a config set by a library consumer, which, if present, takes the place
+// of the config generated from our program args
+static LIBRARY_CONFIG: OnceCell<Config> = OnceCell::new();
+
+// library consumers may set their own config, but only once, and only
+// before any lookup, as the global config is fixed upon first use
+pub fn set_config(config: Config) -> HttmResult<()> {
+    if Lazy::get(&GLOBAL_CONFIG).is_some() {
+        return Err(HttmError::new("httm config has already been initialized.").into());
+    }
+
+    LIBRARY_CONFIG
+        .set(config)
+        .map_err(|_| HttmError::new("httm config may only be set once.").into())
+}
+
+// library lookups should never parse a consumer's program args, so if no config
+// has been set, use httm's defaults, as if "httm /" had been run
+pub(crate) fn init_library_config() -> HttmResult<()> {
+    if LIBRARY_CONFIG.get().is_some() || Lazy::get(&GLOBAL_CONFIG).is_some() {
+        return Ok(());
+    }
+
+    let config = Config::from_args(["httm", ROOT_DIRECTORY])?;
+
+    // another thread may have beaten us here, and that's fine
+    let _ = LIBRARY_CONFIG.set(config);
+
+    Ok(())
+}
+
+// get our program args and generate a config for use
+// everywhere else
+#[doc(hidden)]
+pub static GLOBAL_CONFIG: Lazy<Config> = Lazy::new(|| {
+    if let Some(config) = LIBRARY_CONFIG.get() {
+        return config.clone();
+    }
+
+    Config::new()
+        .map_err(|error| {
+            eprintln!("Error: {error}");
+            std::process::exit(ERROR_EXIT_CODE)
+        })
+        .unwrap()
+});
@@ -0,0 +1,89 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::Path;
+
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::deleted::DeletedFiles;
+use crate::lookup::versions::VersionsMap;
+use crate::{init_library_config, GLOBAL_CONFIG};
+
+// a stable surface for other Rust tools to query snapshot versions without
+// shelling out to the httm binary, for example:
+//
+// let versions = httm::Versions::new(Path::new("/etc/hosts"))?;
+// let deleted = httm::Deleted::in_dir(Path::new("/etc"))?;
+//
+// lookups use the config set via httm::set_config(), or, if none, httm's defaults
+#[derive(Debug, Clone)]
+pub struct Versions {
+    live: PathData,
+    snaps: Vec<PathData>,
+}
+
+impl Versions {
+    pub fn new(path: &Path) -> HttmResult<Self> {
+        init_library_config()?;
+
+        let pathdata = PathData::from(path);
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &[pathdata])?;
+
+        match versions_map.into_inner().into_iter().next() {
+            Some((live, snaps)) => Ok(Self { live, snaps }),
+            None => Err(HttmError::new(
+                "httm could not find any snapshot versions for the path requested.",
+            )
+            .into()),
+        }
+    }
+
+    pub fn live(&self) -> &PathData {
+        &self.live
+    }
+
+    pub fn snaps(&self) -> &[PathData] {
+        &self.snaps
+    }
+
+    // all versions in the order httm displays them, snapshot versions first,
+    // and the live version last
+    pub fn all(&self) -> Vec<&PathData> {
+        self.snaps
+            .iter()
+            .chain(std::iter::once(&self.live))
+            .collect()
+    }
+}
+
+pub struct Deleted;
+
+impl Deleted {
+    // one snapshot version for each file deleted from the requested directory
+    pub fn in_dir(requested_dir: &Path) -> HttmResult<Vec<PathData>> {
+        init_library_config()?;
+
+        let deleted = DeletedFiles::try_from(requested_dir)?
+            .into_inner()
+            .into_iter()
+            .map(PathData::from)
+            .collect();
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,111 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{create_dir_all, read_to_string, rename, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::library::httm_dirs::HttmDir;
+use crate::library::results::HttmResult;
+use crate::library::utility::make_tmp_path;
+
+const BOOKMARKS_FILE_NAME: &str = "bookmarks";
+
+// bookmarks are paths the user recovers again and again, like a config file, one absolute path per line
+pub struct Bookmarks;
+
+impl Bookmarks {
+    // bookmarks live in the XDG data dir, usually ~/.local/share/httm/bookmarks
+    fn path() -> HttmResult<PathBuf> {
+        HttmDir::Data.file(BOOKMARKS_FILE_NAME)
+    }
+
+    pub fn entries() -> HttmResult<Vec<PathBuf>> {
+        let bookmarks_path = Self::path()?;
+
+        if !bookmarks_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = read_to_string(bookmarks_path)?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect();
+
+        Ok(entries)
+    }
+
+    // returns the number of paths newly added
+    pub fn add(paths: &[&Path]) -> HttmResult<usize> {
+        let mut entries = Self::entries()?;
+        let num_before = entries.len();
+
+        // our format is line delimited, so we can't bookmark a path containing a newline
+        entries.extend(
+            paths
+                .iter()
+                .filter(|path| !path.to_string_lossy().contains('\n'))
+                .map(|path| path.to_path_buf()),
+        );
+        entries.sort();
+        entries.dedup();
+
+        let num_added = entries.len() - num_before;
+
+        Self::write(&entries)?;
+
+        Ok(num_added)
+    }
+
+    // returns the number of paths removed
+    pub fn remove(paths: &[&Path]) -> HttmResult<usize> {
+        let mut entries = Self::entries()?;
+        let num_before = entries.len();
+
+        entries.retain(|entry| !paths.contains(&entry.as_path()));
+
+        let num_removed = num_before - entries.len();
+
+        Self::write(&entries)?;
+
+        Ok(num_removed)
+    }
+
+    fn write(entries: &[PathBuf]) -> HttmResult<()> {
+        let bookmarks_path = Self::path()?;
+
+        if let Some(parent) = bookmarks_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let buffer: String = entries
+            .iter()
+            .map(|entry| entry.to_string_lossy() + "\n")
+            .collect();
+
+        // write to a tmp file and rename, so bookmarks are never left half written
+        let tmp_path = make_tmp_path(&bookmarks_path);
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(buffer.as_bytes())?;
+        tmp_file.sync_data()?;
+
+        rename(tmp_path, bookmarks_path)?;
+
+        Ok(())
+    }
+}
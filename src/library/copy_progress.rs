@@ -0,0 +1,73 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+use indicatif::{ProgressBar, ProgressStyle};
+use once_cell::sync::Lazy;
+
+use crate::GLOBAL_CONFIG;
+
+const PROGRESS_TEMPLATE: &str =
+    "{spinner} [{elapsed_precise}] [{wide_bar}] {bytes}/{total_bytes} ({bytes_per_sec}, ETA {eta})";
+
+// the bar of the restore in progress, if any, as our copy fns are called from far below
+// the restore itself, and a restore which reports no progress should pay nothing for it
+static COPY_PROGRESS: Lazy<Mutex<Option<ProgressBar>>> = Lazy::new(|| Mutex::new(None));
+
+// reports the bytes copied of the total to be copied, while in scope, cleared on drop
+pub struct CopyProgress;
+
+impl CopyProgress {
+    pub fn new(total_bytes: u64) -> Self {
+        // no one would see the bar, or the user asked that we be quiet
+        if GLOBAL_CONFIG.opt_quiet || !std::io::stderr().is_terminal() {
+            return Self;
+        }
+
+        let progress_bar = ProgressBar::new(total_bytes);
+
+        if let Ok(style) = ProgressStyle::with_template(PROGRESS_TEMPLATE) {
+            progress_bar.set_style(style.progress_chars("=> "));
+        }
+
+        if let Ok(mut opt_progress) = COPY_PROGRESS.lock() {
+            *opt_progress = Some(progress_bar);
+        }
+
+        Self
+    }
+
+    pub fn inc(amt: u64) {
+        if let Ok(opt_progress) = COPY_PROGRESS.lock() {
+            if let Some(progress_bar) = opt_progress.as_ref() {
+                progress_bar.inc(amt);
+            }
+        }
+    }
+}
+
+impl Drop for CopyProgress {
+    fn drop(&mut self) {
+        if let Ok(mut opt_progress) = COPY_PROGRESS.lock() {
+            if let Some(progress_bar) = opt_progress.take() {
+                progress_bar.finish_and_clear();
+            }
+        }
+    }
+}
@@ -24,14 +24,244 @@ use std::io::ErrorKind;
 use std::io::Seek;
 use std::io::SeekFrom;
 use std::io::Write;
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::{FileExt, MetadataExt};
 use std::path::Path;
 
 use simd_adler32::Adler32;
 
+use crate::library::copy_progress::CopyProgress;
 use crate::library::results::HttmResult;
 
 const CHUNK_SIZE: usize = 65_536;
 
+// _IOW(0x94, 9, int), not yet available in the version of libc we depend upon
+#[cfg(target_os = "linux")]
+const FICLONE: u32 = 0x4004_9409;
+
+// attempt a copy which shares its blocks with the source, that is, a copy which is instantaneous
+// and space free, on filesystems which support cloning, returns false should the caller fall back
+// to a byte copy
+#[cfg(target_os = "linux")]
+pub fn clone_copy(src: &Path, dst: &Path) -> HttmResult<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let src_file = File::open(src)?;
+    let src_md = src_file.metadata()?;
+    let src_len = src_md.len();
+
+    let dst_existed = dst.exists();
+    let dst_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(dst)?;
+
+    // a reflink replaces the whole of the destination's contents, no truncation needed
+    let res = unsafe { libc::ioctl(dst_file.as_raw_fd(), FICLONE as _, src_file.as_raw_fd()) };
+
+    if res == 0 {
+        dst_file.sync_data()?;
+        CopyProgress::inc(src_len);
+        return Ok(true);
+    }
+
+    // an in-kernel copy may also clone, but, upon a filesystem which can't, it would rewrite every block
+    // of an existing file, so leave existing files to diff_copy, which only writes those blocks which differ,
+    // and it may also fill holes, so leave sparse files to sparse_copy
+    if dst_existed || is_sparse(&src_md) {
+        return Ok(false);
+    }
+
+    let mut remaining = src_len as usize;
+
+    while remaining > 0 {
+        match nix::fcntl::copy_file_range(
+            src_file.as_raw_fd(),
+            None,
+            dst_file.as_raw_fd(),
+            None,
+            remaining,
+        ) {
+            Ok(0) => break,
+            Ok(amt_copied) => remaining -= amt_copied,
+            // diff_copy will write whatever was not copied here, like cross device copies
+            Err(_) => return Ok(false),
+        }
+    }
+
+    if remaining > 0 || dst_file.metadata()?.len() != src_len {
+        return Ok(false);
+    }
+
+    dst_file.sync_data()?;
+    CopyProgress::inc(src_len);
+
+    Ok(true)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn clone_copy(_src: &Path, _dst: &Path) -> HttmResult<bool> {
+    Ok(false)
+}
+
+// fewer blocks allocated than the file's length requires means the file has holes,
+// though a compressed file may also appear so
+#[cfg(target_os = "linux")]
+fn is_sparse(md: &std::fs::Metadata) -> bool {
+    md.blocks() * 512 < md.len()
+}
+
+// copy only the data segments of a sparse file, like a VM image, and leave holes where the source has holes,
+// returns false should the file not be sparse, or should the filesystem not support SEEK_DATA/SEEK_HOLE,
+// so the caller may fall back to a plain copy
+#[cfg(target_os = "linux")]
+pub fn sparse_copy(src: &Path, dst: &Path) -> HttmResult<bool> {
+    use std::os::unix::io::AsRawFd;
+
+    let src_file = File::open(src)?;
+    let src_md = src_file.metadata()?;
+
+    if !is_sparse(&src_md) {
+        return Ok(false);
+    }
+
+    let src_fd = src_file.as_raw_fd();
+    let src_len = src_md.len() as i64;
+
+    // probe for support before we touch the destination
+    if seek_data_or_hole(src_fd, 0, libc::SEEK_DATA).is_err() {
+        return Ok(false);
+    }
+
+    let dst_existed = dst.exists();
+    let dst_file = OpenOptions::new()
+        .write(true)
+        .read(true)
+        .create(true)
+        .truncate(false)
+        .open(dst)?;
+
+    // extending a file's length leaves a hole, not zeroes
+    dst_file.set_len(src_len as u64)?;
+
+    let mut cur_pos = 0i64;
+
+    while cur_pos < src_len {
+        // no further data means the remainder of the file is a hole
+        let data_start = seek_data_or_hole(src_fd, cur_pos, libc::SEEK_DATA)?.unwrap_or(src_len);
+
+        // a new destination already has holes wherever it has not been written
+        if dst_existed && data_start > cur_pos {
+            punch_hole(&dst_file, cur_pos, data_start - cur_pos)?;
+        }
+
+        CopyProgress::inc((data_start - cur_pos) as u64);
+
+        if data_start >= src_len {
+            break;
+        }
+
+        let hole_start = seek_data_or_hole(src_fd, data_start, libc::SEEK_HOLE)?.unwrap_or(src_len);
+
+        copy_range(&src_file, &dst_file, data_start, hole_start, dst_existed)?;
+
+        cur_pos = hole_start;
+    }
+
+    dst_file.sync_data()?;
+
+    Ok(true)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sparse_copy(_src: &Path, _dst: &Path) -> HttmResult<bool> {
+    Ok(false)
+}
+
+// ENXIO means there is no data, or hole, beyond this offset
+#[cfg(target_os = "linux")]
+fn seek_data_or_hole(fd: i32, offset: i64, whence: i32) -> HttmResult<Option<i64>> {
+    let res = unsafe { libc::lseek(fd, offset, whence) };
+
+    if res >= 0 {
+        return Ok(Some(res));
+    }
+
+    let err = std::io::Error::last_os_error();
+
+    match err.raw_os_error() {
+        Some(libc::ENXIO) => Ok(None),
+        _ => Err(err.into()),
+    }
+}
+
+// like diff_copy, only write those chunks which differ from an existing destination
+#[cfg(target_os = "linux")]
+fn copy_range(
+    src_file: &File,
+    dst_file: &File,
+    start: i64,
+    end: i64,
+    dst_existed: bool,
+) -> HttmResult<()> {
+    let mut src_buf = vec![0u8; CHUNK_SIZE];
+    let mut dst_buf = vec![0u8; CHUNK_SIZE];
+
+    let mut cur_pos = start as u64;
+    let end = end as u64;
+
+    while cur_pos < end {
+        let amt = std::cmp::min(CHUNK_SIZE as u64, end - cur_pos) as usize;
+
+        src_file.read_exact_at(&mut src_buf[..amt], cur_pos)?;
+
+        if !dst_existed || {
+            dst_file.read_exact_at(&mut dst_buf[..amt], cur_pos)?;
+            !is_same_bytes(&src_buf[..amt], &dst_buf[..amt])
+        } {
+            dst_file.write_all_at(&src_buf[..amt], cur_pos)?;
+        }
+
+        CopyProgress::inc(amt as u64);
+        cur_pos += amt as u64;
+    }
+
+    Ok(())
+}
+
+// where the source has a hole, but an existing destination may have data, deallocate that data,
+// or, should the filesystem not support punching holes, at least write zeroes
+#[cfg(target_os = "linux")]
+fn punch_hole(dst_file: &File, offset: i64, len: i64) -> HttmResult<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let res = unsafe {
+        libc::fallocate(
+            dst_file.as_raw_fd(),
+            libc::FALLOC_FL_PUNCH_HOLE | libc::FALLOC_FL_KEEP_SIZE,
+            offset,
+            len,
+        )
+    };
+
+    if res == 0 {
+        return Ok(());
+    }
+
+    let zeroes = vec![0u8; CHUNK_SIZE];
+    let mut cur_pos = offset as u64;
+    let end = (offset + len) as u64;
+
+    while cur_pos < end {
+        let amt = std::cmp::min(CHUNK_SIZE as u64, end - cur_pos) as usize;
+        dst_file.write_all_at(&zeroes[..amt], cur_pos)?;
+        cur_pos += amt as u64;
+    }
+
+    Ok(())
+}
+
 pub fn diff_copy(src: &Path, dst: &Path) -> HttmResult<()> {
     // create source file reader
     let src_file = File::open(src)?;
@@ -80,6 +310,7 @@ pub fn diff_copy(src: &Path, dst: &Path) -> HttmResult<()> {
                         }
 
                         cur_pos += src_amt_read as u64;
+                        CopyProgress::inc(src_amt_read as u64);
 
                         dst_amt_read
                     }
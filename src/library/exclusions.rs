@@ -0,0 +1,189 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::library::utility::glob_match;
+use crate::GLOBAL_CONFIG;
+
+const GITIGNORE_FILE_NAME: &str = ".gitignore";
+const GIT_DIRECTORY: &str = ".git";
+
+// each directory's .gitignore is read and parsed only once, however many entries are checked against it
+static GITIGNORE_CACHE: Lazy<Mutex<HashMap<PathBuf, Arc<IgnoreFile>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// the user's exclusions, from the exclude globs and, if requested, from .gitignore files, apply to both
+// the live entries of a recursive search and the deleted entries found on snapshots
+pub struct Exclusions;
+
+impl Exclusions {
+    pub fn is_excluded(path: &Path, is_dir: bool) -> bool {
+        if let Some(exclude_globs) = GLOBAL_CONFIG.opt_exclude_globs.as_ref() {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+
+            if exclude_globs
+                .iter()
+                .any(|glob| glob_match(glob, &file_name))
+            {
+                return true;
+            }
+        }
+
+        GLOBAL_CONFIG.opt_respect_gitignore && Self::is_gitignored(path, is_dir)
+    }
+
+    // like git, the rules of the .gitignore closest to the path are checked last, and the last rule to match wins,
+    // we look no further up than the root of the repository, the first directory which contains a ".git"
+    fn is_gitignored(path: &Path, is_dir: bool) -> bool {
+        if path.file_name().map(|name| name == GIT_DIRECTORY) == Some(true) {
+            return true;
+        }
+
+        let mut ignore_files: Vec<Arc<IgnoreFile>> = Vec::new();
+
+        for ancestor in path.ancestors().skip(1) {
+            let ignore_file = Self::ignore_file(ancestor);
+            let is_repo_root = ignore_file.is_repo_root;
+
+            ignore_files.push(ignore_file);
+
+            if is_repo_root {
+                break;
+            }
+        }
+
+        ignore_files
+            .iter()
+            .rev()
+            .fold(false, |is_ignored, ignore_file| {
+                ignore_file.is_ignored(path, is_dir).unwrap_or(is_ignored)
+            })
+    }
+
+    fn ignore_file(dir: &Path) -> Arc<IgnoreFile> {
+        let mut cache = GITIGNORE_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        cache
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| Arc::new(IgnoreFile::new(dir)))
+            .clone()
+    }
+}
+
+struct IgnoreFile {
+    dir: PathBuf,
+    rules: Vec<IgnoreRule>,
+    is_repo_root: bool,
+}
+
+impl IgnoreFile {
+    fn new(dir: &Path) -> Self {
+        // a missing or unreadable .gitignore simply has no rules
+        let rules = std::fs::read_to_string(dir.join(GITIGNORE_FILE_NAME))
+            .map(|contents| contents.lines().filter_map(IgnoreRule::new).collect())
+            .unwrap_or_default();
+
+        Self {
+            dir: dir.to_path_buf(),
+            rules,
+            is_repo_root: dir.join(GIT_DIRECTORY).exists(),
+        }
+    }
+
+    // None, if no rule matches, otherwise, whether the last rule to match ignores, or re-includes, the path
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let relative_path = path.strip_prefix(&self.dir).ok()?.to_string_lossy();
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.is_match(&relative_path, is_dir))
+            .map(|rule| !rule.is_negated)
+    }
+}
+
+// a subset of gitignore patterns: comments, negation with a leading '!', directory only patterns with a trailing '/',
+// patterns anchored to the .gitignore's directory when they contain a '/', otherwise matched against the file name
+// at any depth, and '*', '**', and '?' wildcards
+struct IgnoreRule {
+    pattern: String,
+    is_negated: bool,
+    is_dir_only: bool,
+    is_anchored: bool,
+}
+
+impl IgnoreRule {
+    fn new(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        let (is_negated, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+
+        let (is_dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        // a leading "**/" matches in any directory, just like a pattern without a '/'
+        let line = line.strip_prefix("**/").unwrap_or(line);
+
+        let is_anchored = line.contains('/');
+        let pattern = line.strip_prefix('/').unwrap_or(line).replace("**", "*");
+
+        if pattern.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            pattern,
+            is_negated,
+            is_dir_only,
+            is_anchored,
+        })
+    }
+
+    fn is_match(&self, relative_path: &str, is_dir: bool) -> bool {
+        if self.is_dir_only && !is_dir {
+            return false;
+        }
+
+        if self.is_anchored {
+            return glob_match(&self.pattern, relative_path);
+        }
+
+        let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+
+        glob_match(&self.pattern, file_name)
+    }
+}
@@ -0,0 +1,204 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{create_dir_all, read_to_string, rename, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::library::httm_dirs::HttmDir;
+use crate::library::results::HttmResult;
+use crate::library::utility::{date_string, make_tmp_path, DateFormat};
+use crate::GLOBAL_CONFIG;
+
+const HISTORY_FILE_NAME: &str = "history";
+const HISTORY_MAX_ENTRIES: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryKind {
+    Browse,
+    Query,
+    Select,
+    Restore,
+}
+
+impl HistoryKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            HistoryKind::Browse => "browse",
+            HistoryKind::Query => "query",
+            HistoryKind::Select => "select",
+            HistoryKind::Restore => "restore",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "browse" => Some(HistoryKind::Browse),
+            "query" => Some(HistoryKind::Query),
+            "select" => Some(HistoryKind::Select),
+            "restore" => Some(HistoryKind::Restore),
+            _ => None,
+        }
+    }
+}
+
+// each entry is a single tab delimited line: date, kind, value, and, where known,
+// the live path which corresponds to a snapshot version selected or restored.
+// for query entries, the value is the query and the live path is the dir browsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub date: String,
+    pub kind: HistoryKind,
+    pub value: String,
+    pub opt_live_path: Option<PathBuf>,
+}
+
+impl HistoryEntry {
+    fn from_line(line: &str) -> Option<Self> {
+        let mut split = line.split('\t');
+
+        let date = split.next()?.to_owned();
+        let kind = HistoryKind::from_str(split.next()?)?;
+        let value = split.next()?.to_owned();
+        let opt_live_path = split
+            .next()
+            .filter(|live_path| !live_path.is_empty())
+            .map(PathBuf::from);
+
+        Some(Self {
+            date,
+            kind,
+            value,
+            opt_live_path,
+        })
+    }
+
+    fn to_line(&self) -> String {
+        let live_path = self
+            .opt_live_path
+            .as_ref()
+            .map(|live_path| live_path.to_string_lossy())
+            .unwrap_or_default();
+
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.date,
+            self.kind.as_str(),
+            self.value,
+            live_path
+        )
+    }
+
+    pub fn display(&self) -> String {
+        match &self.opt_live_path {
+            Some(live_path) if self.kind == HistoryKind::Query => format!(
+                "{}  {:<7}  \"{}\" in {:?}",
+                self.date,
+                self.kind.as_str(),
+                self.value,
+                live_path
+            ),
+            _ => format!(
+                "{}  {:<7}  \"{}\"",
+                self.date,
+                self.kind.as_str(),
+                self.value
+            ),
+        }
+    }
+}
+
+pub struct History;
+
+impl History {
+    // history lives in the XDG data dir, usually ~/.local/share/httm/history
+    fn path() -> HttmResult<PathBuf> {
+        HttmDir::Data.file(HISTORY_FILE_NAME)
+    }
+
+    pub fn entries() -> HttmResult<Vec<HistoryEntry>> {
+        let history_path = Self::path()?;
+
+        if !history_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = read_to_string(history_path)?
+            .lines()
+            .filter_map(HistoryEntry::from_line)
+            .collect();
+
+        Ok(entries)
+    }
+
+    // history is merely a convenience, so we never fail the caller, but only warn
+    pub fn record(kind: HistoryKind, value: &str, opt_live_path: Option<&Path>) {
+        if let Err(error) = Self::try_record(kind, value, opt_live_path) {
+            eprintln!("WARNING: httm could not record this selection in its history: {error}");
+        }
+    }
+
+    fn try_record(kind: HistoryKind, value: &str, opt_live_path: Option<&Path>) -> HttmResult<()> {
+        // our format is line and tab delimited, so we can't record values containing either
+        let is_recordable = |value: &str| !value.is_empty() && !value.contains(['\t', '\n']);
+
+        if !is_recordable(value)
+            || opt_live_path.is_some_and(|live_path| !is_recordable(&live_path.to_string_lossy()))
+        {
+            return Ok(());
+        }
+
+        let history_path = Self::path()?;
+
+        if let Some(parent) = history_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut entries = Self::entries()?;
+
+        entries.push(HistoryEntry {
+            date: date_string(
+                GLOBAL_CONFIG.requested_utc_offset,
+                &SystemTime::now(),
+                DateFormat::Display,
+            ),
+            kind,
+            value: value.to_owned(),
+            opt_live_path: opt_live_path.map(Path::to_path_buf),
+        });
+
+        // keep only the most recent entries
+        let num_to_skip = entries.len().saturating_sub(HISTORY_MAX_ENTRIES);
+
+        let buffer: String = entries
+            .iter()
+            .skip(num_to_skip)
+            .map(|entry| entry.to_line() + "\n")
+            .collect();
+
+        // write to a tmp file and rename, so a history is never left half written
+        let tmp_path = make_tmp_path(&history_path);
+        let mut tmp_file = File::create(&tmp_path)?;
+        tmp_file.write_all(buffer.as_bytes())?;
+        tmp_file.sync_data()?;
+
+        rename(tmp_path, history_path)?;
+
+        Ok(())
+    }
+}
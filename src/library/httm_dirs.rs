@@ -0,0 +1,93 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::PathBuf;
+
+use crate::library::results::{HttmError, HttmResult};
+
+const HTTM_DIR_NAME: &str = "httm";
+
+// every file httm keeps between invocations should live in one of these dirs.
+// each may be overridden, as a whole, by its HTTM_*_DIR environment variable,
+// else we follow the XDG base dir spec, else we fall back to the XDG defaults beneath $HOME
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttmDir {
+    Config,
+    Data,
+    Cache,
+    State,
+}
+
+impl HttmDir {
+    fn override_var(&self) -> &'static str {
+        match self {
+            HttmDir::Config => "HTTM_CONFIG_DIR",
+            HttmDir::Data => "HTTM_DATA_DIR",
+            HttmDir::Cache => "HTTM_CACHE_DIR",
+            HttmDir::State => "HTTM_STATE_DIR",
+        }
+    }
+
+    fn xdg_var(&self) -> &'static str {
+        match self {
+            HttmDir::Config => "XDG_CONFIG_HOME",
+            HttmDir::Data => "XDG_DATA_HOME",
+            HttmDir::Cache => "XDG_CACHE_HOME",
+            HttmDir::State => "XDG_STATE_HOME",
+        }
+    }
+
+    fn home_default(&self) -> &'static str {
+        match self {
+            HttmDir::Config => ".config",
+            HttmDir::Data => ".local/share",
+            HttmDir::Cache => ".cache",
+            HttmDir::State => ".local/state",
+        }
+    }
+
+    pub fn path(&self) -> HttmResult<PathBuf> {
+        // the spec says to ignore relative paths, and we treat empty vars as unset
+        let non_empty_abs_var = |var: &str| -> Option<PathBuf> {
+            std::env::var_os(var)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+
+        if let Some(override_dir) = non_empty_abs_var(self.override_var()) {
+            return Ok(override_dir);
+        }
+
+        if let Some(xdg_dir) = non_empty_abs_var(self.xdg_var()) {
+            return Ok(xdg_dir.join(HTTM_DIR_NAME));
+        }
+
+        match std::env::var_os("HOME") {
+            Some(home) => Ok(PathBuf::from(home)
+                .join(self.home_default())
+                .join(HTTM_DIR_NAME)),
+            None => {
+                Err(HttmError::new("$HOME does not appear to be set in your environment").into())
+            }
+        }
+    }
+
+    pub fn file(&self, file_name: &str) -> HttmResult<PathBuf> {
+        self.path().map(|dir| dir.join(file_name))
+    }
+}
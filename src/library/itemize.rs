@@ -0,0 +1,153 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::Metadata;
+use std::os::unix::fs::MetadataExt;
+use std::time::SystemTime;
+
+use crate::library::metadata_changes::MODE_PERMISSION_BITS;
+
+// rsync pads the deletion message to the width of any other change code
+pub const ITEMIZED_DELETING: &str = "*deleting  ";
+
+// the attributes of an entry which an rsync style itemized change code reports, see ITEMIZE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemizedEntry {
+    file_type: char,
+    size: u64,
+    opt_modify_time: Option<SystemTime>,
+    // not every source of entries records the mode and ownership of an entry
+    opt_ownership: Option<(u32, u32, u32)>,
+}
+
+impl From<&Metadata> for ItemizedEntry {
+    fn from(md: &Metadata) -> Self {
+        let file_type = if md.is_symlink() {
+            'L'
+        } else if md.is_dir() {
+            'd'
+        } else if md.is_file() {
+            'f'
+        } else {
+            'D'
+        };
+
+        Self {
+            file_type,
+            size: md.len(),
+            opt_modify_time: md.modified().ok(),
+            opt_ownership: Some((md.mode() & MODE_PERMISSION_BITS, md.uid(), md.gid())),
+        }
+    }
+}
+
+impl ItemizedEntry {
+    pub fn new(
+        is_dir: bool,
+        is_symlink: bool,
+        size: u64,
+        opt_modify_time: Option<SystemTime>,
+    ) -> Self {
+        let file_type = match (is_dir, is_symlink) {
+            (_, true) => 'L',
+            (true, false) => 'd',
+            (false, false) => 'f',
+        };
+
+        Self {
+            file_type,
+            size,
+            opt_modify_time,
+            opt_ownership: None,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.file_type == 'd'
+    }
+
+    // an rsync style itemized change code, "YXcstpoguax", for the change from the older entry to the newer,
+    // where the older entry is what exists, and the newer entry is what would take its place
+    pub fn itemize(
+        opt_older: Option<&Self>,
+        opt_newer: Option<&Self>,
+        is_content_changed: bool,
+    ) -> String {
+        let (older, newer) = match (opt_older, opt_newer) {
+            (_, None) => return ITEMIZED_DELETING.to_owned(),
+            (Some(older), Some(newer)) if older.file_type == newer.file_type => (older, newer),
+            // an entry which replaces an entry of another type is, like rsync, a new entry
+            (_, Some(newer)) => {
+                let update = if newer.file_type == 'f' { '>' } else { 'c' };
+                return format!("{update}{}+++++++++", newer.file_type);
+            }
+        };
+
+        let is_file = newer.file_type == 'f';
+
+        let (is_mode_changed, is_owner_changed, is_group_changed) =
+            match older.opt_ownership.zip(newer.opt_ownership) {
+                Some(((older_mode, older_uid, older_gid), (newer_mode, newer_uid, newer_gid))) => (
+                    older_mode != newer_mode,
+                    older_uid != newer_uid,
+                    older_gid != newer_gid,
+                ),
+                None => (false, false, false),
+            };
+
+        let attributes = [
+            Self::code(is_file && is_content_changed, 'c'),
+            Self::code(is_file && older.size != newer.size, 's'),
+            Self::code(older.opt_modify_time != newer.opt_modify_time, 't'),
+            Self::code(is_mode_changed, 'p'),
+            Self::code(is_owner_changed, 'o'),
+            Self::code(is_group_changed, 'g'),
+            // atime, ACLs and xattrs are not compared
+            '.',
+            '.',
+            '.',
+        ];
+
+        // like rsync's quick check, a file is transferred whenever its content, size, or modify time differs
+        let is_transferred = is_file && attributes[..3].iter().any(|code| *code != '.');
+
+        // like rsync, an entry which would not change at all has no attribute codes
+        if attributes.iter().all(|code| *code == '.') {
+            return format!(".{}         ", newer.file_type);
+        }
+
+        let update = if is_transferred { '>' } else { '.' };
+
+        std::iter::once(update)
+            .chain(std::iter::once(newer.file_type))
+            .chain(attributes)
+            .collect()
+    }
+
+    // an entry which would not change at all has neither an update nor any attribute codes
+    pub fn is_unchanged(code: &str) -> bool {
+        code.starts_with('.') && code[2..].trim().is_empty()
+    }
+
+    fn code(is_changed: bool, code: char) -> char {
+        if is_changed {
+            code
+        } else {
+            '.'
+        }
+    }
+}
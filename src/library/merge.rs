@@ -0,0 +1,286 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{read, rename, write};
+use std::path::{Path, PathBuf};
+
+use filetime::FileTime;
+
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::unified_diff::UnifiedDiff;
+use crate::library::utility::{copy_attributes, is_binary, make_tmp_path};
+use crate::lookup::versions::VersionsMap;
+use crate::GLOBAL_CONFIG;
+
+// a three-way merge, as "diff3 -m" would produce: the changes made to the base in ours, and in theirs,
+// are each kept, and, where both changed the same lines differently, both are kept, between conflict markers
+pub struct ThreeWayMerge {
+    pub merged: Vec<u8>,
+    pub num_conflicts: usize,
+}
+
+pub struct MergeLabels<'a> {
+    pub ours: &'a str,
+    pub base: &'a str,
+    pub theirs: &'a str,
+}
+
+impl ThreeWayMerge {
+    pub fn new(base: &[u8], ours: &[u8], theirs: &[u8], labels: &MergeLabels) -> Self {
+        let base_lines: Vec<&[u8]> = base.split_inclusive(|byte| *byte == b'\n').collect();
+        let ours_lines: Vec<&[u8]> = ours.split_inclusive(|byte| *byte == b'\n').collect();
+        let theirs_lines: Vec<&[u8]> = theirs.split_inclusive(|byte| *byte == b'\n').collect();
+
+        // for each line of the base, the line, if any, it matches in ours, and in theirs
+        let matches_of = |other: &[u8]| -> Vec<Option<usize>> {
+            let mut matches = vec![None; base_lines.len()];
+
+            UnifiedDiff::new(base, other)
+                .unchanged_lines()
+                .for_each(|(base_idx, other_idx)| matches[base_idx] = Some(other_idx));
+
+            matches
+        };
+
+        let ours_matches = matches_of(ours);
+        let theirs_matches = matches_of(theirs);
+
+        let mut merge = Self {
+            merged: Vec::with_capacity(ours.len().max(theirs.len())),
+            num_conflicts: 0,
+        };
+
+        let (mut base_idx, mut ours_idx, mut theirs_idx) = (0usize, 0usize, 0usize);
+
+        loop {
+            // a stable line is unchanged in both, and lies just where we are in each
+            let num_stable = (base_idx..base_lines.len())
+                .take_while(|idx| {
+                    let offset = idx - base_idx;
+                    ours_matches[*idx] == Some(ours_idx + offset)
+                        && theirs_matches[*idx] == Some(theirs_idx + offset)
+                })
+                .count();
+
+            if num_stable > 0 {
+                base_lines[base_idx..base_idx + num_stable]
+                    .iter()
+                    .for_each(|line| merge.merged.extend_from_slice(line));
+
+                base_idx += num_stable;
+                ours_idx += num_stable;
+                theirs_idx += num_stable;
+                continue;
+            }
+
+            // otherwise, the unstable chunk ends at the next line of the base unchanged in both
+            let opt_next_stable = (base_idx..base_lines.len()).find_map(|idx| {
+                match (ours_matches[idx], theirs_matches[idx]) {
+                    (Some(ours_end), Some(theirs_end)) => Some((idx, ours_end, theirs_end)),
+                    _ => None,
+                }
+            });
+
+            let (base_end, ours_end, theirs_end) =
+                opt_next_stable.unwrap_or((base_lines.len(), ours_lines.len(), theirs_lines.len()));
+
+            merge.resolve(
+                &base_lines[base_idx..base_end],
+                &ours_lines[ours_idx..ours_end],
+                &theirs_lines[theirs_idx..theirs_end],
+                labels,
+            );
+
+            if opt_next_stable.is_none() {
+                break;
+            }
+
+            base_idx = base_end;
+            ours_idx = ours_end;
+            theirs_idx = theirs_end;
+        }
+
+        merge
+    }
+
+    // a change made by only one side is taken, as is the same change made by both
+    fn resolve(&mut self, base: &[&[u8]], ours: &[&[u8]], theirs: &[&[u8]], labels: &MergeLabels) {
+        if ours == base || ours == theirs {
+            self.extend(theirs);
+            return;
+        }
+
+        if theirs == base {
+            self.extend(ours);
+            return;
+        }
+
+        self.num_conflicts += 1;
+
+        self.marker(&format!("<<<<<<< {}", labels.ours));
+        self.extend(ours);
+        self.marker(&format!("||||||| {}", labels.base));
+        self.extend(base);
+        self.marker("=======");
+        self.extend(theirs);
+        self.marker(&format!(">>>>>>> {}", labels.theirs));
+    }
+
+    fn extend(&mut self, lines: &[&[u8]]) {
+        lines
+            .iter()
+            .for_each(|line| self.merged.extend_from_slice(line));
+    }
+
+    // a marker always begins a line of its own, even after a last line with no newline
+    fn marker(&mut self, marker: &str) {
+        if !self.merged.is_empty() && !self.merged.ends_with(b"\n") {
+            self.merged.push(b'\n');
+        }
+
+        self.merged.extend_from_slice(marker.as_bytes());
+        self.merged.push(b'\n');
+    }
+}
+
+// a merge of the selected snapshot version into the live file, whose common ancestor is the
+// snapshot version just before the version selected, or, should there be none, an empty file
+pub struct MergeRestore {
+    src: PathBuf,
+    dst: PathBuf,
+    opt_base: Option<PathBuf>,
+    live_bytes: Vec<u8>,
+    merge: ThreeWayMerge,
+}
+
+impl MergeRestore {
+    pub fn new(snap_pathdata: &PathData, live_path: &Path) -> HttmResult<Self> {
+        if snap_pathdata.path_buf.is_dir() || live_path.is_dir() {
+            return Err(HttmError::new(
+                "The \"merge\" restore mode can only merge files, not directories.",
+            )
+            .into());
+        }
+
+        if !live_path.exists() {
+            let msg = format!(
+                "There is no live file {live_path:?} into which to merge.  Perhaps use the \"copy\" restore mode instead?"
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let opt_base = Self::base(snap_pathdata, live_path)?;
+
+        let snap_bytes = read(&snap_pathdata.path_buf)?;
+        let live_bytes = read(live_path)?;
+        let base_bytes = match &opt_base {
+            Some(base) => read(base)?,
+            None => Vec::new(),
+        };
+
+        if is_binary(&snap_bytes) || is_binary(&live_bytes) || is_binary(&base_bytes) {
+            let msg = format!(
+                "The \"merge\" restore mode can only merge text files, and {live_path:?} appears to be binary."
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let base_label = opt_base
+            .as_ref()
+            .map(|base| base.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "(empty)".to_owned());
+
+        let labels = MergeLabels {
+            ours: &live_path.to_string_lossy(),
+            base: &base_label,
+            theirs: &snap_pathdata.path_buf.to_string_lossy(),
+        };
+
+        let merge = ThreeWayMerge::new(&base_bytes, &live_bytes, &snap_bytes, &labels);
+
+        Ok(Self {
+            src: snap_pathdata.path_buf.clone(),
+            dst: live_path.to_path_buf(),
+            opt_base,
+            live_bytes,
+            merge,
+        })
+    }
+
+    // the newest snapshot version older than the version selected
+    fn base(snap_pathdata: &PathData, live_path: &Path) -> HttmResult<Option<PathBuf>> {
+        let snap_modify_time = snap_pathdata.md_infallible().modify_time;
+
+        let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &[PathData::from(live_path)])?;
+
+        let opt_base = versions_map
+            .values()
+            .flatten()
+            .filter(|version| version.md_infallible().modify_time < snap_modify_time)
+            .max_by_key(|version| version.md_infallible().modify_time)
+            .map(|version| version.path_buf.clone());
+
+        Ok(opt_base)
+    }
+
+    pub fn num_conflicts(&self) -> usize {
+        self.merge.num_conflicts
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.merge.merged == self.live_bytes
+    }
+
+    pub fn describe(&self) -> String {
+        let base = match &self.opt_base {
+            Some(base) => format!("{base:?}"),
+            None => "none, as no older snapshot version exists".to_owned(),
+        };
+
+        let result = if self.is_unchanged() {
+            "none, the live file already includes every change".to_owned()
+        } else {
+            match self.merge.num_conflicts {
+                0 => "merged cleanly".to_owned(),
+                1 => "merged, with 1 conflict".to_owned(),
+                num => format!("merged, with {num} conflicts"),
+            }
+        };
+
+        format!(
+            "\tfrom:   {:?}\n\
+            \tto:     {:?}\n\
+            \tbase:   {base}\n\
+            \tresult: {result}\n",
+            self.src, self.dst
+        )
+    }
+
+    // the merged file keeps the attributes of the live file, but, as its content has changed, not its modify time
+    pub fn apply(&self) -> HttmResult<()> {
+        let tmp_path = make_tmp_path(&self.dst);
+
+        write(&tmp_path, &self.merge.merged)?;
+        copy_attributes(&self.dst, &tmp_path)?;
+        filetime::set_file_mtime(&tmp_path, FileTime::now())?;
+
+        rename(tmp_path, &self.dst)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::OsString;
+use std::fs::{set_permissions, Permissions};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use crate::library::results::{HttmError, HttmResult};
+
+// only the permission bits, and not the file type bits, of a mode
+pub const MODE_PERMISSION_BITS: u32 = 0o7777;
+
+// how the attributes of a live file differ from those of a snapshot version, and what, in the
+// metadata only restore mode, would be changed, leaving the file's content, and timestamps, untouched
+pub struct MetadataChanges {
+    src: PathBuf,
+    dst: PathBuf,
+    changes: Vec<MetadataChange>,
+}
+
+enum MetadataChange {
+    Owner {
+        from: u32,
+        to: u32,
+    },
+    Group {
+        from: u32,
+        to: u32,
+    },
+    Mode {
+        from: u32,
+        to: u32,
+    },
+    #[cfg(feature = "acls")]
+    Acls,
+    XattrSet(OsString),
+    XattrRemove(OsString),
+}
+
+impl MetadataChanges {
+    pub fn new(src: &Path, dst: &Path) -> HttmResult<Self> {
+        let src_md = src.symlink_metadata()?;
+        let dst_md = dst.symlink_metadata().map_err(|_err| {
+            let msg = format!(
+                "httm can only restore the metadata of a live file which exists, and {dst:?} does not."
+            );
+            HttmError::new(&msg)
+        })?;
+
+        // the mode of a symlink can't be changed, and its ownership is rarely what's mangled
+        if src_md.is_symlink() || dst_md.is_symlink() {
+            let msg =
+                format!("httm will not restore only the metadata of a symlink, like {dst:?}.");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let mut changes = Vec::new();
+
+        if src_md.uid() != dst_md.uid() {
+            changes.push(MetadataChange::Owner {
+                from: dst_md.uid(),
+                to: src_md.uid(),
+            });
+        }
+
+        if src_md.gid() != dst_md.gid() {
+            changes.push(MetadataChange::Group {
+                from: dst_md.gid(),
+                to: src_md.gid(),
+            });
+        }
+
+        let (src_mode, dst_mode) = (
+            src_md.mode() & MODE_PERMISSION_BITS,
+            dst_md.mode() & MODE_PERMISSION_BITS,
+        );
+
+        if src_mode != dst_mode {
+            changes.push(MetadataChange::Mode {
+                from: dst_mode,
+                to: src_mode,
+            });
+        }
+
+        #[cfg(feature = "acls")]
+        {
+            if exacl::getfacl(src, None).ok() != exacl::getfacl(dst, None).ok() {
+                changes.push(MetadataChange::Acls);
+            }
+        }
+
+        let src_xattrs = Self::xattrs(src);
+        let dst_xattrs = Self::xattrs(dst);
+
+        src_xattrs
+            .iter()
+            .filter(|src_xattr| !dst_xattrs.contains(src_xattr))
+            .for_each(|(name, _value)| changes.push(MetadataChange::XattrSet(name.clone())));
+
+        dst_xattrs
+            .iter()
+            .filter(|(dst_name, _value)| {
+                src_xattrs
+                    .iter()
+                    .all(|(src_name, _value)| src_name != dst_name)
+            })
+            .for_each(|(name, _value)| changes.push(MetadataChange::XattrRemove(name.clone())));
+
+        Ok(Self {
+            src: src.to_path_buf(),
+            dst: dst.to_path_buf(),
+            changes,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn describe(&self) -> String {
+        let description = format!("\tfrom:  {:?}\n\tto:    {:?}\n", self.src, self.dst);
+
+        if self.changes.is_empty() {
+            return description + "\tnone, the metadata is identical\n";
+        }
+
+        self.changes.iter().fold(description, |mut buffer, change| {
+            buffer += &match change {
+                MetadataChange::Owner { from, to } => format!("\towner: {from} -> {to}\n"),
+                MetadataChange::Group { from, to } => format!("\tgroup: {from} -> {to}\n"),
+                MetadataChange::Mode { from, to } => format!("\tmode:  {from:o} -> {to:o}\n"),
+                #[cfg(feature = "acls")]
+                MetadataChange::Acls => "\tacls:  replaced\n".to_owned(),
+                MetadataChange::XattrSet(name) => format!("\txattr: set {name:?}\n"),
+                MetadataChange::XattrRemove(name) => {
+                    format!("\txattr: remove {name:?}\n")
+                }
+            };
+            buffer
+        })
+    }
+
+    pub fn apply(&self) -> HttmResult<()> {
+        let src_md = self.src.symlink_metadata()?;
+
+        // as when we copy attributes, ownership must come before mode, as a chown may clear any setuid/setgid bits
+        if self.changes.iter().any(|change| {
+            matches!(
+                change,
+                MetadataChange::Owner { .. } | MetadataChange::Group { .. }
+            )
+        }) {
+            nix::unistd::chown(
+                &self.dst,
+                Some(src_md.uid().into()),
+                Some(src_md.gid().into()),
+            )?;
+        }
+
+        // always, as a chown may have changed the mode
+        set_permissions(
+            &self.dst,
+            Permissions::from_mode(src_md.mode() & MODE_PERMISSION_BITS),
+        )?;
+
+        self.changes.iter().try_for_each(|change| {
+            match change {
+                #[cfg(feature = "acls")]
+                MetadataChange::Acls => {
+                    let acls = exacl::getfacl(&self.src, None)?;
+                    exacl::setfacl(&[&self.dst], &acls, None)?;
+                }
+                MetadataChange::XattrSet(name) => {
+                    if let Some(value) = xattr::get(&self.src, name)? {
+                        xattr::set(&self.dst, name, value.as_slice())?;
+                    }
+                }
+                MetadataChange::XattrRemove(name) => xattr::remove(&self.dst, name)?,
+                MetadataChange::Owner { .. }
+                | MetadataChange::Group { .. }
+                | MetadataChange::Mode { .. } => {}
+            }
+
+            Ok(())
+        })
+    }
+
+    fn xattrs(path: &Path) -> Vec<(OsString, Option<Vec<u8>>)> {
+        match xattr::list(path) {
+            Ok(names) => names
+                .map(|name| {
+                    let opt_value = xattr::get(path, &name).ok().flatten();
+                    (name, opt_value)
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
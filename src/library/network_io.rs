@@ -0,0 +1,93 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::Path;
+use std::sync::{Condvar, Mutex};
+
+use once_cell::sync::Lazy;
+
+use crate::parse::mounts::MountType;
+use crate::GLOBAL_CONFIG;
+
+// thousands of parallel stats of a remote snapshot dir can overwhelm an NFS or SMB server, so every
+// stat or read of a remote snapshot dir must first take a permit, and there are only so many permits
+static NETWORK_IO: Lazy<NetworkIo> = Lazy::new(|| NetworkIo {
+    permits: Mutex::new(GLOBAL_CONFIG.network_concurrency),
+    condvar: Condvar::new(),
+});
+
+pub struct NetworkIo {
+    permits: Mutex<usize>,
+    condvar: Condvar,
+}
+
+// returns its permit on drop, even should the work panic
+struct NetworkIoPermit;
+
+impl Drop for NetworkIoPermit {
+    fn drop(&mut self) {
+        let mut permits = NETWORK_IO
+            .permits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        *permits += 1;
+        NETWORK_IO.condvar.notify_one();
+    }
+}
+
+impl NetworkIo {
+    // only remote filesystems, as detected by their fstype, are scheduled
+    pub fn is_remote(dataset_mount: &Path) -> bool {
+        GLOBAL_CONFIG
+            .dataset_collection
+            .map_of_datasets
+            .get(dataset_mount)
+            .map(|md| md.mount_type == MountType::Network)
+            .unwrap_or(false)
+    }
+
+    // the number of stats or reads performed, one after another, upon a single permit
+    pub fn batch_size() -> usize {
+        GLOBAL_CONFIG.network_batch_size
+    }
+
+    // wait for a permit, and then do the work given
+    pub fn scheduled<T>(work: impl FnOnce() -> T) -> T {
+        let _permit = Self::acquire();
+
+        work()
+    }
+
+    fn acquire() -> NetworkIoPermit {
+        let mut permits = NETWORK_IO
+            .permits
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        while *permits == 0 {
+            permits = NETWORK_IO
+                .condvar
+                .wait(permits)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+
+        *permits -= 1;
+
+        NetworkIoPermit
+    }
+}
@@ -0,0 +1,183 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::fs::{read_dir, read_link, read_to_string, File};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use nix::errno::Errno;
+use nix::fcntl::{flock, FlockArg};
+
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::walk_tree;
+use crate::GLOBAL_CONFIG;
+
+const PROC_DIR: &str = "/proc";
+
+// overwriting a file which a process holds open, like a database or a log, is likely to be undone, or worse,
+// to corrupt the file, as soon as that process writes again, so, before an overwrite, httm looks for any holders.
+// an overwrite of a dir replaces every file beneath, so the holders of any file beneath a dir are found too
+pub struct OpenFiles {
+    inner: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl OpenFiles {
+    pub fn new(destinations: &[&Path]) -> Self {
+        let canonical: Vec<PathBuf> = destinations
+            .iter()
+            .filter(|dst| dst.is_file() || dst.is_dir())
+            .filter_map(|dst| dst.canonicalize().ok())
+            .collect();
+
+        let mut inner: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+
+        if canonical.is_empty() {
+            return Self { inner };
+        }
+
+        Self::scan_proc(&canonical, &mut inner);
+
+        // without /proc, or without permission to read another user's fds, a held lock is all we can detect
+        let locked: Vec<PathBuf> = canonical
+            .iter()
+            .flat_map(|dst| Self::files_within(dst))
+            .filter(|file| !inner.contains_key(file))
+            .filter(|file| Self::is_locked(file))
+            .collect();
+
+        locked.into_iter().for_each(|dst| {
+            inner.insert(dst, vec!["an unknown process (holds a lock)".to_owned()]);
+        });
+
+        Self { inner }
+    }
+
+    fn scan_proc(canonical: &[PathBuf], inner: &mut BTreeMap<PathBuf, Vec<String>>) {
+        let proc_entries = match read_dir(PROC_DIR) {
+            Ok(proc_entries) => proc_entries,
+            Err(_) => return,
+        };
+
+        let own_pid = std::process::id().to_string();
+
+        proc_entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|pid| pid.chars().all(|c| c.is_ascii_digit()) && pid != &own_pid)
+            .for_each(|pid| {
+                let proc_path = Path::new(PROC_DIR).join(&pid);
+
+                let fds = match read_dir(proc_path.join("fd")) {
+                    Ok(fds) => fds,
+                    Err(_) => return,
+                };
+
+                let mut held: Vec<PathBuf> = fds
+                    .flatten()
+                    .filter_map(|fd| read_link(fd.path()).ok())
+                    .filter(|target| canonical.iter().any(|dst| target.starts_with(dst)))
+                    .collect();
+
+                if held.is_empty() {
+                    return;
+                }
+
+                held.sort();
+                held.dedup();
+
+                let command = read_to_string(proc_path.join("comm"))
+                    .map(|comm| comm.trim_end().to_owned())
+                    .unwrap_or_else(|_| "unknown".to_owned());
+
+                held.into_iter().for_each(|target| {
+                    inner
+                        .entry(target)
+                        .or_default()
+                        .push(format!("{command} (pid {pid})"))
+                });
+            });
+    }
+
+    // a file is itself, and a dir is every file beneath
+    fn files_within(dst: &Path) -> Vec<PathBuf> {
+        if !dst.is_dir() {
+            return vec![dst.to_path_buf()];
+        }
+
+        let mut files = Vec::new();
+
+        walk_tree(dst, usize::MAX, |dir_entry, _depth| {
+            if dir_entry
+                .file_type()
+                .map(|file_type| file_type.is_file())
+                .unwrap_or(false)
+            {
+                files.push(dir_entry.path());
+            }
+
+            true
+        });
+
+        files
+    }
+
+    fn is_locked(path: &Path) -> bool {
+        match File::open(path) {
+            // the lock, if taken, is released when the file is closed
+            Ok(file) => matches!(
+                flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock),
+                Err(Errno::EWOULDBLOCK)
+            ),
+            Err(_) => false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    // warn the user of every holder, and refuse to overwrite, unless the user has asked us to force the restore
+    pub fn check(destinations: &[&Path]) -> HttmResult<()> {
+        let open_files = Self::new(destinations);
+
+        if open_files.is_empty() {
+            return Ok(());
+        }
+
+        let holders: String = open_files
+            .inner
+            .iter()
+            .map(|(path, processes)| format!("\t{path:?}: {}\n", processes.join(", ")))
+            .collect();
+
+        if GLOBAL_CONFIG.opt_force {
+            eprintln!(
+                "WARNING: httm will overwrite files which are currently open:\n\n{holders}\n\
+                The processes above may overwrite, or be confused by, the restored files."
+            );
+            return Ok(());
+        }
+
+        let msg = format!(
+            "httm will not overwrite files which are currently open:\n\n{holders}\n\
+            Stop the processes above, or use the FORCE flag to restore anyway.  No files were restored."
+        );
+
+        Err(HttmError::new(&msg).into())
+    }
+}
@@ -0,0 +1,465 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::ffi::OsString;
+use std::fs::{create_dir_all, read_link, read_to_string, Metadata, OpenOptions};
+use std::io::Write;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use simd_adler32::Adler32;
+
+use crate::data::paths::HashFromFile;
+use crate::library::httm_dirs::HttmDir;
+use crate::library::results::{HttmError, HttmResult};
+use crate::library::utility::{copy_recursive, date_string, walk_tree, DateFormat};
+use crate::GLOBAL_CONFIG;
+
+const JOURNAL_FILE_NAME: &str = "restore_journal";
+const STASH_DIR_NAME: &str = "restore_stash";
+// stashes are whole copies of overwritten files, so we keep only those of the most recent overwrite restores
+const STASH_MAX_SESSIONS: usize = 10;
+const UNSET_FIELD: &str = "-";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalMode {
+    Copy,
+    Overwrite,
+}
+
+impl JournalMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            JournalMode::Copy => "copy",
+            JournalMode::Overwrite => "overwrite",
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "copy" => Some(JournalMode::Copy),
+            "overwrite" => Some(JournalMode::Overwrite),
+            _ => None,
+        }
+    }
+}
+
+// like the history, each entry is a single tab delimited line, and entries are only ever appended:
+// "restore", session, date, mode, source, destination, pre-existing hash, restored hash, stash, or,
+// "undo", session, date, once a session has been reverted.  paths are percent encoded, see encode_path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalEntry {
+    Restore {
+        session: String,
+        date: String,
+        mode: JournalMode,
+        source: PathBuf,
+        destination: PathBuf,
+        opt_pre_hash: Option<String>,
+        opt_post_hash: Option<String>,
+        opt_stash: Option<PathBuf>,
+    },
+    Undo {
+        session: String,
+        date: String,
+    },
+}
+
+impl JournalEntry {
+    fn from_line(line: &str) -> Option<Self> {
+        let mut split = line.split('\t');
+
+        let opt_field = |value: &str| -> Option<String> {
+            Some(value.to_owned()).filter(|value| value != UNSET_FIELD)
+        };
+
+        match split.next()? {
+            "restore" => Some(JournalEntry::Restore {
+                session: split.next()?.to_owned(),
+                date: split.next()?.to_owned(),
+                mode: JournalMode::from_str(split.next()?)?,
+                source: Self::decode_path(split.next()?)?,
+                destination: Self::decode_path(split.next()?)?,
+                opt_pre_hash: opt_field(split.next()?),
+                opt_post_hash: opt_field(split.next()?),
+                opt_stash: match opt_field(split.next()?) {
+                    Some(stash) => Some(Self::decode_path(&stash)?),
+                    None => None,
+                },
+            }),
+            "undo" => Some(JournalEntry::Undo {
+                session: split.next()?.to_owned(),
+                date: split.next()?.to_owned(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn to_line(&self) -> String {
+        match self {
+            JournalEntry::Restore {
+                session,
+                date,
+                mode,
+                source,
+                destination,
+                opt_pre_hash,
+                opt_post_hash,
+                opt_stash,
+            } => format!(
+                "restore\t{session}\t{date}\t{}\t{}\t{}\t{}\t{}\t{}",
+                mode.as_str(),
+                Self::encode_path(source),
+                Self::encode_path(destination),
+                opt_pre_hash.as_deref().unwrap_or(UNSET_FIELD),
+                opt_post_hash.as_deref().unwrap_or(UNSET_FIELD),
+                opt_stash
+                    .as_deref()
+                    .map(Self::encode_path)
+                    .unwrap_or_else(|| UNSET_FIELD.to_owned()),
+            ),
+            JournalEntry::Undo { session, date } => format!("undo\t{session}\t{date}"),
+        }
+    }
+
+    // a path is any bytes but a NUL, so, that undo may put back exactly the path restored, any bytes which are not
+    // valid UTF-8, and any tab, newline, carriage return or percent sign, are written as "%XX", while all else is as is
+    fn encode_path(path: &Path) -> String {
+        let mut encoded = String::new();
+        let mut rest = path.as_os_str().as_bytes();
+
+        let escape = |encoded: &mut String, byte: u8| encoded.push_str(&format!("%{byte:02X}"));
+
+        loop {
+            let (valid, invalid_len) = match std::str::from_utf8(rest) {
+                Ok(valid) => (valid, 0),
+                Err(err) => {
+                    let valid = std::str::from_utf8(&rest[..err.valid_up_to()]).unwrap_or_default();
+                    let invalid_len = err.error_len().unwrap_or(rest.len() - err.valid_up_to());
+                    (valid, invalid_len)
+                }
+            };
+
+            valid.chars().for_each(|c| match c {
+                '%' | '\t' | '\n' | '\r' => escape(&mut encoded, c as u8),
+                _ => encoded.push(c),
+            });
+
+            if invalid_len == 0 {
+                break;
+            }
+
+            rest[valid.len()..valid.len() + invalid_len]
+                .iter()
+                .for_each(|byte| escape(&mut encoded, *byte));
+
+            rest = &rest[valid.len() + invalid_len..];
+        }
+
+        encoded
+    }
+
+    fn decode_path(encoded: &str) -> Option<PathBuf> {
+        let mut decoded = Vec::with_capacity(encoded.len());
+        let mut bytes = encoded.bytes();
+
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'%' => {
+                    let hex = [bytes.next()?, bytes.next()?];
+                    let hex_str = std::str::from_utf8(&hex).ok()?;
+                    decoded.push(u8::from_str_radix(hex_str, 16).ok()?);
+                }
+                _ => decoded.push(byte),
+            }
+        }
+
+        Some(PathBuf::from(OsString::from_vec(decoded)))
+    }
+
+    pub fn session(&self) -> &str {
+        match self {
+            JournalEntry::Restore { session, .. } | JournalEntry::Undo { session, .. } => session,
+        }
+    }
+}
+
+// a journal of a single restore session, that is, of every file restored at once
+pub struct RestoreJournal {
+    session: String,
+    mode: JournalMode,
+}
+
+impl RestoreJournal {
+    pub fn new(mode: JournalMode) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos())
+            .unwrap_or_default();
+
+        Self {
+            session: format!("{nanos}_{}", std::process::id()),
+            mode,
+        }
+    }
+
+    // the journal lives in the XDG data dir, usually ~/.local/share/httm/restore_journal
+    fn path() -> HttmResult<PathBuf> {
+        HttmDir::Data.file(JOURNAL_FILE_NAME)
+    }
+
+    fn stash_dir(session: &str) -> HttmResult<PathBuf> {
+        HttmDir::Data
+            .file(STASH_DIR_NAME)
+            .map(|dir| dir.join(session))
+    }
+
+    pub fn entries() -> HttmResult<Vec<JournalEntry>> {
+        let journal_path = Self::path()?;
+
+        if !journal_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = read_to_string(journal_path)?
+            .lines()
+            .filter_map(JournalEntry::from_line)
+            .collect();
+
+        Ok(entries)
+    }
+
+    // the hash of a file's contents, or of a dir's whole tree, see tree_hash, other kinds of files have none.
+    // an Adler32 alone is too weak to vouch that a file is unchanged, so it is recorded with the file's size
+    // and modify time, or, for a dir, with the total size, and the newest modify time, of the files beneath
+    pub fn hash(path: &Path) -> Option<String> {
+        let md = path.symlink_metadata().ok()?;
+
+        if md.is_file() {
+            let hash = HashFromFile::try_from(path).ok()?.into_inner();
+
+            Some(Self::format_hash(hash, md.len(), Self::mtime(&md)))
+        } else if md.is_dir() {
+            Self::tree_hash(path)
+        } else {
+            None
+        }
+    }
+
+    fn format_hash(hash: u32, size: u64, mtime: (i64, i64)) -> String {
+        let (secs, nanos) = mtime;
+
+        format!("{hash:08x}:{size}:{secs}.{nanos:09}")
+    }
+
+    fn mtime(md: &Metadata) -> (i64, i64) {
+        (md.mtime(), md.mtime_nsec())
+    }
+
+    // a dir is summarized by the relative path of each entry beneath, and the contents, size and modify time
+    // of each file, or the target of each symlink, so that undo may know whether anything in a restored tree
+    // has changed since.  should any file be unreadable, we can't vouch for the tree, and so it has no hash
+    fn tree_hash(dir: &Path) -> Option<String> {
+        let mut entries: Vec<Vec<u8>> = Vec::new();
+        let mut is_complete = true;
+        let mut total_size = 0u64;
+        let mut newest_mtime = (i64::MIN, 0i64);
+
+        walk_tree(dir, usize::MAX, |dir_entry, _depth| {
+            let path = dir_entry.path();
+
+            let mut entry = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .as_os_str()
+                .as_bytes()
+                .to_vec();
+
+            let opt_content = match dir_entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => Some(b"dir".to_vec()),
+                Ok(file_type) if file_type.is_symlink() => read_link(&path)
+                    .ok()
+                    .map(|target| target.as_os_str().as_bytes().to_vec()),
+                Ok(file_type) if file_type.is_file() => dir_entry
+                    .metadata()
+                    .ok()
+                    .zip(HashFromFile::try_from(path.as_path()).ok())
+                    .map(|(md, hash)| {
+                        let (secs, nanos) = Self::mtime(&md);
+
+                        total_size += md.len();
+                        newest_mtime = newest_mtime.max((secs, nanos));
+
+                        [
+                            hash.into_inner().to_be_bytes().as_slice(),
+                            md.len().to_be_bytes().as_slice(),
+                            secs.to_be_bytes().as_slice(),
+                            nanos.to_be_bytes().as_slice(),
+                        ]
+                        .concat()
+                    }),
+                Ok(_) => Some(b"other".to_vec()),
+                Err(_) => None,
+            };
+
+            match opt_content {
+                Some(content) => {
+                    entry.push(b'\0');
+                    entry.extend(content);
+                    entries.push(entry);
+                }
+                None => is_complete = false,
+            }
+
+            true
+        });
+
+        if !is_complete {
+            return None;
+        }
+
+        // the order in which a dir's entries are read is not the same from one read to the next
+        entries.sort_unstable();
+
+        let mut hash = Adler32::default();
+
+        entries.iter().for_each(|entry| {
+            hash.write(entry);
+            hash.write(b"\n");
+        });
+
+        Some(Self::format_hash(hash.finish(), total_size, newest_mtime))
+    }
+
+    // before an overwrite, we stash the pre-existing file, so that the restore may be undone
+    pub fn stash(&self, idx: usize, destination: &Path) -> HttmResult<Option<PathBuf>> {
+        if self.mode != JournalMode::Overwrite || destination.symlink_metadata().is_err() {
+            return Ok(None);
+        }
+
+        let stash_path = Self::stash_dir(&self.session)?.join(idx.to_string());
+
+        copy_recursive(destination, &stash_path, true).map_err(|err| {
+            HttmError::new(&format!(
+                "httm could not stash the pre-existing file {destination:?}, so that the restore may be undone: {err}"
+            ))
+        })?;
+
+        Ok(Some(stash_path))
+    }
+
+    pub fn record(
+        &self,
+        source: &Path,
+        destination: &Path,
+        opt_pre_hash: Option<String>,
+        opt_stash: Option<PathBuf>,
+    ) -> HttmResult<()> {
+        let entry = JournalEntry::Restore {
+            session: self.session.clone(),
+            date: Self::now(),
+            mode: self.mode,
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+            opt_pre_hash,
+            opt_post_hash: Self::hash(destination),
+            opt_stash,
+        };
+
+        Self::append(&entry)
+    }
+
+    pub fn record_undo(session: &str) -> HttmResult<()> {
+        Self::append(&JournalEntry::Undo {
+            session: session.to_owned(),
+            date: Self::now(),
+        })?;
+
+        // a reverted session's stash is of no further use
+        let _ = std::fs::remove_dir_all(Self::stash_dir(session)?);
+
+        Ok(())
+    }
+
+    // the journal is append only, but old stashes are removed, as they may be large
+    pub fn prune_stashes() -> HttmResult<()> {
+        let stash_root = HttmDir::Data.file(STASH_DIR_NAME)?;
+
+        let mut sessions: Vec<String> = match std::fs::read_dir(&stash_root) {
+            Ok(dir_entries) => dir_entries
+                .flatten()
+                .map(|dir_entry| dir_entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(_) => return Ok(()),
+        };
+
+        // session names begin with a timestamp, so sort oldest first
+        sessions.sort_by_key(|session| {
+            session
+                .split('_')
+                .next()
+                .and_then(|nanos| nanos.parse::<u128>().ok())
+                .unwrap_or_default()
+        });
+
+        let num_to_remove = sessions.len().saturating_sub(STASH_MAX_SESSIONS);
+
+        sessions.iter().take(num_to_remove).for_each(|session| {
+            let _ = std::fs::remove_dir_all(stash_root.join(session));
+        });
+
+        Ok(())
+    }
+
+    fn append(entry: &JournalEntry) -> HttmResult<()> {
+        // paths are encoded, so no tab or newline should remain, but our format is line and tab delimited,
+        // so we make certain
+        let line = entry.to_line();
+
+        if line.contains('\n') || line.split('\t').count() > 9 {
+            let msg =
+                format!("httm could not record this restore in its restore journal: {entry:?}");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let journal_path = Self::path()?;
+
+        if let Some(parent) = journal_path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut journal_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal_path)?;
+
+        journal_file.write_all((line + "\n").as_bytes())?;
+        journal_file.sync_data()?;
+
+        Ok(())
+    }
+
+    fn now() -> String {
+        date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Display,
+        )
+    }
+}
@@ -21,15 +21,36 @@ use std::{error::Error, fmt, io::Error as IoError};
 // into something more simple looking. This error, FYI, is really easy to use with rayon.
 pub type HttmResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
+// the exit code contract, so scripts may branch on the outcome without parsing stderr:
+// 0 = success, 1 = any other error, 2 = no snapshot versions were found,
+// 3 = a path does not reside on a snapshot-capable filesystem, 4 = a restore was declined,
+// 5 = a verified file's contents differ from its latest snapshot version, though its size and modify time do not,
+// 6 = a dry run would have made changes.  an interactive abort exits with the user's ABORT_EXIT_CODE, 0 by default
+pub const SUCCESS_EXIT_CODE: i32 = 0;
+pub const ERROR_EXIT_CODE: i32 = 1;
+pub const NO_VERSIONS_EXIT_CODE: i32 = 2;
+pub const NO_SNAPSHOT_SOURCE_EXIT_CODE: i32 = 3;
+pub const RESTORE_DECLINED_EXIT_CODE: i32 = 4;
+pub const VERIFY_MISMATCH_EXIT_CODE: i32 = 5;
+pub const DRY_RUN_CHANGED_EXIT_CODE: i32 = 6;
+
 #[derive(Debug)]
 pub struct HttmError {
     pub details: String,
+    pub exit_code: i32,
 }
 
 impl HttmError {
     pub fn new(msg: &str) -> Self {
         HttmError {
             details: msg.to_owned(),
+            exit_code: ERROR_EXIT_CODE,
+        }
+    }
+    pub fn with_exit_code(msg: &str, exit_code: i32) -> Self {
+        HttmError {
+            details: msg.to_owned(),
+            exit_code,
         }
     }
     pub fn with_context(msg: &str, err: &dyn Error) -> Self {
@@ -37,6 +58,7 @@ impl HttmError {
 
         HttmError {
             details: msg_plus_context,
+            exit_code: ERROR_EXIT_CODE,
         }
     }
 }
@@ -53,16 +75,49 @@ impl Error for HttmError {
     }
 }
 
+// returned when the user aborts an interactive session, so that the user's abort
+// exit code is only used once everything else has been unwound and cleaned up
+#[derive(Debug)]
+pub struct HttmAborted {
+    pub details: String,
+}
+
+impl HttmAborted {
+    pub fn new(msg: &str) -> Self {
+        HttmAborted {
+            details: msg.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for HttmAborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.details)
+    }
+}
+
+impl Error for HttmAborted {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}
+
 impl From<&dyn Error> for HttmError {
     fn from(err: &dyn Error) -> Self {
         let context = format!("{err:?}");
-        HttmError { details: context }
+        HttmError {
+            details: context,
+            exit_code: ERROR_EXIT_CODE,
+        }
     }
 }
 
 impl From<IoError> for HttmError {
     fn from(err: IoError) -> Self {
         let context = format!("{err:?}");
-        HttmError { details: context }
+        HttmError {
+            details: context,
+            exit_code: ERROR_EXIT_CODE,
+        }
     }
 }
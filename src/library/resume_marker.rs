@@ -0,0 +1,107 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::generate::{ExecMode, InteractiveMode};
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+
+const PARTIAL_SUFFIX: &str = ".httm_partial";
+const MARKER_SUFFIX: &str = ".httm_resume";
+
+// an interrupted restore must never leave a truncated file which looks restored, so, when restoring, each file
+// is first copied to a hidden partial file beside its destination, and only renamed into place once complete,
+// and a marker, naming the source, is left beside the destination until the whole restore has completed,
+// so that re-running the same restore may resume, rather than refuse, an existing destination
+pub struct ResumeMarker;
+
+impl ResumeMarker {
+    // only restores are made resumable, other copies, like a roll forward, rely on writing only those blocks which differ
+    pub fn is_restore() -> bool {
+        matches!(
+            GLOBAL_CONFIG.exec_mode,
+            ExecMode::Interactive(InteractiveMode::Restore(_)) | ExecMode::BatchRestore(_)
+        )
+    }
+
+    // like "/home/user/.file.txt.httm_partial" for "/home/user/file.txt"
+    pub fn partial_path(dst: &Path) -> HttmResult<PathBuf> {
+        Self::sibling_path(dst, PARTIAL_SUFFIX)
+    }
+
+    fn marker_path(dst: &Path) -> HttmResult<PathBuf> {
+        Self::sibling_path(dst, MARKER_SUFFIX)
+    }
+
+    fn sibling_path(dst: &Path, suffix: &str) -> HttmResult<PathBuf> {
+        match (dst.parent(), dst.file_name()) {
+            (Some(parent), Some(file_name)) => {
+                Ok(parent.join(format!(".{}{suffix}", file_name.to_string_lossy())))
+            }
+            _ => {
+                let msg =
+                    format!("Could not detect a parent or file name for destination: {dst:?}");
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    pub fn create(src: &Path, dst: &Path) -> HttmResult<()> {
+        let marker_path = Self::marker_path(dst)?;
+
+        if let Some(parent) = marker_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut marker_file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(marker_path)?;
+
+        marker_file.write_all(src.to_string_lossy().as_bytes())?;
+        marker_file.sync_data()?;
+
+        Ok(())
+    }
+
+    pub fn remove(dst: &Path) -> HttmResult<()> {
+        let marker_path = Self::marker_path(dst)?;
+
+        if marker_path.exists() {
+            std::fs::remove_file(marker_path)?;
+        }
+
+        Ok(())
+    }
+
+    // an existing destination may be restored to again, only if an earlier restore, of the same source, was interrupted
+    pub fn is_resumable(src: &Path, dst: &Path) -> bool {
+        Self::marker_path(dst)
+            .ok()
+            .and_then(|marker_path| read_to_string(marker_path).ok())
+            .map(|marker_src| Path::new(&marker_src) == src)
+            .unwrap_or(false)
+    }
+
+    pub fn print_resume_notice(src: &Path, dst: &Path) {
+        eprintln!("NOTICE: httm will resume an interrupted restore of {src:?} to {dst:?}.");
+    }
+}
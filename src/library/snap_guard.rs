@@ -16,14 +16,12 @@
 // that was distributed with this source code.
 
 use std::path::Path;
-use std::process::Command as ExecProcess;
 use std::time::SystemTime;
 
-use which::which;
-
 use crate::data::paths::PathData;
 use crate::library::results::{HttmError, HttmResult};
 use crate::library::utility::{date_string, DateFormat};
+use crate::library::zfs::{ZfsCommand, ZfsPermission};
 use crate::print_output_buf;
 use crate::GLOBAL_CONFIG;
 
@@ -31,12 +29,26 @@ pub enum PrecautionarySnapType {
     PreRollForward,
     PostRollForward(String),
     PreRestore,
+    FromBrowse,
 }
 
 impl TryFrom<&Path> for SnapGuard {
     type Error = Box<dyn std::error::Error + Send + Sync>;
 
     fn try_from(path: &Path) -> HttmResult<Self> {
+        let dataset_name = SnapGuard::dataset_name(path)?;
+
+        SnapGuard::new(&dataset_name, PrecautionarySnapType::PreRestore)
+    }
+}
+
+pub struct SnapGuard {
+    inner: String,
+}
+
+impl SnapGuard {
+    // the name of the dataset which contains the path, which is what we snapshot
+    pub fn dataset_name(path: &Path) -> HttmResult<String> {
         let pathdata = PathData::from(path);
         let dataset_mount =
             pathdata.proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)?;
@@ -52,20 +64,11 @@ impl TryFrom<&Path> for SnapGuard {
             }
         };
 
-        SnapGuard::new(
-            &dataset_name.to_string_lossy(),
-            PrecautionarySnapType::PreRestore,
-        )
+        Ok(dataset_name.to_string_lossy().into_owned())
     }
-}
 
-pub struct SnapGuard {
-    inner: String,
-}
-
-impl SnapGuard {
     pub fn new(dataset_name: &str, snap_type: PrecautionarySnapType) -> HttmResult<Self> {
-        let zfs_command = which("zfs")?;
+        let zfs_command = ZfsCommand::new()?;
 
         let timestamp = date_string(
             GLOBAL_CONFIG.requested_utc_offset,
@@ -96,19 +99,24 @@ impl SnapGuard {
                 let new_snap_name =
                     format!("{}@snap_pre_{}_httmSnapRestore", dataset_name, timestamp);
 
+                new_snap_name
+            }
+            PrecautionarySnapType::FromBrowse => {
+                let new_snap_name = format!("{}@snap_{}_httmSnapBrowse", dataset_name, timestamp);
+
                 new_snap_name
             }
         };
 
         let process_args = vec!["snapshot".to_owned(), new_snap_name.clone()];
 
-        let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
+        let process_output = zfs_command.exec(ZfsPermission::Snapshot, &process_args)?;
         let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
         // stderr_string is a string not an error, so here we build an err or output
         if !stderr_string.is_empty() {
             let msg = if stderr_string.contains("cannot create snapshots : permission denied") {
-                "httm must have root privileges to snapshot a filesystem".to_owned()
+                "httm must have root privileges, or 'zfs allow' privileges, to snapshot a filesystem, see also ESCALATE".to_owned()
             } else {
                 "httm was unable to take snapshots. The 'zfs' command issued the following error: "
                     .to_owned()
@@ -130,9 +138,16 @@ impl SnapGuard {
                         &new_snap_name
                     )
                 }
+                PrecautionarySnapType::FromBrowse => {
+                    format!("httm took a snapshot named: {}\n", &new_snap_name)
+                }
             };
 
-            print_output_buf(output_buf)?;
+            match snap_type {
+                // a browse session prints its selections to stdout, so this notice must go elsewhere
+                PrecautionarySnapType::FromBrowse => eprint!("{output_buf}"),
+                _ => print_output_buf(output_buf)?,
+            }
 
             Ok(SnapGuard {
                 inner: new_snap_name,
@@ -141,10 +156,10 @@ impl SnapGuard {
     }
 
     pub fn rollback(&self) -> HttmResult<()> {
-        let zfs_command = which("zfs")?;
-        let process_args = vec!["rollback", "-r", &self.inner];
+        let zfs_command = ZfsCommand::new()?;
+        let process_args = vec!["rollback".to_owned(), "-r".to_owned(), self.inner.clone()];
 
-        let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
+        let process_output = zfs_command.exec(ZfsPermission::Rollback, &process_args)?;
         let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
 
         // stderr_string is a string not an error, so here we build an err or output
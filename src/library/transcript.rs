@@ -0,0 +1,132 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use once_cell::sync::Lazy;
+
+use crate::library::utility::{date_string, DateFormat};
+use crate::GLOBAL_CONFIG;
+
+// the transcript is opened upon the first entry, and remains open for the rest of the session
+static TRANSCRIPT: Lazy<Mutex<Option<File>>> = Lazy::new(|| Mutex::new(Transcript::open()));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptKind {
+    Begin,
+    Query,
+    Browse,
+    Select,
+    Action,
+    Restore,
+    Failure,
+    End,
+}
+
+impl TranscriptKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptKind::Begin => "begin",
+            TranscriptKind::Query => "query",
+            TranscriptKind::Browse => "browse",
+            TranscriptKind::Select => "select",
+            TranscriptKind::Action => "action",
+            TranscriptKind::Restore => "restore",
+            TranscriptKind::Failure => "failure",
+            TranscriptKind::End => "end",
+        }
+    }
+}
+
+// a record of the interactive session, one entry per line: the date, with its offset, as a transcript
+// may be read on another machine, the kind of entry, and what was typed, selected, or done, see TRANSCRIPT
+pub struct Transcript;
+
+impl Transcript {
+    fn open() -> Option<File> {
+        let transcript_path = GLOBAL_CONFIG.opt_transcript.as_ref()?;
+
+        match OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(transcript_path)
+        {
+            Ok(mut file) => {
+                let args: Vec<String> = std::env::args().collect();
+                let begin = Self::line(
+                    TranscriptKind::Begin,
+                    &format!(
+                        "httm {} as {:?}, in {:?}",
+                        env!("CARGO_PKG_VERSION"),
+                        args.join(" "),
+                        GLOBAL_CONFIG.pwd.path_buf
+                    ),
+                );
+
+                if let Err(err) = file.write_all(begin.as_bytes()) {
+                    eprintln!(
+                        "WARN: httm could not write to its transcript {transcript_path:?}: {err}"
+                    );
+                    return None;
+                }
+
+                Some(file)
+            }
+            Err(err) => {
+                eprintln!("WARN: httm could not open its transcript {transcript_path:?}: {err}");
+                None
+            }
+        }
+    }
+
+    fn line(kind: TranscriptKind, value: &str) -> String {
+        let date = date_string(
+            GLOBAL_CONFIG.requested_utc_offset,
+            &SystemTime::now(),
+            DateFormat::Iso8601,
+        );
+
+        // one entry per line, whatever the user may have typed
+        let value = value.replace('\n', "\\n");
+
+        format!("{date}  {:<7}  {value}\n", kind.as_str())
+    }
+
+    // like history, a transcript is never a reason to fail the caller, and so we only warn, and each entry
+    // is synced as it is written, as a session during an incident may well end badly
+    pub fn record(kind: TranscriptKind, value: &str) {
+        if GLOBAL_CONFIG.opt_transcript.is_none() {
+            return;
+        }
+
+        if let Ok(mut opt_file) = TRANSCRIPT.lock() {
+            if let Some(file) = opt_file.as_mut() {
+                let line = Self::line(kind, value);
+
+                if let Err(err) = file
+                    .write_all(line.as_bytes())
+                    .and_then(|_| file.sync_data())
+                {
+                    eprintln!("WARN: httm could not write to its transcript: {err}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use hashbrown::HashMap;
+
+// the lines of unchanged context before and after each change, as with "diff -u"
+const CONTEXT_LINES: usize = 3;
+// the memory required to find the shortest edit script grows with the square of the number of edits, so, beyond this
+// many edits, we give up on the shortest, and simply remove every line which differs, and add every line which replaces it
+const MAX_EDIT_DISTANCE: usize = 2_048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+// a unified diff, as "diff -u" would produce, but which requires no diff command, see DIFF
+pub struct UnifiedDiff<'a> {
+    old_lines: Vec<&'a [u8]>,
+    new_lines: Vec<&'a [u8]>,
+    edits: Vec<Edit>,
+}
+
+impl<'a> UnifiedDiff<'a> {
+    pub fn new(old: &'a [u8], new: &'a [u8]) -> Self {
+        let old_lines: Vec<&[u8]> = old.split_inclusive(|byte| *byte == b'\n').collect();
+        let new_lines: Vec<&[u8]> = new.split_inclusive(|byte| *byte == b'\n').collect();
+
+        // lines are compared many times over, so we compare ids, rather than bytes
+        let mut ids: HashMap<&[u8], usize> = HashMap::new();
+        let mut line_id = |line: &'a [u8]| {
+            let next_id = ids.len();
+            *ids.entry(line).or_insert(next_id)
+        };
+
+        let old_ids: Vec<usize> = old_lines.iter().map(|line| line_id(line)).collect();
+        let new_ids: Vec<usize> = new_lines.iter().map(|line| line_id(line)).collect();
+
+        let edits = Self::edits(&old_ids, &new_ids);
+
+        Self {
+            old_lines,
+            new_lines,
+            edits,
+        }
+    }
+
+    // pairs of the lines, old and new, which are unchanged, in order, see ThreeWayMerge
+    pub fn unchanged_lines(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.edits.iter().filter_map(|edit| match edit {
+            Edit::Equal(old_idx, new_idx) => Some((*old_idx, *new_idx)),
+            _ => None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits
+            .iter()
+            .all(|edit| matches!(edit, Edit::Equal(_, _)))
+    }
+
+    fn edits(old: &[usize], new: &[usize]) -> Vec<Edit> {
+        // most versions of a file share most of their lines, so we need only search what lies between
+        let prefix_len = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(old_id, new_id)| old_id == new_id)
+            .count();
+
+        let suffix_len = old[prefix_len..]
+            .iter()
+            .rev()
+            .zip(new[prefix_len..].iter().rev())
+            .take_while(|(old_id, new_id)| old_id == new_id)
+            .count();
+
+        let old_middle = &old[prefix_len..old.len() - suffix_len];
+        let new_middle = &new[prefix_len..new.len() - suffix_len];
+
+        let middle = Self::shortest_edits(old_middle, new_middle).unwrap_or_else(|| {
+            (0..old_middle.len())
+                .map(Edit::Delete)
+                .chain((0..new_middle.len()).map(Edit::Insert))
+                .collect()
+        });
+
+        (0..prefix_len)
+            .map(|idx| Edit::Equal(idx, idx))
+            .chain(middle.into_iter().map(|edit| match edit {
+                Edit::Equal(old_idx, new_idx) => {
+                    Edit::Equal(old_idx + prefix_len, new_idx + prefix_len)
+                }
+                Edit::Delete(old_idx) => Edit::Delete(old_idx + prefix_len),
+                Edit::Insert(new_idx) => Edit::Insert(new_idx + prefix_len),
+            }))
+            .chain(
+                (0..suffix_len).map(|idx| {
+                    Edit::Equal(old.len() - suffix_len + idx, new.len() - suffix_len + idx)
+                }),
+            )
+            .collect()
+    }
+
+    // Myers' algorithm: for each number of edits, d, find how far along each diagonal, k, we may reach,
+    // keeping each round's furthest reaches, so that we may retrace the path taken, once we arrive at the end
+    fn shortest_edits(old: &[usize], new: &[usize]) -> Option<Vec<Edit>> {
+        let old_len = old.len() as isize;
+        let new_len = new.len() as isize;
+        let max = old_len + new_len;
+        let offset = max + 1;
+
+        let mut furthest: Vec<isize> = vec![0; (2 * max + 3) as usize];
+        // each round's reaches, for the diagonals -(d + 1) through (d + 1)
+        let mut trace: Vec<Vec<isize>> = Vec::new();
+
+        for d in 0..=max.min(MAX_EDIT_DISTANCE as isize) {
+            trace.push(furthest[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+
+            for k in (-d..=d).step_by(2) {
+                let idx = (offset + k) as usize;
+
+                let mut x = if k == -d || (k != d && furthest[idx - 1] < furthest[idx + 1]) {
+                    furthest[idx + 1]
+                } else {
+                    furthest[idx - 1] + 1
+                };
+                let mut y = x - k;
+
+                while x < old_len && y < new_len && old[x as usize] == new[y as usize] {
+                    x += 1;
+                    y += 1;
+                }
+
+                furthest[idx] = x;
+
+                if x >= old_len && y >= new_len {
+                    return Some(Self::backtrack(&trace, old_len, new_len));
+                }
+            }
+        }
+
+        None
+    }
+
+    fn backtrack(trace: &[Vec<isize>], old_len: isize, new_len: isize) -> Vec<Edit> {
+        let mut edits = Vec::new();
+        let (mut x, mut y) = (old_len, new_len);
+
+        for (d, reaches) in trace.iter().enumerate().rev() {
+            let d = d as isize;
+            let k = x - y;
+            // the reaches of round d begin at the diagonal -(d + 1)
+            let reach = |k: isize| reaches[(k + d + 1) as usize];
+
+            let prev_k = if k == -d || (k != d && reach(k - 1) < reach(k + 1)) {
+                k + 1
+            } else {
+                k - 1
+            };
+            let prev_x = reach(prev_k);
+            let prev_y = prev_x - prev_k;
+
+            while x > prev_x && y > prev_y {
+                edits.push(Edit::Equal((x - 1) as usize, (y - 1) as usize));
+                x -= 1;
+                y -= 1;
+            }
+
+            if d > 0 {
+                if x == prev_x {
+                    edits.push(Edit::Insert((y - 1) as usize));
+                } else {
+                    edits.push(Edit::Delete((x - 1) as usize));
+                }
+            }
+
+            x = prev_x;
+            y = prev_y;
+        }
+
+        edits.reverse();
+        edits
+    }
+
+    // each hunk includes the changes which lie within twice the context of one another
+    fn hunks(&self) -> Vec<(usize, usize)> {
+        let mut hunks: Vec<(usize, usize)> = Vec::new();
+
+        self.edits
+            .iter()
+            .enumerate()
+            .filter(|(_idx, edit)| !matches!(edit, Edit::Equal(_, _)))
+            .for_each(|(idx, _edit)| {
+                let start = idx.saturating_sub(CONTEXT_LINES);
+                let end = (idx + CONTEXT_LINES + 1).min(self.edits.len());
+
+                match hunks.last_mut() {
+                    Some((_start, last_end)) if start <= *last_end => *last_end = end,
+                    _ => hunks.push((start, end)),
+                }
+            });
+
+        hunks
+    }
+
+    fn num_old_lines(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|edit| matches!(edit, Edit::Equal(_, _) | Edit::Delete(_)))
+            .count()
+    }
+
+    fn num_new_lines(edits: &[Edit]) -> usize {
+        edits
+            .iter()
+            .filter(|edit| matches!(edit, Edit::Equal(_, _) | Edit::Insert(_)))
+            .count()
+    }
+
+    // like diff, a count of 1 is implied, and an empty range begins at the line before it
+    fn range(start: usize, count: usize) -> String {
+        match count {
+            0 => format!("{start},0"),
+            1 => format!("{}", start + 1),
+            _ => format!("{},{count}", start + 1),
+        }
+    }
+
+    pub fn format(&self, old_label: &str, new_label: &str) -> String {
+        let mut buffer = format!("--- {old_label}\n+++ {new_label}\n");
+
+        for (start, end) in self.hunks() {
+            let hunk = &self.edits[start..end];
+
+            // the lines of each version before the hunk, and within it
+            let old_start = Self::num_old_lines(&self.edits[..start]);
+            let new_start = Self::num_new_lines(&self.edits[..start]);
+            let old_count = Self::num_old_lines(hunk);
+            let new_count = Self::num_new_lines(hunk);
+
+            buffer += &format!(
+                "@@ -{} +{} @@\n",
+                Self::range(old_start, old_count),
+                Self::range(new_start, new_count)
+            );
+
+            hunk.iter().for_each(|edit| {
+                let (prefix, line) = match edit {
+                    Edit::Equal(old_idx, _) => (' ', self.old_lines[*old_idx]),
+                    Edit::Delete(old_idx) => ('-', self.old_lines[*old_idx]),
+                    Edit::Insert(new_idx) => ('+', self.new_lines[*new_idx]),
+                };
+
+                buffer.push(prefix);
+                buffer += &String::from_utf8_lossy(line);
+
+                if !line.ends_with(b"\n") {
+                    buffer += "\n\\ No newline at end of file\n";
+                }
+            });
+        }
+
+        buffer
+    }
+}
@@ -0,0 +1,195 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use io_uring::{opcode, types, IoUring};
+use once_cell::sync::Lazy;
+
+use crate::data::paths::PathMetadata;
+use crate::library::results::{HttmError, HttmResult};
+
+// for datasets with tens of thousands of snapshots, the cost of one stat syscall per snapshot dominates,
+// so, with the "uring" feature, the stats of local snapshot versions are submitted in batches to an io_uring.
+// io_uring has no equivalent of getdents, so directories are still read one at a time
+pub struct UringStat;
+
+// whether this kernel, and any seccomp policy, permits io_uring at all, else we stat one path at a time
+static IS_SUPPORTED: Lazy<bool> = Lazy::new(|| IoUring::new(2).is_ok());
+
+thread_local! {
+    // each rayon thread submits its own batches, upon its own ring
+    static RING: RefCell<Option<IoUring>> = const { RefCell::new(None) };
+}
+
+// the result of a statx, which, like symlink_metadata(), does not follow symlinks
+pub struct UringMetadata {
+    pub opt_metadata: Option<PathMetadata>,
+    pub is_symlink: bool,
+}
+
+impl UringStat {
+    // the number of stats submitted at once, and the size of each ring
+    pub const BATCH_SIZE: usize = 256;
+
+    pub fn is_enabled() -> bool {
+        *IS_SUPPORTED
+    }
+
+    // the metadata of each path, in the order given, or an io::Error for each path which could not be stat-ed,
+    // or an error for the whole batch, should the ring itself fail, so the caller may fall back to one stat at a time
+    pub fn batch(paths: &[PathBuf]) -> HttmResult<Vec<std::io::Result<UringMetadata>>> {
+        RING.with(|cell| {
+            let mut opt_ring = cell.borrow_mut();
+
+            if opt_ring.is_none() {
+                *opt_ring = Some(IoUring::new(Self::BATCH_SIZE as u32)?);
+            }
+
+            let res = match opt_ring.as_mut() {
+                Some(ring) => Self::batch_on_ring(ring, paths),
+                None => unreachable!("the ring was created above"),
+            };
+
+            // a failed ring may still hold entries pushed, but never submitted, which point into the leaked
+            // buffers of the failed batch, so the next batch must never submit them, and must begin upon a new ring
+            if res.is_err() {
+                *opt_ring = None;
+            }
+
+            res
+        })
+    }
+
+    fn batch_on_ring(
+        ring: &mut IoUring,
+        paths: &[PathBuf],
+    ) -> HttmResult<Vec<std::io::Result<UringMetadata>>> {
+        let c_paths: Vec<CString> = paths
+            .iter()
+            .map(|path| CString::new(path.as_os_str().as_bytes()))
+            .collect::<Result<Vec<CString>, _>>()?;
+
+        // the kernel writes each result here, so these buffers must outlive every submission
+        let mut statx_bufs: Vec<libc::statx> = paths
+            .iter()
+            .map(|_path| unsafe { std::mem::zeroed() })
+            .collect();
+
+        let mut opt_results: Vec<Option<std::io::Result<UringMetadata>>> =
+            paths.iter().map(|_path| None).collect();
+
+        if let Err(err) = Self::submit_all(ring, &c_paths, &mut statx_bufs, &mut opt_results) {
+            // the kernel may yet read a path, or write a result, of any entry submitted, or submitted
+            // later by whomever uses this ring, so, rather than free these buffers beneath it, we leak them
+            std::mem::forget(c_paths);
+            std::mem::forget(statx_bufs);
+            return Err(err);
+        }
+
+        opt_results
+            .into_iter()
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| {
+                HttmError::new("httm could not obtain the result of every io_uring stat.").into()
+            })
+    }
+
+    fn submit_all(
+        ring: &mut IoUring,
+        c_paths: &[CString],
+        statx_bufs: &mut [libc::statx],
+        opt_results: &mut [Option<std::io::Result<UringMetadata>>],
+    ) -> HttmResult<()> {
+        let paths_len = c_paths.len();
+
+        for start in (0..paths_len).step_by(Self::BATCH_SIZE) {
+            let end = paths_len.min(start + Self::BATCH_SIZE);
+
+            for idx in start..end {
+                let entry = opcode::Statx::new(
+                    types::Fd(libc::AT_FDCWD),
+                    c_paths[idx].as_ptr(),
+                    &mut statx_bufs[idx] as *mut libc::statx as *mut types::statx,
+                )
+                .flags(libc::AT_SYMLINK_NOFOLLOW)
+                .mask(libc::STATX_TYPE | libc::STATX_SIZE | libc::STATX_MTIME)
+                .build()
+                .user_data(idx as u64);
+
+                // safe, so long as the path and the buffer live until the entry completes, which we wait upon below
+                unsafe { ring.submission().push(&entry) }.map_err(|_err| {
+                    HttmError::new("httm could not submit a stat, as the io_uring queue is full.")
+                })?;
+            }
+
+            // should we be interrupted, the entries already submitted remain submitted, so we need only wait again
+            loop {
+                match ring.submit_and_wait(end - start) {
+                    Ok(_) => break,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+
+            ring.completion().for_each(|cqe| {
+                let idx = cqe.user_data() as usize;
+
+                let result = if cqe.result() < 0 {
+                    Err(std::io::Error::from_raw_os_error(-cqe.result()))
+                } else {
+                    Ok(Self::metadata(&statx_bufs[idx]))
+                };
+
+                opt_results[idx] = Some(result);
+            });
+        }
+
+        Ok(())
+    }
+
+    fn metadata(statx_buf: &libc::statx) -> UringMetadata {
+        let is_symlink = u32::from(statx_buf.stx_mode) & libc::S_IFMT == libc::S_IFLNK;
+
+        // like PathData::new, metadata without a modify time is no metadata at all
+        let opt_metadata = (statx_buf.stx_mask & libc::STATX_MTIME != 0).then(|| {
+            let timestamp = statx_buf.stx_mtime;
+            let nanos = Duration::from_nanos(u64::from(timestamp.tv_nsec));
+
+            let modify_time = if timestamp.tv_sec >= 0 {
+                SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp.tv_sec as u64) + nanos
+            } else {
+                SystemTime::UNIX_EPOCH - Duration::from_secs(timestamp.tv_sec.unsigned_abs())
+                    + nanos
+            };
+
+            PathMetadata {
+                size: statx_buf.stx_size,
+                modify_time,
+            }
+        });
+
+        UringMetadata {
+            opt_metadata,
+            is_symlink,
+        }
+    }
+}
@@ -17,7 +17,7 @@
 
 use std::{
     borrow::Cow,
-    fs::{create_dir_all, read_dir, set_permissions, FileType},
+    fs::{create_dir_all, read_dir, set_permissions, DirEntry, File, FileType},
     io::{self, Read, Write},
     iter::Iterator,
     os::unix::fs::MetadataExt,
@@ -30,18 +30,20 @@ use lscolors::{Colorable, LsColors, Style};
 use nu_ansi_term::Style as AnsiTermStyle;
 use number_prefix::NumberPrefix;
 use once_cell::sync::Lazy;
-use time::{format_description, OffsetDateTime, UtcOffset};
-use which::which;
+use rayon::prelude::*;
+use rayon::ThreadPool;
+use time::{format_description, Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use unicode_width::UnicodeWidthStr;
 
 use crate::data::paths::{BasicDirEntryInfo, PathData, PHANTOM_DATE};
 use crate::data::selection::SelectionCandidate;
-use crate::library::diff_copy::diff_copy;
+use crate::library::diff_copy::{clone_copy, diff_copy, sparse_copy};
 use crate::library::results::{HttmError, HttmResult};
+use crate::library::resume_marker::ResumeMarker;
 use crate::parse::aliases::FilesystemType;
 use crate::GLOBAL_CONFIG;
 use crate::{config::generate::PrintMode, data::paths::PathMetadata};
-use crate::{BTRFS_SNAPPER_HIDDEN_DIRECTORY, ZFS_SNAPSHOT_DIRECTORY};
-use std::process::Command as ExecProcess;
+use crate::{BTRFS_SNAPPER_HIDDEN_DIRECTORY, NETWORK_SNAPSHOT_DIRECTORIES, ZFS_SNAPSHOT_DIRECTORY};
 
 pub fn user_has_effective_root() -> HttmResult<()> {
     if !nix::unistd::geteuid().is_root() {
@@ -51,51 +53,6 @@ pub fn user_has_effective_root() -> HttmResult<()> {
     Ok(())
 }
 
-pub fn user_has_zfs_allow_snap_priv(new_file_path: &Path) -> HttmResult<()> {
-    let zfs_command = which("zfs")?;
-
-    let pathdata = PathData::from(new_file_path);
-
-    let dataset_mount =
-        pathdata.proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)?;
-
-    let dataset_name = match GLOBAL_CONFIG
-        .dataset_collection
-        .map_of_datasets
-        .get(dataset_mount)
-    {
-        Some(md) => &md.source,
-        None => return Err(HttmError::new("Could not obtain source dataset for mount: ").into()),
-    };
-
-    let dataset_name = &dataset_name.to_string_lossy();
-    let process_args = vec!["allow", dataset_name];
-
-    let process_output = ExecProcess::new(zfs_command).args(&process_args).output()?;
-    let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
-    let stdout_string = std::str::from_utf8(&process_output.stdout)?.trim();
-
-    // stderr_string is a string not an error, so here we build an err or output
-    if !stderr_string.is_empty() {
-        let msg = "httm was unable to determine 'zfs allow' for the path given. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
-
-        return Err(HttmError::new(&msg).into());
-    }
-
-    let user_name = std::env::var("USER")?;
-
-    if !stdout_string.contains(&user_name)
-        || !stdout_string.contains("mount")
-        || !stdout_string.contains("snapshot")
-    {
-        let msg = "User does not have 'zfs allow' privileges for the path given.";
-
-        return Err(HttmError::new(msg).into());
-    }
-
-    Ok(())
-}
-
 pub fn delimiter() -> char {
     if matches!(GLOBAL_CONFIG.print_mode, PrintMode::RawZero) {
         '\0'
@@ -125,6 +82,14 @@ pub fn make_tmp_path(path: &Path) -> PathBuf {
 pub fn copy_attributes(src: &Path, dst: &Path) -> HttmResult<()> {
     let src_metadata = src.symlink_metadata()?;
 
+    // Ownership - must come before mode, as a chown may clear any setuid/setgid bits
+    {
+        let dst_uid = src_metadata.uid();
+        let dst_gid = src_metadata.gid();
+
+        nix::unistd::chown(dst, Some(dst_uid.into()), Some(dst_gid.into()))?
+    }
+
     // Mode
     {
         set_permissions(dst, src_metadata.permissions())?
@@ -139,14 +104,6 @@ pub fn copy_attributes(src: &Path, dst: &Path) -> HttmResult<()> {
         }
     }
 
-    // Ownership
-    {
-        let dst_uid = src_metadata.uid();
-        let dst_gid = src_metadata.gid();
-
-        nix::unistd::chown(dst, Some(dst_uid.into()), Some(dst_gid.into()))?
-    }
-
     // XAttrs
     {
         if let Ok(xattrs) = xattr::list(src) {
@@ -159,19 +116,80 @@ pub fn copy_attributes(src: &Path, dst: &Path) -> HttmResult<()> {
 
     // Timestamps
     {
-        use filetime::FileTime;
+        copy_timestamps(src, dst)?
+    }
 
-        let mtime = FileTime::from_last_modification_time(&src_metadata);
-        let atime = FileTime::from_last_access_time(&src_metadata);
+    Ok(())
+}
 
-        // does not follow symlinks
-        filetime::set_symlink_file_times(dst, atime, mtime)?
-    }
+pub fn copy_timestamps(src: &Path, dst: &Path) -> HttmResult<()> {
+    use filetime::FileTime;
+
+    let src_metadata = src.symlink_metadata()?;
+
+    let mtime = FileTime::from_last_modification_time(&src_metadata);
+    let atime = FileTime::from_last_access_time(&src_metadata);
+
+    // does not follow symlinks
+    filetime::set_symlink_file_times(dst, atime, mtime)?;
 
     Ok(())
 }
 
 pub fn preserve_recursive(src: &Path, dst: &Path) -> HttmResult<()> {
+    corresponding_ancestors(src, dst)?
+        .into_iter()
+        .try_for_each(|(src_ancestor, dst_ancestor)| copy_attributes(src_ancestor, dst_ancestor))
+}
+
+// populating a dir changes its timestamps, so, as a final pass after a restore, we copy the timestamps
+// of each restored dir, and then its parents, from the snapshot
+pub fn restore_dir_times(src: &Path, dst: &Path) -> HttmResult<()> {
+    if src.is_dir() {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        let mut queue: Vec<PathBuf> = vec![src.to_path_buf()];
+
+        while let Some(item) = queue.pop() {
+            for entry in read_dir(&item)? {
+                let entry = entry?;
+
+                if entry.file_type()?.is_dir() {
+                    queue.push(entry.path());
+                }
+            }
+
+            dirs.push(item);
+        }
+
+        // deepest first, because setting the times of a dir does not change the times of its parent
+        dirs.sort_unstable_by_key(|dir| std::cmp::Reverse(dir.components().count()));
+
+        for src_dir in dirs {
+            let relative_path = src_dir
+                .strip_prefix(src)
+                .expect("dir should be located beneath the restored dir");
+
+            let dst_dir = dst.join(relative_path);
+
+            if dst_dir.exists() {
+                copy_timestamps(&src_dir, &dst_dir)?;
+            }
+        }
+    }
+
+    // skip the restored path itself, which is handled above
+    corresponding_ancestors(src, dst)?
+        .into_iter()
+        .skip(1)
+        .try_for_each(|(src_ancestor, dst_ancestor)| copy_timestamps(src_ancestor, dst_ancestor))
+}
+
+// only those ancestors which correspond to one another, so, when restoring
+// a copy to a new location, we leave unrelated dirs, like the pwd, untouched
+fn corresponding_ancestors<'a>(
+    src: &'a Path,
+    dst: &'a Path,
+) -> HttmResult<Vec<(&'a Path, &'a Path)>> {
     let dst_pathdata: PathData = dst.into();
 
     let proximate_dataset_mount =
@@ -183,10 +201,18 @@ pub fn preserve_recursive(src: &Path, dst: &Path) -> HttmResult<()> {
         .components()
         .count();
 
-    src.ancestors()
+    let res = src
+        .ancestors()
         .zip(dst.ancestors())
         .take(relative_path_components_len)
-        .try_for_each(|(src_ancestor, dst_ancestor)| copy_attributes(src_ancestor, dst_ancestor))
+        .enumerate()
+        .take_while(|(idx, (src_ancestor, dst_ancestor))| {
+            *idx == 0 || src_ancestor.file_name() == dst_ancestor.file_name()
+        })
+        .map(|(_idx, pair)| pair)
+        .collect();
+
+    Ok(res)
 }
 
 pub fn copy_direct(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
@@ -201,7 +227,12 @@ pub fn copy_direct(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<
         }
 
         if src.is_file() {
-            diff_copy(src, dst)?;
+            // a symlink's target is written through the link, as before
+            if ResumeMarker::is_restore() && !src.is_symlink() {
+                copy_file_resumable(src, dst)?;
+            } else {
+                copy_file(src, dst)?;
+            }
         }
     }
 
@@ -212,6 +243,38 @@ pub fn copy_direct(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<
     Ok(())
 }
 
+fn copy_file(src: &Path, dst: &Path) -> HttmResult<()> {
+    if (GLOBAL_CONFIG.opt_no_clone || !clone_copy(src, dst)?) && !sparse_copy(src, dst)? {
+        diff_copy(src, dst)?;
+    }
+
+    Ok(())
+}
+
+// copy to a partial file, which is renamed into place only once complete, and which, should it exist already,
+// because a previous copy was interrupted, is copied to again, where only those blocks which differ are written
+fn copy_file_resumable(src: &Path, dst: &Path) -> HttmResult<()> {
+    let partial_path = ResumeMarker::partial_path(dst)?;
+
+    copy_file(src, &partial_path)?;
+
+    // the partial file must be on disk before it is renamed into place, and the rename itself must be on disk
+    // before we report the restore complete, else, after a power loss, the destination may be empty or truncated
+    File::open(&partial_path)?.sync_all()?;
+
+    if dst.is_dir() {
+        std::fs::remove_dir_all(dst)?;
+    }
+
+    std::fs::rename(&partial_path, dst)?;
+
+    if let Some(dst_parent) = dst.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        File::open(dst_parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
 pub fn generate_dst_parent(dst: &Path) -> HttmResult<()> {
     if let Some(dst_parent) = dst.parent() {
         create_dir_all(&dst_parent)?;
@@ -223,29 +286,48 @@ pub fn generate_dst_parent(dst: &Path) -> HttmResult<()> {
     Ok(())
 }
 
+// many small files are copied far faster in parallel, but more workers than this only contend for the same disks
+const MAX_COPY_THREADS: usize = 8;
+
+static COPY_THREAD_POOL: Lazy<ThreadPool> = Lazy::new(|| {
+    let num_threads = std::thread::available_parallelism()
+        .map(|num| num.get().min(MAX_COPY_THREADS))
+        .unwrap_or(1);
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .expect("Could not initialize rayon threadpool for recursive copy")
+});
+
 pub fn copy_recursive(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
     if src.is_dir() {
-        copy_direct(src, dst, should_preserve)?;
+        COPY_THREAD_POOL.install(|| copy_dir_parallel(src, dst, should_preserve))
+    } else {
+        copy_direct(src, dst, should_preserve)
+    }
+}
 
-        for entry in read_dir(src)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            let entry_src = entry.path();
-            let entry_dst = dst.join(entry.file_name());
+fn copy_dir_parallel(src: &Path, dst: &Path, should_preserve: bool) -> HttmResult<()> {
+    copy_direct(src, dst, should_preserve)?;
 
-            if entry_src.exists() {
-                if file_type.is_dir() {
-                    copy_recursive(&entry_src, &entry_dst, should_preserve)?;
-                } else {
-                    copy_direct(src, dst, should_preserve)?;
-                }
+    let entries: Vec<DirEntry> = read_dir(src)?.collect::<Result<_, _>>()?;
+
+    entries.par_iter().try_for_each(|entry| {
+        let file_type = entry.file_type()?;
+        let entry_src = entry.path();
+        let entry_dst = dst.join(entry.file_name());
+
+        if entry_src.exists() {
+            if file_type.is_dir() {
+                copy_dir_parallel(&entry_src, &entry_dst, should_preserve)?;
+            } else {
+                copy_direct(&entry_src, &entry_dst, should_preserve)?;
             }
         }
-    } else {
-        copy_direct(src, dst, should_preserve)?;
-    }
 
-    Ok(())
+        Ok(())
+    })
 }
 
 pub fn remove_recursive(src: &Path) -> HttmResult<()> {
@@ -276,6 +358,35 @@ pub fn remove_recursive(src: &Path) -> HttmResult<()> {
     Ok(())
 }
 
+// walks a tree, without following symlinks, to a bounded depth, where the entries of the given dir are at depth 0.
+// the visitor is given each entry, and its depth, and returns whether we may enter that entry, should it be a dir
+pub fn walk_tree<F>(dir: &Path, max_depth: usize, mut visitor: F)
+where
+    F: FnMut(&DirEntry, usize) -> bool,
+{
+    let mut queue: Vec<(PathBuf, usize)> = vec![(dir.to_path_buf(), 0usize)];
+
+    while let Some((item, depth)) = queue.pop() {
+        let dir_entries = match read_dir(&item) {
+            Ok(dir_entries) => dir_entries,
+            Err(_) => continue,
+        };
+
+        dir_entries.flatten().for_each(|dir_entry| {
+            let may_enter = visitor(&dir_entry, depth);
+
+            let is_dir = dir_entry
+                .file_type()
+                .map(|file_type| file_type.is_dir())
+                .unwrap_or(false);
+
+            if may_enter && is_dir && depth < max_depth {
+                queue.push((dir_entry.path(), depth + 1));
+            }
+        });
+    }
+}
+
 pub fn read_stdin() -> HttmResult<Vec<PathData>> {
     let stdin = std::io::stdin();
     let mut stdin = stdin.lock();
@@ -340,11 +451,23 @@ fn cmp_path<A: AsRef<Path>, B: AsRef<Path>>(a: A, b: B) -> Option<PathBuf> {
     }
 }
 
+// like git, we guess a file is binary, if it contains a NUL within its first few bytes
+const BINARY_SNIFF_LEN: usize = 8000;
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.iter().take(BINARY_SNIFF_LEN).any(|byte| *byte == 0)
+}
+
 pub fn print_output_buf(output_buf: String) -> HttmResult<()> {
+    print_output_bytes(output_buf.as_bytes())
+}
+
+// paths need not be UTF-8, so raw output is written as bytes
+pub fn print_output_bytes(output_buf: &[u8]) -> HttmResult<()> {
     // mutex keeps threads from writing over each other
     let out = std::io::stdout();
     let mut out_locked = out.lock();
-    out_locked.write_all(output_buf.as_bytes())?;
+    out_locked.write_all(output_buf)?;
     out_locked.flush().map_err(std::convert::Into::into)
 }
 
@@ -428,10 +551,18 @@ static PHANTOM_STYLE: Lazy<AnsiTermStyle> = Lazy::new(|| {
     )
 });
 
+// ghosts are dimmed and struck through, so deleted entries shown inline are not mistaken for live entries
+static GHOST_STYLE: Lazy<AnsiTermStyle> =
+    Lazy::new(|| AnsiTermStyle::new().dimmed().strikethrough());
+
 pub fn paint_string<T>(path: T, display_name: &str) -> Cow<str>
 where
     T: PaintString,
 {
+    if path.is_phantom() && GLOBAL_CONFIG.opt_ghosts {
+        return Cow::Owned(GHOST_STYLE.paint(display_name).to_string());
+    }
+
     if path.is_phantom() {
         // paint all other phantoms/deleted files the same color, light pink
         return Cow::Owned(PHANTOM_STYLE.paint(display_name).to_string());
@@ -489,15 +620,28 @@ pub fn fs_type_from_hidden_dir(dataset_mount: &Path) -> Option<FilesystemType> {
     }
 }
 
+// network shares and user defined remote dirs may also use the snapshot dir conventions of NAS appliances
+pub fn fs_type_from_network_hidden_dir(dataset_mount: &Path) -> Option<FilesystemType> {
+    fs_type_from_hidden_dir(dataset_mount).or_else(|| {
+        NETWORK_SNAPSHOT_DIRECTORIES
+            .into_iter()
+            .find(|snapshot_dir| dataset_mount.join(snapshot_dir).is_dir())
+            .map(FilesystemType::SnapshotDir)
+    })
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DateFormat {
     Display,
     Timestamp,
+    Iso8601,
 }
 
 static DATE_FORMAT_DISPLAY: &str =
     "[weekday repr:short] [month repr:short] [day] [hour]:[minute]:[second] [year]";
 static DATE_FORMAT_TIMESTAMP: &str = "[year]-[month]-[day]-[hour]:[minute]:[second]";
+static DATE_FORMAT_ISO8601: &str =
+    "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]";
 
 pub fn date_string(
     utc_offset: UtcOffset,
@@ -518,6 +662,8 @@ pub fn date_string(
         return match &date_format {
             DateFormat::Timestamp => raw_string + "_UTC",
             DateFormat::Display => raw_string + " UTC",
+            // the offset is already unambiguous
+            DateFormat::Iso8601 => raw_string,
         };
     }
 
@@ -528,9 +674,100 @@ fn date_string_format<'a>(format: &DateFormat) -> &'a str {
     match format {
         DateFormat::Display => DATE_FORMAT_DISPLAY,
         DateFormat::Timestamp => DATE_FORMAT_TIMESTAMP,
+        DateFormat::Iso8601 => DATE_FORMAT_ISO8601,
     }
 }
 
+static DATE_FORMAT_DAY: &str = "[year]-[month]-[day]";
+
+const DEFAULT_TZ_DIR: &str = "/usr/share/zoneinfo";
+
+// the offset, now, of a time zone named in the tz database, like "Europe/Berlin", as our
+// platform's localtime would determine it, were the zone the system's local time zone
+pub fn time_zone_offset(time_zone: &str) -> HttmResult<UtcOffset> {
+    let tz_dir = std::env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_TZ_DIR));
+
+    // an unknown zone is silently treated as UTC by libc, so we must check for ourselves
+    let is_valid_name = !time_zone.is_empty()
+        && !time_zone.starts_with('/')
+        && !time_zone.split('/').any(|component| component == "..");
+
+    if !is_valid_name || !tz_dir.join(time_zone).is_file() {
+        let msg = format!(
+            "Could not find the time zone {time_zone:?} in the tz database at {tz_dir:?}.  Time zones must be named like \"Europe/Berlin\"."
+        );
+        return Err(HttmError::new(&msg).into());
+    }
+
+    std::env::set_var("TZ", time_zone);
+
+    UtcOffset::current_local_offset().map_err(|_err| {
+        let msg = format!("Could not determine the current offset of the time zone {time_zone:?}.");
+        HttmError::new(&msg).into()
+    })
+}
+
+// accepts either a day, "2022-06-01", or the same timestamp format we use for snapshot names,
+// "2022-06-01-12:00:00", and interprets either in the requested offset, or a time relative to now,
+// see parse_relative_date
+pub fn parse_date_string(input: &str, utc_offset: UtcOffset) -> HttmResult<SystemTime> {
+    if let Some(relative) = parse_relative_date(input, utc_offset) {
+        return Ok(relative);
+    }
+
+    let timestamp_format =
+        format_description::parse(DATE_FORMAT_TIMESTAMP).expect("timestamp date format is invalid");
+    let day_format =
+        format_description::parse(DATE_FORMAT_DAY).expect("timestamp date format is invalid");
+
+    let date_time = match PrimitiveDateTime::parse(input, &timestamp_format) {
+        Ok(date_time) => date_time,
+        Err(_) => match Date::parse(input, &day_format) {
+            Ok(date) => PrimitiveDateTime::new(date, Time::MIDNIGHT),
+            Err(_) => {
+                let msg = format!(
+                    "Could not parse timestamp {input:?}.  Timestamps must be of the form \"YYYY-MM-DD\" or \"YYYY-MM-DD-HH:MM:SS\", \
+                    or one of \"now\", \"today\", \"yesterday\", or a number of minutes, hours, days, or weeks ago, like \"90m\", \"12h\", \"2d\", or \"1w\"."
+                );
+                return Err(HttmError::new(&msg).into());
+            }
+        },
+    };
+
+    Ok(date_time.assume_offset(utc_offset).into())
+}
+
+// "today" and "yesterday" are the midnight which begins each day, like a day given as "YYYY-MM-DD"
+fn parse_relative_date(input: &str, utc_offset: UtcOffset) -> Option<SystemTime> {
+    let now = OffsetDateTime::now_utc().to_offset(utc_offset);
+    let midnight = now.replace_time(Time::MIDNIGHT);
+
+    match input {
+        "now" => return Some(now.into()),
+        "today" => return Some(midnight.into()),
+        "yesterday" => return Some((midnight - time::Duration::DAY).into()),
+        _ => {}
+    }
+
+    let (unit_idx, unit) = input.char_indices().last()?;
+
+    let unit_secs: u64 = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+
+    let number: u64 = input[..unit_idx].parse().ok()?;
+
+    SystemTime::now().checked_sub(std::time::Duration::from_secs(
+        number.checked_mul(unit_secs)?,
+    ))
+}
+
 pub fn display_human_size(size: u64) -> String {
     let size = size as f64;
 
@@ -540,6 +777,30 @@ pub fn display_human_size(size: u64) -> String {
     }
 }
 
+// like "3 days ago", in the largest whole unit which fits, where a month is 30 days, and a year is 365 days
+pub fn display_relative_date(system_time: &SystemTime) -> String {
+    let secs = match SystemTime::now().duration_since(*system_time) {
+        Ok(duration) => duration.as_secs(),
+        // a version may be modified in the future, should the clocks of two machines disagree
+        Err(_) => return "in the future".to_owned(),
+    };
+
+    let (number, unit) = match secs {
+        0..=59 => return "just now".to_owned(),
+        60..=3_599 => (secs / 60, "minute"),
+        3_600..=86_399 => (secs / 3_600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+
+    if number == 1 {
+        return format!("1 {unit} ago");
+    }
+
+    format!("{number} {unit}s ago")
+}
+
 pub fn is_metadata_same<T>(src: T, dst: T) -> HttmResult<()>
 where
     T: ComparePathMetadata,
@@ -588,3 +849,77 @@ impl<T: AsRef<Path>> ComparePathMetadata for T {
         self.as_ref()
     }
 }
+
+// a simple glob, where '*' matches any run of chars, and '?' matches any single char
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut pat_idx, mut name_idx) = (0usize, 0usize);
+    // where to backtrack to, if the last '*' needs to match more chars
+    let mut opt_backtrack: Option<(usize, usize)> = None;
+
+    while name_idx < name.len() {
+        match pattern.get(pat_idx) {
+            Some('*') => {
+                opt_backtrack = Some((pat_idx, name_idx));
+                pat_idx += 1;
+            }
+            Some(pat_char) if *pat_char == '?' || *pat_char == name[name_idx] => {
+                pat_idx += 1;
+                name_idx += 1;
+            }
+            _ => match opt_backtrack {
+                Some((star_idx, star_name_idx)) => {
+                    pat_idx = star_idx + 1;
+                    name_idx = star_name_idx + 1;
+                    opt_backtrack = Some((star_idx, star_name_idx + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pat_idx..].iter().all(|pat_char| *pat_char == '*')
+}
+
+// the select buffer may be colored, but we want to display and return plain text
+// the number of terminal columns a string occupies, where wide chars, like CJK or emoji, occupy two columns,
+// and ANSI escapes, like ls colors, occupy none
+pub fn display_width(string: &str) -> usize {
+    if string.contains('\x1b') {
+        return UnicodeWidthStr::width(strip_ansi(string).as_str());
+    }
+
+    UnicodeWidthStr::width(string)
+}
+
+// like format!("{:<width$}", string), except the padding reflects the display width, rather than the number of chars
+pub fn pad_to_width(string: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(string));
+
+    format!("{string}{:padding$}", "")
+}
+
+pub fn strip_ansi(line: &str) -> String {
+    let mut res = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            res.push(c);
+            continue;
+        }
+
+        // skip a CSI sequence, like "\x1b[1;31m", through its final byte
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+
+    res
+}
@@ -0,0 +1,436 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::HashMap;
+use std::fs::{create_dir_all, read_to_string, rename, File};
+use std::io::Write;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use once_cell::sync::Lazy;
+use simd_adler32::Adler32;
+use which::which;
+
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::httm_dirs::HttmDir;
+use crate::library::results::HttmResult;
+use crate::library::utility::make_tmp_path;
+use crate::lookup::versions::RelativePathAndSnapMounts;
+use crate::parse::aliases::FilesystemType;
+use crate::parse::mounts::{DatasetMetadata, MountType};
+use crate::GLOBAL_CONFIG;
+
+const INDEX_DIR_NAME: &str = "version_index";
+const ABSENT_FIELD: &str = "-";
+
+// a snapshot never changes, so whether a snapshot contains a relative path, and that version's metadata,
+// need only be asked once.  for a dataset with thousands of snapshots, every lookup otherwise stats
+// thousands of paths, so, when asked, httm remembers every answer, per dataset of interest, in the cache dir.
+//
+// only the snapshots the index has never seen, like newly taken snapshots, are stat-ed, and answers for
+// snapshots which no longer exist are forgotten.  as a snap mount's name, like "daily.0", may be given to another
+// snapshot, each answer is only good for the snapshot identity it was recorded with, see SnapIdentity
+static VERSION_INDEX: Lazy<Mutex<HashMap<PathBuf, DatasetIndex>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// key: relative path, val: (key: snap mount, val: the version, if the snapshot contains the relative path)
+struct DatasetIndex {
+    inner: HashMap<PathBuf, HashMap<PathBuf, Option<PathData>>>,
+    // key: snap mount, val: the identity of the snapshot now at that snap mount, if any is known
+    identities: HashMap<PathBuf, Option<String>>,
+    is_dirty: bool,
+}
+
+impl DatasetIndex {
+    fn load(
+        dataset: &Path,
+        opt_dataset_info: Option<&DatasetMetadata>,
+        snap_mounts: &[PathBuf],
+    ) -> Self {
+        let identities = SnapIdentity::of(opt_dataset_info, snap_mounts);
+
+        let contents = VersionIndex::index_file(dataset)
+            .ok()
+            .and_then(|index_file| read_to_string(index_file).ok())
+            .unwrap_or_default();
+
+        let mut lines = contents.lines();
+        let mut inner: HashMap<PathBuf, HashMap<PathBuf, Option<PathData>>> = HashMap::new();
+        let mut is_dirty = false;
+
+        // the first line names the dataset, as two datasets may share an index file name
+        if lines.next() != Some(dataset.to_string_lossy().as_ref()) {
+            return Self {
+                inner,
+                identities,
+                is_dirty,
+            };
+        }
+
+        lines.for_each(|line| match Self::from_line(line) {
+            Some((snap_mount, identity, relative_path, opt_version))
+                if identities.get(&snap_mount).and_then(Option::as_deref)
+                    == Some(identity.as_str()) =>
+            {
+                inner
+                    .entry(relative_path)
+                    .or_default()
+                    .insert(snap_mount, opt_version);
+            }
+            // forget any snapshot which no longer exists, or which is no longer the snapshot at its snap mount
+            _ => is_dirty = true,
+        });
+
+        Self {
+            inner,
+            identities,
+            is_dirty,
+        }
+    }
+
+    // a long running session may see new snapshots, whose identities we have yet to ask for
+    fn refresh_identities(
+        &mut self,
+        opt_dataset_info: Option<&DatasetMetadata>,
+        snap_mounts: &[PathBuf],
+    ) {
+        if snap_mounts
+            .iter()
+            .all(|snap_mount| self.identities.contains_key(snap_mount))
+        {
+            return;
+        }
+
+        self.identities = SnapIdentity::of(opt_dataset_info, snap_mounts);
+
+        let identities = &self.identities;
+
+        self.inner.values_mut().for_each(|known| {
+            known.retain(|snap_mount, _opt_version| {
+                matches!(identities.get(snap_mount), Some(Some(_)))
+            })
+        });
+    }
+
+    // snap mount, snapshot identity, relative path, then either "-", or the version's size, modify time, and path
+    fn from_line(line: &str) -> Option<(PathBuf, String, PathBuf, Option<PathData>)> {
+        let mut split = line.split('\t');
+
+        let snap_mount = PathBuf::from(split.next()?);
+        let identity = split.next()?.to_owned();
+        let relative_path = PathBuf::from(split.next()?);
+
+        let opt_version = match split.next()? {
+            ABSENT_FIELD => None,
+            size => {
+                let size: u64 = size.parse().ok()?;
+                let secs: u64 = split.next()?.parse().ok()?;
+                let nanos: u32 = split.next()?.parse().ok()?;
+                let path_buf = PathBuf::from(split.next()?);
+
+                Some(PathData {
+                    path_buf,
+                    metadata: Some(PathMetadata {
+                        size,
+                        modify_time: SystemTime::UNIX_EPOCH + Duration::new(secs, nanos),
+                    }),
+                })
+            }
+        };
+
+        Some((snap_mount, identity, relative_path, opt_version))
+    }
+
+    fn to_line(
+        snap_mount: &Path,
+        identity: &str,
+        relative_path: &Path,
+        opt_version: &Option<PathData>,
+    ) -> Option<String> {
+        let fields = match opt_version {
+            None => ABSENT_FIELD.to_owned(),
+            Some(version) => {
+                let metadata = version.metadata?;
+                let since_epoch = metadata
+                    .modify_time
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .ok()?;
+
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    metadata.size,
+                    since_epoch.as_secs(),
+                    since_epoch.subsec_nanos(),
+                    version.path_buf.to_string_lossy()
+                )
+            }
+        };
+
+        let line = format!(
+            "{}\t{identity}\t{}\t{fields}",
+            snap_mount.to_string_lossy(),
+            relative_path.to_string_lossy()
+        );
+
+        // paths which would break our lines are simply never indexed
+        if line.contains('\n') || line.split('\t').count() > 7 {
+            return None;
+        }
+
+        Some(line)
+    }
+}
+
+// what makes a snapshot the snapshot it is, whatever its name: for a local ZFS dataset, the snapshot's guid, which
+// "zfs list" gives for every snapshot at once, and, otherwise, the inode number and birth time, or, where a filesystem
+// has no birth time, the change time, of the snap mount itself, so a btrfs snapshot is known by when its subvolume
+// was created, and a renamed snapshot dir keeps its identity, but a new dir of the same name does not
+struct SnapIdentity;
+
+impl SnapIdentity {
+    fn of(
+        opt_dataset_info: Option<&DatasetMetadata>,
+        snap_mounts: &[PathBuf],
+    ) -> HashMap<PathBuf, Option<String>> {
+        let zfs_guids = match opt_dataset_info {
+            Some(dataset_info)
+                if dataset_info.fs_type == FilesystemType::Zfs
+                    && dataset_info.mount_type == MountType::Local =>
+            {
+                Self::zfs_guids(&dataset_info.source).unwrap_or_default()
+            }
+            _ => HashMap::new(),
+        };
+
+        snap_mounts
+            .iter()
+            .map(|snap_mount| {
+                // the snap mount's file name is the snapshot's name, as in ".zfs/snapshot/<name>"
+                let opt_identity = snap_mount
+                    .file_name()
+                    .and_then(|snap_name| snap_name.to_str())
+                    .and_then(|snap_name| zfs_guids.get(snap_name))
+                    .map(|guid| format!("guid:{guid}"))
+                    .or_else(|| Self::from_metadata(snap_mount));
+
+                (snap_mount.clone(), opt_identity)
+            })
+            .collect()
+    }
+
+    fn from_metadata(snap_mount: &Path) -> Option<String> {
+        let md = snap_mount.metadata().ok()?;
+
+        let identity = match md
+            .created()
+            .ok()
+            .and_then(|created| created.duration_since(SystemTime::UNIX_EPOCH).ok())
+        {
+            Some(since_epoch) => format!(
+                "btime:{}:{}.{:09}",
+                md.ino(),
+                since_epoch.as_secs(),
+                since_epoch.subsec_nanos()
+            ),
+            None => format!("ctime:{}:{}.{:09}", md.ino(), md.ctime(), md.ctime_nsec()),
+        };
+
+        Some(identity)
+    }
+
+    // one exec for the dataset, like SnapOrderSource, instead of a "zfs get" for each snapshot
+    fn zfs_guids(dataset: &Path) -> Option<HashMap<String, String>> {
+        let zfs_command = which("zfs").ok()?;
+        let arg_dataset = dataset.to_string_lossy();
+        let args = vec![
+            "list",
+            "-H",
+            "-p",
+            "-t",
+            "snapshot",
+            "-d",
+            "1",
+            "-o",
+            "name,guid",
+            &arg_dataset,
+        ];
+
+        let command_output = ExecProcess::new(zfs_command).args(&args).output().ok()?;
+
+        if !command_output.status.success() {
+            return None;
+        }
+
+        let res = std::str::from_utf8(&command_output.stdout)
+            .ok()?
+            .lines()
+            .filter_map(|line| {
+                let (name, guid) = line.split_once('\t')?;
+                let (_dataset, snap_name) = name.split_once('@')?;
+
+                Some((snap_name.to_owned(), guid.trim().to_owned()))
+            })
+            .collect();
+
+        Some(res)
+    }
+}
+
+pub struct VersionIndex;
+
+impl VersionIndex {
+    pub fn is_enabled() -> bool {
+        GLOBAL_CONFIG.opt_version_index
+    }
+
+    fn index_file(dataset: &Path) -> HttmResult<PathBuf> {
+        let mut hash = Adler32::default();
+        hash.write(dataset.to_string_lossy().as_bytes());
+
+        HttmDir::Cache.path().map(|dir| {
+            dir.join(INDEX_DIR_NAME)
+                .join(format!("{:08x}", hash.finish()))
+        })
+    }
+
+    // the versions of a relative path upon a dataset, asking the index first, and the filesystem only
+    // about those snapshots the index has never seen
+    pub fn versions(bundle: &RelativePathAndSnapMounts) -> HttmResult<Vec<PathData>> {
+        let (mut versions, unseen): (Vec<PathData>, Vec<&PathBuf>) = {
+            let mut index = match VERSION_INDEX.lock() {
+                Ok(index) => index,
+                Err(_) => return Ok(Vec::new()),
+            };
+
+            let dataset_index = index
+                .entry(bundle.dataset_of_interest.to_path_buf())
+                .or_insert_with(|| {
+                    DatasetIndex::load(
+                        bundle.dataset_of_interest,
+                        bundle.opt_dataset_info,
+                        bundle.snap_mounts,
+                    )
+                });
+
+            dataset_index.refresh_identities(bundle.opt_dataset_info, bundle.snap_mounts);
+
+            let opt_known = dataset_index.inner.get(bundle.relative_path);
+
+            let mut versions = Vec::new();
+            let mut unseen = Vec::new();
+
+            // a snapshot without a known identity is never answered from, or recorded in, the index
+            bundle.snap_mounts.iter().for_each(|snap_mount| {
+                match opt_known.and_then(|known| known.get(snap_mount)) {
+                    Some(opt_version) => versions.extend(opt_version.clone()),
+                    None => unseen.push(snap_mount),
+                }
+            });
+
+            (versions, unseen)
+        };
+
+        if unseen.is_empty() {
+            return Ok(versions);
+        }
+
+        // stat outside the lock, so other lookups may proceed
+        let probed = bundle.probe(&unseen)?;
+
+        if let Ok(mut index) = VERSION_INDEX.lock() {
+            if let Some(dataset_index) = index.get_mut(bundle.dataset_of_interest) {
+                let identities = &dataset_index.identities;
+                let known = dataset_index
+                    .inner
+                    .entry(bundle.relative_path.to_path_buf())
+                    .or_default();
+
+                probed
+                    .iter()
+                    .filter(|(snap_mount, _opt_version)| {
+                        matches!(identities.get(*snap_mount), Some(Some(_)))
+                    })
+                    .for_each(|(snap_mount, opt_version)| {
+                        known.insert(snap_mount.to_path_buf(), opt_version.clone());
+                    });
+
+                dataset_index.is_dirty = true;
+            }
+        }
+
+        versions.extend(
+            probed
+                .into_iter()
+                .flat_map(|(_snap_mount, opt_version)| opt_version),
+        );
+
+        Ok(versions)
+    }
+
+    // write each changed dataset index, whole, to a temp file, then rename, so a reader never sees half an index.
+    // this is done once, as httm exits, not after each lookup, as an interactive session may make many lookups
+    pub fn flush() -> HttmResult<()> {
+        let mut index = match VERSION_INDEX.lock() {
+            Ok(index) => index,
+            Err(_) => return Ok(()),
+        };
+
+        index
+            .iter_mut()
+            .filter(|(_dataset, dataset_index)| dataset_index.is_dirty)
+            .try_for_each(|(dataset, dataset_index)| {
+                let index_file = Self::index_file(dataset)?;
+
+                if let Some(parent) = index_file.parent() {
+                    create_dir_all(parent)?;
+                }
+
+                let mut buffer = dataset.to_string_lossy().to_string() + "\n";
+
+                let identities = &dataset_index.identities;
+
+                dataset_index
+                    .inner
+                    .iter()
+                    .flat_map(|(relative_path, known)| {
+                        known.iter().filter_map(move |(snap_mount, opt_version)| {
+                            let identity = identities.get(snap_mount)?.as_deref()?;
+
+                            DatasetIndex::to_line(snap_mount, identity, relative_path, opt_version)
+                        })
+                    })
+                    .for_each(|line| {
+                        buffer += &line;
+                        buffer += "\n";
+                    });
+
+                let tmp_path = make_tmp_path(&index_file);
+                let mut tmp_file = File::create(&tmp_path)?;
+                tmp_file.write_all(buffer.as_bytes())?;
+                tmp_file.sync_data()?;
+
+                rename(tmp_path, index_file)?;
+
+                dataset_index.is_dirty = false;
+
+                Ok(())
+            })
+    }
+}
@@ -0,0 +1,245 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Output as ProcessOutput};
+
+use hashbrown::{HashMap, HashSet};
+use nix::unistd::{getegid, geteuid, Gid, Group, User};
+use which::which;
+
+use crate::config::generate::PrivilegeEscalation;
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::GLOBAL_CONFIG;
+
+// the 'zfs allow' permissions which httm's snapshot operations require
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZfsPermission {
+    Snapshot,
+    Destroy,
+    Rollback,
+}
+
+impl ZfsPermission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZfsPermission::Snapshot => "snapshot",
+            ZfsPermission::Destroy => "destroy",
+            ZfsPermission::Rollback => "rollback",
+        }
+    }
+}
+
+// how, if at all, the user may perform an operation which requires a permission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZfsCapability {
+    Root,
+    Delegated,
+    Escalated(PrivilegeEscalation),
+    Unprivileged,
+}
+
+pub struct ZfsCommand {
+    zfs_command: PathBuf,
+}
+
+impl ZfsCommand {
+    pub fn new() -> HttmResult<Self> {
+        let zfs_command = which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        Ok(Self { zfs_command })
+    }
+
+    // privileges are only escalated when the user is neither root, nor has been delegated the permission
+    // upon each dataset, see ESCALATE
+    pub fn capability(&self, dataset_names: &[&str], permission: ZfsPermission) -> ZfsCapability {
+        if geteuid().is_root() {
+            return ZfsCapability::Root;
+        }
+
+        if dataset_names
+            .iter()
+            .all(|dataset_name| self.is_delegated(dataset_name, permission))
+        {
+            return ZfsCapability::Delegated;
+        }
+
+        match &GLOBAL_CONFIG.opt_privilege_escalation {
+            Some(escalation) => ZfsCapability::Escalated(escalation.clone()),
+            None => ZfsCapability::Unprivileged,
+        }
+    }
+
+    // whether the user may perform the operation upon the dataset which contains the path, one way or another
+    pub fn is_capable(&self, path: &Path, permission: ZfsPermission) -> bool {
+        let pathdata = PathData::from(path);
+
+        let opt_dataset_name = pathdata
+            .proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)
+            .ok()
+            .and_then(|dataset_mount| {
+                GLOBAL_CONFIG
+                    .dataset_collection
+                    .map_of_datasets
+                    .get(dataset_mount)
+            })
+            .map(|dataset_info| dataset_info.source.to_string_lossy());
+
+        match opt_dataset_name {
+            Some(dataset_name) => !matches!(
+                self.capability(&[dataset_name.as_ref()], permission),
+                ZfsCapability::Unprivileged
+            ),
+            None => false,
+        }
+    }
+
+    // args are a 'zfs' subcommand, any options, and then its snapshot or dataset names, like "destroy pool/data@snap"
+    pub fn exec(&self, permission: ZfsPermission, args: &[String]) -> HttmResult<ProcessOutput> {
+        let dataset_names: Vec<&str> = args
+            .iter()
+            .skip(1)
+            .filter(|arg| !arg.starts_with('-'))
+            .map(|arg| arg.split('@').next().unwrap_or_default())
+            .collect();
+
+        let process_output = match self.capability(&dataset_names, permission) {
+            ZfsCapability::Escalated(escalation) => {
+                let escalation_command = match escalation {
+                    PrivilegeEscalation::Sudo(path) | PrivilegeEscalation::Doas(path) => path,
+                };
+
+                ExecProcess::new(escalation_command)
+                    .arg(&self.zfs_command)
+                    .args(args)
+                    .output()?
+            }
+            ZfsCapability::Root | ZfsCapability::Delegated | ZfsCapability::Unprivileged => {
+                ExecProcess::new(&self.zfs_command).args(args).output()?
+            }
+        };
+
+        Ok(process_output)
+    }
+
+    // 'zfs allow' lists the permissions upon a dataset, and those it inherits from its ancestors, like:
+    // "---- Permissions on pool/data ----", "Local+Descendent permissions:", "\tuser alice mount,snapshot"
+    pub fn is_delegated(&self, dataset_name: &str, permission: ZfsPermission) -> bool {
+        let process_output = match ExecProcess::new(&self.zfs_command)
+            .arg("allow")
+            .arg(dataset_name)
+            .output()
+        {
+            Ok(process_output) if process_output.status.success() => process_output,
+            _ => return false,
+        };
+
+        let user_name = match User::from_uid(geteuid()) {
+            Ok(Some(user)) => user.name,
+            _ => return false,
+        };
+
+        let group_names: Vec<String> = Self::group_ids()
+            .into_iter()
+            .filter_map(|gid| Group::from_gid(gid).ok().flatten())
+            .map(|group| group.name)
+            .collect();
+
+        let stdout_string = String::from_utf8_lossy(&process_output.stdout);
+
+        let mut opt_permissions_on: Option<&str> = None;
+        let mut section = "";
+        let mut permission_sets: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut granted: Vec<&str> = Vec::new();
+
+        for line in stdout_string.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("---- Permissions on ") {
+                opt_permissions_on = Some(rest.trim_end_matches('-').trim());
+                continue;
+            }
+
+            if line.ends_with(':') {
+                section = line;
+                continue;
+            }
+
+            let is_own_permission = opt_permissions_on == Some(dataset_name);
+
+            let is_applicable = match section {
+                "Permission sets:" => {
+                    if let Some((set_name, set_permissions)) = line.split_once(char::is_whitespace)
+                    {
+                        permission_sets
+                            .entry(set_name)
+                            .or_default()
+                            .extend(set_permissions.trim().split(','));
+                    }
+                    continue;
+                }
+                "Local+Descendent permissions:" => true,
+                "Local permissions:" => is_own_permission,
+                "Descendent permissions:" => !is_own_permission,
+                _ => false,
+            };
+
+            if !is_applicable {
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+
+            let permissions = match (tokens.next(), tokens.next(), tokens.next()) {
+                (Some("user"), Some(name), Some(permissions)) if name == user_name => permissions,
+                (Some("group"), Some(name), Some(permissions))
+                    if group_names.iter().any(|group_name| group_name == name) =>
+                {
+                    permissions
+                }
+                (Some("everyone"), Some(permissions), None) => permissions,
+                _ => continue,
+            };
+
+            granted.extend(permissions.split(','));
+        }
+
+        let granted: HashSet<&str> = granted
+            .iter()
+            .flat_map(|granted| match permission_sets.get(granted) {
+                Some(set_permissions) => set_permissions.clone(),
+                None => vec![*granted],
+            })
+            .collect();
+
+        // each of these operations also requires the "mount" permission, see zfs-allow(8)
+        granted.contains("mount") && granted.contains(permission.as_str())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    fn group_ids() -> Vec<Gid> {
+        let mut group_ids = nix::unistd::getgroups().unwrap_or_default();
+        group_ids.push(getegid());
+        group_ids
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "freebsd")))]
+    fn group_ids() -> Vec<Gid> {
+        vec![getegid()]
+    }
+}
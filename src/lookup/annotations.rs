@@ -0,0 +1,234 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::PathBuf;
+use std::process::Command as ExecProcess;
+use std::{collections::BTreeMap, ops::Deref};
+
+use hashbrown::HashMap;
+use rayon::prelude::*;
+
+use crate::data::paths::PathData;
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::snap_names::SnapNameMap;
+use crate::lookup::versions::VersionsMap;
+use crate::parse::aliases::FilesystemType;
+use crate::parse::provenance::BtrfsProvenance;
+use crate::GLOBAL_CONFIG;
+
+const BTRFS_PROPERTY_PREFIX: &str = "btrfs:";
+
+// values of user properties, keyed by property name, for each snapshot version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapAnnotations {
+    inner: BTreeMap<PathBuf, BTreeMap<String, String>>,
+}
+
+impl From<BTreeMap<PathBuf, BTreeMap<String, String>>> for SnapAnnotations {
+    fn from(map: BTreeMap<PathBuf, BTreeMap<String, String>>) -> Self {
+        Self { inner: map }
+    }
+}
+
+impl Deref for SnapAnnotations {
+    type Target = BTreeMap<PathBuf, BTreeMap<String, String>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl SnapAnnotations {
+    pub fn new(versions_map: &VersionsMap, properties: &[String]) -> HttmResult<Self> {
+        // btrfs pseudo-properties are not ZFS user properties, so we ask btrfs about those instead
+        let (btrfs_properties, zfs_properties): (Vec<String>, Vec<String>) = properties
+            .iter()
+            .cloned()
+            .partition(|property| property.starts_with(BTRFS_PROPERTY_PREFIX));
+
+        let mut inner = if zfs_properties.is_empty() {
+            BTreeMap::new()
+        } else {
+            Self::zfs_annotations(versions_map, &zfs_properties)?
+        };
+
+        if !btrfs_properties.is_empty() {
+            inner.extend(Self::btrfs_annotations(versions_map, &btrfs_properties)?);
+        }
+
+        Ok(Self { inner })
+    }
+
+    fn zfs_annotations(
+        versions_map: &VersionsMap,
+        properties: &[String],
+    ) -> HttmResult<BTreeMap<PathBuf, BTreeMap<String, String>>> {
+        // many versions will share a snapshot, so we only ask zfs about each snapshot once
+        let snap_names: HashMap<&PathBuf, String> = versions_map
+            .values()
+            .flatten()
+            .collect::<Vec<&PathData>>()
+            .into_par_iter()
+            .filter_map(|pathdata| {
+                SnapNameMap::deconstruct_snap_paths(pathdata)
+                    .map(|snap_name| (&pathdata.path_buf, snap_name))
+            })
+            .collect();
+
+        if snap_names.is_empty() {
+            return Ok(BTreeMap::new());
+        }
+
+        let mut unique_names: Vec<&str> = snap_names.values().map(String::as_str).collect();
+        unique_names.sort_unstable();
+        unique_names.dedup();
+
+        let props_by_snap = Self::snap_properties(&unique_names, properties)?;
+
+        let res = snap_names
+            .into_iter()
+            .filter_map(|(path, snap_name)| {
+                props_by_snap
+                    .get(&snap_name)
+                    .map(|props| (path.clone(), props.clone()))
+            })
+            .collect();
+
+        Ok(res)
+    }
+
+    fn btrfs_annotations(
+        versions_map: &VersionsMap,
+        properties: &[String],
+    ) -> HttmResult<BTreeMap<PathBuf, BTreeMap<String, String>>> {
+        let dataset_collection = &GLOBAL_CONFIG.dataset_collection;
+
+        // key: version path, val: the btrfs snapshot subvolume which contains it
+        let snap_mounts: HashMap<&PathBuf, &PathBuf> = versions_map
+            .values()
+            .flatten()
+            .filter_map(|pathdata| {
+                dataset_collection
+                    .snap_mount_containing(&pathdata.path_buf)
+                    .filter(|(mount, _snap_mount)| {
+                        dataset_collection
+                            .map_of_datasets
+                            .get(*mount)
+                            .map(|dataset_info| dataset_info.fs_type == FilesystemType::Btrfs)
+                            .unwrap_or(false)
+                    })
+                    .map(|(_mount, snap_mount)| (&pathdata.path_buf, snap_mount))
+            })
+            .collect();
+
+        // as with zfs, many versions will share a snapshot, so we only ask btrfs about each snapshot once
+        let mut unique_snap_mounts: Vec<&PathBuf> = snap_mounts.values().copied().collect();
+        unique_snap_mounts.sort_unstable();
+        unique_snap_mounts.dedup();
+
+        let props_by_snap_mount: HashMap<&PathBuf, BTreeMap<String, String>> = unique_snap_mounts
+            .into_par_iter()
+            .map(|snap_mount| {
+                BtrfsProvenance::new(snap_mount).map(|provenance| {
+                    let props = provenance
+                        .properties()
+                        .into_iter()
+                        .filter(|(property, _value)| properties.iter().any(|p| p == property))
+                        .map(|(property, value)| (property.to_owned(), value))
+                        .collect();
+
+                    (snap_mount, props)
+                })
+            })
+            .collect::<HttmResult<_>>()?;
+
+        let res = snap_mounts
+            .into_iter()
+            .filter_map(|(path, snap_mount)| {
+                props_by_snap_mount
+                    .get(snap_mount)
+                    .map(|props| (path.clone(), props.clone()))
+            })
+            .collect();
+
+        Ok(res)
+    }
+
+    fn snap_properties(
+        snap_names: &[&str],
+        properties: &[String],
+    ) -> HttmResult<HashMap<String, BTreeMap<String, String>>> {
+        let zfs_command = which::which("zfs").map_err(|_err| {
+            HttmError::new("'zfs' command not found. Make sure the command 'zfs' is in your path.")
+        })?;
+
+        let process_output = ExecProcess::new(zfs_command)
+            .arg("get")
+            .arg("-H")
+            .arg("-o")
+            .arg("name,property,value,source")
+            .arg(properties.join(","))
+            .args(snap_names)
+            .output()?;
+
+        let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+
+        if !stderr_string.is_empty() {
+            let msg = "httm was unable to read snapshot properties. The 'zfs' command issued the following error: ".to_owned() + stderr_string;
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        // each line is tab delimited: name, property, value, source
+        let res = stdout_string.lines().fold(
+            HashMap::new(),
+            |mut acc: HashMap<String, BTreeMap<String, String>>, line| {
+                let mut split = line.split('\t');
+
+                if let (Some(name), Some(property), Some(value), Some(source)) =
+                    (split.next(), split.next(), split.next(), split.next())
+                {
+                    // a source of "-" means the property is unset on this snapshot
+                    if source != "-" {
+                        acc.entry(name.to_owned())
+                            .or_default()
+                            .insert(property.to_owned(), value.to_owned());
+                    }
+                }
+
+                acc
+            },
+        );
+
+        Ok(res)
+    }
+
+    pub fn display(&self, pathdata: &PathData) -> Option<String> {
+        self.get(&pathdata.path_buf)
+            .filter(|props| !props.is_empty())
+            .map(|props| {
+                let annotation = props
+                    .iter()
+                    .map(|(property, value)| format!("{property}={value}"))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                format!("[{annotation}]")
+            })
+    }
+}
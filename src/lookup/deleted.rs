@@ -24,9 +24,18 @@ use std::{
 
 use hashbrown::{HashMap, HashSet};
 
+use crate::config::generate::DeletedScan;
 use crate::data::paths::{BasicDirEntryInfo, PathData};
+use crate::library::exclusions::Exclusions;
+use crate::library::network_io::NetworkIo;
 use crate::library::results::HttmResult;
 use crate::lookup::versions::{ProximateDatasetAndOptAlts, RelativePathAndSnapMounts};
+use crate::parse::snaps::MapOfSnaps;
+use crate::GLOBAL_CONFIG;
+
+// in a fast deleted scan, the number of consecutive snapshots with no new
+// file names, after which we stop reading snapshots
+const FAST_SCAN_MAX_STALE_SNAPS: usize = 16;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct DeletedFiles {
@@ -47,12 +56,23 @@ impl TryFrom<&Path> for DeletedFiles {
         // as these will be the filenames that populate our interactive views, so deduplicate
         // by filename and latest file version here
         let basic_info_map: HashMap<OsString, BasicDirEntryInfo> =
-            ProximateDatasetAndOptAlts::new(&requested_dir_pathdata)?
+            ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, &requested_dir_pathdata)?
                 .into_search_bundles()
                 .flat_map(|search_bundle| {
                     Self::unique_deleted_for_dir(&requested_dir_pathdata.path_buf, &search_bundle)
                 })
                 .flatten()
+                // deleted files are subject to the same exclusions as live files
+                .filter(|basic_info| {
+                    GLOBAL_CONFIG.opt_no_filter
+                        || !Exclusions::is_excluded(
+                            &requested_dir.join(basic_info.filename()),
+                            basic_info
+                                .file_type
+                                .map(|file_type| file_type.is_dir())
+                                .unwrap_or(false),
+                        )
+                })
                 .map(|basic_info| (basic_info.filename().to_os_string(), basic_info))
                 .collect();
 
@@ -84,7 +104,7 @@ impl DeletedFiles {
             .collect();
 
         let unique_snap_filenames: HashMap<OsString, BasicDirEntryInfo> =
-            Self::unique_snap_filenames(search_bundle.snap_mounts, search_bundle.relative_path);
+            Self::unique_snap_filenames(search_bundle);
 
         // compare local filenames to all unique snap filenames - none values are unique, here
         let all_deleted_versions = unique_snap_filenames
@@ -102,17 +122,98 @@ impl DeletedFiles {
     }
 
     fn unique_snap_filenames(
-        mounts: &[PathBuf],
+        search_bundle: &RelativePathAndSnapMounts,
+    ) -> HashMap<OsString, BasicDirEntryInfo> {
+        let mounts = search_bundle.snap_mounts;
+        let relative_path = search_bundle.relative_path;
+        let is_remote = search_bundle.is_remote;
+
+        if GLOBAL_CONFIG.deleted_scan == DeletedScan::Fast {
+            // a fast scan may stop early only when we know the order in which snapshots were created,
+            // see SnapOrderSource, as the mtime of a snapshot root says nothing of when it was taken
+            if let Some(newest_first) = MapOfSnaps::newest_first(
+                &GLOBAL_CONFIG.dataset_collection.map_of_datasets,
+                search_bundle.dataset_of_interest,
+                mounts,
+            ) {
+                return Self::unique_snap_filenames_fast(&newest_first, relative_path, is_remote);
+            }
+        }
+
+        let read_batch = |batch: &[PathBuf]| -> Vec<(OsString, BasicDirEntryInfo)> {
+            batch
+                .iter()
+                .map(|path| path.join(relative_path))
+                .flat_map(read_dir)
+                .flatten()
+                .flatten()
+                .map(|dir_entry| (dir_entry.file_name(), BasicDirEntryInfo::from(&dir_entry)))
+                .collect()
+        };
+
+        // reads of remote snapshot dirs are batched, and only so many batches are in flight at once
+        if is_remote {
+            return mounts
+                .chunks(NetworkIo::batch_size())
+                .flat_map(|batch| NetworkIo::scheduled(|| read_batch(batch)))
+                .collect();
+        }
+
+        read_batch(mounts).into_iter().collect()
+    }
+
+    // datasets with thousands of snapshots make reading every snapshot dir very slow,
+    // so read snapshots newest first, and stop once a run of consecutive snapshots
+    // has given us no file names we haven't already seen
+    fn unique_snap_filenames_fast(
+        newest_first: &[PathBuf],
         relative_path: &Path,
+        is_remote: bool,
     ) -> HashMap<OsString, BasicDirEntryInfo> {
-        mounts
-            .iter()
-            .map(|path| path.join(relative_path))
-            .flat_map(read_dir)
-            .flatten()
-            .flatten()
-            .map(|dir_entry| (dir_entry.file_name(), BasicDirEntryInfo::from(&dir_entry)))
-            .collect::<HashMap<OsString, BasicDirEntryInfo>>()
+        let mut unique_snap_filenames: HashMap<OsString, BasicDirEntryInfo> = HashMap::new();
+        let mut num_stale = 0usize;
+
+        for mount in newest_first {
+            if num_stale >= FAST_SCAN_MAX_STALE_SNAPS {
+                break;
+            }
+
+            let read_snap_dir = || -> Option<Vec<(OsString, BasicDirEntryInfo)>> {
+                read_dir(mount.join(relative_path)).ok().map(|dir_entries| {
+                    dir_entries
+                        .flatten()
+                        .map(|dir_entry| {
+                            (dir_entry.file_name(), BasicDirEntryInfo::from(&dir_entry))
+                        })
+                        .collect()
+                })
+            };
+
+            let opt_dir_entries = if is_remote {
+                NetworkIo::scheduled(read_snap_dir)
+            } else {
+                read_snap_dir()
+            };
+
+            let dir_entries = match opt_dir_entries {
+                Some(dir_entries) => dir_entries,
+                None => continue,
+            };
+
+            let num_before = unique_snap_filenames.len();
+
+            dir_entries.into_iter().for_each(|(file_name, basic_info)| {
+                unique_snap_filenames.entry(file_name).or_insert(basic_info);
+            });
+
+            if unique_snap_filenames.len() == num_before {
+                num_stale += 1;
+            } else {
+                num_stale = 0;
+            }
+        }
+
+        unique_snap_filenames
     }
 }
 
@@ -140,14 +241,23 @@ impl TryFrom<Vec<PathData>> for LastInTimeSet {
     // this fn is also missing parallel iter fns, to make the searches more responsive
     // by leaving parallel search for the interactive views
     fn try_from(path_set: Vec<PathData>) -> HttmResult<Self> {
-        let res = path_set
+        let last_versions = path_set
             .iter()
-            .flat_map(ProximateDatasetAndOptAlts::new)
-            .map(|prox_opt_alts| prox_opt_alts.into_search_bundles())
-            .filter_map(|relative_paths_snap_mounts| {
-                relative_paths_snap_mounts
+            .flat_map(|pathdata| ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, pathdata))
+            .map(|prox_opt_alts| {
+                prox_opt_alts
+                    .into_search_bundles()
+                    .map(|search_bundle| search_bundle.last_version())
+                    .collect::<HttmResult<Vec<Option<PathData>>>>()
+            })
+            .collect::<HttmResult<Vec<Vec<Option<PathData>>>>>()?;
+
+        let res = last_versions
+            .into_iter()
+            .filter_map(|versions| {
+                versions
                     .into_iter()
-                    .filter_map(|search_bundle| search_bundle.last_version())
+                    .flatten()
                     .max_by_key(|pathdata| pathdata.md_infallible().modify_time)
                     .map(|pathdata| pathdata.path_buf)
             })
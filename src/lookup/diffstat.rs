@@ -0,0 +1,188 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::{collections::BTreeMap, ops::Deref};
+
+use hashbrown::HashMap;
+use rayon::prelude::*;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+use crate::config::generate::BulkExclusion;
+use crate::data::paths::PathData;
+use crate::library::utility::is_binary;
+use crate::lookup::versions::VersionsMap;
+
+// larger files are summarized only by their change in size, as reading each in full would be too slow
+const MAX_TEXT_DIFF_SIZE: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffStat {
+    Text {
+        bytes_added: u64,
+        bytes_removed: u64,
+    },
+    Binary {
+        size_delta: i64,
+    },
+}
+
+impl Serialize for DiffStat {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            DiffStat::Text {
+                bytes_added,
+                bytes_removed,
+            } => {
+                let mut state = serializer.serialize_struct("DiffStat", 2)?;
+                state.serialize_field("bytes_added", bytes_added)?;
+                state.serialize_field("bytes_removed", bytes_removed)?;
+                state.end()
+            }
+            DiffStat::Binary { size_delta } => {
+                let mut state = serializer.serialize_struct("DiffStat", 1)?;
+                state.serialize_field("size_delta", size_delta)?;
+                state.end()
+            }
+        }
+    }
+}
+
+// key: version path, val: the change from the version displayed just before it,
+// the first version of each file, and any directory, has no such change, and no entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionDiffStats {
+    inner: BTreeMap<PathBuf, DiffStat>,
+}
+
+impl Deref for VersionDiffStats {
+    type Target = BTreeMap<PathBuf, DiffStat>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl VersionDiffStats {
+    pub fn new(versions_map: &VersionsMap, opt_bulk_exclusion: &Option<BulkExclusion>) -> Self {
+        // pairs of each version and the version displayed before it, the live version is displayed last
+        let pairs: Vec<(&PathData, &PathData)> = versions_map
+            .iter()
+            .flat_map(|(live_version, snaps)| {
+                let opt_live_version = Some(live_version).filter(|live_version| {
+                    live_version.metadata.is_some() && opt_bulk_exclusion.is_none()
+                });
+
+                let displayed: Vec<&PathData> = match opt_bulk_exclusion {
+                    Some(BulkExclusion::NoSnap) => Vec::new(),
+                    _ => snaps.iter().chain(opt_live_version).collect(),
+                };
+
+                displayed
+                    .windows(2)
+                    .map(|window| (window[0], window[1]))
+                    .collect::<Vec<(&PathData, &PathData)>>()
+            })
+            .collect();
+
+        let inner = pairs
+            .into_par_iter()
+            .filter_map(|(prior, current)| {
+                Self::diff_stat(prior, current).map(|stat| (current.path_buf.clone(), stat))
+            })
+            .collect();
+
+        Self { inner }
+    }
+
+    fn diff_stat(prior: &PathData, current: &PathData) -> Option<DiffStat> {
+        let prior_md = prior.metadata?;
+        let current_md = current.metadata?;
+
+        if current.path_buf.is_dir() {
+            return None;
+        }
+
+        let binary = DiffStat::Binary {
+            size_delta: current_md.size as i64 - prior_md.size as i64,
+        };
+
+        if prior_md.size > MAX_TEXT_DIFF_SIZE || current_md.size > MAX_TEXT_DIFF_SIZE {
+            return Some(binary);
+        }
+
+        let (prior_bytes, current_bytes) = match (
+            Self::read_contents(&prior.path_buf),
+            Self::read_contents(&current.path_buf),
+        ) {
+            (Ok(prior_bytes), Ok(current_bytes)) => (prior_bytes, current_bytes),
+            _ => return Some(binary),
+        };
+
+        if is_binary(&prior_bytes) || is_binary(&current_bytes) {
+            return Some(binary);
+        }
+
+        Some(Self::line_stat(&prior_bytes, &current_bytes))
+    }
+
+    fn read_contents(path: &Path) -> std::io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        File::open(path)?.read_to_end(&mut buf)?;
+        Ok(buf)
+    }
+
+    // lines are compared as a whole, regardless of where they moved, so a line present in both versions
+    // is unchanged, only those lines of the current version not in the prior are added, and vice versa
+    fn line_stat(prior: &[u8], current: &[u8]) -> DiffStat {
+        let mut prior_lines: HashMap<&[u8], usize> = HashMap::new();
+
+        prior
+            .split_inclusive(|byte| *byte == b'\n')
+            .for_each(|line| {
+                *prior_lines.entry(line).or_insert(0) += 1;
+            });
+
+        let bytes_added = current
+            .split_inclusive(|byte| *byte == b'\n')
+            .filter(|line| match prior_lines.get_mut(line) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .map(|line| line.len() as u64)
+            .sum();
+
+        let bytes_removed = prior_lines
+            .iter()
+            .map(|(line, count)| (line.len() * count) as u64)
+            .sum();
+
+        DiffStat::Text {
+            bytes_added,
+            bytes_removed,
+        }
+    }
+}
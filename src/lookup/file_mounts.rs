@@ -58,12 +58,13 @@ impl<'a> MountsForFiles<'a> {
 
                 true
             })
-            .flat_map(ProximateDatasetAndOptAlts::new)
+            .flat_map(|pathdata| ProximateDatasetAndOptAlts::new(&GLOBAL_CONFIG, pathdata))
             .map(|prox_opt_alts| {
                 let vec = prox_opt_alts
                     .datasets_of_interest
                     .iter()
                     .map(PathData::from)
+                    .chain(prox_opt_alts.opt_merged_dataset_mount.map(PathData::from))
                     .collect();
                 (prox_opt_alts.pathdata, vec)
             })
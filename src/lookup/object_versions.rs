@@ -0,0 +1,258 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command as ExecProcess, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use proc_mounts::MountIter;
+use rayon::prelude::*;
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use which::which;
+
+use crate::config::generate::{Config, ListSnapsOfType, ObjectStore};
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::versions::VersionsMap;
+
+const RCLONE_FSTYPE: &str = "fuse.rclone";
+// each provider has its own flag to list old versions, and a provider simply ignores the flags of the others
+const RCLONE_VERSIONS_FLAGS: [&str; 2] = ["--s3-versions", "--b2-versions"];
+// rclone names each old version of an object with the time it was superseded, in UTC, inserted before any extension,
+// like "report-v2023-06-01-123456-000.txt"
+const VERSION_TIMESTAMP_LEN: usize = "-v2023-06-01-123456-000".len();
+
+// an object store, like S3 or B2, mounted via rclone or s3fs, which keeps each version of an object, lists those
+// versions by way of rclone, just as it lists any other objects, see OBJECT_VERSIONS
+pub struct ObjectVersions;
+
+impl ObjectVersions {
+    // an rclone mount names its remote as its mount source, like "s3:bucket/dir", other mounts must be named by the user
+    pub fn rclone_mounts() -> Vec<ObjectStore> {
+        match MountIter::new() {
+            Ok(mount_iter) => mount_iter
+                .flatten()
+                .filter(|mount_info| mount_info.fstype.as_str() == RCLONE_FSTYPE)
+                .map(|mount_info| ObjectStore {
+                    mount: mount_info.dest,
+                    remote: mount_info.source.to_string_lossy().into_owned(),
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub fn lookup(
+        config: &Config,
+        object_stores: &[ObjectStore],
+        path_set: &[PathData],
+    ) -> HttmResult<VersionsMap> {
+        let rclone_command = which("rclone").map_err(|_err| {
+            HttmError::new(
+                "'rclone' command not found. Make sure the command 'rclone' is in your path.",
+            )
+        })?;
+
+        // the most proximate mount, should one object store be mounted beneath another
+        let opt_object_store = |pathdata: &PathData| {
+            object_stores
+                .iter()
+                .filter(|object_store| pathdata.path_buf.starts_with(&object_store.mount))
+                .max_by_key(|object_store| object_store.mount.components().count())
+        };
+
+        // paths which are not beneath any object store are looked up as usual
+        let other_paths: Vec<PathData> = path_set
+            .iter()
+            .filter(|pathdata| opt_object_store(pathdata).is_none())
+            .cloned()
+            .collect();
+
+        let mut versions_map = VersionsMap::lookup(config, &other_paths)?;
+
+        let object_versions: Vec<(PathData, Vec<PathData>)> = path_set
+            .par_iter()
+            .filter_map(|pathdata| {
+                opt_object_store(pathdata).map(|object_store| (pathdata, object_store))
+            })
+            .map(|(pathdata, object_store)| {
+                let versions =
+                    Self::versions(&rclone_command, object_store, pathdata, &config.uniqueness)?;
+                Ok((pathdata.clone(), versions))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        versions_map.extend(object_versions);
+
+        Ok(versions_map)
+    }
+
+    fn versions(
+        rclone_command: &Path,
+        object_store: &ObjectStore,
+        pathdata: &PathData,
+        uniqueness: &ListSnapsOfType,
+    ) -> HttmResult<Vec<PathData>> {
+        let (remote_dir, file_name) = match (
+            pathdata
+                .path_buf
+                .parent()
+                .and_then(|parent| object_store.remote_path(parent)),
+            pathdata.path_buf.file_name(),
+        ) {
+            (Some(remote_dir), Some(file_name)) => (remote_dir, file_name.to_string_lossy()),
+            _ => return Ok(Vec::new()),
+        };
+
+        let process_output = ExecProcess::new(rclone_command)
+            .arg("lsjson")
+            .arg("--files-only")
+            .arg("--no-mimetype")
+            .args(RCLONE_VERSIONS_FLAGS)
+            .arg(&remote_dir)
+            .stdin(Stdio::null())
+            .output()?;
+
+        if !process_output.status.success() {
+            let msg = format!(
+                "httm was unable to list the versions of {:?}. The 'rclone' command issued the following error: {}",
+                pathdata.path_buf,
+                String::from_utf8_lossy(&process_output.stderr).trim()
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let entries: Vec<Value> = serde_json::from_slice(&process_output.stdout)?;
+
+        let mut versions: Vec<PathData> = entries
+            .iter()
+            .filter_map(|entry| {
+                let name = entry.get("Name").and_then(Value::as_str)?;
+
+                // the current version is the live version
+                if !Self::is_version_of(name, &file_name) {
+                    return None;
+                }
+
+                let size = entry.get("Size").and_then(Value::as_u64)?;
+                let modify_time = entry
+                    .get("ModTime")
+                    .and_then(Value::as_str)
+                    .and_then(Self::parse_mod_time)?;
+
+                Some(PathData {
+                    path_buf: PathBuf::from(ObjectStore::join(&remote_dir, name)),
+                    metadata: Some(PathMetadata { size, modify_time }),
+                })
+            })
+            .collect();
+
+        // like all other versions, oldest to newest, and a remote object can't be hashed, so contents are
+        // compared only by metadata
+        versions.sort_by_key(|version| version.md_infallible().modify_time);
+
+        if !matches!(uniqueness, ListSnapsOfType::All) {
+            versions.dedup_by_key(|version| version.metadata);
+        }
+
+        Ok(versions)
+    }
+
+    fn is_version_of(name: &str, file_name: &str) -> bool {
+        let (stem, extension) = match file_name.rfind('.') {
+            Some(idx) if idx > 0 => file_name.split_at(idx),
+            _ => (file_name, ""),
+        };
+
+        match name
+            .strip_prefix(stem)
+            .and_then(|rest| rest.strip_suffix(extension))
+        {
+            Some(timestamp) => {
+                timestamp.len() == VERSION_TIMESTAMP_LEN
+                    && timestamp.starts_with("-v")
+                    && timestamp[2..]
+                        .chars()
+                        .all(|c| c.is_ascii_digit() || c == '-')
+            }
+            None => false,
+        }
+    }
+
+    fn parse_mod_time(mod_time: &str) -> Option<SystemTime> {
+        let date_time = OffsetDateTime::parse(mod_time, &Rfc3339).ok()?;
+        let secs = u64::try_from(date_time.unix_timestamp()).ok()?;
+
+        Some(UNIX_EPOCH + Duration::new(secs, date_time.nanosecond()))
+    }
+
+    // an object version is named as rclone names it, like "s3:bucket/report-v2023-06-01-123456-000.txt",
+    // and never as an absolute path
+    pub fn is_object_version(path: &Path) -> bool {
+        !path.is_absolute()
+    }
+
+    // the version, as a local file, with the modify time of the version, see FETCH_TO
+    pub fn fetch(version_path: &Path, fetch_to: &Path) -> HttmResult<()> {
+        let rclone_command = which("rclone").map_err(|_err| {
+            HttmError::new(
+                "'rclone' command not found. Make sure the command 'rclone' is in your path.",
+            )
+        })?;
+
+        let status = ExecProcess::new(rclone_command)
+            .arg("copyto")
+            .args(RCLONE_VERSIONS_FLAGS)
+            .arg(version_path)
+            .arg(fetch_to)
+            .stdin(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            let msg =
+                format!("httm was unable to fetch {version_path:?} to {fetch_to:?} via 'rclone'.");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+
+    // the content is streamed by rclone directly to stdout, see CAT
+    pub fn cat(version_path: &Path) -> HttmResult<()> {
+        let rclone_command = which("rclone").map_err(|_err| {
+            HttmError::new(
+                "'rclone' command not found. Make sure the command 'rclone' is in your path.",
+            )
+        })?;
+
+        let status = ExecProcess::new(rclone_command)
+            .arg("cat")
+            .args(RCLONE_VERSIONS_FLAGS)
+            .arg(version_path)
+            .stdin(Stdio::null())
+            .status()?;
+
+        if !status.success() {
+            let msg = format!("httm was unable to fetch {version_path:?} via 'rclone'.");
+            return Err(HttmError::new(&msg).into());
+        }
+
+        Ok(())
+    }
+}
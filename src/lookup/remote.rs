@@ -0,0 +1,185 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command as ExecProcess, Stdio};
+use std::time::SystemTime;
+
+use serde_json::{json, Value};
+use which::which;
+
+use crate::config::generate::RemoteHost;
+use crate::data::paths::{PathData, PathMetadata};
+use crate::library::results::{HttmError, HttmResult};
+use crate::lookup::versions::VersionsMap;
+
+// the robot protocol we expect the remote httm to speak, see exec::robot
+const REMOTE_PROTOCOL_VERSION: u64 = 1;
+
+// a session with an httm on another host, over SSH, in which each path's versions are requested just as a
+// front end would request them, see ROBOT, so the remote host need only have an httm, and no special server
+pub struct RemoteVersions<'a> {
+    remote_host: &'a RemoteHost,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl<'a> RemoteVersions<'a> {
+    pub fn lookup(remote_host: &'a RemoteHost, path_set: &[PathData]) -> HttmResult<VersionsMap> {
+        let mut session = Self::connect(remote_host)?;
+
+        // one session, so paths are requested one at a time, in order
+        let all_snap_versions: BTreeMap<PathData, Vec<PathData>> = path_set
+            .iter()
+            .map(|pathdata| {
+                let remote_path = remote_host.remote_path(&pathdata.path_buf);
+                let values = session.versions(&remote_path)?;
+                Ok((pathdata.clone(), values))
+            })
+            .collect::<HttmResult<_>>()?;
+
+        session.quit()?;
+
+        Ok(all_snap_versions.into())
+    }
+
+    fn connect(remote_host: &'a RemoteHost) -> HttmResult<Self> {
+        let ssh_command = which("ssh").map_err(|_err| {
+            HttmError::new("'ssh' command not found. Make sure the command 'ssh' is in your path.")
+        })?;
+
+        // stderr is inherited, so the user sees any password prompt, or ssh error, as is,
+        // and "--" ends ssh's options, so no destination may be taken as one
+        let mut child = ExecProcess::new(ssh_command)
+            .arg("-T")
+            .arg("--")
+            .arg(&remote_host.destination)
+            .arg(&remote_host.remote_command)
+            .args(["--robot", "--raw"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, BufReader::new(stdout)),
+            _ => {
+                return Err(
+                    HttmError::new("Could not open a session with the remote host.").into(),
+                );
+            }
+        };
+
+        let mut session = Self {
+            remote_host,
+            child,
+            stdin,
+            stdout,
+            next_id: 0,
+        };
+
+        let greeting = session.read_response()?;
+
+        if greeting.get("protocol").and_then(Value::as_u64) != Some(REMOTE_PROTOCOL_VERSION) {
+            return Err(HttmError::new(&format!(
+                "The httm on {} does not speak the expected protocol.  Perhaps the remote httm is too old or too new?",
+                remote_host.destination
+            ))
+            .into());
+        }
+
+        Ok(session)
+    }
+
+    fn request(&mut self, cmd: &str, opt_path: Option<&Path>) -> HttmResult<Value> {
+        self.next_id += 1;
+
+        let request = match opt_path {
+            Some(path) => json!({ "id": self.next_id, "cmd": cmd, "path": path }),
+            None => json!({ "id": self.next_id, "cmd": cmd }),
+        };
+
+        writeln!(self.stdin, "{request}")?;
+        self.stdin.flush()?;
+
+        self.read_response()
+    }
+
+    fn read_response(&mut self) -> HttmResult<Value> {
+        let mut line = String::new();
+
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(HttmError::new(&format!(
+                "The session with {} closed unexpectedly.  Is httm in the remote user's PATH?",
+                self.remote_host.destination
+            ))
+            .into());
+        }
+
+        Ok(serde_json::from_str(&line)?)
+    }
+
+    // the remote httm reports a path with no versions, or no snapshot-capable filesystem, as an error, which,
+    // just as with a local lookup, is only an error should no path be found at all
+    fn versions(&mut self, remote_path: &Path) -> HttmResult<Vec<PathData>> {
+        let response = self.request("list-versions", Some(remote_path))?;
+
+        if response.get("ok").and_then(Value::as_bool) != Some(true) {
+            return Ok(Vec::new());
+        }
+
+        let versions = match response.get("versions").and_then(Value::as_array) {
+            Some(versions) => versions,
+            None => return Ok(Vec::new()),
+        };
+
+        Ok(versions
+            .iter()
+            .filter_map(|version| self.remote_pathdata(version))
+            .collect())
+    }
+
+    // a remote version is displayed as it would be given to scp or rsync, like HOST:/.zfs/snapshot/snap_1/file
+    fn remote_pathdata(&self, version: &Value) -> Option<PathData> {
+        let path = version.get("path").and_then(Value::as_str)?;
+
+        let metadata = version.get("metadata").and_then(|metadata| {
+            let size = metadata.get("size").and_then(Value::as_u64)?;
+            let modify_time: SystemTime =
+                serde_json::from_value(metadata.get("modify_time")?.clone()).ok()?;
+
+            Some(PathMetadata { size, modify_time })
+        });
+
+        Some(PathData {
+            path_buf: PathBuf::from(format!("{}:{path}", self.remote_host.destination)),
+            metadata,
+        })
+    }
+
+    fn quit(mut self) -> HttmResult<()> {
+        let _ = self.request("quit", None)?;
+
+        drop(self.stdin);
+        self.child.wait()?;
+
+        Ok(())
+    }
+}
@@ -20,8 +20,9 @@ use std::{collections::BTreeMap, ops::Deref};
 
 use rayon::prelude::*;
 
-use crate::config::generate::ListSnapsFilters;
+use crate::config::generate::{Config, ListSnapsFilters};
 use crate::data::paths::PathData;
+use crate::exec::digest::DigestOfSnaps;
 use crate::library::results::{HttmError, HttmResult};
 use crate::lookup::versions::VersionsMap;
 use crate::parse::aliases::FilesystemType;
@@ -110,7 +111,7 @@ impl SnapNameMap {
         Ok(inner.into())
     }
 
-    fn deconstruct_snap_paths(pathdata: &PathData) -> Option<String> {
+    pub fn deconstruct_snap_paths(pathdata: &PathData) -> Option<String> {
         let path_string = &pathdata.path_buf.to_string_lossy();
 
         let (dataset_path, (snap, _relpath)) = if let Some((lhs, rhs)) =
@@ -138,3 +139,43 @@ impl SnapNameMap {
         }
     }
 }
+
+// which snapshot, and which dataset, a version came from, see SNAP_INFO
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapOrigin {
+    // the live version has no snapshot
+    pub opt_snap_name: Option<String>,
+    pub dataset: String,
+}
+
+impl SnapOrigin {
+    pub fn new(config: &Config, pathdata: &PathData) -> Option<Self> {
+        let dataset_collection = &config.dataset_collection;
+
+        // a snapshot version is found within a snap mount, while the live version is found within its dataset
+        let (mount, opt_snap_name) =
+            match dataset_collection.snap_mount_containing(&pathdata.path_buf) {
+                Some((mount, snap_mount)) => {
+                    (mount.as_path(), Some(DigestOfSnaps::snap_name(snap_mount)))
+                }
+                None => (
+                    pathdata
+                        .proximate_dataset(&dataset_collection.map_of_datasets)
+                        .ok()?,
+                    None,
+                ),
+            };
+
+        let dataset = dataset_collection
+            .map_of_datasets
+            .get(mount)?
+            .source
+            .to_string_lossy()
+            .into_owned();
+
+        Some(Self {
+            opt_snap_name,
+            dataset,
+        })
+    }
+}
@@ -21,20 +21,34 @@ use std::{
     ops::Deref,
     ops::DerefMut,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
 use rayon::prelude::*;
 
-use crate::library::results::{HttmError, HttmResult};
-use crate::{
-    config::generate::ListSnapsOfType,
-    data::paths::{CompareVersionsContainer, PathData},
+use crate::config::generate::{
+    BulkExclusion, CatVersion, Config, DatasetPreference, DittoOfType, ExecMode, LastSnapMode,
+};
+use crate::data::filesystem_info::FilesystemInfo;
+use crate::library::network_io::NetworkIo;
+use crate::library::results::{
+    HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE, NO_VERSIONS_EXIT_CODE,
 };
+#[cfg(all(feature = "uring", target_os = "linux"))]
+use crate::library::uring::UringStat;
+use crate::library::version_index::VersionIndex;
+use crate::lookup::object_versions::ObjectVersions;
+use crate::lookup::remote::RemoteVersions;
+use crate::parse::mounts::DatasetMetadata;
+use crate::parse::snaps::MapOfSnaps;
 use crate::{
-    config::generate::{BulkExclusion, Config, LastSnapMode},
-    GLOBAL_CONFIG,
+    config::generate::ListSnapsOfType,
+    data::paths::{CompareVersionsContainer, HashFromFile, PathData},
 };
 
+pub const NOTHING_FOUND_MSG: &str = "httm could not find either a live copy or a snapshot copy of any specified file, so, umm, 🤷? Please try another file.";
+pub const NO_SNAPSHOT_SOURCE_MSG: &str = "httm could not identify a snapshot-capable filesystem for any specified file.  Maybe consider specifying manually at SNAP_POINT?";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VersionsMap {
     inner: BTreeMap<PathData, Vec<PathData>>,
@@ -66,80 +80,185 @@ impl VersionsMap {
     }
 
     pub fn new(config: &Config, path_set: &[PathData]) -> HttmResult<VersionsMap> {
+        let mut versions_map = match (&config.opt_remote_host, &config.opt_object_stores) {
+            (Some(remote_host), _) => RemoteVersions::lookup(remote_host, path_set)?,
+            (None, Some(object_stores)) => ObjectVersions::lookup(config, object_stores, path_set)?,
+            (None, None) => Self::lookup(config, path_set)?,
+        };
+
+        // check if all files (snap and live) do not exist, if this is true, then user probably messed up
+        // and entered a file that never existed (that is, perhaps a wrong file name)?
+        if versions_map.is_nothing_found(config) {
+            return Err(versions_map.nothing_found_error().into());
+        }
+
+        versions_map.post_process(config);
+
+        Ok(versions_map)
+    }
+
+    // lookup without any check or post processing, see VersionsMap::new, useful where
+    // we lookup one path at a time, and an error upon a single path would be premature
+    pub fn lookup(config: &Config, path_set: &[PathData]) -> HttmResult<VersionsMap> {
+        let opt_newest_versions = NewestVersions::new(config);
+
         let all_snap_versions: BTreeMap<PathData, Vec<PathData>> = path_set
             .par_iter()
-            .flat_map(ProximateDatasetAndOptAlts::new)
-            .map(|prox_opt_alts| {
+            .filter_map(|pathdata| {
+                // the versions found beneath another root are still the versions of this path, see RELATIVE_TO
+                let opt_search_pathdata = config
+                    .opt_relative_to
+                    .as_ref()
+                    .and_then(|relative_to| relative_to.search_pathdata(pathdata));
+
+                let search_pathdata = opt_search_pathdata.as_ref().unwrap_or(pathdata);
+
+                let prox_opt_alts =
+                    ProximateDatasetAndOptAlts::new(config, search_pathdata).ok()?;
+
                 // don't want to flatten this iter here b/c
                 // we want to keep these values with this key
-                let key = prox_opt_alts.pathdata.clone();
-                let values = prox_opt_alts
+                let key = pathdata.clone();
+                let res = prox_opt_alts
                     .into_search_bundles()
                     .par_bridge()
-                    .flat_map(|relative_path_snap_mounts| {
-                        relative_path_snap_mounts.versions_processed(&config.uniqueness)
+                    .map(|relative_path_snap_mounts| {
+                        let opt_newest = match &opt_newest_versions {
+                            Some(newest_versions) => relative_path_snap_mounts.newest_versions(
+                                &config.dataset_collection,
+                                newest_versions,
+                                &config.uniqueness,
+                            )?,
+                            None => None,
+                        };
+
+                        match opt_newest {
+                            Some(versions) => Ok(versions),
+                            None => {
+                                relative_path_snap_mounts.versions_processed(&config.uniqueness)
+                            }
+                        }
                     })
-                    .collect();
-                (key, values)
+                    .collect::<HttmResult<Vec<Vec<PathData>>>>()
+                    .map(|values| (key, values.into_iter().flatten().collect()));
+
+                Some(res)
             })
-            .collect();
+            .collect::<HttmResult<_>>()?;
 
-        let mut versions_map: VersionsMap = all_snap_versions.into();
+        Ok(all_snap_versions.into())
+    }
 
-        // check if all files (snap and live) do not exist, if this is true, then user probably messed up
-        // and entered a file that never existed (that is, perhaps a wrong file name)?
-        if versions_map.values().all(std::vec::Vec::is_empty)
-            && versions_map
-                .keys()
-                .all(|pathdata| pathdata.metadata.is_none())
+    pub fn is_nothing_found(&self, config: &Config) -> bool {
+        self.values().all(std::vec::Vec::is_empty)
+            && self.keys().all(|pathdata| pathdata.metadata.is_none())
             && !matches!(config.opt_bulk_exclusion, Some(BulkExclusion::NoSnap))
-        {
-            return Err(HttmError::new(
-                "httm could not find either a live copy or a snapshot copy of any specified file, so, umm, 🤷? Please try another file.",
-            )
-            .into());
+    }
+
+    // a path which resolves to no dataset never makes it into the map, so an empty map means
+    // no path resides on a snapshot-capable filesystem, see NO_SNAPSHOT_SOURCE_EXIT_CODE
+    pub fn nothing_found_error(&self) -> HttmError {
+        if self.is_empty() {
+            return HttmError::with_exit_code(NO_SNAPSHOT_SOURCE_MSG, NO_SNAPSHOT_SOURCE_EXIT_CODE);
         }
 
+        HttmError::with_exit_code(NOTHING_FOUND_MSG, NO_VERSIONS_EXIT_CODE)
+    }
+
+    // whether no snapshot version was found for any path, which, so long as snapshot versions
+    // are wanted, exits with NO_VERSIONS_EXIT_CODE
+    pub fn is_no_snap_versions(&self, config: &Config) -> bool {
+        self.values().all(std::vec::Vec::is_empty)
+            && !matches!(config.opt_bulk_exclusion, Some(BulkExclusion::NoSnap))
+    }
+
+    pub fn post_process(&mut self, config: &Config) {
         // process last snap mode after omit_ditto
         if config.opt_omit_ditto {
-            versions_map.omit_ditto()
+            self.omit_ditto(&config.ditto_of_type)
         }
 
         if let Some(last_snap_mode) = &config.opt_last_snap {
-            versions_map.last_snap(last_snap_mode)
+            self.last_snap(last_snap_mode, &config.ditto_of_type)
         }
-
-        Ok(versions_map)
     }
 
-    pub fn is_live_version_redundant(live_pathdata: &PathData, snaps: &[PathData]) -> bool {
+    pub fn is_live_version_redundant(
+        live_pathdata: &PathData,
+        snaps: &[PathData],
+        ditto_of_type: &DittoOfType,
+    ) -> bool {
         if let Some(last_snap) = snaps.last() {
-            return last_snap.md_infallible() == live_pathdata.md_infallible();
+            return Self::is_ditto(live_pathdata, last_snap, ditto_of_type);
         }
 
         false
     }
 
-    fn omit_ditto(&mut self) {
+    pub fn is_ditto(
+        live_pathdata: &PathData,
+        snap_pathdata: &PathData,
+        ditto_of_type: &DittoOfType,
+    ) -> bool {
+        let live_md = live_pathdata.md_infallible();
+        let snap_md = snap_pathdata.md_infallible();
+
+        match ditto_of_type {
+            DittoOfType::ModifyTime => live_md.modify_time == snap_md.modify_time,
+            DittoOfType::Metadata => live_md == snap_md,
+            // only bother reading the files when the sizes match
+            DittoOfType::Contents => {
+                if live_md.size != snap_md.size {
+                    return false;
+                }
+
+                let (live_hash, snap_hash) = rayon::join(
+                    || HashFromFile::try_from(live_pathdata.path_buf.as_path()),
+                    || HashFromFile::try_from(snap_pathdata.path_buf.as_path()),
+                );
+
+                match (live_hash, snap_hash) {
+                    (Ok(live_hash), Ok(snap_hash)) => {
+                        live_hash.into_inner() == snap_hash.into_inner()
+                    }
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    fn omit_ditto(&mut self, ditto_of_type: &DittoOfType) {
         self.iter_mut().for_each(|(pathdata, snaps)| {
             // process omit_ditto before last snap
-            if Self::is_live_version_redundant(pathdata, snaps) {
+            if Self::is_live_version_redundant(pathdata, snaps, ditto_of_type) {
                 snaps.pop();
             }
         });
     }
 
-    fn last_snap(&mut self, last_snap_mode: &LastSnapMode) {
+    fn last_snap(&mut self, last_snap_mode: &LastSnapMode, ditto_of_type: &DittoOfType) {
         self.iter_mut().for_each(|(pathdata, snaps)| {
+            // snaps are sorted by modify time, so the last before the requested time is the one we want
+            if let LastSnapMode::Before(requested_time) = last_snap_mode {
+                *snaps = snaps
+                    .iter()
+                    .rev()
+                    .find(|snap| &snap.md_infallible().modify_time < requested_time)
+                    .cloned()
+                    .into_iter()
+                    .collect();
+                return;
+            }
+
             *snaps = match snaps.last() {
                 // if last() is some, then should be able to unwrap pop()
                 Some(last) => match last_snap_mode {
                     LastSnapMode::Any => vec![last.to_owned()],
-                    LastSnapMode::DittoOnly if pathdata.md_infallible() == last.md_infallible() => {
+                    LastSnapMode::DittoOnly if Self::is_ditto(pathdata, last, ditto_of_type) => {
                         vec![last.to_owned()]
                     }
                     LastSnapMode::NoDittoExclusive | LastSnapMode::NoDittoInclusive
-                        if pathdata.md_infallible() != last.md_infallible() =>
+                        if !Self::is_ditto(pathdata, last, ditto_of_type) =>
                     {
                         vec![last.to_owned()]
                     }
@@ -156,15 +275,67 @@ impl VersionsMap {
     }
 }
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+// some policies only ever need the newest few versions, like LAST_SNAP, or CAT, so, given snapshots in order,
+// we may stop looking once we have them, see RelativePathAndSnapMounts::newest_versions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewestVersions {
+    count: usize,
+    opt_bound: Option<VersionBound>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum VersionBound {
+    Before(SystemTime),
+    AsOf(SystemTime),
+}
+
+impl NewestVersions {
+    pub fn new(config: &Config) -> Option<Self> {
+        let (count, opt_bound) = match (&config.exec_mode, &config.opt_last_snap) {
+            (ExecMode::Cat(CatVersion::Nth(nth)), _) => (*nth, None),
+            (ExecMode::Cat(CatVersion::AsOf(as_of)), _) => (1, Some(VersionBound::AsOf(*as_of))),
+            (_, Some(LastSnapMode::Before(before))) => (1, Some(VersionBound::Before(*before))),
+            (_, Some(_)) => (1, None),
+            _ => return None,
+        };
+
+        // omit ditto compares the live version to the newest of all versions, not just the newest within a bound,
+        // and, when the newest is a ditto, removes it, so we need one more
+        if config.opt_omit_ditto && opt_bound.is_some() {
+            return None;
+        }
+
+        Some(Self {
+            count: count + usize::from(config.opt_omit_ditto),
+            opt_bound,
+        })
+    }
+
+    fn is_within_bound(&self, version: &PathData) -> bool {
+        let modify_time = version.md_infallible().modify_time;
+
+        match &self.opt_bound {
+            Some(VersionBound::Before(before)) => modify_time < *before,
+            Some(VersionBound::AsOf(as_of)) => modify_time <= *as_of,
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ProximateDatasetAndOptAlts<'a> {
     pub pathdata: &'a PathData,
+    // the collection of the config which asked, which, in an interactive session, may have been refreshed
+    pub dataset_collection: &'a FilesystemInfo,
     pub proximate_dataset_mount: &'a Path,
+    pub opt_merged_dataset_mount: Option<&'a Path>,
     pub datasets_of_interest: Vec<PathBuf>,
 }
 
 impl<'a> ProximateDatasetAndOptAlts<'a> {
-    pub fn new(pathdata: &'a PathData) -> HttmResult<Self> {
+    pub fn new(config: &'a Config, pathdata: &'a PathData) -> HttmResult<Self> {
+        let dataset_collection: &FilesystemInfo = &config.dataset_collection;
+
         // here, we take our file path and get back possibly multiple ZFS dataset mountpoints
         // and our most proximate dataset mount point (which is always the same) for
         // a single file
@@ -178,20 +349,30 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
         // will compare the most proximate dataset to our our canonical path and the difference
         // between ZFS mount point and the canonical path is the path we will use to search the
         // hidden snapshot dirs
-        let proximate_dataset_mount: &Path = match GLOBAL_CONFIG
-            .dataset_collection
+        //
+        // when a path is covered by both a user defined alias and a native dataset,
+        // the user's dataset preference decides which is used, or whether both are searched
+        let opt_alias_dataset: Option<&Path> = dataset_collection
             .opt_map_of_aliases
             .as_ref()
-            .and_then(|map_of_aliases| pathdata.alias_dataset(map_of_aliases))
-        {
-            Some(alias_dataset) => alias_dataset,
-            None => {
-                pathdata.proximate_dataset(&GLOBAL_CONFIG.dataset_collection.map_of_datasets)?
-            }
-        };
+            .and_then(|map_of_aliases| pathdata.alias_dataset(map_of_aliases));
+
+        let (proximate_dataset_mount, opt_merged_dataset_mount): (&Path, Option<&Path>) =
+            match (opt_alias_dataset, &config.dataset_preference) {
+                (Some(alias_dataset), DatasetPreference::Alias) => (alias_dataset, None),
+                (Some(alias_dataset), DatasetPreference::Both) => (
+                    alias_dataset,
+                    pathdata
+                        .proximate_dataset(&dataset_collection.map_of_datasets)
+                        .ok(),
+                ),
+                (Some(_), DatasetPreference::Native) | (None, _) => (
+                    pathdata.proximate_dataset(&dataset_collection.map_of_datasets)?,
+                    None,
+                ),
+            };
 
-        let res: Self = match GLOBAL_CONFIG
-            .dataset_collection
+        let res: Self = match dataset_collection
             .opt_map_of_alts
             .as_ref()
             .and_then(|map_of_alts| map_of_alts.get(proximate_dataset_mount))
@@ -202,14 +383,18 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
 
                 Self {
                     pathdata,
+                    dataset_collection,
                     proximate_dataset_mount,
+                    opt_merged_dataset_mount,
                     datasets_of_interest,
                 }
             }
 
             None => Self {
                 pathdata,
+                dataset_collection,
                 proximate_dataset_mount,
+                opt_merged_dataset_mount,
                 datasets_of_interest: vec![proximate_dataset_mount.to_path_buf()],
             },
         };
@@ -218,15 +403,31 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
     }
 
     pub fn into_search_bundles(self) -> impl Iterator<Item = RelativePathAndSnapMounts<'a>> {
+        // a merged native dataset has its own relative path, so it can't share
+        // the proximate dataset mount with the other datasets of interest
+        let opt_merged_bundle = self
+            .opt_merged_dataset_mount
+            .and_then(|merged_dataset_mount| {
+                RelativePathAndSnapMounts::new(
+                    self.dataset_collection,
+                    self.pathdata,
+                    merged_dataset_mount,
+                    merged_dataset_mount,
+                )
+                .ok()
+            });
+
         self.datasets_of_interest
             .into_iter()
             .flat_map(|dataset_of_interest| {
                 RelativePathAndSnapMounts::new(
+                    self.dataset_collection,
                     self.pathdata,
                     self.proximate_dataset_mount,
                     &dataset_of_interest,
                 )
             })
+            .chain(opt_merged_bundle)
     }
 }
 
@@ -234,11 +435,15 @@ impl<'a> ProximateDatasetAndOptAlts<'a> {
 pub struct RelativePathAndSnapMounts<'a> {
     pub pathdata: &'a PathData,
     pub relative_path: &'a Path,
+    pub dataset_of_interest: &'a Path,
     pub snap_mounts: &'a Vec<PathBuf>,
+    pub opt_dataset_info: Option<&'a DatasetMetadata>,
+    pub is_remote: bool,
 }
 
 impl<'a> RelativePathAndSnapMounts<'a> {
     fn new(
+        dataset_collection: &'a FilesystemInfo,
         pathdata: &'a PathData,
         proximate_dataset_mount: &'a Path,
         dataset_of_interest: &Path,
@@ -249,10 +454,9 @@ impl<'a> RelativePathAndSnapMounts<'a> {
         // for user specified dirs/aliases these are specified by the user
         let relative_path = pathdata.relative_path(proximate_dataset_mount)?;
 
-        let snap_mounts = GLOBAL_CONFIG
-            .dataset_collection
+        let (dataset_of_interest, snap_mounts) = dataset_collection
             .map_of_snaps
-            .get(dataset_of_interest)
+            .get_key_value(dataset_of_interest)
             .ok_or_else(|| {
                 HttmError::new(
                     "httm could find no snap mount for your files.  \
@@ -263,68 +467,275 @@ impl<'a> RelativePathAndSnapMounts<'a> {
         Ok(Self {
             pathdata,
             relative_path,
+            dataset_of_interest,
             snap_mounts,
+            opt_dataset_info: dataset_collection.map_of_datasets.get(dataset_of_interest),
+            is_remote: NetworkIo::is_remote(dataset_of_interest),
         })
     }
 
-    pub fn versions_processed(&'a self, uniqueness: &ListSnapsOfType) -> Vec<PathData> {
-        let all_versions = self.versions_unprocessed(uniqueness);
+    pub fn versions_processed(&'a self, uniqueness: &ListSnapsOfType) -> HttmResult<Vec<PathData>> {
+        let all_versions = self.versions_unprocessed()?;
 
-        Self::sort_dedup_versions(all_versions, uniqueness)
+        Ok(Self::sort_dedup_versions(
+            all_versions.into_par_iter(),
+            uniqueness,
+        ))
     }
 
-    pub fn last_version(&self) -> Option<PathData> {
-        let mut sorted_versions = self.versions_processed(&ListSnapsOfType::All);
+    pub fn last_version(&self) -> HttmResult<Option<PathData>> {
+        let mut sorted_versions = self.versions_processed(&ListSnapsOfType::All)?;
 
-        sorted_versions.pop()
+        Ok(sorted_versions.pop())
     }
 
-    fn versions_unprocessed(
-        &'a self,
-        uniqueness: &'a ListSnapsOfType,
-    ) -> impl ParallelIterator<Item = CompareVersionsContainer> + 'a {
+    // when the provider knows the order in which its snapshots were created, see SnapOrderSource, we probe the newest
+    // snapshots first, and stop once we have as many versions as the policy needs, instead of a stat of every snapshot.
+    // this assumes, as is nearly always the case, a file's modify time never goes backwards from one snapshot to the next
+    pub fn newest_versions(
+        &self,
+        dataset_collection: &FilesystemInfo,
+        newest_versions: &NewestVersions,
+        uniqueness: &ListSnapsOfType,
+    ) -> HttmResult<Option<Vec<PathData>>> {
+        let newest_first = match MapOfSnaps::newest_first(
+            &dataset_collection.map_of_datasets,
+            self.dataset_of_interest,
+            self.snap_mounts,
+        ) {
+            Some(newest_first) => newest_first,
+            None => return Ok(None),
+        };
+
+        let snap_mounts: Vec<&PathBuf> = newest_first.iter().collect();
+
+        // probe only so many snapshots at once as we might probe in parallel anyway
+        let chunk_size = if self.is_remote {
+            NetworkIo::batch_size()
+        } else {
+            rayon::current_num_threads()
+        };
+
+        let mut found: Vec<PathData> = Vec::new();
+        let mut processed: Vec<PathData> = Vec::new();
+
+        for chunk in snap_mounts.chunks(chunk_size.max(1)) {
+            found.extend(
+                self.probe(chunk)?
+                    .into_iter()
+                    .flat_map(|(_snap_mount, opt_version)| opt_version)
+                    .filter(|version| newest_versions.is_within_bound(version)),
+            );
+
+            processed = Self::sort_dedup_versions(found.clone().into_par_iter(), uniqueness);
+
+            if processed.len() >= newest_versions.count {
+                break;
+            }
+        }
+
+        // like all other versions, oldest to newest
+        processed.sort_by_key(|version| version.md_infallible().modify_time);
+
+        Ok(Some(processed))
+    }
+
+    fn versions_unprocessed(&'a self) -> HttmResult<Vec<PathData>> {
+        // with an index, only those snapshots the index has never seen are stat-ed, see VersionIndex
+        if VersionIndex::is_enabled() {
+            return VersionIndex::versions(self);
+        }
+
         // get the DirEntry for our snapshot path which will have all our possible
         // snapshots, like so: .zfs/snapshots/<some snap name>/
-        self
-            .snap_mounts
+        let snap_mounts: Vec<&PathBuf> = self.snap_mounts.iter().collect();
+
+        let versions = self
+            .probe(&snap_mounts)?
+            .into_iter()
+            .flat_map(|(_snap_mount, opt_version)| opt_version)
+            .collect();
+
+        Ok(versions)
+    }
+
+    // whether each snap mount contains our relative path, and, if remote, stat in batches,
+    // and only so many batches at once, see NetworkIo
+    pub fn probe<'b>(
+        &self,
+        snap_mounts: &[&'b PathBuf],
+    ) -> HttmResult<Vec<(&'b PathBuf, Option<PathData>)>> {
+        if self.is_remote {
+            let batches = snap_mounts
+                .par_chunks(NetworkIo::batch_size())
+                .map(|batch| {
+                    NetworkIo::scheduled(|| {
+                        batch
+                            .iter()
+                            .map(|snap_mount| Ok((*snap_mount, self.snap_version(snap_mount)?)))
+                            .collect::<HttmResult<Vec<(&PathBuf, Option<PathData>)>>>()
+                    })
+                })
+                .collect::<HttmResult<Vec<_>>>()?;
+
+            return Ok(batches.into_iter().flatten().collect());
+        }
+
+        #[cfg(all(feature = "uring", target_os = "linux"))]
+        if UringStat::is_enabled() {
+            let batches = snap_mounts
+                .par_chunks(UringStat::BATCH_SIZE)
+                .map(|batch| self.probe_batched(batch))
+                .collect::<HttmResult<Vec<_>>>()?;
+
+            return Ok(batches.into_iter().flatten().collect());
+        }
+
+        snap_mounts
             .par_iter()
-            .map(|path| path.join(self.relative_path))
-            .filter_map(|joined_path| {
-                match joined_path.symlink_metadata() {
-                    Ok(md) => {
-                        Some(CompareVersionsContainer::new(PathData::new(joined_path.as_path(), Some(md)), uniqueness))
-                    },
-                    Err(err) => {
-                        match err.kind() {
-                            // if we do not have permissions to read the snapshot directories
-                            // fail/panic printing a descriptive error instead of flattening
-                            ErrorKind::PermissionDenied => {
-                                eprintln!("Error: When httm tried to find a file contained within a snapshot directory, permission was denied.  \
-                                Perhaps you need to use sudo or equivalent to view the contents of this snapshot (for instance, btrfs by default creates privileged snapshots).  \
-                                \nDetails: {err}");
-                                std::process::exit(1)
-                            },
-                            // if file metadata is not found, or is otherwise not available, 
-                            // continue, it simply means we do not have a snapshot of this file
-                            _ => None,
-                        }
-                    },
-                }
+            .map(|snap_mount| Ok((*snap_mount, self.snap_version(snap_mount)?)))
+            .collect()
+    }
+
+    // like snap_version, but for a batch of local snap mounts at once, see UringStat
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    fn probe_batched<'b>(
+        &self,
+        batch: &[&'b PathBuf],
+    ) -> HttmResult<Vec<(&'b PathBuf, Option<PathData>)>> {
+        let joined_paths: Vec<PathBuf> = batch
+            .iter()
+            .map(|snap_mount| snap_mount.join(self.relative_path))
+            .collect();
+
+        let results = match UringStat::batch(&joined_paths) {
+            Ok(results) => results,
+            Err(_) => {
+                return batch
+                    .iter()
+                    .map(|snap_mount| Ok((*snap_mount, self.snap_version(snap_mount)?)))
+                    .collect()
+            }
+        };
+
+        batch
+            .iter()
+            .zip(joined_paths)
+            .zip(results)
+            .map(|((snap_mount, joined_path), result)| {
+                let opt_version = match result {
+                    // a symlink is canonicalized by PathData::new, and a permission error is explained
+                    // by snap_version, so each takes the ordinary path
+                    Ok(uring_md) if uring_md.is_symlink => self.snap_version(snap_mount)?,
+                    Err(err) if err.kind() == ErrorKind::PermissionDenied => {
+                        self.snap_version(snap_mount)?
+                    }
+                    // the joined path is already absolute, so, unlike PathData::new, there is nothing to canonicalize
+                    Ok(uring_md) => Some(PathData {
+                        path_buf: joined_path,
+                        metadata: uring_md.opt_metadata,
+                    }),
+                    Err(_) => None,
+                };
+
+                Ok((*snap_mount, opt_version))
             })
+            .collect()
+    }
+
+    fn snap_version(&self, snap_mount: &Path) -> HttmResult<Option<PathData>> {
+        let joined_path = snap_mount.join(self.relative_path);
+
+        match joined_path.symlink_metadata() {
+            Ok(md) => Ok(Some(PathData::new(joined_path.as_path(), Some(md)))),
+            Err(err) => {
+                match err.kind() {
+                    // if we do not have permissions to read the snapshot directories
+                    // return a descriptive error instead of flattening
+                    ErrorKind::PermissionDenied => {
+                        let msg = format!("When httm tried to find a file contained within a snapshot directory, permission was denied.  \
+                        Perhaps you need to use sudo or equivalent to view the contents of this snapshot (for instance, btrfs by default creates privileged snapshots).  \
+                        \nDetails: {err}");
+                        Err(HttmError::new(&msg).into())
+                    }
+                    // if file metadata is not found, or is otherwise not available,
+                    // continue, it simply means we do not have a snapshot of this file
+                    _ => Ok(None),
+                }
+            }
+        }
     }
 
     // remove duplicates with the same system modify time and size/file len (or contents! See --uniqueness)
     #[allow(clippy::mutable_key_type)]
     fn sort_dedup_versions(
-        iter: impl ParallelIterator<Item = CompareVersionsContainer>,
+        iter: impl ParallelIterator<Item = PathData>,
         snaps_of_type: &ListSnapsOfType,
     ) -> Vec<PathData> {
         match snaps_of_type {
-            ListSnapsOfType::All => iter.map(PathData::from).collect(),
-            ListSnapsOfType::UniqueContents | ListSnapsOfType::UniqueMetadata => {
-                let sorted_and_deduped: BTreeSet<CompareVersionsContainer> = iter.collect();
+            ListSnapsOfType::All => iter.collect(),
+            ListSnapsOfType::UniqueMetadata => {
+                let sorted_and_deduped: BTreeSet<CompareVersionsContainer> =
+                    iter.map(CompareVersionsContainer::from).collect();
                 sorted_and_deduped.into_iter().map(PathData::from).collect()
             }
+            ListSnapsOfType::UniqueContents => {
+                let mut sorted: Vec<PathData> = iter.collect();
+                sorted.par_sort_unstable_by_key(|pathdata| {
+                    let md = pathdata.md_infallible();
+                    (md.modify_time, md.size)
+                });
+                Self::dedup_by_contents(sorted)
+            }
         }
     }
+
+    // collapses neighboring versions with the same contents, whatever their modify times, keeping the earliest.
+    // only versions with a neighbor of the same size could be the same, so only those are read and hashed
+    fn dedup_by_contents(sorted: Vec<PathData>) -> Vec<PathData> {
+        let sizes: Vec<u64> = sorted
+            .iter()
+            .map(|pathdata| pathdata.md_infallible().size)
+            .collect();
+
+        let opt_hashes: Vec<Option<u32>> = sorted
+            .par_iter()
+            .enumerate()
+            .map(|(idx, pathdata)| {
+                let has_same_size_neighbor = (idx > 0 && sizes[idx - 1] == sizes[idx])
+                    || sizes.get(idx + 1) == Some(&sizes[idx]);
+
+                if !has_same_size_neighbor {
+                    return None;
+                }
+
+                HashFromFile::try_from(pathdata.path_buf.as_path())
+                    .ok()
+                    .map(HashFromFile::into_inner)
+            })
+            .collect();
+
+        let mut opt_last_kept: Option<(u64, Option<u32>)> = None;
+
+        sorted
+            .into_iter()
+            .zip(sizes)
+            .zip(opt_hashes)
+            .filter_map(|((pathdata, size), opt_hash)| {
+                let is_dup = match (opt_last_kept, opt_hash) {
+                    (Some((last_size, Some(last_hash))), Some(hash)) => {
+                        last_size == size && last_hash == hash
+                    }
+                    _ => false,
+                };
+
+                if is_dup {
+                    return None;
+                }
+
+                opt_last_kept = Some((size, opt_hash));
+                Some(pathdata)
+            })
+            .collect()
+    }
 }
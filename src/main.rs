@@ -15,115 +15,90 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-mod data {
-    pub mod filesystem_info;
-    pub mod paths;
-    pub mod selection;
-}
-mod display_map {
-    pub mod format;
-}
-mod display_versions {
-    pub mod format;
-    pub mod num_versions;
-    pub mod wrapper;
-}
-mod exec {
-    pub mod deleted;
-    pub mod interactive;
-    pub mod preview;
-    pub mod purge;
-    pub mod recursive;
-    pub mod roll_forward;
-    pub mod snap_mounts;
-}
-mod config {
-    pub mod generate;
-    pub mod install_hot_keys;
-}
-mod library {
-    pub mod diff_copy;
-    pub mod iter_extensions;
-    pub mod results;
-    pub mod snap_guard;
-    pub mod utility;
-}
-mod lookup {
-    pub mod deleted;
-    pub mod file_mounts;
-    pub mod snap_names;
-    pub mod versions;
-}
-mod parse {
-    pub mod aliases;
-    pub mod alts;
-    pub mod mounts;
-    pub mod snaps;
-}
+use httm::config::generate::ExecMode;
+use httm::display_map::datasets::PrintDatasetMap;
+use httm::display_map::format::PrintAsMap;
+use httm::display_versions::stream::RawStream;
+use httm::exec::batch_restore::BatchRestore;
+use httm::exec::bookmarks::BookmarkView;
+use httm::exec::cat::CatFromSnaps;
+use httm::exec::churn::ChurnLeaderboard;
+use httm::exec::complete::CompleteFromSnaps;
+use httm::exec::dedup_ratio::DedupRatio;
+use httm::exec::diff::DiffVersions;
+use httm::exec::digest::DigestOfSnaps;
+use httm::exec::find::FindInSnaps;
+use httm::exec::history::HistoryView;
+use httm::exec::mount_snap::MountSnap;
+use httm::exec::pipeline::Pipeline;
+use httm::exec::prune_ditto::PruneDitto;
+use httm::exec::purge::PurgeSnaps;
+use httm::exec::recursive::NonInteractiveRecursiveWrapper;
+use httm::exec::robot::RobotSession;
+use httm::exec::roll_forward::RollForward;
+use httm::exec::self_test::SelfTest;
+use httm::exec::serve::ServeVersions;
+use httm::exec::size_report::SizeReport;
+use httm::exec::snap_mounts::SnapshotMounts;
+use httm::exec::snap_select::SnapshotSelect;
+use httm::exec::timeline::TimelineView;
+use httm::exec::undo_restore::UndoRestore;
+use httm::exec::verify::VerifyFiles;
+use httm::exec::watch::WatchSnaps;
+use httm::library::results::{
+    HttmAborted, HttmError, HttmResult, ERROR_EXIT_CODE, SUCCESS_EXIT_CODE,
+};
+use httm::library::transcript::{Transcript, TranscriptKind};
+use httm::library::version_index::VersionIndex;
+use httm::{print_output_buf, MountsForFiles, SnapNameMap, VersionsMap, GLOBAL_CONFIG};
 
-use crate::display_map::format::PrintAsMap;
-use exec::purge::PurgeSnaps;
-use exec::roll_forward::RollForward;
-use exec::snap_mounts::SnapshotMounts;
-use library::utility::print_output_buf;
-use once_cell::sync::Lazy;
+fn main() {
+    let res = exec();
 
-use crate::config::generate::{Config, ExecMode};
-use crate::lookup::file_mounts::MountsForFiles;
+    // the version index is written once, as we exit, see VersionIndex::flush
+    if VersionIndex::is_enabled() {
+        let _ = VersionIndex::flush();
+    }
 
-use crate::display_versions::wrapper::VersionsDisplayWrapper;
-use crate::exec::interactive::InteractiveBrowse;
-use crate::exec::recursive::NonInteractiveRecursiveWrapper;
-use crate::library::results::HttmResult;
-use crate::lookup::snap_names::SnapNameMap;
-use crate::lookup::versions::VersionsMap;
+    match res {
+        Ok(_) => {
+            Transcript::record(TranscriptKind::End, "completed");
+            std::process::exit(SUCCESS_EXIT_CODE)
+        }
+        Err(error) => {
+            Transcript::record(TranscriptKind::End, &error.to_string());
 
-pub const ZFS_HIDDEN_DIRECTORY: &str = ".zfs";
-pub const ZFS_SNAPSHOT_DIRECTORY: &str = ".zfs/snapshot";
-pub const BTRFS_SNAPPER_HIDDEN_DIRECTORY: &str = ".snapshots";
-pub const BTRFS_SNAPPER_SUFFIX: &str = "snapshot";
-pub const ROOT_DIRECTORY: &str = "/";
-pub const NILFS2_SNAPSHOT_ID_KEY: &str = "cp=";
+            // an abort is not a failure, so we exit with the code the user requested
+            if let Some(aborted) = error.downcast_ref::<HttmAborted>() {
+                eprintln!("{aborted}");
+                std::process::exit(GLOBAL_CONFIG.abort_exit_code)
+            }
 
-fn main() {
-    match exec() {
-        Ok(_) => std::process::exit(0),
-        Err(error) => {
             eprintln!("Error: {error}");
-            std::process::exit(1)
+
+            // see the exit code contract in library::results, so scripts need not parse stderr
+            match error.downcast_ref::<HttmError>() {
+                Some(httm_error) => std::process::exit(httm_error.exit_code),
+                None => std::process::exit(ERROR_EXIT_CODE),
+            }
         }
     }
 }
 
-// get our program args and generate a config for use
-// everywhere else
-static GLOBAL_CONFIG: Lazy<Config> = Lazy::new(|| {
-    Config::new()
-        .map_err(|error| {
-            eprintln!("Error: {error}");
-            std::process::exit(1)
-        })
-        .unwrap()
-});
-
 fn exec() -> HttmResult<()> {
     // fn exec() handles the basic display cases, and sends other cases to be processed elsewhere
     match &GLOBAL_CONFIG.exec_mode {
-        // ExecMode::Interactive *may* return back to this function to be printed
+        // ExecMode::Interactive, ExecMode::Display, and ExecMode::NumVersions are each a pipeline of stages,
+        // see struct Pipeline
         ExecMode::Interactive(interactive_mode) => {
-            let pathdata_set = InteractiveBrowse::exec(interactive_mode)?;
-            let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &pathdata_set)?;
-            let output_buf = VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string();
-
-            print_output_buf(output_buf)
+            Pipeline::exec(&GLOBAL_CONFIG, Some(interactive_mode))
         }
-        // ExecMode::Display will be just printed, we already know the paths
-        ExecMode::Display | ExecMode::NumVersions(_) => {
-            let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
-            let output_buf = VersionsDisplayWrapper::from(&GLOBAL_CONFIG, versions_map).to_string();
-
-            print_output_buf(output_buf)
+        // raw output of ExecMode::Display may be printed as each path's versions are found
+        ExecMode::Display if RawStream::is_eligible(&GLOBAL_CONFIG) => {
+            RawStream::exec(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)
         }
+        // ExecMode::Display will be just printed, we already know the paths
+        ExecMode::Display | ExecMode::NumVersions(_) => Pipeline::exec(&GLOBAL_CONFIG, None),
         // ExecMode::NonInteractiveRecursive, ExecMode::SnapFileMount, and ExecMode::MountsForFiles will print their
         // output elsewhere
         ExecMode::NonInteractiveRecursive(_) => NonInteractiveRecursiveWrapper::exec(),
@@ -132,7 +107,7 @@ fn exec() -> HttmResult<()> {
             let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
             let snap_name_map = SnapNameMap::new(versions_map, opt_filters)?;
             let printable_map = PrintAsMap::from(&snap_name_map);
-            let output_buf = printable_map.to_string();
+            let output_buf = printable_map.to_formatted()?;
 
             print_output_buf(output_buf)
         }
@@ -140,13 +115,42 @@ fn exec() -> HttmResult<()> {
             let versions_map = VersionsMap::new(&GLOBAL_CONFIG, &GLOBAL_CONFIG.paths)?;
             PurgeSnaps::exec(versions_map, opt_filters)
         }
+        ExecMode::PruneDitto => PruneDitto::exec(),
         ExecMode::MountsForFiles(mount_display) => {
             let mounts_map = &MountsForFiles::new(mount_display);
             let printable_map: PrintAsMap = mounts_map.into();
-            let output_buf = printable_map.to_string();
+            let output_buf = printable_map.to_formatted()?;
 
             print_output_buf(output_buf)
         }
         ExecMode::RollForward(roll_config) => RollForward::new(roll_config.clone())?.exec(),
+        ExecMode::History => HistoryView::exec(),
+        ExecMode::MountSnap(mount_snap_action) => MountSnap::exec(mount_snap_action),
+        ExecMode::Find(pattern) => FindInSnaps::exec(pattern),
+        ExecMode::Complete(prefix) => CompleteFromSnaps::exec(prefix),
+        ExecMode::BatchRestore(batch_config) => {
+            BatchRestore::exec(batch_config).and_then(Pipeline::output)
+        }
+        ExecMode::Digest(num_snaps) => DigestOfSnaps::exec(*num_snaps),
+        ExecMode::UndoRestore => UndoRestore::exec(),
+        ExecMode::DedupRatio => DedupRatio::exec(),
+        ExecMode::Bookmark(bookmark_action) => BookmarkView::exec(bookmark_action),
+        ExecMode::Watch(interval_secs) => WatchSnaps::exec(*interval_secs),
+        ExecMode::Cat(cat_version) => CatFromSnaps::exec(cat_version),
+        ExecMode::Diff => DiffVersions::exec(),
+        ExecMode::Verify => VerifyFiles::exec(),
+        ExecMode::Timeline => TimelineView::exec(),
+        ExecMode::SizeReport => SizeReport::exec(),
+        ExecMode::Churn(churn_config) => ChurnLeaderboard::exec(churn_config),
+        ExecMode::Robot => RobotSession::exec(),
+        ExecMode::Serve(socket_addr) => ServeVersions::exec(socket_addr),
+        ExecMode::SelfTest => SelfTest::exec(),
+        ExecMode::SnapshotSelect => SnapshotSelect::exec(),
+        ExecMode::DatasetMap => {
+            let printable_map = PrintDatasetMap::from(GLOBAL_CONFIG.dataset_collection.as_ref());
+            let output_buf = printable_map.to_formatted()?;
+
+            print_output_buf(output_buf)
+        }
     }
 }
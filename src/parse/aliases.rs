@@ -20,13 +20,15 @@ use std::{ffi::OsString, ops::Deref, path::Path, path::PathBuf};
 use hashbrown::HashMap;
 
 use crate::library::results::{HttmError, HttmResult};
-use crate::library::utility::fs_type_from_hidden_dir;
+use crate::library::utility::fs_type_from_network_hidden_dir;
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum FilesystemType {
     Zfs,
     Btrfs,
     Nilfs2,
+    // a share whose snapshots are each a dir within this hidden dir, at the share root
+    SnapshotDir(&'static str),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,8 +58,8 @@ impl Deref for MapOfAliases {
 
 impl MapOfAliases {
     pub fn new(
-        raw_local_dir: &Option<OsString>,
         raw_snap_dir: &Option<OsString>,
+        raw_local_dir: &Option<OsString>,
         pwd: &Path,
         opt_input_aliases: &Option<Vec<String>>,
     ) -> HttmResult<Self> {
@@ -72,13 +74,15 @@ impl MapOfAliases {
                 None => pwd.to_path_buf(),
             };
 
-            (snap_dir, local_dir)
+            (local_dir, snap_dir)
         });
 
         let mut aliases_iter: Vec<(PathBuf, PathBuf)> = match opt_input_aliases {
             Some(input_aliases) => {
                 let res: Option<Vec<(PathBuf, PathBuf)>> = input_aliases
                     .iter()
+                    .map(|alias| alias.trim())
+                    .filter(|alias| !alias.is_empty())
                     .map(|alias| {
                         alias
                             .split_once(':')
@@ -99,6 +103,23 @@ impl MapOfAliases {
             aliases_iter.push(value)
         }
 
+        // one local dir may only have one alias, the last specified wins
+        let mut local_dirs: Vec<&PathBuf> = aliases_iter
+            .iter()
+            .map(|(local_dir, _snap_dir)| local_dir)
+            .collect();
+        local_dirs.sort_unstable();
+
+        local_dirs
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .for_each(|pair| {
+                eprintln!(
+                    "Warning: More than one alias was specified for the same local directory, only the last will be used: {:?}",
+                    pair[0]
+                )
+            });
+
         let map_of_aliases: HashMap<PathBuf, RemotePathAndFsType> = aliases_iter
             .into_iter()
             .filter_map(|(local_dir, snap_dir)| {
@@ -118,7 +139,7 @@ impl MapOfAliases {
                 Some((local_dir, snap_dir))
             })
             .filter_map(|(local_dir, remote_dir)| {
-                fs_type_from_hidden_dir(&remote_dir)
+                fs_type_from_network_hidden_dir(&remote_dir)
                     .map(|fs_type| {
                         (
                             local_dir,
@@ -21,7 +21,9 @@ use hashbrown::HashMap;
 use rayon::prelude::*;
 
 use crate::library::results::{HttmError, HttmResult};
+use crate::parse::aliases::FilesystemType;
 use crate::parse::mounts::MapOfDatasets;
+use crate::parse::replication::{AltStore, ReplicationConfigs, SnapshotGuids};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapOfAlts {
@@ -50,12 +52,29 @@ impl Deref for MapOfAlts {
 
 impl MapOfAlts {
     // instead of looking up, precompute possible alt replicated mounts before exec
-    pub fn new(map_of_datasets: &MapOfDatasets) -> Self {
+    pub fn new(map_of_datasets: &MapOfDatasets, alt_stores: &[AltStore]) -> Self {
+        let replication_configs = ReplicationConfigs::new(alt_stores);
+
+        // only ask ZFS for the guid of every snapshot if necessary
+        let opt_snapshot_guids = if map_of_datasets
+            .values()
+            .any(|dataset_info| matches!(dataset_info.fs_type, FilesystemType::Zfs))
+        {
+            SnapshotGuids::new()
+        } else {
+            None
+        };
+
         let res: HashMap<PathBuf, AltMetadata> = map_of_datasets
             .par_iter()
             .flat_map(|(mount, _dataset_info)| {
-                Self::alt_replicated_from_mount(mount, map_of_datasets)
-                    .map(|datasets| (mount.clone(), datasets))
+                Self::alt_replicated_from_mount(
+                    mount,
+                    map_of_datasets,
+                    &replication_configs,
+                    opt_snapshot_guids.as_ref(),
+                )
+                .map(|datasets| (mount.clone(), datasets))
             })
             .collect();
 
@@ -65,6 +84,8 @@ impl MapOfAlts {
     fn alt_replicated_from_mount(
         proximate_dataset_mount: &Path,
         map_of_datasets: &MapOfDatasets,
+        replication_configs: &ReplicationConfigs,
+        opt_snapshot_guids: Option<&SnapshotGuids>,
     ) -> HttmResult<AltMetadata> {
         let proximate_dataset_fs_name = match &map_of_datasets.get(proximate_dataset_mount) {
             Some(dataset_info) => dataset_info.source.as_os_str(),
@@ -73,19 +94,55 @@ impl MapOfAlts {
             }
         };
 
-        // find a filesystem that ends with our most local filesystem name
+        // first, find any filesystem which the user, or a replication tool's config, names as a replica
+        // of our most local filesystem, whatever its name, see ALT_STORE
+        let mut alt_replicated_mounts: Vec<PathBuf> = if replication_configs.is_empty() {
+            Vec::new()
+        } else {
+            map_of_datasets
+                .iter()
+                .filter(|(_mount, dataset_info)| {
+                    replication_configs
+                        .is_replica_of(Path::new(proximate_dataset_fs_name), &dataset_info.source)
+                })
+                .map(|(mount, _dataset_info)| mount)
+                .cloned()
+                .collect()
+        };
+
+        // otherwise, find any filesystem which shares a snapshot with our most local filesystem,
+        // as a snapshot keeps its guid when it is sent and received, whatever the receiving filesystem's name
+        if alt_replicated_mounts.is_empty() {
+            if let Some(snapshot_guids) = opt_snapshot_guids {
+                alt_replicated_mounts = map_of_datasets
+                    .iter()
+                    .filter(|(_mount, dataset_info)| {
+                        snapshot_guids.is_replica_of(
+                            Path::new(proximate_dataset_fs_name),
+                            &dataset_info.source,
+                        )
+                    })
+                    .map(|(mount, _dataset_info)| mount)
+                    .cloned()
+                    .collect();
+            }
+        }
+
+        // otherwise, find a filesystem that ends with our most local filesystem name
         // but which has a prefix, like a different pool name: rpool might be
         // replicated to tank/rpool
-        let mut alt_replicated_mounts: Vec<PathBuf> = map_of_datasets
-            .iter()
-            .map(|(mount, dataset_info)| (mount, Path::new(&dataset_info.source)))
-            .filter(|(_mount, source)| {
-                source.as_os_str() != proximate_dataset_fs_name
-                    && source.ends_with(proximate_dataset_fs_name)
-            })
-            .map(|(mount, _source)| mount)
-            .cloned()
-            .collect();
+        if alt_replicated_mounts.is_empty() {
+            alt_replicated_mounts = map_of_datasets
+                .iter()
+                .map(|(mount, dataset_info)| (mount, Path::new(&dataset_info.source)))
+                .filter(|(_mount, source)| {
+                    source.as_os_str() != proximate_dataset_fs_name
+                        && source.ends_with(proximate_dataset_fs_name)
+                })
+                .map(|(mount, _source)| mount)
+                .cloned()
+                .collect();
+        }
 
         if alt_replicated_mounts.is_empty() {
             // could not find the any replicated mounts
@@ -16,7 +16,10 @@
 // that was distributed with this source code.
 
 use std::collections::BTreeMap;
+use std::fs::{read_dir, read_to_string};
 use std::ops::Deref;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
 use std::{path::PathBuf, process::Command as ExecProcess};
 
 use hashbrown::{HashMap, HashSet};
@@ -25,8 +28,10 @@ use rayon::iter::Either;
 use rayon::prelude::*;
 use which::which;
 
-use crate::library::results::{HttmError, HttmResult};
-use crate::library::utility::{find_common_path, fs_type_from_hidden_dir};
+use crate::library::results::{HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE};
+use crate::library::utility::{
+    find_common_path, fs_type_from_hidden_dir, fs_type_from_network_hidden_dir,
+};
 use crate::parse::aliases::FilesystemType;
 use crate::parse::snaps::MapOfSnaps;
 use crate::{NILFS2_SNAPSHOT_ID_KEY, ZFS_HIDDEN_DIRECTORY};
@@ -37,6 +42,7 @@ pub const BTRFS_FSTYPE: &str = "btrfs";
 pub const SMB_FSTYPE: &str = "smbfs";
 pub const NFS_FSTYPE: &str = "nfs";
 pub const AFP_FSTYPE: &str = "afpfs";
+pub const ZFS_LIST_CACHE_DIR: &str = "/etc/zfs/zfs-list.cache";
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum MountType {
@@ -95,6 +101,8 @@ impl MaxLen for MapOfDatasets {
     }
 }
 
+type RawDatasetsAndFilterDirs = (HashMap<PathBuf, DatasetMetadata>, HashSet<PathBuf>);
+
 pub struct BaseFilesystemInfo {
     pub map_of_datasets: MapOfDatasets,
     pub map_of_snaps: MapOfSnaps,
@@ -104,8 +112,15 @@ pub struct BaseFilesystemInfo {
 impl BaseFilesystemInfo {
     // divide by the type of system we are on
     // Linux allows us the read proc mounts
-    pub fn new() -> HttmResult<Self> {
-        let (raw_datasets, filter_dirs_set) = if cfg!(target_os = "linux") {
+    pub fn new(opt_zfs_list_cache: Option<&Path>) -> HttmResult<Self> {
+        let opt_from_cache = match opt_zfs_list_cache {
+            Some(cache_path) => Self::from_zfs_list_cache(cache_path)?,
+            None => None,
+        };
+
+        let (raw_datasets, filter_dirs_set) = if let Some(from_cache) = opt_from_cache {
+            from_cache
+        } else if cfg!(target_os = "linux") {
             Self::from_proc_mounts()?
         } else {
             Self::from_mount_cmd()?
@@ -185,24 +200,16 @@ impl BaseFilesystemInfo {
                         },
                     )),
                     SMB_FSTYPE | AFP_FSTYPE | NFS_FSTYPE => {
-                        match fs_type_from_hidden_dir(&mount_info.dest) {
-                            Some(FilesystemType::Zfs) => Either::Left((
-                                mount_info.dest,
-                                DatasetMetadata {
-                                    source: mount_info.source,
-                                    fs_type: FilesystemType::Zfs,
-                                    mount_type: MountType::Network,
-                                },
-                            )),
-                            Some(FilesystemType::Btrfs) => Either::Left((
+                        match fs_type_from_network_hidden_dir(&mount_info.dest) {
+                            Some(FilesystemType::Nilfs2) | None => Either::Right(mount_info.dest),
+                            Some(fs_type) => Either::Left((
                                 mount_info.dest,
                                 DatasetMetadata {
                                     source: mount_info.source,
-                                    fs_type: FilesystemType::Btrfs,
+                                    fs_type,
                                     mount_type: MountType::Network,
                                 },
                             )),
-                            _ => Either::Right(mount_info.dest),
                         }
                     }
                     BTRFS_FSTYPE => {
@@ -239,7 +246,11 @@ impl BaseFilesystemInfo {
                 });
 
         if map_of_datasets.is_empty() {
-            Err(HttmError::new("httm could not find any valid datasets on the system.").into())
+            Err(HttmError::with_exit_code(
+                "httm could not find any valid datasets on the system.",
+                NO_SNAPSHOT_SOURCE_EXIT_CODE,
+            )
+            .into())
         } else {
             Ok((map_of_datasets, filter_dirs))
         }
@@ -311,12 +322,107 @@ impl BaseFilesystemInfo {
             });
 
         if map_of_datasets.is_empty() {
-            Err(HttmError::new("httm could not find any valid datasets on the system.").into())
+            Err(HttmError::with_exit_code(
+                "httm could not find any valid datasets on the system.",
+                NO_SNAPSHOT_SOURCE_EXIT_CODE,
+            )
+            .into())
         } else {
             Ok((map_of_datasets, filter_dirs))
         }
     }
 
+    // the cache files written for zfs-mount-generator list every dataset of a pool, one per line,
+    // tab delimited, beginning: name, mountpoint, canmount.  reading these is much faster than parsing
+    // the mounts of a system with thousands of datasets, but, as these list only ZFS datasets,
+    // no other filesystems will be filtered, and there will be no btrfs or nilfs2 datasets
+    fn from_zfs_list_cache(cache_path: &Path) -> HttmResult<Option<RawDatasetsAndFilterDirs>> {
+        if !cache_path.exists() {
+            eprintln!(
+                "WARNING: The ZFS list cache does not exist: {:?}.  Falling back to parsing mounts.",
+                cache_path
+            );
+            return Ok(None);
+        }
+
+        // may be a single cache file, or the directory of cache files, one per pool
+        let cache_files: Vec<PathBuf> = if cache_path.is_dir() {
+            read_dir(cache_path)?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect()
+        } else {
+            vec![cache_path.to_path_buf()]
+        };
+
+        let map_of_datasets: HashMap<PathBuf, DatasetMetadata> = cache_files
+            .par_iter()
+            .map(read_to_string)
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|err| {
+                HttmError::with_context(
+                    &format!("Could not read the ZFS list cache: {:?}", cache_path),
+                    &err,
+                )
+            })?
+            .par_iter()
+            .flat_map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.split('\t');
+                        let (name, mountpoint, canmount) =
+                            (fields.next()?, fields.next()?, fields.next()?);
+
+                        if canmount == "off" || !mountpoint.starts_with('/') {
+                            return None;
+                        }
+
+                        Some((PathBuf::from(mountpoint), PathBuf::from(name)))
+                    })
+                    .collect::<Vec<(PathBuf, PathBuf)>>()
+            })
+            // a cache may be stale, so only keep those datasets which are actually mounted
+            .filter(|(mount, _source)| Self::is_zfs_mount(mount))
+            .map(|(mount, source)| {
+                (
+                    mount,
+                    DatasetMetadata {
+                        source,
+                        fs_type: FilesystemType::Zfs,
+                        mount_type: MountType::Local,
+                    },
+                )
+            })
+            .collect();
+
+        if map_of_datasets.is_empty() {
+            eprintln!(
+                "WARNING: No mounted datasets were found in the ZFS list cache: {:?}.  Falling back to parsing mounts.",
+                cache_path
+            );
+            return Ok(None);
+        }
+
+        Ok(Some((map_of_datasets, HashSet::new())))
+    }
+
+    // a mount point is a different device than its parent, and a ZFS mount has a hidden dir
+    fn is_zfs_mount(mount: &Path) -> bool {
+        let is_mount_point = match (mount.metadata(), mount.parent()) {
+            (Ok(mount_md), Some(parent)) => parent
+                .metadata()
+                .map(|parent_md| parent_md.dev() != mount_md.dev())
+                .unwrap_or(false),
+            // the root directory is always mounted
+            (Ok(_), None) => true,
+            (Err(_), _) => false,
+        };
+
+        is_mount_point && mount.join(ZFS_HIDDEN_DIRECTORY).exists()
+    }
+
     // if we have some btrfs mounts, we check to see if there is a snap directory in common
     // so we can hide that common path from searches later
     pub fn common_snap_dir(&self) -> Option<PathBuf> {
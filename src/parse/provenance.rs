@@ -0,0 +1,121 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::path::Path;
+use std::process::Command as ExecProcess;
+
+use which::which;
+
+use crate::library::results::{HttmError, HttmResult};
+
+// like a ZFS alt replicated dataset, a btrfs snapshot may have been received from another machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapOrigin {
+    Local,
+    Received,
+}
+
+impl SnapOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapOrigin::Local => "local",
+            SnapOrigin::Received => "received",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtrfsProvenance {
+    pub uuid: String,
+    pub opt_parent_uuid: Option<String>,
+    pub opt_received_uuid: Option<String>,
+}
+
+impl BtrfsProvenance {
+    pub fn new(snap_mount: &Path) -> HttmResult<Self> {
+        let btrfs_command = which("btrfs").map_err(|_err| {
+            HttmError::new(
+                "'btrfs' command not found. Make sure the command 'btrfs' is in your path.",
+            )
+        })?;
+
+        let process_output = ExecProcess::new(btrfs_command)
+            .arg("subvolume")
+            .arg("show")
+            .arg(snap_mount)
+            .output()?;
+
+        if !process_output.status.success() {
+            let stderr_string = std::str::from_utf8(&process_output.stderr)?.trim();
+            let msg = format!(
+                "httm was unable to determine the origin of snapshot {snap_mount:?}. The 'btrfs' command issued the following error: {stderr_string}"
+            );
+            return Err(HttmError::new(&msg).into());
+        }
+
+        let stdout_string = std::str::from_utf8(&process_output.stdout)?;
+
+        // the subvolume's own fields precede the list of its snapshots, eg. "\tReceived UUID: \t\t-"
+        let field = |name: &str| -> Option<String> {
+            stdout_string
+                .split("Snapshot(s):")
+                .next()
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(key, _value)| key.trim() == name)
+                .map(|(_key, value)| value.trim())
+                // a value of "-" means the field is unset
+                .filter(|value| !value.is_empty() && *value != "-")
+                .map(|value| value.to_owned())
+        };
+
+        let uuid = field("UUID").ok_or_else(|| {
+            HttmError::new(&format!(
+                "httm was unable to determine the origin of snapshot {snap_mount:?}. The 'btrfs' command did not report a UUID."
+            ))
+        })?;
+
+        Ok(Self {
+            uuid,
+            opt_parent_uuid: field("Parent UUID"),
+            opt_received_uuid: field("Received UUID"),
+        })
+    }
+
+    // only a received subvolume has a received UUID
+    pub fn origin(&self) -> SnapOrigin {
+        if self.opt_received_uuid.is_some() {
+            SnapOrigin::Received
+        } else {
+            SnapOrigin::Local
+        }
+    }
+
+    // pseudo-properties, named like ZFS user properties, for use as snapshot annotations
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        [
+            ("btrfs:origin", Some(self.origin().as_str().to_owned())),
+            ("btrfs:uuid", Some(self.uuid.clone())),
+            ("btrfs:parent_uuid", self.opt_parent_uuid.clone()),
+            ("btrfs:received_uuid", self.opt_received_uuid.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(property, opt_value)| opt_value.map(|value| (property, value)))
+        .collect()
+    }
+}
@@ -0,0 +1,324 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::Command as ExecProcess;
+
+use hashbrown::{HashMap, HashSet};
+use which::which;
+
+use crate::library::results::{HttmError, HttmResult};
+
+// replication tools' configs, which may name a replica which shares no suffix with its source
+const ZREPL_CONFIGS: [&str; 2] = ["/etc/zrepl/zrepl.yml", "/usr/local/etc/zrepl/zrepl.yml"];
+const PYZNAP_CONFIGS: [&str; 2] = [
+    "/etc/pyznap/pyznap.conf",
+    "/usr/local/etc/pyznap/pyznap.conf",
+];
+// syncoid is usually scheduled by cron, or by a systemd unit
+const SYNCOID_SCHEDULE_FILES: [&str; 1] = ["/etc/crontab"];
+const SYNCOID_SCHEDULE_DIRS: [&str; 5] = [
+    "/etc/cron.d",
+    "/var/spool/cron",
+    "/var/spool/cron/crontabs",
+    "/var/cron/tabs",
+    "/etc/systemd/system",
+];
+// syncoid options which take their value as the next arg, rather than after an "="
+const SYNCOID_VALUE_OPTIONS: [&str; 18] = [
+    "--compress",
+    "--identifier",
+    "--source-bwlimit",
+    "--target-bwlimit",
+    "--mbuffer-size",
+    "--pv-options",
+    "--sshkey",
+    "--sshport",
+    "--sshcipher",
+    "--sshconfig",
+    "--sshoption",
+    "--exclude",
+    "--exclude-datasets",
+    "--exclude-snaps",
+    "--include-snaps",
+    "--sendoptions",
+    "--recvoptions",
+    "-o",
+];
+
+// a user defined replica, given like "tank/rpool=backup/laptop", see ALT_STORE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltStore {
+    source: PathBuf,
+    target: PathBuf,
+}
+
+impl AltStore {
+    pub fn new(value: &str) -> HttmResult<Self> {
+        match value.split_once('=') {
+            Some((source, target)) if !source.trim().is_empty() && !target.trim().is_empty() => {
+                Ok(Self {
+                    source: PathBuf::from(source.trim()),
+                    target: PathBuf::from(target.trim()),
+                })
+            }
+            _ => {
+                let msg = format!(
+                    "httm could not parse the ALT_STORE value {value:?}.  Such a value must be given in the form <SOURCE_DATASET>=<REPLICA_DATASET>."
+                );
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Replica {
+    // a source dataset, and its children, are received at the target dataset, as with syncoid or pyznap
+    Pair { source: PathBuf, target: PathBuf },
+    // any source dataset is received somewhere beneath this dataset, as with zrepl's "root_fs"
+    ReceiveRoot(PathBuf),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplicationConfigs {
+    replicas: Vec<Replica>,
+}
+
+impl ReplicationConfigs {
+    // configs which are absent, or which we may not read, are simply ignored
+    pub fn new(alt_stores: &[AltStore]) -> Self {
+        let mut replicas: Vec<Replica> = alt_stores
+            .iter()
+            .map(|alt_store| Replica::Pair {
+                source: alt_store.source.clone(),
+                target: alt_store.target.clone(),
+            })
+            .collect();
+
+        ZREPL_CONFIGS
+            .iter()
+            .filter_map(|path| read_to_string(path).ok())
+            .for_each(|contents| replicas.extend(Self::from_zrepl(&contents)));
+
+        PYZNAP_CONFIGS
+            .iter()
+            .filter_map(|path| read_to_string(path).ok())
+            .for_each(|contents| replicas.extend(Self::from_pyznap(&contents)));
+
+        SYNCOID_SCHEDULE_DIRS
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .chain(SYNCOID_SCHEDULE_FILES.iter().map(PathBuf::from))
+            .filter(|path| path.is_file())
+            .filter_map(|path| read_to_string(path).ok())
+            .for_each(|contents| replicas.extend(Self::from_syncoid(&contents)));
+
+        Self { replicas }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replicas.is_empty()
+    }
+
+    // whether the candidate dataset is a replica of the source dataset, by dataset name, eg. "rpool/home"
+    pub fn is_replica_of(&self, source_name: &Path, candidate_name: &Path) -> bool {
+        if source_name == candidate_name {
+            return false;
+        }
+
+        self.replicas.iter().any(|replica| match replica {
+            Replica::Pair { source, target } => match source_name.strip_prefix(source) {
+                Ok(relative) if relative.as_os_str().is_empty() => candidate_name == target,
+                Ok(relative) => candidate_name == target.join(relative),
+                Err(_) => false,
+            },
+            Replica::ReceiveRoot(root) => {
+                candidate_name.starts_with(root) && candidate_name.ends_with(source_name)
+            }
+        })
+    }
+
+    // zrepl receives a "pull" or "sink" job's datasets at "root_fs", followed by any client identity,
+    // followed by the full name of the source dataset, eg. "root_fs: \"backup/zrepl\""
+    fn from_zrepl(contents: &str) -> Vec<Replica> {
+        contents
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim())
+            .filter_map(|line| line.strip_prefix("root_fs:"))
+            .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\''))
+            .filter(|value| !value.is_empty())
+            .map(|value| Replica::ReceiveRoot(PathBuf::from(value)))
+            .collect()
+    }
+
+    // pyznap names each source dataset in an INI section header, and any of its destinations at "dest",
+    // as a comma separated list, where a remote destination begins with "ssh:"
+    fn from_pyznap(contents: &str) -> Vec<Replica> {
+        let mut opt_section: Option<&str> = None;
+        let mut res = Vec::new();
+
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                opt_section = Some(section.trim());
+                continue;
+            }
+
+            let source = match opt_section {
+                Some(source) => source,
+                None => continue,
+            };
+
+            match line.split_once('=') {
+                Some((key, value)) if key.trim() == "dest" => {
+                    res.extend(
+                        value
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|dest| !dest.is_empty() && !dest.starts_with("ssh:"))
+                            .map(|dest| Replica::Pair {
+                                source: PathBuf::from(source),
+                                target: PathBuf::from(dest),
+                            }),
+                    );
+                }
+                _ => continue,
+            }
+        }
+
+        res
+    }
+
+    // the first two args to syncoid which are not options are the source and target datasets,
+    // where a remote dataset is named like "user@host:pool/dataset"
+    fn from_syncoid(contents: &str) -> Vec<Replica> {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut args = line
+                    .split_whitespace()
+                    .skip_while(|arg| Path::new(arg).file_name() != Some("syncoid".as_ref()))
+                    .skip(1)
+                    .map(|arg| arg.trim_matches(|c| c == '"' || c == '\''))
+                    .take_while(|arg| {
+                        !matches!(*arg, ";" | "&&" | "||" | "|") && !arg.contains('>')
+                    });
+
+                let mut datasets: Vec<&str> = Vec::with_capacity(2);
+
+                while let Some(arg) = args.next() {
+                    if arg.starts_with('-') {
+                        if SYNCOID_VALUE_OPTIONS.contains(&arg) {
+                            args.next();
+                        }
+                        continue;
+                    }
+
+                    datasets.push(arg);
+
+                    if datasets.len() == 2 {
+                        break;
+                    }
+                }
+
+                match datasets.as_slice() {
+                    [source, target] if !source.contains(':') && !target.contains(':') => {
+                        Some(Replica::Pair {
+                            source: PathBuf::from(source),
+                            target: PathBuf::from(target),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+// key: dataset name, val: the guids of its snapshots, which a snapshot keeps when it is sent and received
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotGuids {
+    inner: HashMap<PathBuf, HashSet<u64>>,
+}
+
+impl SnapshotGuids {
+    pub fn new() -> Option<Self> {
+        let zfs_command = which("zfs").ok()?;
+
+        let process_output = ExecProcess::new(zfs_command)
+            .args([
+                "get",
+                "-H",
+                "-p",
+                "-t",
+                "snapshot",
+                "-o",
+                "name,value",
+                "guid",
+            ])
+            .output()
+            .ok()?;
+
+        if !process_output.status.success() {
+            return None;
+        }
+
+        let mut inner: HashMap<PathBuf, HashSet<u64>> = HashMap::new();
+
+        // each line is like "pool/data@snap\t1234567890"
+        std::str::from_utf8(&process_output.stdout)
+            .ok()?
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter_map(|(snap_name, guid)| {
+                let (dataset_name, _snap) = snap_name.split_once('@')?;
+                let guid = guid.trim().parse::<u64>().ok()?;
+                Some((dataset_name, guid))
+            })
+            .for_each(|(dataset_name, guid)| {
+                inner
+                    .entry(PathBuf::from(dataset_name))
+                    .or_default()
+                    .insert(guid);
+            });
+
+        Some(Self { inner })
+    }
+
+    pub fn is_replica_of(&self, source_name: &Path, candidate_name: &Path) -> bool {
+        if source_name == candidate_name {
+            return false;
+        }
+
+        match (self.inner.get(source_name), self.inner.get(candidate_name)) {
+            (Some(source_guids), Some(candidate_guids)) => {
+                !source_guids.is_disjoint(candidate_guids)
+            }
+            _ => false,
+        }
+    }
+}
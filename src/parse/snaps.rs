@@ -15,64 +15,271 @@
 // For the full copyright and license information, please view the LICENSE file
 // that was distributed with this source code.
 
-use std::{fs::read_dir, ops::Deref, path::Path, path::PathBuf, process::Command as ExecProcess};
+use std::fs::read_to_string;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use std::{fs::read_dir, path::Path, path::PathBuf, process::Command as ExecProcess};
 
 use hashbrown::HashMap;
+use once_cell::sync::{Lazy, OnceCell};
 use proc_mounts::MountIter;
 use rayon::prelude::*;
+use time::{format_description, OffsetDateTime, PrimitiveDateTime};
 use which::which;
 
-use crate::library::results::{HttmError, HttmResult};
+use crate::library::results::{HttmError, HttmResult, NO_SNAPSHOT_SOURCE_EXIT_CODE};
 use crate::parse::aliases::FilesystemType;
 use crate::parse::mounts::{DatasetMetadata, MountType};
-use crate::{BTRFS_SNAPPER_HIDDEN_DIRECTORY, BTRFS_SNAPPER_SUFFIX, ZFS_SNAPSHOT_DIRECTORY};
+use crate::parse::provenance::{BtrfsProvenance, SnapOrigin};
+use crate::{
+    BTRFS_SNAPPER_HIDDEN_DIRECTORY, BTRFS_SNAPPER_SUFFIX, NILFS2_SNAPSHOT_ID_KEY,
+    ZFS_SNAPSHOT_DIRECTORY,
+};
 
+// key: dataset mount, val: the snap mounts as given, and those snap mounts newest first, should their order be known.
+// the snap mounts given are kept, so that, should a dataset's snapshots change, its order is asked for again
+static NEWEST_FIRST_CACHE: Lazy<Mutex<HashMap<PathBuf, NewestFirstEntry>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+type NewestFirstEntry = (Vec<PathBuf>, Option<Arc<Vec<PathBuf>>>);
+
+// the snap mounts of a dataset, oldest first, each with the time its snapshot was taken, see MapOfSnaps::oldest_first
+pub struct SnapsInOrder {
+    pub oldest_first: Vec<(Option<SystemTime>, PathBuf)>,
+    // false, when the provider can't say in which order its snapshots were created, so, instead, they are
+    // ordered by the modify time of each snap mount, which may be any time at all
+    pub is_creation_order: bool,
+}
+
+// key: dataset mount, val: the dataset, and its snap mounts, which are only listed once asked for, and then only once,
+// as a run may only ever search a few of the datasets on the system.  The listing is shared by every thread, and by
+// every clone of the map, so multiple paths, and recursive searches, never list a dataset's snap mounts again.
+// A dataset whose snap mounts can't be listed has none, as if it were not in the map at all.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MapOfSnaps {
-    inner: HashMap<PathBuf, Vec<PathBuf>>,
+    inner: Arc<HashMap<PathBuf, SnapListing>>,
 }
 
-impl From<HashMap<PathBuf, Vec<PathBuf>>> for MapOfSnaps {
-    fn from(map: HashMap<PathBuf, Vec<PathBuf>>) -> Self {
-        Self { inner: map }
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SnapListing {
+    dataset_info: DatasetMetadata,
+    snap_mounts: OnceCell<Option<Vec<PathBuf>>>,
 }
 
-impl Deref for MapOfSnaps {
-    type Target = HashMap<PathBuf, Vec<PathBuf>>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
+impl SnapListing {
+    fn get(&self, mount: &Path) -> Option<&Vec<PathBuf>> {
+        self.snap_mounts
+            .get_or_init(|| MapOfSnaps::snap_mounts(mount, &self.dataset_info).ok())
+            .as_ref()
     }
 }
 
 impl MapOfSnaps {
-    // fans out precompute of snap mounts to the appropriate function based on fstype
+    // snap mounts are listed lazily, see get, by the appropriate function based on fstype
     pub fn new(map_of_datasets: &HashMap<PathBuf, DatasetMetadata>) -> HttmResult<Self> {
-        let map_of_snaps: HashMap<PathBuf, Vec<PathBuf>> = map_of_datasets
-            .par_iter()
-            .flat_map(|(mount, dataset_info)| {
-                let snap_mounts: HttmResult<Vec<PathBuf>> = match dataset_info.fs_type {
-                    FilesystemType::Zfs | FilesystemType::Nilfs2 => {
-                        Self::from_defined_mounts(mount, dataset_info)
-                    }
-                    FilesystemType::Btrfs => match dataset_info.mount_type {
-                        MountType::Local => Self::from_btrfs_cmd(mount),
-                        MountType::Network => Self::from_defined_mounts(mount, dataset_info),
-                    },
+        if map_of_datasets.is_empty() {
+            return Err(HttmError::with_exit_code(
+                "httm could not find any valid datasets on the system.",
+                NO_SNAPSHOT_SOURCE_EXIT_CODE,
+            )
+            .into());
+        }
+
+        let inner: HashMap<PathBuf, SnapListing> = map_of_datasets
+            .iter()
+            .map(|(mount, dataset_info)| {
+                let listing = SnapListing {
+                    dataset_info: dataset_info.clone(),
+                    snap_mounts: OnceCell::new(),
                 };
 
-                snap_mounts.map(|snap_mounts| (mount.clone(), snap_mounts))
+                (mount.clone(), listing)
             })
             .collect();
 
-        if map_of_snaps.is_empty() {
-            Err(HttmError::new("httm could not find any valid datasets on the system.").into())
-        } else {
-            Ok(map_of_snaps.into())
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn get(&self, mount: &Path) -> Option<&Vec<PathBuf>> {
+        self.inner.get(mount).and_then(|listing| listing.get(mount))
+    }
+
+    pub fn get_key_value(&self, mount: &Path) -> Option<(&PathBuf, &Vec<PathBuf>)> {
+        self.inner
+            .get_key_value(mount)
+            .and_then(|(mount, listing)| listing.get(mount).map(|snap_mounts| (mount, snap_mounts)))
+    }
+
+    // lists, in parallel, the snap mounts of any dataset not yet listed, as every dataset is of interest
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Vec<PathBuf>)> {
+        self.inner.par_iter().for_each(|(mount, listing)| {
+            listing.get(mount);
+        });
+
+        self.inner.iter().filter_map(|(mount, listing)| {
+            listing.get(mount).map(|snap_mounts| (mount, snap_mounts))
+        })
+    }
+
+    // a copy of the map, with the given snap mounts for this dataset, as of now, see SelfTest
+    pub fn with_snap_mounts(&self, mount: &Path, snap_mounts: Vec<PathBuf>) -> Self {
+        let mut inner: HashMap<PathBuf, SnapListing> = (*self.inner).clone();
+
+        if let Some(listing) = inner.get_mut(mount) {
+            listing.snap_mounts = OnceCell::with_value(Some(snap_mounts));
+        }
+
+        Self {
+            inner: Arc::new(inner),
         }
     }
 
+    // the snap mounts of a single dataset, as of now, for those who must notice new snapshots
+    pub fn snap_mounts(mount: &Path, dataset_info: &DatasetMetadata) -> HttmResult<Vec<PathBuf>> {
+        match dataset_info.fs_type {
+            FilesystemType::Zfs | FilesystemType::Nilfs2 | FilesystemType::SnapshotDir(_) => {
+                Self::from_defined_mounts(mount, dataset_info)
+            }
+            FilesystemType::Btrfs => match dataset_info.mount_type {
+                MountType::Local => Self::from_btrfs_cmd(mount),
+                MountType::Network => Self::from_defined_mounts(mount, dataset_info),
+            },
+        }
+    }
+
+    // the snap mounts of a dataset, newest first, when its provider can cheaply say in which order its snapshots
+    // were created, see SnapOrderSource, so that those who need only the newest versions may stop early
+    pub fn newest_first(
+        map_of_datasets: &HashMap<PathBuf, DatasetMetadata>,
+        mount: &Path,
+        snap_mounts: &[PathBuf],
+    ) -> Option<Arc<Vec<PathBuf>>> {
+        let dataset_info = map_of_datasets.get(mount)?;
+        let order_source = SnapOrderSource::new(dataset_info)?;
+
+        let mut cache = NEWEST_FIRST_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some((cached_snap_mounts, opt_newest_first)) = cache.get(mount) {
+            if cached_snap_mounts.as_slice() == snap_mounts {
+                return opt_newest_first.clone();
+            }
+        }
+
+        let opt_newest_first = order_source
+            .newest_first(dataset_info, snap_mounts)
+            .map(Arc::new);
+
+        cache.insert(
+            mount.to_path_buf(),
+            (snap_mounts.to_vec(), opt_newest_first.clone()),
+        );
+
+        opt_newest_first
+    }
+
+    // the snap mounts of a dataset, oldest first, in the order in which they were created, see SnapOrderSource, each with
+    // its creation time, should the provider know it, else its modify time, or, when no order is known, ordered by
+    // the modify time of each snap mount, which is not when the snapshot was taken, so the caller should say so
+    pub fn oldest_first(
+        map_of_datasets: &HashMap<PathBuf, DatasetMetadata>,
+        mount: &Path,
+        snap_mounts: &[PathBuf],
+    ) -> SnapsInOrder {
+        let snap_mount_mtime = |snap_mount: &PathBuf| {
+            snap_mount
+                .symlink_metadata()
+                .and_then(|md| md.modified())
+                .ok()
+        };
+
+        if let Some(newest_first) = Self::newest_first(map_of_datasets, mount, snap_mounts) {
+            let creation_times: HashMap<PathBuf, SystemTime> = map_of_datasets
+                .get(mount)
+                .and_then(|dataset_info| {
+                    SnapOrderSource::new(dataset_info)?.creation_times(dataset_info, &newest_first)
+                })
+                .unwrap_or_default();
+
+            let oldest_first = newest_first
+                .iter()
+                .rev()
+                .map(|snap_mount| {
+                    let opt_time = creation_times
+                        .get(snap_mount)
+                        .copied()
+                        .or_else(|| snap_mount_mtime(snap_mount));
+
+                    (opt_time, snap_mount.clone())
+                })
+                .collect();
+
+            return SnapsInOrder {
+                oldest_first,
+                is_creation_order: true,
+            };
+        }
+
+        let mut oldest_first: Vec<(Option<SystemTime>, PathBuf)> = snap_mounts
+            .iter()
+            .map(|snap_mount| (snap_mount_mtime(snap_mount), snap_mount.clone()))
+            .collect();
+
+        oldest_first.sort_unstable_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+
+        SnapsInOrder {
+            oldest_first,
+            is_creation_order: false,
+        }
+    }
+
+    // retain only those btrfs snapshots of the requested origin, snapshots on other filesystems are unaffected
+    pub fn retain_origin(self, snap_origin: SnapOrigin) -> HttmResult<Self> {
+        let inner: HttmResult<HashMap<PathBuf, SnapListing>> = self
+            .inner
+            .par_iter()
+            .map(|(mount, listing)| {
+                let is_btrfs = listing.dataset_info.fs_type == FilesystemType::Btrfs;
+
+                // only btrfs snap mounts need be listed now
+                if !is_btrfs {
+                    return Ok((mount.clone(), listing.clone()));
+                }
+
+                let snap_mounts = match listing.get(mount) {
+                    Some(snap_mounts) => snap_mounts.clone(),
+                    None => return Ok((mount.clone(), listing.clone())),
+                };
+
+                let retained: HttmResult<Vec<PathBuf>> = snap_mounts
+                    .into_par_iter()
+                    .map(|snap_mount| {
+                        BtrfsProvenance::new(&snap_mount)
+                            .map(|provenance| (provenance.origin() == snap_origin, snap_mount))
+                    })
+                    .filter(|res| !matches!(res, Ok((false, _))))
+                    .map(|res| res.map(|(_is_retained, snap_mount)| snap_mount))
+                    .collect();
+
+                retained.map(|snap_mounts| {
+                    let listing = SnapListing {
+                        dataset_info: listing.dataset_info.clone(),
+                        snap_mounts: OnceCell::with_value(Some(snap_mounts)),
+                    };
+
+                    (mount.clone(), listing)
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            inner: Arc::new(inner?),
+        })
+    }
+
     // build paths to all snap mounts
     fn from_btrfs_cmd(mount: &Path) -> HttmResult<Vec<PathBuf>> {
         let btrfs_command = which("btrfs").map_err(|_err| {
@@ -134,6 +341,13 @@ impl MapOfSnaps {
                 .par_bridge()
                 .map(|entry| entry.path())
                 .collect(),
+            FilesystemType::SnapshotDir(snapshot_dir) => {
+                read_dir(mount_point_path.join(snapshot_dir))?
+                    .flatten()
+                    .par_bridge()
+                    .map(|entry| entry.path())
+                    .collect()
+            }
             FilesystemType::Nilfs2 => {
                 let source_path = Path::new(&dataset_metadata.source);
 
@@ -150,3 +364,190 @@ impl MapOfSnaps {
         Ok(snaps)
     }
 }
+
+// how a provider knows the order in which its snapshots were created, without a stat of each snapshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapOrderSource {
+    // "zfs list" reports the creation time of each snapshot of a local dataset
+    ZfsCreation,
+    // snapper numbers its snapshots in sequence, as in ".snapshots/<number>/snapshot"
+    SnapperNumber,
+    // each nilfs2 snapshot is a mounted checkpoint, and checkpoint numbers only ever increase
+    Checkpoint,
+}
+
+impl SnapOrderSource {
+    pub fn new(dataset_info: &DatasetMetadata) -> Option<Self> {
+        match (&dataset_info.fs_type, &dataset_info.mount_type) {
+            (FilesystemType::Zfs, MountType::Local) => Some(Self::ZfsCreation),
+            (FilesystemType::Btrfs, _) => Some(Self::SnapperNumber),
+            (FilesystemType::Nilfs2, _) => Some(Self::Checkpoint),
+            // the names of snapshot dirs, like "hourly.0", say nothing reliable about their order,
+            // and the zfs command can't see the datasets behind a network share
+            (FilesystemType::Zfs, MountType::Network) | (FilesystemType::SnapshotDir(_), _) => None,
+        }
+    }
+
+    // None, if any snap mount can't be placed in order, as a partial order is no order at all
+    fn newest_first(
+        &self,
+        dataset_info: &DatasetMetadata,
+        snap_mounts: &[PathBuf],
+    ) -> Option<Vec<PathBuf>> {
+        let order_keys: HashMap<PathBuf, u64> = match self {
+            Self::ZfsCreation => Self::zfs_creation_times(&dataset_info.source, snap_mounts)?,
+            Self::SnapperNumber => snap_mounts
+                .iter()
+                .filter(|snap_mount| snap_mount.file_name() == Some(BTRFS_SNAPPER_SUFFIX.as_ref()))
+                .filter_map(|snap_mount| {
+                    let number = snap_mount
+                        .parent()?
+                        .file_name()?
+                        .to_str()?
+                        .parse::<u64>()
+                        .ok()?;
+
+                    Some((snap_mount.clone(), number))
+                })
+                .collect(),
+            Self::Checkpoint => Self::nilfs2_checkpoints(&dataset_info.source)?,
+        };
+
+        let mut keyed: Vec<(u64, &PathBuf)> = snap_mounts
+            .iter()
+            .map(|snap_mount| {
+                order_keys
+                    .get(snap_mount)
+                    .map(|order_key| (*order_key, snap_mount))
+            })
+            .collect::<Option<Vec<(u64, &PathBuf)>>>()?;
+
+        keyed.sort_unstable_by(|a, b| b.cmp(a));
+
+        Some(
+            keyed
+                .into_iter()
+                .map(|(_order_key, snap_mount)| snap_mount.clone())
+                .collect(),
+        )
+    }
+
+    // when each snapshot was taken, where the provider records it, as a nilfs2 checkpoint number says nothing of time
+    fn creation_times(
+        &self,
+        dataset_info: &DatasetMetadata,
+        snap_mounts: &[PathBuf],
+    ) -> Option<HashMap<PathBuf, SystemTime>> {
+        match self {
+            Self::ZfsCreation => Some(
+                Self::zfs_creation_times(&dataset_info.source, snap_mounts)?
+                    .into_iter()
+                    .map(|(snap_mount, secs)| {
+                        (
+                            snap_mount,
+                            SystemTime::UNIX_EPOCH + Duration::from_secs(secs),
+                        )
+                    })
+                    .collect(),
+            ),
+            Self::SnapperNumber => Some(
+                snap_mounts
+                    .iter()
+                    .filter_map(|snap_mount| {
+                        Some((snap_mount.clone(), Self::snapper_date(snap_mount)?))
+                    })
+                    .collect(),
+            ),
+            Self::Checkpoint => None,
+        }
+    }
+
+    // snapper records when it took each snapshot, in UTC, as in "<date>2023-05-01 10:00:01</date>",
+    // in ".snapshots/<number>/info.xml", beside ".snapshots/<number>/snapshot"
+    fn snapper_date(snap_mount: &Path) -> Option<SystemTime> {
+        let info = read_to_string(snap_mount.parent()?.join("info.xml")).ok()?;
+
+        let (_before, after) = info.split_once("<date>")?;
+        let (date, _after) = after.split_once("</date>")?;
+
+        let format =
+            format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").ok()?;
+
+        let date_time: OffsetDateTime = PrimitiveDateTime::parse(date.trim(), &format)
+            .ok()?
+            .assume_utc();
+
+        Some(date_time.into())
+    }
+
+    // one exec for the dataset, instead of a stat of each snapshot, which, for zfs, may mean an automount of each
+    fn zfs_creation_times(
+        dataset: &Path,
+        snap_mounts: &[PathBuf],
+    ) -> Option<HashMap<PathBuf, u64>> {
+        let zfs_command = which("zfs").ok()?;
+        let arg_dataset = dataset.to_string_lossy();
+        let args = vec![
+            "list",
+            "-H",
+            "-p",
+            "-t",
+            "snapshot",
+            "-d",
+            "1",
+            "-o",
+            "name,creation",
+            &arg_dataset,
+        ];
+
+        let command_output = ExecProcess::new(zfs_command).args(&args).output().ok()?;
+
+        if !command_output.status.success() {
+            return None;
+        }
+
+        let creation_times: HashMap<&str, u64> = std::str::from_utf8(&command_output.stdout)
+            .ok()?
+            .lines()
+            .filter_map(|line| {
+                let (name, creation) = line.split_once('\t')?;
+                let (_dataset, snap_name) = name.split_once('@')?;
+
+                Some((snap_name, creation.trim().parse::<u64>().ok()?))
+            })
+            .collect();
+
+        // the snap mount's file name is the snapshot's name, as in ".zfs/snapshot/<name>"
+        let res = snap_mounts
+            .iter()
+            .filter_map(|snap_mount| {
+                let snap_name = snap_mount.file_name()?.to_str()?;
+
+                creation_times
+                    .get(snap_name)
+                    .map(|creation| (snap_mount.clone(), *creation))
+            })
+            .collect();
+
+        Some(res)
+    }
+
+    fn nilfs2_checkpoints(source: &Path) -> Option<HashMap<PathBuf, u64>> {
+        let res = MountIter::new()
+            .ok()?
+            .flatten()
+            .filter(|mount_info| mount_info.source == source)
+            .filter_map(|mount_info| {
+                let checkpoint = mount_info.options.iter().find_map(|opt| {
+                    opt.strip_prefix(NILFS2_SNAPSHOT_ID_KEY)?
+                        .parse::<u64>()
+                        .ok()
+                })?;
+
+                Some((mount_info.dest, checkpoint))
+            })
+            .collect();
+
+        Some(res)
+    }
+}
@@ -0,0 +1,533 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::collections::BTreeSet;
+use std::io::Stderr;
+use std::time::Duration;
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Terminal;
+use skim::prelude::*;
+
+use crate::exec::interactive::ViewMode;
+use crate::exec::recursive::SkippedDirs;
+use crate::library::results::{HttmAborted, HttmError, HttmResult};
+use crate::library::utility::strip_ansi;
+use crate::ui::picker::{FilterChange, Picker, PickerOutput};
+use crate::GLOBAL_CONFIG;
+
+// how long to wait on a key press before checking for newly arrived items
+const TICK: Duration = Duration::from_millis(50);
+const PAGE_LEN: isize = 10;
+
+pub struct NativePicker;
+
+impl Picker for NativePicker {
+    fn browse(
+        &self,
+        rx_item: SkimItemReceiver,
+        view_mode: &ViewMode,
+        multi: bool,
+        opt_query: Option<&str>,
+    ) -> HttmResult<PickerOutput> {
+        let mut state = PickerState::new(Vec::new(), multi, true);
+
+        if let Some(query) = opt_query {
+            state.query = query.to_owned();
+            state.refilter();
+        }
+
+        match run(&mut state, view_mode, Some(&rx_item)) {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Err(HttmAborted::new(
+                "httm interactive file browse session was aborted.  Quitting.",
+            )
+            .into()),
+            Err(err) => {
+                let msg = format!("httm interactive file browse session failed: {err}");
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+
+    fn select(&self, buffer: &str, view_mode: &ViewMode, multi: bool) -> HttmResult<PickerOutput> {
+        // like skim's "tac", the last line of the buffer is shown first
+        let entries = buffer
+            .trim()
+            .lines()
+            .rev()
+            .map(|line| {
+                let text = strip_ansi(line);
+                Entry {
+                    display: text.clone(),
+                    output: text,
+                }
+            })
+            .collect();
+
+        let mut state = PickerState::new(entries, multi, false);
+
+        match run(&mut state, view_mode, None) {
+            Ok(Some(output)) => Ok(output),
+            Ok(None) => Err(HttmAborted::new(
+                "httm select/restore/purge/roll forward session was aborted.  Quitting.",
+            )
+            .into()),
+            Err(err) => {
+                let msg = format!("httm select/restore/purge/roll forward session failed: {err}");
+                Err(HttmError::new(&msg).into())
+            }
+        }
+    }
+}
+
+struct Entry {
+    display: String,
+    output: String,
+}
+
+enum Action {
+    Continue,
+    Accept,
+    Abort,
+    ChangeFilter(FilterChange),
+    Snapshot,
+}
+
+struct PickerState {
+    entries: Vec<Entry>,
+    // indices into entries which match the query, in display order
+    filtered: Vec<usize>,
+    // indices into entries the user has marked in a multi select
+    marked: BTreeSet<usize>,
+    list_state: ListState,
+    query: String,
+    multi: bool,
+    filterable: bool,
+}
+
+impl PickerState {
+    fn new(entries: Vec<Entry>, multi: bool, filterable: bool) -> Self {
+        let mut state = Self {
+            entries,
+            filtered: Vec::new(),
+            marked: BTreeSet::new(),
+            list_state: ListState::default(),
+            query: String::new(),
+            multi,
+            filterable,
+        };
+
+        state.refilter();
+        state
+    }
+
+    fn refilter(&mut self) {
+        let query = if GLOBAL_CONFIG.opt_exact {
+            self.query.clone()
+        } else {
+            self.query.to_lowercase()
+        };
+
+        let query_groups = QueryTerm::parse(&query);
+
+        self.filtered = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_idx, entry)| {
+                if query_groups.is_empty() {
+                    return true;
+                }
+
+                let display = if GLOBAL_CONFIG.opt_exact {
+                    Cow::Borrowed(entry.display.as_str())
+                } else {
+                    Cow::Owned(entry.display.to_lowercase())
+                };
+
+                // every group must match, and any term within a group may match
+                query_groups
+                    .iter()
+                    .all(|group| group.iter().any(|term| term.is_match(&display)))
+            })
+            .map(|(idx, _entry)| idx)
+            .collect();
+
+        let opt_selected = match self.list_state.selected() {
+            _ if self.filtered.is_empty() => None,
+            Some(selected) => Some(selected.min(self.filtered.len() - 1)),
+            None => Some(0),
+        };
+
+        self.list_state.select(opt_selected);
+    }
+
+    fn receive(&mut self, rx_item: &SkimItemReceiver) {
+        let before = self.entries.len();
+
+        self.entries.extend(rx_item.try_iter().map(|item| Entry {
+            display: item.text().to_string(),
+            output: item.output().to_string(),
+        }));
+
+        if self.entries.len() != before {
+            self.refilter();
+        }
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.filtered.is_empty() {
+            return;
+        }
+
+        let last = self.filtered.len() as isize - 1;
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+
+        self.list_state
+            .select(Some((current + delta).clamp(0, last) as usize));
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|selected| self.filtered.get(selected))
+            .copied()
+    }
+
+    fn toggle_mark(&mut self) {
+        if !self.multi {
+            return;
+        }
+
+        if let Some(idx) = self.current() {
+            if !self.marked.remove(&idx) {
+                self.marked.insert(idx);
+            }
+        }
+
+        self.move_by(1);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => Action::Abort,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Action::Abort,
+            // only the browse view is filterable, and only the browse view has filters to change
+            KeyCode::Char('o')
+                if self.filterable && key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::ChangeFilter(FilterChange::Cycle)
+            }
+            KeyCode::Char('s')
+                if self.filterable && key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                Action::Snapshot
+            }
+            KeyCode::Char(c) if self.filterable && key.modifiers.contains(KeyModifiers::ALT) => {
+                match FilterChange::from_alt_key(c) {
+                    Some(filter_change) => Action::ChangeFilter(filter_change),
+                    None => Action::Continue,
+                }
+            }
+            KeyCode::Enter => Action::Accept,
+            KeyCode::Up => {
+                self.move_by(-1);
+                Action::Continue
+            }
+            KeyCode::Down => {
+                self.move_by(1);
+                Action::Continue
+            }
+            KeyCode::PageUp => {
+                self.move_by(-PAGE_LEN);
+                Action::Continue
+            }
+            KeyCode::PageDown => {
+                self.move_by(PAGE_LEN);
+                Action::Continue
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.toggle_mark();
+                Action::Continue
+            }
+            KeyCode::Backspace if self.filterable => {
+                self.query.pop();
+                self.refilter();
+                Action::Continue
+            }
+            KeyCode::Char(c) if self.filterable => {
+                self.query.push(c);
+                self.refilter();
+                Action::Continue
+            }
+            _ => Action::Continue,
+        }
+    }
+
+    fn output(
+        &self,
+        opt_filter_change: Option<FilterChange>,
+        is_snapshot_request: bool,
+    ) -> PickerOutput {
+        // marked entries, in the order given, else only the current entry
+        let selected = if self.marked.is_empty() {
+            self.current()
+                .map(|idx| vec![self.entries[idx].output.clone()])
+                .unwrap_or_default()
+        } else {
+            self.marked
+                .iter()
+                .map(|idx| self.entries[*idx].output.clone())
+                .collect()
+        };
+
+        PickerOutput {
+            query: self.query.clone(),
+            selected,
+            opt_filter_change,
+            is_snapshot_request,
+        }
+    }
+}
+
+// the same query syntax as skim's: terms separated by spaces must all match, terms separated by " | " may
+// match instead of one another, and a term may be negated with a leading "!", anchored to the start with a
+// leading "^", or to the end with a trailing "$", a leading "'" requests an exact match, which is the only
+// kind of match here
+enum TermKind {
+    Contains,
+    Prefix,
+    Suffix,
+    Equal,
+}
+
+struct QueryTerm {
+    text: String,
+    kind: TermKind,
+    is_negated: bool,
+}
+
+impl QueryTerm {
+    fn parse(query: &str) -> Vec<Vec<QueryTerm>> {
+        let mut groups: Vec<Vec<QueryTerm>> = Vec::new();
+        let mut is_or = false;
+
+        for word in query.split_whitespace() {
+            if word == "|" {
+                is_or = !groups.is_empty();
+                continue;
+            }
+
+            let term = match Self::new(word) {
+                Some(term) => term,
+                None => continue,
+            };
+
+            match groups.last_mut() {
+                Some(group) if is_or => group.push(term),
+                _ => groups.push(vec![term]),
+            }
+
+            is_or = false;
+        }
+
+        groups
+    }
+
+    fn new(word: &str) -> Option<Self> {
+        let (is_negated, word) = match word.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, word),
+        };
+
+        let word = word.strip_prefix('\'').unwrap_or(word);
+
+        let (is_prefix, word) = match word.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, word),
+        };
+
+        let (is_suffix, word) = match word.strip_suffix('$') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, word),
+        };
+
+        if word.is_empty() {
+            return None;
+        }
+
+        let kind = match (is_prefix, is_suffix) {
+            (true, true) => TermKind::Equal,
+            (true, false) => TermKind::Prefix,
+            (false, true) => TermKind::Suffix,
+            (false, false) => TermKind::Contains,
+        };
+
+        Some(Self {
+            text: word.to_owned(),
+            kind,
+            is_negated,
+        })
+    }
+
+    fn is_match(&self, display: &str) -> bool {
+        let is_match = match self.kind {
+            TermKind::Contains => display.contains(&self.text),
+            TermKind::Prefix => display.starts_with(&self.text),
+            TermKind::Suffix => display.ends_with(&self.text),
+            TermKind::Equal => display == self.text,
+        };
+
+        is_match != self.is_negated
+    }
+}
+
+// restores the terminal on drop, so we restore even on error, but
+// we must drop before printing anything else or exiting
+struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stderr>>,
+}
+
+impl TerminalGuard {
+    fn new() -> HttmResult<Self> {
+        enable_raw_mode()?;
+
+        let mut stderr = std::io::stderr();
+        execute!(stderr, EnterAlternateScreen)?;
+
+        let terminal = Terminal::new(CrosstermBackend::new(stderr))?;
+
+        Ok(Self { terminal })
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        let _ = self.terminal.show_cursor();
+    }
+}
+
+fn run(
+    state: &mut PickerState,
+    view_mode: &ViewMode,
+    opt_rx_item: Option<&SkimItemReceiver>,
+) -> HttmResult<Option<PickerOutput>> {
+    let header = format!(
+        "{}\n\
+        SELECT: enter | SELECT, MULTIPLE: tab | PAGE UP/DOWN: page up/down | EXIT: esc{}{}",
+        view_mode.print_mode(),
+        if state.filterable {
+            " | FILTER: ctrl-o, alt-1..4 | SNAPSHOT: ctrl-s"
+        } else {
+            ""
+        },
+        view_mode.print_notice()
+    );
+
+    let mut guard = TerminalGuard::new()?;
+
+    loop {
+        if let Some(rx_item) = opt_rx_item {
+            state.receive(rx_item);
+        }
+
+        guard.terminal.draw(|frame| {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(4),
+                    Constraint::Min(1),
+                    Constraint::Length(1),
+                ])
+                .split(frame.size());
+
+            let header_widget =
+                Paragraph::new(header.as_str()).block(Block::default().borders(Borders::BOTTOM));
+
+            let items: Vec<ListItem> = state
+                .filtered
+                .iter()
+                .map(|idx| {
+                    let mark = if state.marked.contains(idx) {
+                        "* "
+                    } else {
+                        "  "
+                    };
+                    ListItem::new(format!("{mark}{}", state.entries[*idx].display))
+                })
+                .collect();
+
+            let list_widget = List::new(items)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol("> ");
+
+            let prompt = if state.filterable {
+                format!("> {}", state.query)
+            } else {
+                String::new()
+            };
+
+            // only the browse view recurses, and so only the browse view may skip directories
+            let skipped = opt_rx_item
+                .and_then(|_| SkippedDirs::status_line())
+                .map(|status_line| format!("  {status_line}"))
+                .unwrap_or_default();
+
+            let prompt_widget = Paragraph::new(format!(
+                "{prompt}  [{}/{}]{skipped}",
+                state.filtered.len(),
+                state.entries.len()
+            ));
+
+            frame.render_widget(header_widget, chunks[0]);
+            frame.render_stateful_widget(list_widget, chunks[1], &mut state.list_state);
+            frame.render_widget(prompt_widget, chunks[2]);
+        })?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            // ignore key releases, which some terminals report
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match state.handle_key(key) {
+                Action::Continue => {}
+                Action::Accept => return Ok(Some(state.output(None, false))),
+                Action::Abort => return Ok(None),
+                Action::ChangeFilter(filter_change) => {
+                    return Ok(Some(state.output(Some(filter_change), false)))
+                }
+                Action::Snapshot => return Ok(Some(state.output(None, true))),
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use skim::prelude::*;
+
+use crate::config::generate::{BrowseFilter, UiFrontend};
+use crate::exec::interactive::ViewMode;
+use crate::library::results::HttmResult;
+use crate::ui::skim_picker::SkimPicker;
+use crate::GLOBAL_CONFIG;
+
+#[cfg(feature = "native-ui")]
+use crate::ui::native::NativePicker;
+
+pub struct PickerOutput {
+    pub query: String,
+    pub selected: Vec<String>,
+    // the user asked the browse view to change its filter, see BrowseRelay, rather than to select anything
+    pub opt_filter_change: Option<FilterChange>,
+    // the user asked to snapshot the dataset of each selected item, with ctrl-s, and then to return to the browse view
+    pub is_snapshot_request: bool,
+}
+
+// how the user may change the filter of the browse view: ctrl-o cycles through each filter,
+// and alt-1 through alt-4 toggle showing only one kind of entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterChange {
+    Cycle,
+    Toggle(BrowseFilter),
+}
+
+impl FilterChange {
+    pub fn from_alt_key(key: char) -> Option<Self> {
+        match key {
+            '1' => Some(FilterChange::Toggle(BrowseFilter::FilesOnly)),
+            '2' => Some(FilterChange::Toggle(BrowseFilter::DirsOnly)),
+            '3' => Some(FilterChange::Toggle(BrowseFilter::DeletedOnly)),
+            '4' => Some(FilterChange::Toggle(BrowseFilter::VersionedOnly)),
+            _ => None,
+        }
+    }
+
+    pub fn apply(&self, filter: BrowseFilter) -> BrowseFilter {
+        match self {
+            FilterChange::Cycle => filter.next(),
+            FilterChange::Toggle(toggled) => filter.toggle(*toggled),
+        }
+    }
+}
+
+// each frontend must provide both of our interactive views, and should return
+// an HttmAborted error should the user abort
+pub trait Picker {
+    // a view of items which may still be arriving, like browse, which begins with the query given
+    fn browse(
+        &self,
+        rx_item: SkimItemReceiver,
+        view_mode: &ViewMode,
+        multi: bool,
+        opt_query: Option<&str>,
+    ) -> HttmResult<PickerOutput>;
+
+    // a view of a fixed buffer, one item per line, like select, restore, purge and their consent views
+    fn select(&self, buffer: &str, view_mode: &ViewMode, multi: bool) -> HttmResult<PickerOutput>;
+}
+
+pub fn picker() -> Box<dyn Picker> {
+    match GLOBAL_CONFIG.ui_frontend {
+        UiFrontend::Skim => Box::new(SkimPicker),
+        #[cfg(feature = "native-ui")]
+        UiFrontend::Native => Box::new(NativePicker),
+        #[cfg(not(feature = "native-ui"))]
+        UiFrontend::Native => {
+            unreachable!("Config should not allow the native UI when built without the \"native-ui\" feature")
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//       ___           ___           ___           ___
+//      /\__\         /\  \         /\  \         /\__\
+//     /:/  /         \:\  \        \:\  \       /::|  |
+//    /:/__/           \:\  \        \:\  \     /:|:|  |
+//   /::\  \ ___       /::\  \       /::\  \   /:/|:|__|__
+//  /:/\:\  /\__\     /:/\:\__\     /:/\:\__\ /:/ |::::\__\
+//  \/__\:\/:/  /    /:/  \/__/    /:/  \/__/ \/__/~~/:/  /
+//       \::/  /    /:/  /        /:/  /            /:/  /
+//       /:/  /     \/__/         \/__/            /:/  /
+//      /:/  /                                    /:/  /
+//      \/__/                                     \/__/
+//
+// Copyright (c) 2023, Robert Swinford <robert.swinford<...at...>gmail.com>
+//
+// For the full copyright and license information, please view the LICENSE file
+// that was distributed with this source code.
+
+use std::io::Cursor;
+
+use skim::prelude::*;
+
+use crate::exec::interactive::ViewMode;
+use crate::exec::preview::PreviewSelection;
+use crate::library::results::{HttmAborted, HttmError, HttmResult};
+use crate::library::utility::strip_ansi;
+use crate::ui::picker::{FilterChange, Picker, PickerOutput};
+use crate::GLOBAL_CONFIG;
+
+// pressed in the browse view, skim exits with any of these keys, so the browse view may be reopened
+// with another filter, see FilterChange
+const FILTER_KEYS: &str = "ctrl-o,alt-1,alt-2,alt-3,alt-4";
+// likewise, so the browse view may be reopened once the dataset of the highlighted item has been snapshot
+const SNAPSHOT_KEY: &str = "ctrl-s";
+
+pub struct SkimPicker;
+
+impl Picker for SkimPicker {
+    fn browse(
+        &self,
+        rx_item: SkimItemReceiver,
+        view_mode: &ViewMode,
+        multi: bool,
+        opt_query: Option<&str>,
+    ) -> HttmResult<PickerOutput> {
+        let header = view_mode.print_header();
+
+        // create the skim component for previews
+        let skim_opts = SkimOptionsBuilder::default()
+            .preview_window(Some("up:50%"))
+            .preview(Some(""))
+            .nosort(true)
+            .exact(GLOBAL_CONFIG.opt_exact)
+            .query(opt_query)
+            .header(Some(&header))
+            .multi(multi)
+            .regex(false)
+            .expect(Some(format!("{FILTER_KEYS},{SNAPSHOT_KEY}")))
+            .build()
+            .expect("Could not initialized skim options for browse_view");
+
+        // run_with() reads and shows items from the thread stream created above
+        match skim::Skim::run_with(&skim_opts, Some(rx_item)) {
+            Some(output) if output.is_abort => Err(HttmAborted::new(
+                "httm interactive file browse session was aborted.  Quitting.",
+            )
+            .into()),
+            Some(output) => {
+                let selected = output
+                    .selected_items
+                    .iter()
+                    .map(|i| i.output().to_string())
+                    .collect();
+
+                let opt_filter_change = match output.final_key {
+                    Key::Ctrl('o') => Some(FilterChange::Cycle),
+                    Key::Alt(key) => FilterChange::from_alt_key(key),
+                    _ => None,
+                };
+
+                let is_snapshot_request = matches!(output.final_key, Key::Ctrl('s'));
+
+                Ok(PickerOutput {
+                    query: output.query,
+                    selected,
+                    opt_filter_change,
+                    is_snapshot_request,
+                })
+            }
+            None => Err(HttmError::new("httm interactive file browse session failed.").into()),
+        }
+    }
+
+    fn select(&self, buffer: &str, view_mode: &ViewMode, multi: bool) -> HttmResult<PickerOutput> {
+        let preview_selection = Arc::new(PreviewSelection::new(view_mode)?);
+
+        // items preview themselves, see PreviewItem
+        let opt_preview = preview_selection.opt_preview_command.as_ref().map(|_| "");
+
+        let header = view_mode.print_header();
+
+        // build our browse view - less to do than before - no previews, looking through one 'lil buffer
+        let skim_opts = SkimOptionsBuilder::default()
+            .preview_window(preview_selection.opt_preview_window.as_deref())
+            .preview(opt_preview)
+            .disabled(true)
+            .tac(true)
+            .nosort(true)
+            .tabstop(Some("4"))
+            .exact(true)
+            .multi(multi)
+            .regex(false)
+            .tiebreak(Some("length,index".to_string()))
+            .header(Some(&header))
+            .build()
+            .expect("Could not initialized skim options for select_restore_view");
+
+        let items = if preview_selection.opt_preview_command.is_some() {
+            let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+
+            buffer.trim().lines().for_each(|line| {
+                let _ = tx_item.send(Arc::new(PreviewItem::new(line, &preview_selection)));
+            });
+
+            rx_item
+        } else {
+            let item_reader_opts = SkimItemReaderOption::default().ansi(true);
+            let item_reader = SkimItemReader::new(item_reader_opts);
+
+            let (items, _opt_handle) =
+                item_reader.of_bufread(Box::new(Cursor::new(buffer.trim().to_owned())));
+
+            items
+        };
+
+        // run_with() reads and shows items from the thread stream created above
+        match skim::Skim::run_with(&skim_opts, Some(items)) {
+            Some(output) if output.is_abort => Err(HttmAborted::new(
+                "httm select/restore/purge/roll forward session was aborted.  Quitting.",
+            )
+            .into()),
+            Some(output) => {
+                let selected = output
+                    .selected_items
+                    .iter()
+                    .map(|i| i.output().into_owned())
+                    .collect();
+
+                Ok(PickerOutput {
+                    query: output.query,
+                    selected,
+                    opt_filter_change: None,
+                    is_snapshot_request: false,
+                })
+            }
+            None => {
+                Err(HttmError::new("httm select/restore/purge/roll forward session failed.").into())
+            }
+        }
+    }
+}
+
+// a line of the select view which, unlike a line read by skim from a buffer,
+// may be previewed with a metadata header generated by httm
+struct PreviewItem {
+    line: String,
+    text: String,
+    preview_selection: Arc<PreviewSelection>,
+}
+
+impl PreviewItem {
+    fn new(line: &str, preview_selection: &Arc<PreviewSelection>) -> Self {
+        Self {
+            line: line.to_owned(),
+            text: strip_ansi(line),
+            preview_selection: preview_selection.clone(),
+        }
+    }
+}
+
+impl SkimItem for PreviewItem {
+    fn text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.text)
+    }
+    fn display(&self, _context: DisplayContext<'_>) -> AnsiString {
+        AnsiString::parse(&self.line)
+    }
+    fn output(&self) -> Cow<'_, str> {
+        self.text()
+    }
+    fn preview(&self, _: PreviewContext<'_>) -> ItemPreview {
+        ItemPreview::AnsiText(self.preview_selection.preview(&self.text))
+    }
+}